@@ -6,6 +6,12 @@ BIN_DIR="$HOME/.local/bin"
 SERVICE_DIR="$HOME/.config/systemd/user"
 ENV_FILE="$HOME/.config/mavoice/env"
 
+# ./install.sh --headless installs the service without overlay windows
+SERVICE_FILE="mavoice.service"
+if [ "${1:-}" = "--headless" ]; then
+    SERVICE_FILE="mavoice-headless.service"
+fi
+
 echo "=== maVoice Installer ==="
 
 # 1. Build release binary
@@ -39,7 +45,7 @@ fi
 # 4. Install and enable systemd user service
 echo "[4/4] Installing systemd user service..."
 mkdir -p "$SERVICE_DIR"
-cp "$SCRIPT_DIR/mavoice.service" "$SERVICE_DIR/mavoice.service"
+cp "$SCRIPT_DIR/$SERVICE_FILE" "$SERVICE_DIR/mavoice.service"
 systemctl --user daemon-reload
 systemctl --user enable mavoice.service
 echo "  Service enabled. Starting..."
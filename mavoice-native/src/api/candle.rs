@@ -0,0 +1,352 @@
+use candle_core::{Device, IndexOp, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::whisper::{self as m, audio, model::Whisper};
+use futures_util::future::BoxFuture;
+use hf_hub::api::sync::Api;
+use hf_hub::{Repo, RepoType};
+use rubato::{FftFixedIn, Resampler};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokenizers::Tokenizer;
+
+use super::key_check::KeyStatus;
+use super::provider::{Transcript, TranscriptionOptions, TranscriptionProvider, TranscriptionResult};
+use crate::audio::wav::decode_wav;
+
+const SOT: &str = "<|startoftranscript|>";
+const SOT_PREV: &str = "<|startofprev|>";
+const TRANSCRIBE: &str = "<|transcribe|>";
+const NO_TIMESTAMPS: &str = "<|notimestamps|>";
+const EOT: &str = "<|endoftext|>";
+
+/// Whisper run locally with candle, on the CPU or (in builds with the
+/// `cuda` feature) an NVIDIA GPU. Nothing leaves the machine once the model
+/// is downloaded.
+///
+/// The model is fetched from the Hugging Face hub (or read from a local
+/// directory holding `config.json`, `tokenizer.json` and
+/// `model.safetensors`) on the first dictation and kept loaded. Decoding is
+/// greedy, like the cloud providers at temperature 0.
+pub struct CandleWhisper {
+    /// Hub repo ("openai/whisper-small") or model directory
+    model: String,
+    /// "auto", "cpu" or "cuda"
+    device: String,
+    loaded: Arc<Mutex<Option<Loaded>>>,
+}
+
+impl CandleWhisper {
+    pub fn new(model: String, device: String) -> Self {
+        Self {
+            model,
+            device,
+            loaded: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl TranscriptionProvider for CandleWhisper {
+    fn name(&self) -> &'static str {
+        "candle"
+    }
+
+    fn has_api_key(&self) -> bool {
+        true
+    }
+
+    fn validate_api_key(&self) -> BoxFuture<'_, KeyStatus> {
+        Box::pin(async { KeyStatus::Valid })
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult> {
+        Box::pin(async move {
+            let model = opts.model.clone().unwrap_or_else(|| self.model.clone());
+            let device = self.device.clone();
+            let language = opts.language.clone();
+            let prompt = opts.prompt.clone().filter(|p| !p.trim().is_empty());
+            let loaded = self.loaded.clone();
+            let wav = audio.to_vec();
+            let job_model = model.clone();
+            let text = tokio::task::spawn_blocking(move || -> Result<String, String> {
+                let mut loaded = loaded.lock().unwrap();
+                if loaded.as_ref().map_or(true, |l| l.model != job_model) {
+                    // Free the old model before the new one is read
+                    *loaded = None;
+                    *loaded = Some(Loaded::load(&job_model, &device)?);
+                }
+                let whisper = loaded.as_mut().expect("model loaded");
+                let (samples, rate) = decode_wav(&wav)?;
+                let pcm = to_whisper_rate(&samples, rate)?;
+                whisper.transcribe(&pcm, language.as_deref(), prompt.as_deref())
+            })
+            .await
+            .map_err(|e| format!("Local transcription stopped: {}", e))??;
+            opts.report_usage("candle", &model, audio);
+            Ok(Transcript {
+                text,
+                provider: "candle",
+                fallback_from: None,
+            })
+        })
+    }
+}
+
+/// A model in memory with what decoding needs.
+struct Loaded {
+    model: String,
+    whisper: Whisper,
+    config: m::Config,
+    tokenizer: Tokenizer,
+    mel_filters: Vec<f32>,
+    device: Device,
+    /// Added to the logits: -inf for tokens Whisper must never emit here
+    suppress: Tensor,
+}
+
+impl Loaded {
+    fn load(model: &str, device: &str) -> Result<Self, String> {
+        let device = match device {
+            "cpu" => Device::Cpu,
+            "cuda" => Device::new_cuda(0).map_err(|e| format!("CUDA unavailable: {}", e))?,
+            _ => Device::cuda_if_available(0).map_err(err)?,
+        };
+        let files = model_files(model)?;
+        let config: m::Config = serde_json::from_str(
+            &std::fs::read_to_string(&files.config).map_err(|e| format!("Cannot read the model config: {}", e))?,
+        )
+        .map_err(|e| format!("Bad model config: {}", e))?;
+        let tokenizer = Tokenizer::from_file(&files.tokenizer).map_err(|e| format!("Bad tokenizer: {}", e))?;
+        // SAFETY: the weights file is only read, and stays in the hub cache
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[&files.weights], m::DTYPE, &device) }
+            .map_err(|e| format!("Cannot read the model weights: {}", e))?;
+        let whisper = Whisper::load(&vb, config.clone()).map_err(|e| format!("Cannot load the model: {}", e))?;
+
+        let no_timestamps = token(&tokenizer, NO_TIMESTAMPS)?;
+        let suppress: Vec<f32> = (0..config.vocab_size as u32)
+            .map(|t| {
+                if t == no_timestamps || config.suppress_tokens.contains(&t) {
+                    f32::NEG_INFINITY
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let suppress = Tensor::new(suppress.as_slice(), &device).map_err(err)?;
+
+        log::info!("[Candle] Loaded {} on {:?}", model, device);
+        Ok(Self {
+            model: model.to_string(),
+            mel_filters: mel_filters(config.num_mel_bins),
+            whisper,
+            config,
+            tokenizer,
+            device,
+            suppress,
+        })
+    }
+
+    /// Transcribe 16kHz mono samples, 30 seconds at a time.
+    fn transcribe(&mut self, pcm: &[f32], language: Option<&str>, prompt: Option<&str>) -> Result<String, String> {
+        let started = std::time::Instant::now();
+        let mel = audio::pcm_to_mel(&self.config, pcm, &self.mel_filters);
+        let bins = self.config.num_mel_bins;
+        let frames = mel.len() / bins;
+        let mel = Tensor::from_vec(mel, (1, bins, frames), &self.device).map_err(err)?;
+
+        let content_frames = pcm.len() / m::HOP_LENGTH;
+        let mut text = String::new();
+        let mut seek = 0;
+        while seek < content_frames.min(frames) {
+            let size = (content_frames - seek).min(m::N_FRAMES);
+            let segment = mel.narrow(2, seek, size).map_err(err)?;
+            let piece = self.decode(&segment, language, prompt)?;
+            if !piece.trim().is_empty() {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(piece.trim());
+            }
+            seek += size;
+        }
+        log::info!(
+            "[Candle] Transcribed {:.1}s of audio in {:.2}s",
+            pcm.len() as f64 / m::SAMPLE_RATE as f64,
+            started.elapsed().as_secs_f64()
+        );
+        Ok(text)
+    }
+
+    /// Greedy decoding of one segment of at most 30 seconds.
+    fn decode(&mut self, mel: &Tensor, language: Option<&str>, prompt: Option<&str>) -> Result<String, String> {
+        let features = self.whisper.encoder.forward(mel, true).map_err(err)?;
+        let eot = token(&self.tokenizer, EOT)?;
+        let max_tokens = self.config.max_target_positions;
+
+        let mut tokens = Vec::new();
+        if let Some(prompt) = prompt {
+            let encoded = self
+                .tokenizer
+                .encode(format!(" {}", prompt.trim()), false)
+                .map_err(|e| format!("Cannot tokenize the dictionary: {}", e))?;
+            let ids = encoded.get_ids();
+            // The prompt may take at most half the context
+            let keep = ids.len().min(max_tokens / 2 - 1);
+            tokens.push(token(&self.tokenizer, SOT_PREV)?);
+            tokens.extend_from_slice(&ids[ids.len() - keep..]);
+        }
+        tokens.push(token(&self.tokenizer, SOT)?);
+        let mut flush = true;
+        if self.is_multilingual() {
+            match language.and_then(|l| self.tokenizer.token_to_id(&format!("<|{}|>", l))) {
+                Some(id) => tokens.push(id),
+                None => {
+                    // Whisper names the language right after the start token
+                    let id = self.next_token(&tokens, &features, true, false)?;
+                    flush = false;
+                    tokens.push(id);
+                }
+            }
+            tokens.push(token(&self.tokenizer, TRANSCRIBE)?);
+        }
+        tokens.push(token(&self.tokenizer, NO_TIMESTAMPS)?);
+        let prefix = tokens.len();
+
+        while tokens.len() < max_tokens {
+            let next = self.next_token(&tokens, &features, flush, true)?;
+            flush = false;
+            if next == eot {
+                break;
+            }
+            tokens.push(next);
+        }
+        self.tokenizer
+            .decode(&tokens[prefix..], true)
+            .map_err(|e| format!("Cannot detokenize: {}", e))
+    }
+
+    /// The most likely token after `tokens`.
+    fn next_token(&mut self, tokens: &[u32], features: &Tensor, flush: bool, suppress: bool) -> Result<u32, String> {
+        let input = Tensor::new(tokens, &self.device).and_then(|t| t.unsqueeze(0)).map_err(err)?;
+        let ys = self.whisper.decoder.forward(&input, features, flush).map_err(err)?;
+        let (_, seq_len, _) = ys.dims3().map_err(err)?;
+        let mut logits = self
+            .whisper
+            .decoder
+            .final_linear(&ys.i((..1, seq_len - 1..)).map_err(err)?)
+            .and_then(|l| l.i(0)?.i(0))
+            .map_err(err)?;
+        if suppress {
+            logits = logits.broadcast_add(&self.suppress).map_err(err)?;
+        }
+        logits.argmax(0).and_then(|t| t.to_scalar::<u32>()).map_err(err)
+    }
+
+    /// English-only models (".en") have no language or task tokens.
+    fn is_multilingual(&self) -> bool {
+        self.config.vocab_size >= 51865
+    }
+}
+
+struct ModelFiles {
+    config: PathBuf,
+    tokenizer: PathBuf,
+    weights: PathBuf,
+}
+
+/// The model's files from a local directory, else from the Hugging Face
+/// hub cache (downloading them the first time).
+fn model_files(model: &str) -> Result<ModelFiles, String> {
+    let dir = Path::new(model);
+    if dir.is_dir() {
+        return Ok(ModelFiles {
+            config: dir.join("config.json"),
+            tokenizer: dir.join("tokenizer.json"),
+            weights: dir.join("model.safetensors"),
+        });
+    }
+    let api = Api::new().map_err(|e| format!("Hugging Face hub unavailable: {}", e))?;
+    let repo = api.repo(Repo::with_revision(model.to_string(), RepoType::Model, "main".to_string()));
+    let get = |file: &str| {
+        log::info!("[Candle] Fetching {} from {}", file, model);
+        repo.get(file).map_err(|e| format!("Cannot download {} of {}: {}", file, model, e))
+    };
+    Ok(ModelFiles {
+        config: get("config.json")?,
+        tokenizer: get("tokenizer.json")?,
+        weights: get("model.safetensors")?,
+    })
+}
+
+fn token(tokenizer: &Tokenizer, name: &str) -> Result<u32, String> {
+    tokenizer
+        .token_to_id(name)
+        .ok_or_else(|| format!("The tokenizer has no {} token", name))
+}
+
+fn err(e: impl Display) -> String {
+    e.to_string()
+}
+
+/// Resample to Whisper's 16kHz if the recording used another rate.
+fn to_whisper_rate(samples: &[f32], rate: u32) -> Result<Vec<f32>, String> {
+    let target = m::SAMPLE_RATE as u32;
+    if rate == target || samples.is_empty() {
+        return Ok(samples.to_vec());
+    }
+    const CHUNK: usize = 1024;
+    let mut resampler = FftFixedIn::<f32>::new(rate as usize, target as usize, CHUNK, 2, 1)
+        .map_err(|e| format!("Cannot resample {} Hz audio: {}", rate, e))?;
+    let mut out = Vec::with_capacity(samples.len() * target as usize / rate as usize + CHUNK);
+    for chunk in samples.chunks(CHUNK) {
+        let mut block = chunk.to_vec();
+        block.resize(CHUNK, 0.0);
+        let frames = resampler.process(&[block], None).map_err(err)?;
+        out.extend_from_slice(&frames[0]);
+    }
+    out.truncate(samples.len() * target as usize / rate as usize);
+    Ok(out)
+}
+
+/// Slaney-style mel filterbank for Whisper's 400-point FFT at 16kHz, laid
+/// out as `n_mels` rows of 201 frequency bins (what librosa computes and
+/// OpenAI ships as `mel_filters.npz`).
+fn mel_filters(n_mels: usize) -> Vec<f32> {
+    let sample_rate = m::SAMPLE_RATE as f64;
+    let n_freqs = m::N_FFT / 2 + 1;
+    let hz_to_mel = |hz: f64| {
+        if hz < 1000.0 {
+            hz * 3.0 / 200.0
+        } else {
+            15.0 + (hz / 1000.0).ln() / (6.4f64.ln() / 27.0)
+        }
+    };
+    let mel_to_hz = |mel: f64| {
+        if mel < 15.0 {
+            mel * 200.0 / 3.0
+        } else {
+            1000.0 * ((mel - 15.0) * 6.4f64.ln() / 27.0).exp()
+        }
+    };
+    let top = hz_to_mel(sample_rate / 2.0);
+    let edges: Vec<f64> = (0..n_mels + 2)
+        .map(|i| mel_to_hz(top * i as f64 / (n_mels + 1) as f64))
+        .collect();
+
+    let mut filters = vec![0f32; n_mels * n_freqs];
+    for band in 0..n_mels {
+        let (lower, center, upper) = (edges[band], edges[band + 1], edges[band + 2]);
+        // Each triangle has the same area
+        let norm = 2.0 / (upper - lower);
+        for bin in 0..n_freqs {
+            let hz = bin as f64 * sample_rate / m::N_FFT as f64;
+            let weight = ((hz - lower) / (center - lower)).min((upper - hz) / (upper - center)).max(0.0);
+            filters[band * n_freqs + bin] = (weight * norm) as f32;
+        }
+    }
+    filters
+}
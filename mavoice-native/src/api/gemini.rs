@@ -1,11 +1,15 @@
 use base64::prelude::*;
 use futures_util::{SinkExt, StreamExt};
+use reqwest::Url;
 use serde_json::{json, Value};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio_tungstenite::tungstenite::protocol::Message;
 
+use super::key_check::{self, KeyStatus};
+use crate::privacy;
+
 /// A single function call from Gemini.
 #[derive(Debug, Clone)]
 pub struct FunctionCall {
@@ -20,12 +24,62 @@ pub struct FunctionResponse {
     pub id: String,
     pub name: String,
     pub response: serde_json::Value,
+    /// More responses follow (progress of a `NON_BLOCKING` call)
+    pub will_continue: bool,
+    /// For `NON_BLOCKING` calls: when Gemini acts on the response,
+    /// "INTERRUPT", "WHEN_IDLE" or "SILENT"
+    pub scheduling: Option<&'static str>,
+}
+
+/// How a session authenticates with the Live API.
+#[derive(Debug, Clone)]
+pub enum GeminiAuth {
+    /// Long-lived API key, sent in the WebSocket URL
+    ApiKey(String),
+    /// Endpoint handing out short-lived tokens, asked before every connect
+    TokenEndpoint(String),
 }
 
+/// Per-session options sent in the setup message.
+#[derive(Debug, Clone)]
+pub struct GeminiSetup {
+    pub voice_name: String,
+    pub system_instruction: String,
+    /// Reply with text instead of speech
+    pub text_only: bool,
+    /// Server-side voice activity detection. `None` turns it off and the
+    /// client marks speech with `send_activity_start`/`send_activity_end`
+    /// (push-to-talk).
+    pub activity_detection: Option<ActivityDetection>,
+}
+
+/// Tuning for Gemini's automatic voice activity detection.
+#[derive(Debug, Clone)]
+pub struct ActivityDetection {
+    /// "high" starts a user turn on quieter or shorter sounds, "low" needs
+    /// clearer speech (noisy rooms)
+    pub start_sensitivity: String,
+    /// "high" ends a turn at shorter pauses, "low" waits longer
+    pub end_sensitivity: String,
+    /// Audio kept from before detected speech
+    pub prefix_padding_ms: u64,
+    /// Silence that ends the user's turn
+    pub silence_ms: u64,
+}
+
+/// Native-audio model, used for spoken replies
+pub const AUDIO_MODEL: &str = "models/gemini-2.5-flash-native-audio-preview-12-2025";
+/// Half-cascade Live model; the native-audio one can't reply with text
+pub const TEXT_MODEL: &str = "models/gemini-live-2.5-flash-preview";
+
 /// Commands sent from the main thread to the WebSocket write task.
 enum ClientCommand {
     SendAudio(Vec<u8>),
     SendText(String),
+    SendImage {
+        mime_type: String,
+        data: Vec<u8>,
+    },
     SendToolResponse(Vec<FunctionResponse>),
     ActivityStart,
     ActivityEnd,
@@ -38,10 +92,19 @@ pub enum GeminiEvent {
     Ready,
     Audio(Vec<u8>),
     Text(String),
+    /// Transcription of the user's speech, in pieces as it's recognized
+    InputTranscription(String),
+    /// Transcription of Gemini's spoken reply, in pieces
+    OutputTranscription(String),
     Interrupted,
     TurnComplete,
     ToolCall(Vec<FunctionCall>),
     ToolCallCancellation(Vec<String>),
+    /// Tokens used by the response just sent
+    Usage {
+        input_tokens: u64,
+        output_tokens: u64,
+    },
     Error(String),
     Closed(String),
 }
@@ -58,30 +121,53 @@ pub struct GeminiLiveClient {
 
 impl GeminiLiveClient {
     /// Build the JSON setup message with model config, VAD, compression, and tools.
-    fn build_setup_message(voice_name: &str, system_instruction: &str) -> Value {
-        json!({
-            "setup": {
-                "model": "models/gemini-2.5-flash-native-audio-preview-12-2025",
-                "generationConfig": {
+    fn build_setup_message(setup: &GeminiSetup, function_declarations: &[Value]) -> Value {
+        let (model, generation_config) = if setup.text_only {
+            (TEXT_MODEL, json!({ "responseModalities": ["TEXT"] }))
+        } else {
+            (
+                AUDIO_MODEL,
+                json!({
                     "responseModalities": ["AUDIO"],
                     "speechConfig": {
                         "voiceConfig": {
                             "prebuiltVoiceConfig": {
-                                "voiceName": voice_name
+                                "voiceName": setup.voice_name
                             }
                         }
                     }
+                }),
+            )
+        };
+
+        let activity_detection = match setup.activity_detection {
+            Some(ref vad) => json!({
+                "startOfSpeechSensitivity": if vad.start_sensitivity == "low" {
+                    "START_SENSITIVITY_LOW"
+                } else {
+                    "START_SENSITIVITY_HIGH"
                 },
+                "endOfSpeechSensitivity": if vad.end_sensitivity == "high" {
+                    "END_SENSITIVITY_HIGH"
+                } else {
+                    "END_SENSITIVITY_LOW"
+                },
+                "prefixPaddingMs": vad.prefix_padding_ms,
+                "silenceDurationMs": vad.silence_ms
+            }),
+            None => json!({ "disabled": true }),
+        };
+
+        let mut message = json!({
+            "setup": {
+                "model": model,
+                "generationConfig": generation_config,
                 "systemInstruction": {
-                    "parts": [{ "text": system_instruction }]
+                    "parts": [{ "text": setup.system_instruction }]
                 },
+                "inputAudioTranscription": {},
                 "realtimeInputConfig": {
-                    "automaticActivityDetection": {
-                        "startOfSpeechSensitivity": "START_SENSITIVITY_HIGH",
-                        "endOfSpeechSensitivity": "END_SENSITIVITY_LOW",
-                        "prefixPaddingMs": 100,
-                        "silenceDurationMs": 500
-                    }
+                    "automaticActivityDetection": activity_detection
                 },
                 "contextWindowCompression": {
                     "triggerTokens": 80000,
@@ -89,91 +175,48 @@ impl GeminiLiveClient {
                 },
                 "tools": [
                     { "googleSearch": {} },
-                    {
-                        "functionDeclarations": [
-                            {
-                                "name": "search_memory",
-                                "description": "Search the user's persistent memory/knowledge base for relevant information. Use this when the user asks about something they've previously stored or when context would help.",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "query": {
-                                            "type": "string",
-                                            "description": "Search query to find relevant memories"
-                                        }
-                                    },
-                                    "required": ["query"]
-                                }
-                            },
-                            {
-                                "name": "remember",
-                                "description": "Save a piece of information to the user's persistent memory for future recall. Use when the user asks you to remember something.",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "title": {
-                                            "type": "string",
-                                            "description": "Short title for the memory"
-                                        },
-                                        "content": {
-                                            "type": "string",
-                                            "description": "Detailed content to remember"
-                                        }
-                                    },
-                                    "required": ["title", "content"]
-                                }
-                            },
-                            {
-                                "name": "run_command",
-                                "description": "Run a shell command on the user's Linux desktop. Use for file operations, system info, package management, etc.",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "command": {
-                                            "type": "string",
-                                            "description": "Bash command to execute"
-                                        }
-                                    },
-                                    "required": ["command"]
-                                }
-                            },
-                            {
-                                "name": "ask_claude",
-                                "description": "Delegate a complex task to Claude (Anthropic's AI). Use for code generation, analysis, writing, or anything requiring deep reasoning.",
-                                "parameters": {
-                                    "type": "object",
-                                    "properties": {
-                                        "task": {
-                                            "type": "string",
-                                            "description": "Detailed description of the task for Claude"
-                                        }
-                                    },
-                                    "required": ["task"]
-                                }
-                            }
-                        ]
-                    }
+                    { "functionDeclarations": function_declarations }
                 ]
             }
-        })
+        });
+        // Only spoken replies have a transcription
+        if !setup.text_only {
+            message["setup"]["outputAudioTranscription"] = json!({});
+        }
+        message
     }
 
     /// Connect to Gemini Live and start the read/write tasks.
     ///
+    /// `function_declarations` are the tool schemas the model may call.
     /// `event_tx` is a callback that delivers parsed server events back to the caller.
     /// In practice this is wired to `EventLoopProxy::send_event()`.
     pub async fn connect(
-        api_key: &str,
-        voice_name: &str,
-        system_instruction: &str,
+        auth: &GeminiAuth,
+        setup: &GeminiSetup,
+        function_declarations: Vec<Value>,
         event_tx: mpsc::UnboundedSender<GeminiEvent>,
     ) -> Result<Self, String> {
-        let url = format!(
-            "wss://generativelanguage.googleapis.com/ws/\
-             google.ai.generativelanguage.v1beta.GenerativeService.\
-             BidiGenerateContent?key={}",
-            api_key
-        );
+        let (endpoint, param, value) = match auth {
+            GeminiAuth::ApiKey(api_key) => (
+                "wss://generativelanguage.googleapis.com/ws/\
+                 google.ai.generativelanguage.v1beta.GenerativeService.\
+                 BidiGenerateContent",
+                "key",
+                api_key.to_string(),
+            ),
+            GeminiAuth::TokenEndpoint(token_endpoint) => (
+                "wss://generativelanguage.googleapis.com/ws/\
+                 google.ai.generativelanguage.v1alpha.GenerativeService.\
+                 BidiGenerateContentConstrained",
+                "access_token",
+                fetch_ephemeral_token(token_endpoint).await?,
+            ),
+        };
+        // The token comes from the user's endpoint and may hold any characters
+        let mut url = Url::parse(endpoint).map_err(|e| format!("Invalid Gemini URL: {}", e))?;
+        url.query_pairs_mut().append_pair(param, &value);
+        let url = url.to_string();
 
         let mut ws_config = tokio_tungstenite::tungstenite::protocol::WebSocketConfig::default();
         ws_config.max_message_size = Some(64 * 1024 * 1024);
@@ -189,7 +232,7 @@ impl GeminiLiveClient {
         let (mut ws_write, mut ws_read) = ws_stream.split();
 
         // Send setup message
-        let setup = Self::build_setup_message(voice_name, system_instruction);
+        let setup = Self::build_setup_message(setup, &function_declarations);
         log::info!("[Gemini] Setup JSON: {}", serde_json::to_string_pretty(&setup).unwrap_or_default());
 
         ws_write
@@ -231,15 +274,39 @@ impl GeminiLiveClient {
                             }
                         })
                     }
+                    ClientCommand::SendImage { mime_type, data } => {
+                        // Context for the user's next turn, not a turn of its own
+                        json!({
+                            "clientContent": {
+                                "turns": [{
+                                    "role": "user",
+                                    "parts": [{
+                                        "inlineData": {
+                                            "mimeType": mime_type,
+                                            "data": BASE64_STANDARD.encode(&data)
+                                        }
+                                    }]
+                                }],
+                                "turnComplete": false
+                            }
+                        })
+                    }
                     ClientCommand::SendToolResponse(responses) => {
                         let parts: Vec<Value> = responses
                             .into_iter()
                             .map(|r| {
-                                json!({
+                                let mut part = json!({
                                     "id": r.id,
                                     "name": r.name,
                                     "response": r.response
-                                })
+                                });
+                                if r.will_continue {
+                                    part["willContinue"] = json!(true);
+                                }
+                                if let Some(scheduling) = r.scheduling {
+                                    part["scheduling"] = json!(scheduling);
+                                }
+                                part
                             })
                             .collect();
                         json!({
@@ -282,7 +349,7 @@ impl GeminiLiveClient {
                 match msg_result {
                     Ok(Message::Text(text)) => {
                         let preview: String = text.chars().take(200).collect();
-                        log::debug!("[Gemini] Text msg: {}", preview);
+                        log::debug!("[Gemini] Text msg: {}", privacy::redact(&preview));
                         Self::parse_server_message(&text, &read_event_tx);
                     }
                     Ok(Message::Binary(data)) => {
@@ -290,7 +357,7 @@ impl GeminiLiveClient {
                         match std::str::from_utf8(&data) {
                             Ok(text) => {
                                 let preview: String = text.chars().take(200).collect();
-                                log::debug!("[Gemini] Binary msg (as text): {}", preview);
+                                log::debug!("[Gemini] Binary msg (as text): {}", privacy::redact(&preview));
                                 Self::parse_server_message(text, &read_event_tx);
                             }
                             Err(_) => {
@@ -344,6 +411,15 @@ impl GeminiLiveClient {
             log::debug!("[Gemini] Server message keys: {:?}", keys);
         }
 
+        // usageMetadata — rides along with other messages, so don't return
+        if let Some(usage) = msg.get("usageMetadata") {
+            let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            let _ = tx.send(GeminiEvent::Usage {
+                input_tokens: count("promptTokenCount"),
+                output_tokens: count("responseTokenCount"),
+            });
+        }
+
         // setupComplete
         if msg.get("setupComplete").is_some() {
             log::info!("[Gemini] Setup complete");
@@ -398,6 +474,25 @@ impl GeminiLiveClient {
 
         // serverContent
         if let Some(content) = msg.get("serverContent") {
+            // Input/output transcription (separate from modelTurn, may share a
+            // message with interrupted/turnComplete)
+            if let Some(text) = content
+                .get("outputTranscription")
+                .and_then(|t| t.get("text"))
+                .and_then(|t| t.as_str())
+            {
+                log::debug!("[Gemini] Output transcription: {}", privacy::redact(text));
+                let _ = tx.send(GeminiEvent::OutputTranscription(text.to_string()));
+            }
+            if let Some(text) = content
+                .get("inputTranscription")
+                .and_then(|t| t.get("text"))
+                .and_then(|t| t.as_str())
+            {
+                log::debug!("[Gemini] Input transcription: {}", privacy::redact(text));
+                let _ = tx.send(GeminiEvent::InputTranscription(text.to_string()));
+            }
+
             // Interruption (barge-in)
             if content.get("interrupted").and_then(|v| v.as_bool()) == Some(true) {
                 log::info!("[Gemini] Interrupted (barge-in)");
@@ -440,22 +535,6 @@ impl GeminiLiveClient {
                     }
                 }
             }
-
-            // Input/output transcription (separate from modelTurn)
-            if let Some(text) = content
-                .get("outputTranscription")
-                .and_then(|t| t.get("text"))
-                .and_then(|t| t.as_str())
-            {
-                log::debug!("[Gemini] Output transcription: {}", text);
-            }
-            if let Some(text) = content
-                .get("inputTranscription")
-                .and_then(|t| t.get("text"))
-                .and_then(|t| t.as_str())
-            {
-                log::debug!("[Gemini] Input transcription: {}", text);
-            }
         }
     }
 
@@ -492,13 +571,24 @@ impl GeminiLiveClient {
         }
     }
 
-    /// Send a text message to Gemini (it will respond with audio).
+    /// Send a text message to Gemini (it replies in the session's modality).
     pub fn send_text(&self, text: &str) {
         if self.open.load(Ordering::Relaxed) {
             let _ = self.cmd_tx.send(ClientCommand::SendText(text.to_string()));
         }
     }
 
+    /// Add an image to the conversation without ending the user's turn, so
+    /// Gemini answers the next thing said about it.
+    pub fn send_image(&self, mime_type: &str, data: Vec<u8>) {
+        if self.open.load(Ordering::Relaxed) {
+            let _ = self.cmd_tx.send(ClientCommand::SendImage {
+                mime_type: mime_type.to_string(),
+                data,
+            });
+        }
+    }
+
     /// Close the WebSocket connection.
     pub fn close(&self) {
         self.open.store(false, Ordering::Relaxed);
@@ -516,3 +606,51 @@ impl Drop for GeminiLiveClient {
         self.close();
     }
 }
+
+/// Check `api_key` by listing models, which needs a valid key but costs
+/// nothing.
+pub async fn validate_api_key(api_key: &str) -> KeyStatus {
+    if api_key.is_empty() {
+        return KeyStatus::Missing;
+    }
+    let request = reqwest::Client::new()
+        .get("https://generativelanguage.googleapis.com/v1beta/models")
+        .query(&[("pageSize", "1")])
+        .header("x-goog-api-key", api_key);
+    key_check::check(request).await
+}
+
+/// POST to the user's token endpoint and read the ephemeral token from the
+/// reply: a JSON object with `token` or `name` (the shape of Google's
+/// `authTokens.create`), or the bare token as plain text.
+async fn fetch_ephemeral_token(endpoint: &str) -> Result<String, String> {
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Token endpoint returned {}: {}", status, body.trim()));
+    }
+
+    let token = match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Object(obj)) => ["token", "name"]
+            .iter()
+            .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .ok_or("Token endpoint reply has no 'token' or 'name'")?,
+        Ok(Value::String(token)) => token,
+        _ => body.trim().to_string(),
+    };
+    if token.is_empty() {
+        return Err("Token endpoint returned an empty token".to_string());
+    }
+    log::info!("[Gemini] Got an ephemeral token");
+    Ok(token)
+}
@@ -1,7 +1,28 @@
-use reqwest::multipart::{Form, Part};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, StreamExt};
+use reqwest::multipart::Form;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::sync::Arc;
+
+use super::key_check::{self, KeyStatus};
+use super::provider::{
+    ChunkProgress, HttpTimeouts, Transcript, TranscriptionOptions, TranscriptionProvider,
+    TranscriptionResult,
+};
+use super::rate_limit::RateLimiter;
+use super::retry::{is_retryable_error, is_retryable_status, retry_after, RetryNotice};
+use crate::audio::chunking::{split_on_silence, stitch, AudioChunk, ChunkPlan};
+use crate::audio::encode::{prepare_upload, UploadFormat};
+use crate::audio::wav::{decode_wav, encode_wav, wav_duration_secs};
+
+/// Long recordings are split into ~60s chunks, cut at silence where possible.
+const CHUNK_PLAN: ChunkPlan = ChunkPlan {
+    target_secs: 60.0,
+    search_secs: 10.0,
+    overlap_secs: 2.0,
+};
 
 #[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +47,10 @@ pub struct GroqClient {
     client: Client,
     api_key: String,
     base_url: String,
+    upload_format: UploadFormat,
+    timeouts: Option<HttpTimeouts>,
+    /// Shared by clones, so every upload from this client queues together
+    limits: Arc<RateLimiter>,
 }
 
 impl GroqClient {
@@ -34,6 +59,29 @@ impl GroqClient {
             client: Client::new(),
             api_key,
             base_url: "https://api.groq.com/openai/v1".to_string(),
+            upload_format: UploadFormat::Wav,
+            timeouts: None,
+            limits: Arc::new(RateLimiter::new()),
+        }
+    }
+
+    /// Encode uploads in `format` instead of WAV.
+    pub fn with_upload_format(mut self, format: UploadFormat) -> Self {
+        self.upload_format = format;
+        self
+    }
+
+    /// Fail requests that can't connect or finish within `timeouts`.
+    pub fn with_timeouts(mut self, timeouts: HttpTimeouts) -> Self {
+        self.client = timeouts.client();
+        self.timeouts = Some(timeouts);
+        self
+    }
+
+    fn describe_error(&self, err: reqwest::Error) -> Box<dyn Error + Send + Sync> {
+        match self.timeouts {
+            Some(t) => t.describe(err),
+            None => err.into(),
         }
     }
 
@@ -41,130 +89,155 @@ impl GroqClient {
         !self.api_key.is_empty()
     }
 
+    /// List the models, which needs a valid key but costs nothing.
+    pub async fn validate_api_key(&self) -> KeyStatus {
+        if !self.has_api_key() {
+            return KeyStatus::Missing;
+        }
+        let request = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key));
+        key_check::check(request).await
+    }
+
     pub async fn transcribe_audio_bytes(
         &self,
         audio_data: &[u8],
         filename: &str,
-        model: Option<&str>,
-        language: Option<&str>,
-        prompt: Option<&str>,
-        response_format: Option<&str>,
-        temperature: Option<f32>,
+        opts: &TranscriptionOptions,
     ) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let model = model.unwrap_or("whisper-large-v3-turbo");
-
-        // Check if we need chunking for large files (>25MB or >5 minutes estimated)
+        // Check if we need chunking for large files (>25MB or >5 minutes)
         let file_size_mb = audio_data.len() as f64 / (1024.0 * 1024.0);
-        let estimated_duration_minutes = file_size_mb / 2.0;
+        let duration_minutes = wav_duration_secs(audio_data)
+            .map(|s| s / 60.0)
+            .unwrap_or(file_size_mb / 2.0);
 
         log::info!(
-            "Audio file: {:.1}MB, ~{:.1} minutes estimated",
+            "Audio file: {:.1}MB, {:.1} minutes",
             file_size_mb,
-            estimated_duration_minutes
+            duration_minutes
         );
 
-        if file_size_mb > 25.0 || estimated_duration_minutes > 5.0 {
+        if file_size_mb > 25.0 || duration_minutes > 5.0 {
             log::info!("Large file detected, using chunking strategy...");
             return self
-                .transcribe_with_chunking(
-                    audio_data,
-                    filename,
-                    model,
-                    language,
-                    prompt,
-                    response_format,
-                    temperature,
-                )
+                .transcribe_with_chunking(audio_data, filename, opts)
                 .await;
         }
 
-        self.transcribe_single_chunk(
-            audio_data,
-            filename,
-            model,
-            language,
-            prompt,
-            response_format,
-            temperature,
-        )
-        .await
+        self.transcribe_single_chunk(audio_data, filename, opts).await
     }
 
     async fn transcribe_single_chunk(
         &self,
         audio_data: &[u8],
         filename: &str,
-        model: &str,
-        language: Option<&str>,
-        prompt: Option<&str>,
-        response_format: Option<&str>,
-        temperature: Option<f32>,
+        opts: &TranscriptionOptions,
     ) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let response_format = response_format.unwrap_or("json");
-        let temperature = temperature.unwrap_or(0.0);
-
-        let file_part = Part::bytes(audio_data.to_vec())
-            .file_name(filename.to_string())
-            .mime_str("audio/wav")?;
+        let model = opts.model.as_deref().unwrap_or("whisper-large-v3-turbo");
+        let response_format = opts.response_format.as_deref().unwrap_or("json");
+        let temperature = opts.temperature.unwrap_or(0.0);
+        let policy = opts.retry;
 
-        let mut form = Form::new()
-            .part("file", file_part)
-            .text("model", model.to_string())
-            .text("response_format", response_format.to_string())
-            .text("temperature", temperature.to_string());
-
-        if let Some(lang) = language {
-            form = form.text("language", lang.to_string());
+        if let Some(ref lang) = opts.language {
             log::info!("Using language: {}", lang);
         }
-
-        if let Some(p) = prompt {
+        if let Some(ref p) = opts.prompt {
             if !p.trim().is_empty() {
-                form = form.text("prompt", p.to_string());
                 log::info!("Using prompt/dictionary: {}", p);
             }
         }
 
-        let response = self
-            .client
-            .post(format!("{}/audio/transcriptions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .multipart(form)
-            .send()
-            .await?;
-
-        let status = response.status();
-        let response_text: String = response.text().await?;
-
-        if status.is_success() {
-            let parsed: serde_json::Value = serde_json::from_str(&response_text)?;
-            let text = parsed["text"].as_str().unwrap_or("").to_string();
-
-            // Quality monitoring via segment confidence
-            if let Some(segments) = parsed["segments"].as_array() {
-                let total = segments.len();
-                let low_conf = segments
-                    .iter()
-                    .filter(|s| s["avg_logprob"].as_f64().unwrap_or(0.0) < -0.5)
-                    .count();
-                let ratio = if total > 0 {
-                    1.0 - (low_conf as f64 / total as f64)
-                } else {
-                    1.0
-                };
-                log::info!(
-                    "Quality: {:.1}% confidence ({}/{} segments good)",
-                    ratio * 100.0,
-                    total - low_conf,
-                    total
-                );
+        let upload = prepare_upload(audio_data, filename, self.upload_format);
+        let mut attempt = 1;
+
+        loop {
+            // The form is consumed by each request, so rebuild it per attempt
+            let file_part = opts.audio_part(upload.bytes.clone())
+                .file_name(upload.file_name.clone())
+                .mime_str(upload.mime)?;
+
+            let mut form = Form::new()
+                .part("file", file_part)
+                .text("model", model.to_string())
+                .text("response_format", response_format.to_string())
+                .text("temperature", temperature.to_string());
+
+            if let Some(ref lang) = opts.language {
+                form = form.text("language", lang.clone());
             }
 
-            Ok(text)
-        } else if let Ok(error_response) = serde_json::from_str::<GroqError>(&response_text) {
-            Err(format!("Groq API error: {}", error_response.error.message).into())
-        } else {
-            Err(format!("HTTP error {}: {}", status, response_text).into())
+            if let Some(ref p) = opts.prompt {
+                if !p.trim().is_empty() {
+                    form = form.text("prompt", p.clone());
+                }
+            }
+
+            let turn = self.limits.acquire().await;
+            let sent = self
+                .client
+                .post(format!("{}/audio/transcriptions", self.base_url))
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .multipart(form)
+                .send()
+                .await;
+
+            if let Ok(ref response) = sent {
+                self.limits.update(response.headers());
+            }
+            let (reason, server_delay) = match sent {
+                Ok(response) if response.status().is_success() => {
+                    let response_text: String = response
+                        .text()
+                        .await
+                        .map_err(|e| self.describe_error(e))?;
+                    opts.report_usage("groq", model, audio_data);
+                    return Ok(parse_transcription(&response_text)?);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let server_delay = retry_after(response.headers());
+                    if let Some(delay) = server_delay.filter(|_| status == reqwest::StatusCode::TOO_MANY_REQUESTS) {
+                        self.limits.block_for(delay);
+                    }
+                    let response_text: String = response
+                        .text()
+                        .await
+                        .map_err(|e| self.describe_error(e))?;
+                    let message = error_message(status, &response_text);
+                    if !is_retryable_status(status) || attempt >= policy.max_attempts {
+                        return Err(message.into());
+                    }
+                    (message, server_delay)
+                }
+                Err(e) => {
+                    if !is_retryable_error(&e) || attempt >= policy.max_attempts {
+                        return Err(self.describe_error(e));
+                    }
+                    (e.to_string(), None)
+                }
+            };
+
+            drop(turn);
+            let delay = policy.delay(attempt, server_delay);
+            attempt += 1;
+            log::warn!(
+                "Groq request failed ({}), retry {}/{} in {:.1}s",
+                reason,
+                attempt,
+                policy.max_attempts,
+                delay.as_secs_f32()
+            );
+            if let Some(ref listener) = opts.on_retry {
+                listener.notify(RetryNotice {
+                    attempt,
+                    max_attempts: policy.max_attempts,
+                    delay,
+                    reason,
+                });
+            }
+            tokio::time::sleep(delay).await;
         }
     }
 
@@ -172,66 +245,148 @@ impl GroqClient {
         &self,
         audio_data: &[u8],
         filename: &str,
-        model: &str,
-        language: Option<&str>,
-        prompt: Option<&str>,
-        response_format: Option<&str>,
-        temperature: Option<f32>,
+        opts: &TranscriptionOptions,
     ) -> Result<String, Box<dyn Error + Send + Sync>> {
-        // Split into ~60 second segments with 5 second overlap
-        let chunk_size = audio_data.len() / 6;
-        let overlap_size = chunk_size / 12;
-
-        let mut chunks = Vec::new();
-        let mut pos = 0;
-        let mut chunk_num = 0;
-
-        while pos < audio_data.len() {
-            let end = std::cmp::min(pos + chunk_size, audio_data.len());
-            chunks.push((chunk_num, audio_data[pos..end].to_vec()));
-            pos = if end == audio_data.len() {
-                end
-            } else {
-                pos + chunk_size - overlap_size
-            };
-            chunk_num += 1;
+        // Decode to PCM and split at silence near ~60 second boundaries
+        let (samples, sample_rate) = decode_wav(audio_data)?;
+        let chunks = split_on_silence(&samples, sample_rate, CHUNK_PLAN);
+
+        log::info!(
+            "Created {} chunks for processing ({} clean cuts)",
+            chunks.len(),
+            chunks.iter().filter(|c| c.clean_cut).count()
+        );
+
+        // Uploads run `chunk_concurrency` at a time (the rate limiter still
+        // holds them back near the limit); results are put back in order
+        let total = chunks.len();
+        let samples = &samples;
+        let mut pending = stream::iter(chunks)
+            .map(|chunk| async move {
+                let i = chunk.index;
+                let chunk_filename = format!("chunk_{}_{}", i, filename);
+                let result = match encode_wav(&samples[chunk.start..chunk.end], sample_rate) {
+                    Ok(chunk_data) => {
+                        self.transcribe_single_chunk(&chunk_data, &chunk_filename, opts)
+                            .await
+                    }
+                    Err(e) => Err(e.into()),
+                };
+                (chunk, result)
+            })
+            .buffer_unordered(opts.chunk_concurrency.max(1));
+
+        let mut results = Vec::with_capacity(total);
+        while let Some((chunk, result)) = pending.next().await {
+            let i = chunk.index;
+            match result {
+                Ok(ref text) => log::info!(
+                    "Chunk {} complete ({:.1}s-{:.1}s): {} chars",
+                    i,
+                    chunk.start as f32 / sample_rate as f32,
+                    chunk.end as f32 / sample_rate as f32,
+                    text.len()
+                ),
+                Err(ref e) => log::error!("Chunk {} failed: {}", i, e),
+            }
+            if let Some(ref listener) = opts.on_chunk {
+                listener.notify(ChunkProgress {
+                    index: i,
+                    done: results.len() + 1,
+                    total,
+                    ok: result.is_ok(),
+                });
+            }
+            results.push((chunk, result));
         }
+        results.sort_by_key(|(chunk, _)| chunk.index);
 
-        log::info!("Created {} chunks for processing", chunks.len());
-
-        let mut parts = Vec::new();
-        for (i, chunk_data) in chunks {
-            let chunk_filename = format!("chunk_{}_{}", i, filename);
-            match self
-                .transcribe_single_chunk(
-                    &chunk_data,
-                    &chunk_filename,
-                    model,
-                    language,
-                    prompt,
-                    response_format,
-                    temperature,
-                )
-                .await
-            {
-                Ok(text) => {
-                    log::info!("Chunk {} complete: {} chars", i, text.len());
-                    parts.push(text);
-                }
-                Err(e) => {
-                    log::error!("Chunk {} failed: {}", i, e);
-                }
+        // Drop the words repeated across hard cuts, where neighbouring
+        // chunks share a couple of seconds of audio
+        let mut combined = String::new();
+        let mut joined = 0;
+        let mut prev: Option<AudioChunk> = None;
+        for (chunk, result) in results {
+            if let Ok(text) = result {
+                let overlapped = prev.is_some_and(|p| !p.clean_cut && p.index + 1 == chunk.index);
+                stitch(&mut combined, &text, overlapped);
+                joined += 1;
+                prev = Some(chunk);
             }
-            // Rate limit respect
-            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
         }
 
-        let combined = parts.join(" ");
         log::info!(
             "Final transcription: {} chars from {} chunks",
             combined.len(),
-            parts.len()
+            joined
         );
         Ok(combined)
     }
 }
+
+impl TranscriptionProvider for GroqClient {
+    fn name(&self) -> &'static str {
+        "groq"
+    }
+
+    fn has_api_key(&self) -> bool {
+        GroqClient::has_api_key(self)
+    }
+
+    fn validate_api_key(&self) -> BoxFuture<'_, KeyStatus> {
+        Box::pin(GroqClient::validate_api_key(self))
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult> {
+        Box::pin(async move {
+            let text = self
+                .transcribe_audio_bytes(audio, "recording.wav", opts)
+                .await?;
+            Ok(Transcript {
+                text,
+                provider: self.name(),
+                fallback_from: None,
+            })
+        })
+    }
+}
+
+/// Pull the transcript out of a successful response, logging segment quality
+/// when the verbose format is in use.
+fn parse_transcription(response_text: &str) -> Result<String, serde_json::Error> {
+    let parsed: serde_json::Value = serde_json::from_str(response_text)?;
+    let text = parsed["text"].as_str().unwrap_or("").to_string();
+
+    // Quality monitoring via segment confidence
+    if let Some(segments) = parsed["segments"].as_array() {
+        let total = segments.len();
+        let low_conf = segments
+            .iter()
+            .filter(|s| s["avg_logprob"].as_f64().unwrap_or(0.0) < -0.5)
+            .count();
+        let ratio = if total > 0 {
+            1.0 - (low_conf as f64 / total as f64)
+        } else {
+            1.0
+        };
+        log::info!(
+            "Quality: {:.1}% confidence ({}/{} segments good)",
+            ratio * 100.0,
+            total - low_conf,
+            total
+        );
+    }
+
+    Ok(text)
+}
+
+fn error_message(status: reqwest::StatusCode, response_text: &str) -> String {
+    match serde_json::from_str::<GroqError>(response_text) {
+        Ok(error_response) => format!("Groq API error: {}", error_response.error.message),
+        Err(_) => format!("HTTP error {}: {}", status, response_text),
+    }
+}
@@ -0,0 +1,95 @@
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+/// A key check is a single small GET; don't hold startup feedback hostage
+/// to a slow network.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of checking an API key with a cheap authenticated request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyStatus {
+    Valid,
+    /// No key configured
+    Missing,
+    /// The server rejected the key (401/403, or Google's 400 for a
+    /// malformed key)
+    Invalid(String),
+    /// The key works but its rate limit or quota is used up (429)
+    Quota(String),
+    /// The server couldn't be reached
+    Network(String),
+    /// Any other failure (5xx, unexpected replies)
+    Error(String),
+}
+
+impl KeyStatus {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, KeyStatus::Valid)
+    }
+
+    /// Short identifier for the dashboard.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            KeyStatus::Valid => "valid",
+            KeyStatus::Missing => "missing",
+            KeyStatus::Invalid(_) => "invalid",
+            KeyStatus::Quota(_) => "quota",
+            KeyStatus::Network(_) => "network",
+            KeyStatus::Error(_) => "error",
+        }
+    }
+
+    /// What to tell the user, e.g. "Groq API key rejected: Invalid API Key".
+    pub fn describe(&self, service: &str) -> String {
+        let service = display_name(service);
+        match self {
+            KeyStatus::Valid => format!("{} API key OK", service),
+            KeyStatus::Missing => format!("No {} API key set", service),
+            KeyStatus::Invalid(msg) => format!("{} API key rejected: {}", service, msg),
+            KeyStatus::Quota(msg) => format!("{} quota or rate limit reached: {}", service, msg),
+            KeyStatus::Network(msg) => format!("Cannot reach {}: {}", service, msg),
+            KeyStatus::Error(msg) => format!("{} key check failed: {}", service, msg),
+        }
+    }
+}
+
+fn display_name(service: &str) -> &str {
+    match service {
+        "groq" => "Groq",
+        "openai" => "OpenAI",
+        "gemini" => "Gemini",
+        other => other,
+    }
+}
+
+/// Send `request` (an authenticated call that costs nothing, like listing
+/// models) and classify the reply.
+pub async fn check(request: RequestBuilder) -> KeyStatus {
+    let response = match request.timeout(CHECK_TIMEOUT).send().await {
+        Ok(response) => response,
+        Err(e) if e.is_connect() || e.is_timeout() || e.is_request() => {
+            return KeyStatus::Network(e.to_string());
+        }
+        Err(e) => return KeyStatus::Error(e.to_string()),
+    };
+    let status = response.status();
+    if status.is_success() {
+        return KeyStatus::Valid;
+    }
+    let body = response.text().await.unwrap_or_default();
+    let message = error_message(&body).unwrap_or_else(|| status.to_string());
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => KeyStatus::Invalid(message),
+        // Google answers a malformed key with 400 API_KEY_INVALID
+        StatusCode::BAD_REQUEST if body.contains("API_KEY_INVALID") => KeyStatus::Invalid(message),
+        StatusCode::TOO_MANY_REQUESTS => KeyStatus::Quota(message),
+        _ => KeyStatus::Error(message),
+    }
+}
+
+/// `error.message` from an OpenAI-style or Google error body.
+fn error_message(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    value["error"]["message"].as_str().map(str::to_string)
+}
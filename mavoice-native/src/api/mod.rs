@@ -1,4 +1,11 @@
+#[cfg(feature = "candle")]
+pub mod candle;
 pub mod gemini;
 pub mod groq;
+pub mod key_check;
+pub mod openai;
+pub mod provider;
+pub mod rate_limit;
+pub mod retry;
 pub use gemini::GeminiLiveClient;
-pub use groq::GroqClient;
+pub use provider::{TranscriptionOptions, TranscriptionProvider};
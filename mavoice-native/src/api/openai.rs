@@ -0,0 +1,182 @@
+use futures_util::future::BoxFuture;
+use reqwest::multipart::Form;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+use super::key_check::{self, KeyStatus};
+use super::provider::{
+    HttpTimeouts, Transcript, TranscriptionOptions, TranscriptionProvider, TranscriptionResult,
+};
+use crate::audio::encode::{prepare_upload, UploadFormat};
+
+/// OpenAI rejects uploads above 25MB.
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenAiError {
+    pub error: OpenAiErrorDetail,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenAiErrorDetail {
+    pub message: String,
+    pub r#type: Option<String>,
+    pub code: Option<String>,
+}
+
+/// OpenAI Whisper transcription client (`/v1/audio/transcriptions`).
+#[derive(Clone)]
+pub struct OpenAiClient {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    upload_format: UploadFormat,
+    timeouts: Option<HttpTimeouts>,
+}
+
+impl OpenAiClient {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            base_url: "https://api.openai.com/v1".to_string(),
+            upload_format: UploadFormat::Wav,
+            timeouts: None,
+        }
+    }
+
+    /// Encode uploads in `format` instead of WAV.
+    pub fn with_upload_format(mut self, format: UploadFormat) -> Self {
+        self.upload_format = format;
+        self
+    }
+
+    /// Fail requests that can't connect or finish within `timeouts`.
+    pub fn with_timeouts(mut self, timeouts: HttpTimeouts) -> Self {
+        self.client = timeouts.client();
+        self.timeouts = Some(timeouts);
+        self
+    }
+
+    fn describe_error(&self, err: reqwest::Error) -> Box<dyn Error + Send + Sync> {
+        match self.timeouts {
+            Some(t) => t.describe(err),
+            None => err.into(),
+        }
+    }
+
+    pub fn has_api_key(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    /// List the models, which needs a valid key but costs nothing.
+    pub async fn validate_api_key(&self) -> KeyStatus {
+        if !self.has_api_key() {
+            return KeyStatus::Missing;
+        }
+        let request = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key));
+        key_check::check(request).await
+    }
+
+    pub async fn transcribe_audio_bytes(
+        &self,
+        audio_data: &[u8],
+        filename: &str,
+        opts: &TranscriptionOptions,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let upload = prepare_upload(audio_data, filename, self.upload_format);
+        if upload.bytes.len() > MAX_UPLOAD_BYTES {
+            return Err(format!(
+                "Recording is {:.1}MB, OpenAI accepts at most 25MB",
+                upload.bytes.len() as f64 / (1024.0 * 1024.0)
+            )
+            .into());
+        }
+
+        let model = opts.model.as_deref().unwrap_or("whisper-1");
+        let response_format = opts.response_format.as_deref().unwrap_or("json");
+        let temperature = opts.temperature.unwrap_or(0.0);
+
+        let file_part = opts.audio_part(upload.bytes)
+            .file_name(upload.file_name)
+            .mime_str(upload.mime)?;
+
+        let mut form = Form::new()
+            .part("file", file_part)
+            .text("model", model.to_string())
+            .text("response_format", response_format.to_string())
+            .text("temperature", temperature.to_string());
+
+        if let Some(ref lang) = opts.language {
+            form = form.text("language", lang.clone());
+        }
+
+        if let Some(ref p) = opts.prompt {
+            if !p.trim().is_empty() {
+                form = form.text("prompt", p.clone());
+            }
+        }
+
+        log::info!("[OpenAI] Sending {:.1} KB to {}", audio_data.len() as f32 / 1024.0, model);
+
+        let response = self
+            .client
+            .post(format!("{}/audio/transcriptions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| self.describe_error(e))?;
+
+        let status = response.status();
+        let response_text: String = response
+            .text()
+            .await
+            .map_err(|e| self.describe_error(e))?;
+
+        if status.is_success() {
+            opts.report_usage("openai", model, audio_data);
+            let parsed: serde_json::Value = serde_json::from_str(&response_text)?;
+            Ok(parsed["text"].as_str().unwrap_or("").to_string())
+        } else if let Ok(error_response) = serde_json::from_str::<OpenAiError>(&response_text) {
+            Err(format!("OpenAI API error: {}", error_response.error.message).into())
+        } else {
+            Err(format!("HTTP error {}: {}", status, response_text).into())
+        }
+    }
+}
+
+impl TranscriptionProvider for OpenAiClient {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn has_api_key(&self) -> bool {
+        OpenAiClient::has_api_key(self)
+    }
+
+    fn validate_api_key(&self) -> BoxFuture<'_, KeyStatus> {
+        Box::pin(OpenAiClient::validate_api_key(self))
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult> {
+        Box::pin(async move {
+            let text = self
+                .transcribe_audio_bytes(audio, "recording.wav", opts)
+                .await?;
+            Ok(Transcript {
+                text,
+                provider: self.name(),
+                fallback_from: None,
+            })
+        })
+    }
+}
@@ -0,0 +1,446 @@
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
+use reqwest::multipart::Part;
+use std::error::Error;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+
+use crate::audio::encode::UploadFormat;
+use crate::audio::wav::wav_duration_secs;
+use crate::config::Config;
+use crate::usage::UsageRecord;
+
+use super::key_check::KeyStatus;
+use super::retry::{RetryListener, RetryPolicy};
+
+#[cfg(feature = "candle")]
+use super::candle::CandleWhisper;
+use super::groq::GroqClient;
+use super::openai::OpenAiClient;
+
+/// Piece size when the audio is streamed for `on_upload`
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+pub type TranscriptionResult = Result<Transcript, Box<dyn Error + Send + Sync>>;
+
+/// Per-request transcription parameters, independent of the backend.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionOptions {
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub prompt: Option<String>,
+    pub response_format: Option<String>,
+    pub temperature: Option<f32>,
+    /// Retries for transient failures (rate limits, 5xx, network)
+    pub retry: RetryPolicy,
+    /// Told about each retry so the overlay can show it
+    pub on_retry: Option<RetryListener>,
+    /// Chunks of a long recording transcribed at the same time (0 = 1)
+    pub chunk_concurrency: usize,
+    /// Told as each chunk of a long recording finishes
+    pub on_chunk: Option<ChunkListener>,
+    /// Told about every successful request, for usage tracking
+    pub on_usage: Option<UsageListener>,
+    /// Told when a request has sent all of its audio (`--bench`)
+    pub on_upload: Option<UploadListener>,
+}
+
+impl TranscriptionOptions {
+    /// Build options from the user config for the currently selected provider.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            model: Some(config.transcription_model().to_string()),
+            language: config.effective_language().map(|s| s.to_string()),
+            prompt: config.effective_dictionary().map(|s| s.to_string()),
+            response_format: Some(config.response_format.clone()),
+            temperature: Some(config.temperature),
+            retry: RetryPolicy {
+                max_attempts: config.retry_max_attempts.max(1),
+                base_delay: Duration::from_millis(config.retry_base_delay_ms),
+                ..RetryPolicy::default()
+            },
+            on_retry: None,
+            chunk_concurrency: config.chunk_concurrency,
+            on_chunk: None,
+            on_usage: None,
+            on_upload: None,
+        }
+    }
+}
+
+/// Reported as each chunk of a long recording is transcribed, in the order
+/// they finish.
+#[derive(Debug, Clone)]
+pub struct ChunkProgress {
+    pub index: usize,
+    /// Chunks finished so far, including this one
+    pub done: usize,
+    pub total: usize,
+    /// False if this chunk failed and is left out of the transcript
+    pub ok: bool,
+}
+
+/// Callback invoked with each [`ChunkProgress`].
+#[derive(Clone)]
+pub struct ChunkListener(Arc<dyn Fn(ChunkProgress) + Send + Sync>);
+
+impl ChunkListener {
+    pub fn new(f: impl Fn(ChunkProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn notify(&self, progress: ChunkProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl std::fmt::Debug for ChunkListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ChunkListener")
+    }
+}
+
+/// Callback invoked with the [`UsageRecord`] of each request.
+#[derive(Clone)]
+pub struct UsageListener(Arc<dyn Fn(UsageRecord) + Send + Sync>);
+
+impl UsageListener {
+    pub fn new(f: impl Fn(UsageRecord) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn notify(&self, usage: UsageRecord) {
+        (self.0)(usage)
+    }
+}
+
+impl std::fmt::Debug for UsageListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UsageListener")
+    }
+}
+
+/// Callback invoked once a request's audio has been handed to the connection.
+#[derive(Clone)]
+pub struct UploadListener(Arc<dyn Fn() + Send + Sync>);
+
+impl UploadListener {
+    pub fn new(f: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn notify(&self) {
+        (self.0)()
+    }
+}
+
+impl std::fmt::Debug for UploadListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UploadListener")
+    }
+}
+
+impl TranscriptionOptions {
+    /// The audio file part of a request. With `on_upload` set it's streamed,
+    /// so the listener hears when the last chunk has been sent.
+    pub fn audio_part(&self, bytes: Vec<u8>) -> Part {
+        let Some(listener) = self.on_upload.clone() else {
+            return Part::bytes(bytes);
+        };
+        let len = bytes.len() as u64;
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> =
+            bytes.chunks(UPLOAD_CHUNK_BYTES).map(|c| Ok(c.to_vec())).collect();
+        let sent = futures_util::stream::poll_fn(move |_| {
+            listener.notify();
+            Poll::Ready(None)
+        });
+        let body = reqwest::Body::wrap_stream(futures_util::stream::iter(chunks).chain(sent));
+        Part::stream_with_length(body, len)
+    }
+
+    /// Report a successful request for `audio` (a WAV file) to `on_usage`.
+    pub fn report_usage(&self, provider: &str, model: &str, audio: &[u8]) {
+        if let Some(ref listener) = self.on_usage {
+            let secs = wav_duration_secs(audio).unwrap_or(0.0);
+            listener.notify(UsageRecord::transcription(provider, model, secs));
+        }
+    }
+}
+
+/// Connect and whole-request limits for transcription HTTP calls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpTimeouts {
+    pub connect: Duration,
+    pub request: Duration,
+}
+
+impl HttpTimeouts {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            connect: Duration::from_secs(config.http_connect_timeout_secs.max(1)),
+            request: Duration::from_secs(config.http_request_timeout_secs.max(1)),
+        }
+    }
+
+    /// A reqwest client enforcing these limits.
+    pub fn client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .connect_timeout(self.connect)
+            .timeout(self.request)
+            .build()
+            .unwrap_or_else(|e| {
+                log::warn!("HTTP client with timeouts failed to build, using defaults: {}", e);
+                reqwest::Client::new()
+            })
+    }
+
+    /// Turn a reqwest timeout into a message the user can act on; other
+    /// errors pass through unchanged.
+    pub fn describe(&self, err: reqwest::Error) -> Box<dyn Error + Send + Sync> {
+        if !err.is_timeout() {
+            return err.into();
+        }
+        if err.is_connect() {
+            Unreachable(format!("Could not connect within {}s", self.connect.as_secs())).into()
+        } else {
+            Unreachable(format!(
+                "Transcription request timed out after {}s",
+                self.request.as_secs()
+            ))
+            .into()
+        }
+    }
+}
+
+/// The provider's server couldn't be reached or didn't answer in time.
+#[derive(Debug)]
+pub struct Unreachable(pub String);
+
+impl std::fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for Unreachable {}
+
+/// Whether a transcription failed because the network or the server was
+/// down, as opposed to a rejected key or a bad request.
+pub fn is_unreachable(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    if err.downcast_ref::<Unreachable>().is_some() {
+        return true;
+    }
+    err.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+}
+
+/// A finished transcription and the backend that produced it.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub text: String,
+    pub provider: &'static str,
+    /// The configured provider, when it failed and this one stood in
+    pub fallback_from: Option<&'static str>,
+}
+
+/// Speech-to-text backend. Implementations must be cheap to share across tasks.
+pub trait TranscriptionProvider: Send + Sync {
+    /// Short identifier used in config and logs (e.g. "groq").
+    fn name(&self) -> &'static str;
+
+    fn has_api_key(&self) -> bool;
+
+    /// Check the key with a cheap authenticated request, so a bad key is
+    /// reported at startup instead of on the first dictation.
+    fn validate_api_key(&self) -> BoxFuture<'_, KeyStatus>;
+
+    /// Transcribe a complete WAV recording.
+    fn transcribe<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult>;
+
+    /// Transcribe the recording so far, for live partial results. Batch
+    /// providers re-run `transcribe` on the growing buffer; a streaming
+    /// backend can override this with something cheaper.
+    fn transcribe_partial<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult> {
+        self.transcribe(audio, opts)
+    }
+}
+
+/// Providers tried in order: when one fails for good (after its own
+/// retries), the recording is sent to the next. The [`Transcript`] names
+/// whichever one produced it.
+///
+/// With an offline provider, a failure to reach the server skips the rest
+/// of the chain (it would be unreachable too) and goes to the local model.
+pub struct FallbackChain {
+    /// Each provider with the model it uses when it isn't first
+    providers: Vec<(Arc<dyn TranscriptionProvider>, String)>,
+    /// Local provider for when the network is down
+    offline: Option<(Arc<dyn TranscriptionProvider>, String)>,
+}
+
+impl FallbackChain {
+    fn primary(&self) -> &dyn TranscriptionProvider {
+        self.providers[0].0.as_ref()
+    }
+}
+
+impl TranscriptionProvider for FallbackChain {
+    fn name(&self) -> &'static str {
+        self.primary().name()
+    }
+
+    fn has_api_key(&self) -> bool {
+        self.primary().has_api_key()
+    }
+
+    fn validate_api_key(&self) -> BoxFuture<'_, KeyStatus> {
+        self.primary().validate_api_key()
+    }
+
+    fn transcribe<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult> {
+        Box::pin(async move {
+            let mut failures = Vec::new();
+            for (i, (provider, model)) in self.providers.iter().enumerate() {
+                let result = if i == 0 {
+                    // The options' model (which a profile may override) is
+                    // meant for the primary provider
+                    provider.transcribe(audio, opts).await
+                } else {
+                    let opts = TranscriptionOptions {
+                        model: Some(model.clone()),
+                        ..opts.clone()
+                    };
+                    provider.transcribe(audio, &opts).await
+                };
+                match result {
+                    Ok(mut transcript) => {
+                        if i > 0 {
+                            log::info!("Transcribed by fallback provider {}", transcript.provider);
+                            transcript.fallback_from = Some(self.primary().name());
+                        }
+                        return Ok(transcript);
+                    }
+                    Err(e) => {
+                        log::warn!("{} transcription failed: {}", provider.name(), e);
+                        failures.push(format!("{}: {}", provider.name(), e));
+                        if self.offline.is_some() && is_unreachable(e.as_ref()) {
+                            break;
+                        }
+                    }
+                }
+            }
+            let Some((ref offline, ref model)) = self.offline else {
+                return Err(failures.join("; ").into());
+            };
+            log::info!("Transcription servers unreachable, transcribing locally with {}", offline.name());
+            let opts = TranscriptionOptions {
+                model: Some(model.clone()),
+                ..opts.clone()
+            };
+            match offline.transcribe(audio, &opts).await {
+                Ok(mut transcript) => {
+                    transcript.fallback_from = Some(self.primary().name());
+                    Ok(transcript)
+                }
+                Err(e) => {
+                    failures.push(format!("{}: {}", offline.name(), e));
+                    Err(failures.join("; ").into())
+                }
+            }
+        })
+    }
+
+    /// Partial results are only previews; a failure there isn't worth a
+    /// request to the next provider.
+    fn transcribe_partial<'a>(
+        &'a self,
+        audio: &'a [u8],
+        opts: &'a TranscriptionOptions,
+    ) -> BoxFuture<'a, TranscriptionResult> {
+        self.primary().transcribe_partial(audio, opts)
+    }
+}
+
+/// Construct the provider selected by `config.provider`, followed by the
+/// ones in `config.fallback_providers` that have an API key, and the local
+/// model when `config.offline_fallback` is on. Unknown values fall back to
+/// Groq with a warning.
+pub fn from_config(config: &Config) -> Arc<dyn TranscriptionProvider> {
+    let primary = match build(config, &config.provider) {
+        Some(provider) => provider,
+        None => {
+            log::warn!("Unknown transcription provider '{}', using groq", config.provider);
+            build(config, "groq").expect("groq is a known provider")
+        }
+    };
+
+    let mut providers = vec![(primary, config.transcription_model().to_string())];
+    for name in &config.fallback_providers {
+        if providers.iter().any(|(p, _)| p.name() == name) {
+            continue;
+        }
+        match build(config, name) {
+            Some(provider) if provider.has_api_key() => {
+                providers.push((provider, config.model_for(name).to_string()));
+            }
+            Some(_) => log::warn!("Fallback provider {} has no API key, skipping", name),
+            None => log::warn!("Unknown fallback provider '{}', skipping", name),
+        }
+    }
+    let offline = if config.offline_fallback && !providers.iter().any(|(p, _)| p.name() == "candle") {
+        build(config, "candle").map(|p| (p, config.candle_model.clone()))
+    } else {
+        None
+    };
+    if providers.len() == 1 && offline.is_none() {
+        return providers.remove(0).0;
+    }
+    log::info!(
+        "Transcription fallback chain: {}{}",
+        providers.iter().map(|(p, _)| p.name()).collect::<Vec<_>>().join(" -> "),
+        if offline.is_some() { " (candle when offline)" } else { "" }
+    );
+    Arc::new(FallbackChain { providers, offline })
+}
+
+/// A client for the provider called `name`, if there is one.
+fn build(config: &Config, name: &str) -> Option<Arc<dyn TranscriptionProvider>> {
+    let format = UploadFormat::from_config(&config.upload_format);
+    let timeouts = HttpTimeouts::from_config(config);
+    match name {
+        "groq" => Some(Arc::new(
+            GroqClient::new(config.api_key.clone())
+                .with_upload_format(format)
+                .with_timeouts(timeouts),
+        )),
+        "openai" => Some(Arc::new(
+            OpenAiClient::new(config.openai_api_key.clone())
+                .with_upload_format(format)
+                .with_timeouts(timeouts),
+        )),
+        #[cfg(feature = "candle")]
+        "candle" => Some(Arc::new(CandleWhisper::new(
+            config.candle_model.clone(),
+            config.candle_device.clone(),
+        ))),
+        #[cfg(not(feature = "candle"))]
+        "candle" => {
+            log::warn!("This build has no local Whisper; rebuild with `--features candle`");
+            None
+        }
+        _ => None,
+    }
+}
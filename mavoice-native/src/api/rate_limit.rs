@@ -0,0 +1,175 @@
+use reqwest::header::HeaderMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Below this many remaining requests or tokens, requests go out one at a
+/// time so the last of the budget isn't raced away.
+const NEAR_LIMIT: u64 = 2;
+
+/// Longest a request is held back for a reset time the server announced.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// What the server last said about one limit (requests or tokens).
+#[derive(Debug, Default, Clone, Copy)]
+struct Budget {
+    remaining: Option<u64>,
+    resets_at: Option<Instant>,
+}
+
+impl Budget {
+    fn read(headers: &HeaderMap, kind: &str, now: Instant) -> Option<Self> {
+        let remaining = header(headers, &format!("x-ratelimit-remaining-{}", kind))
+            .and_then(|v| v.parse().ok());
+        let resets_at = header(headers, &format!("x-ratelimit-reset-{}", kind))
+            .and_then(parse_reset)
+            .map(|d| now + d);
+        (remaining.is_some() || resets_at.is_some()).then_some(Self {
+            remaining,
+            resets_at,
+        })
+    }
+
+    /// Time left until the budget refills, if it is used up.
+    fn wait(&self, now: Instant) -> Option<Duration> {
+        if self.remaining != Some(0) {
+            return None;
+        }
+        self.resets_at
+            .filter(|t| *t > now)
+            .map(|t| (t - now).min(MAX_WAIT))
+    }
+
+    fn near(&self, now: Instant) -> bool {
+        self.remaining.is_some_and(|r| r <= NEAR_LIMIT)
+            && self.resets_at.is_none_or(|t| t > now)
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimitState {
+    requests: Budget,
+    tokens: Budget,
+    /// Set from Retry-After on a 429: nobody goes before this
+    blocked_until: Option<Instant>,
+}
+
+/// Request queue in front of an API that reports its rate limits in
+/// `x-ratelimit-*` headers (Groq, OpenAI).
+///
+/// Requests wait their turn in FIFO order. While plenty of budget is left
+/// they go out right away; near the limit they are serialized, and once it
+/// is used up (or after a 429) they wait for the announced reset.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    queue: tokio::sync::Mutex<()>,
+    state: Mutex<LimitState>,
+}
+
+/// Held while a request is in flight. Near the limit it keeps the queue
+/// locked so the next request sees this one's response headers first.
+pub struct Turn<'a> {
+    _serial: Option<tokio::sync::MutexGuard<'a, ()>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until a request may be sent.
+    pub async fn acquire(&self) -> Turn<'_> {
+        let guard = self.queue.lock().await;
+        loop {
+            let now = Instant::now();
+            let wait = {
+                let state = self.state.lock().unwrap();
+                let blocked = state.blocked_until.filter(|t| *t > now).map(|t| t - now);
+                blocked
+                    .into_iter()
+                    .chain(state.requests.wait(now))
+                    .chain(state.tokens.wait(now))
+                    .max()
+            };
+            match wait {
+                Some(delay) => {
+                    log::info!("Rate limit reached, waiting {:.1}s", delay.as_secs_f32());
+                    tokio::time::sleep(delay).await;
+                    // The reset has passed; don't wait for it again
+                    let mut state = self.state.lock().unwrap();
+                    for budget in [&mut state.requests, &mut state.tokens] {
+                        if budget.resets_at.is_some_and(|t| t <= Instant::now()) {
+                            *budget = Budget::default();
+                        }
+                    }
+                }
+                None => break,
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let serial = state.requests.near(now) || state.tokens.near(now);
+        // Count this request against the budget until the server says otherwise
+        if let Some(ref mut r) = state.requests.remaining {
+            *r = r.saturating_sub(1);
+        }
+        Turn {
+            _serial: serial.then_some(guard),
+        }
+    }
+
+    /// Record the limits reported in a response's headers.
+    pub fn update(&self, headers: &HeaderMap) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if let Some(requests) = Budget::read(headers, "requests", now) {
+            state.requests = requests;
+        }
+        if let Some(tokens) = Budget::read(headers, "tokens", now) {
+            state.tokens = tokens;
+        }
+    }
+
+    /// Hold every queued request back for `delay` (a 429's Retry-After).
+    pub fn block_for(&self, delay: Duration) {
+        let until = Instant::now() + delay.min(MAX_WAIT);
+        let mut state = self.state.lock().unwrap();
+        if state.blocked_until.is_none_or(|t| t < until) {
+            state.blocked_until = Some(until);
+        }
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok().map(str::trim)
+}
+
+/// Parse reset times like "7.66s", "2m59.56s", "1h2m3s" or "120ms".
+fn parse_reset(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let n: f64 = number.parse().ok()?;
+        number.clear();
+        total += match c {
+            'h' => n * 3600.0,
+            'm' if chars.peek() == Some(&'s') => {
+                chars.next();
+                n / 1000.0
+            }
+            'm' => n * 60.0,
+            's' => n,
+            _ => return None,
+        };
+    }
+    // A bare number is seconds
+    if !number.is_empty() {
+        total += number.parse::<f64>().ok()?;
+    }
+    (total.is_finite() && total >= 0.0).then(|| Duration::from_secs_f64(total))
+}
@@ -0,0 +1,101 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often and how patiently a failed request is retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each one after.
+    pub base_delay: Duration,
+    /// Upper bound for a single wait, including server-sent Retry-After.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(20),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A single attempt with no retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry number `retry` (1-based). A server Retry-After wins
+    /// over the computed backoff but is still capped at `max_delay`.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(1u32 << retry.saturating_sub(1).min(16));
+        retry_after.unwrap_or(backoff).min(self.max_delay)
+    }
+}
+
+/// Reported before each retry so the UI can show that work is still going.
+#[derive(Debug, Clone)]
+pub struct RetryNotice {
+    /// The attempt about to be made (2 = first retry).
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    pub reason: String,
+}
+
+/// Callback invoked with each [`RetryNotice`].
+#[derive(Clone)]
+pub struct RetryListener(Arc<dyn Fn(RetryNotice) + Send + Sync>);
+
+impl RetryListener {
+    pub fn new(f: impl Fn(RetryNotice) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn notify(&self, notice: RetryNotice) {
+        (self.0)(notice)
+    }
+}
+
+impl std::fmt::Debug for RetryListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RetryListener")
+    }
+}
+
+/// Rate limits and server-side failures are worth another try; other client
+/// errors (bad key, bad audio) will fail the same way again.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// Connection failures and timeouts, as opposed to e.g. a malformed request.
+pub fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout() || err.is_request()
+}
+
+/// Parse a `Retry-After` header given in seconds. HTTP-date values are
+/// ignored and the normal backoff is used instead.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .map(Duration::from_secs_f64)
+}
@@ -1,18 +1,47 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use winit::application::ApplicationHandler;
-use winit::dpi::{LogicalPosition, LogicalSize};
-use winit::event::{ElementState, MouseButton, WindowEvent};
-use winit::event_loop::ActiveEventLoop;
+use winit::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
 use winit::keyboard::{Key, NamedKey};
 use winit::window::{Window, WindowId, WindowLevel};
 
 use serde_json::json;
 
-use crate::api::gemini::{FunctionCall, FunctionResponse, GeminiEvent};
-use crate::api::{GeminiLiveClient, GroqClient};
+use crate::api::gemini::{
+    ActivityDetection, FunctionCall, FunctionResponse, GeminiAuth, GeminiEvent, GeminiSetup,
+    AUDIO_MODEL, TEXT_MODEL,
+};
+use crate::api::key_check::KeyStatus;
+use crate::api::retry::{RetryListener, RetryNotice, RetryPolicy};
+use crate::api::provider::{ChunkListener, ChunkProgress, Transcript, UsageListener};
+use crate::api::{GeminiLiveClient, TranscriptionOptions, TranscriptionProvider};
+use crate::archive::{self, SessionRecording};
+use crate::audio::vad::{GateEvent, SpeechGate};
 use crate::audio::{AudioPlayer, GroqRecorder};
-use crate::dashboard::DashboardBroadcaster;
+use crate::dashboard::{DashboardBroadcaster, DashboardCommand};
+use crate::meeting::MeetingNotes;
+use crate::privacy;
+use crate::metrics::{self, DictationTiming, Metrics};
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::conversation::ConversationLog;
+use crate::history::History;
+use crate::usage::UsageRecord;
+use crate::renderer::{AiUniforms, GpuContext, Renderer, TextOverlay, TextPlacement, ToastUniforms, UserUniforms};
+use crate::settings::{SettingsAction, SettingsWindow};
+use crate::state_machine::{OverlayState, VisualState};
+use crate::system::atspi::{Announcer, CursorContext, Politeness};
+use crate::system::ducking::AudioDucker;
+use crate::system::media::MediaPause;
+use crate::system::screenshot::{self, Screenshot};
+use crate::system::tray::{TrayAction, TrayIcon, TrayState};
+use crate::system::{HotkeyManager, TextInjector};
+use crate::tools::confirm::{self, Approval};
+use crate::tools::mcp::McpHub;
+use crate::tools::{CommandPolicy, ToolContext};
 
 /// Global storage for the Gemini client (needed because it's created in an async task
 /// but used from the winit event loop thread). Protected by Mutex.
@@ -22,10 +51,51 @@ static GEMINI_CLIENT: std::sync::LazyLock<Mutex<Option<GeminiLiveClient>>> =
 /// Global storage for the dashboard broadcast server.
 static DASHBOARD: std::sync::LazyLock<Mutex<Option<DashboardBroadcaster>>> =
     std::sync::LazyLock::new(|| Mutex::new(None));
-use crate::config::Config;
-use crate::renderer::{AiUniforms, GpuContext, Renderer, UserUniforms};
-use crate::state_machine::{OverlayState, VisualState};
-use crate::system::{HotkeyManager, TextInjector};
+
+/// Config keys that would let a dashboard client run arbitrary commands or
+/// swap the API keys. Presets are included since a preset can set any of
+/// the others; a dashboard can still switch between them with `preset:set`.
+const REMOTE_LOCKED_CONFIG_KEYS: &[&str] = &[
+    "api_key",
+    "openai_api_key",
+    "gemini_api_key",
+    "presets",
+    "active_preset",
+    "custom_tools",
+    "mcp_servers",
+    "confirm_tools",
+    "always_allow_tools",
+    "tools",
+    "run_command_allow",
+    "run_command_deny",
+    "run_command_dir",
+    "web_search_url",
+    "file_dirs",
+];
+
+/// Global storage for the MCP servers (started in the background at launch).
+static MCP_HUB: std::sync::LazyLock<Mutex<Option<Arc<McpHub>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+/// Bumped by every `start_mcp`, so a slow start that a newer one replaced
+/// doesn't install its outdated hub.
+static MCP_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Global storage for the AT-SPI focus tracker used by smart spacing.
+static CURSOR_CONTEXT: std::sync::LazyLock<Mutex<Option<Arc<CursorContext>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+/// Global storage for the screen reader announcer (`screen_reader`).
+static ANNOUNCER: std::sync::LazyLock<Mutex<Option<Arc<Announcer>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+/// Apply the capture settings from `config` to a newly opened recorder.
+fn configure_recorder(recorder: &mut GroqRecorder, config: &Config) {
+    recorder.set_max_duration(config.max_recording_secs);
+    recorder.set_preroll(config.preroll_ms);
+    recorder.set_denoise(config.denoise);
+    recorder.set_gain(config.normalize_audio, config.live_agc, config.target_dbfs);
+}
 
 /// Current time as Unix milliseconds (for dashboard event timestamps).
 fn now_ms() -> u128 {
@@ -35,6 +105,36 @@ fn now_ms() -> u128 {
         .as_millis()
 }
 
+/// Config key for a monitor: its connector name, e.g. "DP-1".
+fn monitor_key(monitor: &winit::monitor::MonitorHandle) -> String {
+    monitor.name().unwrap_or_else(|| "default".to_string())
+}
+
+/// A text injector for the config's injection method, typing delay,
+/// window rules and primary selection mode. Rules with the "atspi" method insert through the focus
+/// tracker started by `start_cursor_context`.
+pub(crate) fn make_injector(config: &Config, rt: &Arc<tokio::runtime::Runtime>) -> Result<TextInjector, String> {
+    let mut injector = TextInjector::new(&config.injection_method).map_err(|e| e.to_string())?;
+    injector.set_type_delay(config.type_delay_ms);
+    injector.set_rules(&config.injection_rules);
+    injector.set_primary_selection(&config.primary_selection);
+    let rt = rt.clone();
+    injector.set_caret_inserter(Box::new(move |text: &str| {
+        let ctx = CURSOR_CONTEXT
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("AT-SPI focus tracking is not running")?;
+        let text = text.to_string();
+        rt.block_on(tokio::time::timeout(
+            std::time::Duration::from_millis(500),
+            async move { ctx.insert_at_caret(&text).await },
+        ))
+        .map_err(|_| "The focused application did not answer over AT-SPI".to_string())?
+    }));
+    Ok(injector)
+}
+
 /// Voice mode — determines hotkey behavior.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VoiceMode {
@@ -45,12 +145,46 @@ pub enum VoiceMode {
 /// Events sent from async tasks back to the event loop
 #[derive(Debug)]
 pub enum AppEvent {
-    TranscriptionComplete(String),
-    TranscriptionError(String),
+    /// `generation` is the dictation's `transcription_generation`
+    TranscriptionComplete {
+        generation: u64,
+        transcript: Transcript,
+    },
+    TranscriptionError {
+        generation: u64,
+        error: String,
+    },
+    /// A transcription request failed transiently and will be retried
+    TranscriptionRetry(RetryNotice),
+    /// One chunk of a long recording has been transcribed
+    TranscriptionProgress(ChunkProgress),
+    /// A billable API request finished
+    Usage(UsageRecord),
+    /// Result of checking an API key ("groq", "openai" or "gemini")
+    KeyChecked {
+        service: &'static str,
+        status: KeyStatus,
+    },
+    /// config.toml changed on disk
+    ConfigFileChanged,
+    /// A custom shader file changed on disk
+    ShaderFileChanged,
+    /// One chunk of a meeting recording has been transcribed
+    MeetingChunk {
+        index: u64,
+        result: Result<Transcript, String>,
+    },
+    PartialTranscription {
+        generation: u64,
+        result: Result<String, String>,
+    },
     // Gemini Live events
     GeminiReady,
     GeminiAudio(Vec<u8>),
     GeminiText(String),
+    /// Transcription pieces of the user's speech and of Gemini's reply
+    GeminiHeard(String),
+    GeminiSaid(String),
     GeminiInterrupted,
     GeminiTurnComplete,
     GeminiToolCall(Vec<FunctionCall>),
@@ -62,7 +196,29 @@ pub enum AppEvent {
         call_id: String,
         name: String,
         result: serde_json::Value,
+        /// The call was declared non-blocking and sent progress
+        streamed: bool,
+    },
+    /// A non-blocking tool is still running
+    ToolProgress {
+        call_id: String,
+        name: String,
+        elapsed: std::time::Duration,
+        /// Output since the previous update
+        output: String,
     },
+    /// Screenshot taken for the Gemini session
+    ScreenCaptured(Result<Screenshot, String>),
+    /// User chose "Always allow" for this tool
+    ToolAlwaysAllow(String),
+    /// The user approved a call to a tool the app runs itself
+    AppToolApproved(FunctionCall),
+    /// A reminder set with `set_reminder` is due
+    ReminderDue(u64),
+    // Tray menu
+    Tray(TrayAction),
+    // Remote control from a dashboard client
+    Dashboard(DashboardCommand),
 }
 
 /// Click tracking for single/double click detection
@@ -70,14 +226,81 @@ struct ClickState {
     count: u32,
     timer: Option<std::time::Instant>,
     cooldown_until: Option<std::time::Instant>,
+    /// Touchpad scrolling not yet worth a scroll step, in pixels
+    scroll: f64,
 }
 
+/// Touchpad scroll distance that counts as one wheel notch.
+const SCROLL_STEP_PX: f64 = 40.0;
+
 /// In-app Alt double-press tracking
 struct AltPressState {
     count: u32,
     timer: Option<std::time::Instant>,
 }
 
+/// Partial transcriptions are only requested within this recording length.
+const PARTIAL_MIN_SECS: f64 = 1.0;
+const PARTIAL_MAX_SECS: f64 = 120.0;
+
+/// Quiet period after the last move of a dragged strip before it's saved.
+const STRIP_SAVE_DELAY_MS: u64 = 500;
+
+/// A push-to-talk release only ends the turn if no press follows within
+/// this long (X11 auto-repeat sends release/press pairs while a key is held).
+const TALK_RELEASE_GRACE_MS: u64 = 80;
+
+/// Height of an auto-hidden strip in "shrink" mode, in logical pixels:
+/// enough to hover at the screen edge.
+const AUTOHIDE_STRIP_PX: f64 = 3.0;
+
+/// Size of the typed-text bubble, in logical pixels: three lines of text.
+const TOAST_WIDTH: f64 = 560.0;
+const TOAST_HEIGHT: f64 = 76.0;
+/// Space between the bubble and the strip
+const TOAST_GAP: f64 = 8.0;
+
+/// Quiet time after Gemini's audio before ducked streams come back up.
+const DUCK_RELEASE_MS: u64 = 800;
+
+/// How often a non-blocking tool call reports progress to Gemini.
+const TOOL_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Longest delay `set_reminder` accepts (a day).
+const REMINDER_MAX_MINUTES: f64 = 24.0 * 60.0;
+
+/// Sessions listed in the dashboard's conversation view
+const HISTORY_LIST_LIMIT: usize = 50;
+
+/// Built-in shaders, also the fallback when a custom one fails to compile
+const USER_SHADER: &str = include_str!("shader.wgsl");
+const AI_SHADER: &str = include_str!("ai_shader.wgsl");
+const TOAST_SHADER: &str = include_str!("toast_shader.wgsl");
+const STATIC_SHADER: &str = include_str!("static_shader.wgsl");
+
+/// Opacity of the black plate behind the overlay in high-contrast mode
+const HIGH_CONTRAST_BACKDROP: f32 = 0.85;
+
+/// Event loop wake-up interval in headless mode.
+const HEADLESS_TICK_MS: u64 = 30;
+
+/// The overlay starts shifting to the warning color this long before the
+/// recording limit.
+const LIMIT_WARN_SECS: f64 = 30.0;
+
+/// Live partial transcription progress for the current dictation.
+#[derive(Default)]
+struct PartialState {
+    /// Bumped per recording so late results from an earlier one are dropped
+    generation: u64,
+    in_flight: bool,
+    last_request: Option<std::time::Instant>,
+    /// Words of the previous partial, to see which ones stopped changing
+    last_words: Vec<String>,
+    /// Words already pasted ahead of the final transcript
+    injected: Vec<String>,
+}
+
 pub struct App {
     // User window (bottom strip) — existing waveform
     user_window: Option<Arc<Window>>,
@@ -87,12 +310,20 @@ pub struct App {
     ai_window: Option<Arc<Window>>,
     ai_window_id: Option<WindowId>,
     ai_renderer: Option<Renderer>,
+    // Toast window — typed text after a dictation, hidden otherwise
+    toast_window: Option<Arc<Window>>,
+    toast_window_id: Option<WindowId>,
+    toast_renderer: Option<Renderer>,
+    toast_visible: bool,
     // Shared GPU context
     gpu: Option<GpuContext>,
     // Visual state
     visual: VisualState,
     recorder: Arc<Mutex<GroqRecorder>>,
-    groq_client: GroqClient,
+    /// `input_device` changed while recording; the recorder is reopened
+    /// once it stops
+    recorder_stale: bool,
+    transcriber: Arc<dyn TranscriptionProvider>,
     text_injector: TextInjector,
     config: Config,
     hotkey_manager: Option<HotkeyManager>,
@@ -102,6 +333,19 @@ pub struct App {
     alt_state: AltPressState,
     last_transcript: String,
     is_dragging: bool,
+    /// Last move of a dragged strip; its position is saved once moves settle
+    strip_moved_at: Option<std::time::Instant>,
+    /// When the next animation frame is due under `max_fps`
+    next_frame: std::time::Instant,
+    /// A right-click drag happened, so the next moves are the user's
+    strip_dragged: bool,
+    /// Auto-hidden after idling: `Some(true)` made invisible, `Some(false)`
+    /// shrunk to a sliver
+    strip_hidden: Option<bool>,
+    /// Since when the strip has had nothing to show, for auto-hide
+    idle_since: Option<std::time::Instant>,
+    /// The cursor is over the strip, which keeps it shown
+    cursor_on_strip: bool,
     /// Window ID of the app that was focused before overlay interaction
     previous_window_id: Option<String>,
     // Gemini Live fields
@@ -110,24 +354,105 @@ pub struct App {
     recording_mode: Option<VoiceMode>,
     audio_player: Option<AudioPlayer>,
     gemini_connecting: bool,
+    /// Transcription of the current Gemini turn so far: the user's speech
+    /// and Gemini's spoken reply
+    gemini_heard: String,
+    gemini_said: String,
+    /// The current Gemini session replies with text, which is typed out
+    gemini_text_replies: bool,
+    /// The current Gemini session uses push-to-talk instead of server VAD
+    gemini_ptt: bool,
+    /// Local VAD deciding what mic audio the current Gemini session gets,
+    /// instead of server VAD
+    speech_gate: Option<Arc<Mutex<SpeechGate>>>,
+    /// Mic audio of the current Gemini session, with `archive_recordings`
+    session_recording: Option<Arc<Mutex<SessionRecording>>>,
+    /// Last pass over the recording archive's retention limits
+    archive_cleaned_at: Option<std::time::Instant>,
+    /// Push-to-talk key is held; mic audio only goes out while set
+    gemini_talking: Arc<AtomicBool>,
+    /// Push-to-talk key came up; the turn ends after a short grace period
+    talk_released_at: Option<std::time::Instant>,
+    /// Session log when `gemini_transcript = "log"`
+    conversation: Option<ConversationLog>,
+    /// Gemini session history, if the database could be opened
+    history: Option<History>,
+    /// Row of the running Gemini session in `history`, created with its
+    /// first turn or tool call
+    history_session: Option<i64>,
     /// IDs of tool calls currently in flight (for cancellation tracking)
     pending_tool_calls: HashSet<String>,
+    /// Settings window, when open
+    settings: Option<SettingsWindow>,
+    tray: Option<TrayIcon>,
+    /// Global hotkeys are ignored while paused (from the tray menu)
+    hotkeys_paused: bool,
+    partial: PartialState,
+    /// Active meeting recording, kept until its last chunk is written
+    meeting: Option<MeetingNotes>,
+    /// Dictation is appended to the daily notes file instead of pasted
+    note_mode: bool,
+    /// Reminders waiting to fire, keyed by id
+    reminders: HashMap<u64, String>,
+    next_reminder_id: u64,
+    /// Keeps config.toml watched for live reload while alive
+    config_watcher: Option<notify::RecommendedWatcher>,
+    /// One per custom shader file in use
+    shader_watchers: Vec<notify::RecommendedWatcher>,
+    /// Per-application profile matched for the current dictation target
+    app_profile: Option<String>,
+    /// Config as stored on disk; `config` is this with the active preset and
+    /// command-line overrides applied
+    base_config: Config,
+    /// Command-line flags for this session
+    cli: Cli,
+    /// `resumed` has run (windows may not exist in headless mode)
+    started: bool,
+    /// MPRIS worker, spawned the first time `pause_media` is needed
+    media: Option<MediaPause>,
+    /// Players were paused for the current recording or session
+    media_paused: bool,
+    /// pactl worker, spawned the first time `duck_audio` is needed
+    ducker: Option<AudioDucker>,
+    /// Other streams are turned down while Gemini speaks
+    ducked: bool,
+    /// When Gemini's audio last played, so gaps between chunks don't
+    /// bounce the other streams' volume
+    last_speech: Option<std::time::Instant>,
+    /// The pending final transcription, aborted by `cancel_dictation`
+    transcription: Option<tokio::task::JoinHandle<()>>,
+    /// Bumped per dictation and on cancel, so a result that was already
+    /// queued when its task was aborted is dropped
+    transcription_generation: u64,
+    /// Rolling latencies broadcast as `metrics:*` events
+    metrics: Metrics,
+    dictation_timing: DictationTiming,
+    /// Last speech heard from the user in the current Gemini turn (or the
+    /// text turn sent), for the first-audio latency
+    gemini_turn_ended: Option<std::time::Instant>,
 }
 
 impl App {
     pub fn new(
         tokio_rt: Arc<tokio::runtime::Runtime>,
         event_proxy: winit::event_loop::EventLoopProxy<AppEvent>,
+        cli: Cli,
     ) -> Self {
-        let config = Config::load();
-
-        let recorder = GroqRecorder::new().expect("Failed to init audio recorder");
-        let groq_client = GroqClient::new(config.api_key.clone());
-        let text_injector = TextInjector::new().expect("Failed to init text injector");
-
-        if !groq_client.has_api_key() {
+        let base_config = Config::load();
+        let config = cli.apply(base_config.with_active_preset());
+        privacy::set(config.privacy_mode);
+
+        let mut recorder =
+            GroqRecorder::new(&config.input_device).expect("Failed to init audio recorder");
+        configure_recorder(&mut recorder, &config);
+        let transcriber = crate::api::provider::from_config(&config);
+        let text_injector = make_injector(&config, &tokio_rt).expect("Failed to init text injector");
+
+        log::info!("Transcription provider: {}", transcriber.name());
+        if !transcriber.has_api_key() {
             log::warn!(
-                "No API key set! Edit {} or set GROQ_API_KEY env var",
+                "No {} API key set! Edit {} or set the matching env var",
+                transcriber.name(),
                 Config::config_path().display()
             );
         }
@@ -145,10 +470,15 @@ impl App {
             ai_window: None,
             ai_window_id: None,
             ai_renderer: None,
+            toast_window: None,
+            toast_window_id: None,
+            toast_renderer: None,
+            toast_visible: false,
             gpu: None,
             visual: VisualState::new(),
             recorder: Arc::new(Mutex::new(recorder)),
-            groq_client,
+            recorder_stale: false,
+            transcriber,
             text_injector,
             config,
             hotkey_manager: None,
@@ -158,6 +488,7 @@ impl App {
                 count: 0,
                 timer: None,
                 cooldown_until: None,
+                scroll: 0.0,
             },
             alt_state: AltPressState {
                 count: 0,
@@ -165,12 +496,56 @@ impl App {
             },
             last_transcript: String::new(),
             is_dragging: false,
+            strip_moved_at: None,
+            next_frame: std::time::Instant::now(),
+            strip_dragged: false,
+            strip_hidden: None,
+            idle_since: None,
+            cursor_on_strip: false,
             previous_window_id: None,
             mode: initial_mode,
             recording_mode: None,
             audio_player: None,
             gemini_connecting: false,
+            gemini_heard: String::new(),
+            gemini_said: String::new(),
+            conversation: None,
+            history: History::open(&History::default_path())
+                .map_err(|e| log::warn!("Gemini history disabled: {}", e))
+                .ok(),
+            history_session: None,
+            gemini_text_replies: false,
+            gemini_ptt: false,
+            speech_gate: None,
+            session_recording: None,
+            archive_cleaned_at: None,
+            gemini_talking: Arc::new(AtomicBool::new(false)),
+            talk_released_at: None,
             pending_tool_calls: HashSet::new(),
+            settings: None,
+            tray: None,
+            hotkeys_paused: false,
+            partial: PartialState::default(),
+            meeting: None,
+            note_mode: false,
+            reminders: HashMap::new(),
+            next_reminder_id: 0,
+            config_watcher: None,
+            shader_watchers: Vec::new(),
+            app_profile: None,
+            base_config,
+            cli,
+            started: false,
+            media: None,
+            media_paused: false,
+            ducker: None,
+            ducked: false,
+            last_speech: None,
+            transcription: None,
+            transcription_generation: 0,
+            metrics: Metrics::default(),
+            dictation_timing: DictationTiming::default(),
+            gemini_turn_ended: None,
         }
     }
 
@@ -189,18 +564,283 @@ impl App {
             log::info!("Captured previous window: {}", id);
         }
 
+        self.app_profile = self.previous_window_id.as_deref().and_then(|id| {
+            let classes = self.text_injector.get_window_classes(id);
+            self.config.app_profile_for(&classes)
+        });
+        if let Some(ref name) = self.app_profile {
+            log::info!("Using profile for {}", name);
+        }
+
+        if !self.transcriber.has_api_key() {
+            let message = format!("No {} API key", self.transcriber.name());
+            self.announce(&format!("Error: {}", message), Politeness::Assertive);
+            self.visual.show_status(message);
+        }
+
         log::info!("Starting recording");
         if let Err(e) = self.recorder.lock().unwrap().start_recording() {
             log::error!("Failed to start recording: {}", e);
+            self.show_error(format!("Microphone: {}", e));
             return;
         }
+        self.partial = PartialState {
+            generation: self.partial.generation + 1,
+            ..Default::default()
+        };
         self.visual.set_state(OverlayState::Recording);
+        self.announce("Recording started", Politeness::Polite);
+    }
+
+    /// Send the recording so far for a partial transcript, at most once per
+    /// `partial_interval_ms` and never with a request still outstanding.
+    fn maybe_request_partial(&mut self) {
+        if self.partial.in_flight {
+            return;
+        }
+        let interval = std::time::Duration::from_millis(self.config.partial_interval_ms);
+        if self.partial.last_request.is_some_and(|t| t.elapsed() < interval) {
+            return;
+        }
+        self.partial.last_request = Some(std::time::Instant::now());
+
+        let Some((wav_data, secs)) = self.recorder.lock().unwrap().snapshot_wav() else {
+            return;
+        };
+        if !(PARTIAL_MIN_SECS..=PARTIAL_MAX_SECS).contains(&secs) {
+            return;
+        }
+        self.partial.in_flight = true;
+
+        let generation = self.partial.generation;
+        let transcriber = self.transcriber.clone();
+        let proxy = self.event_proxy.clone();
+        let mut opts = TranscriptionOptions::from_config(&self.dictation_config());
+        // A late partial is useless; the next interval will ask again
+        opts.retry = RetryPolicy::none();
+        self.track_usage(&mut opts);
+
+        self.tokio_rt.spawn(async move {
+            let result = transcriber
+                .transcribe_partial(&wav_data, &opts)
+                .await
+                .map(|t| t.text)
+                .map_err(|e| e.to_string());
+            let _ = proxy.send_event(AppEvent::PartialTranscription { generation, result });
+        });
+    }
+
+    fn handle_partial_result(&mut self, generation: u64, result: Result<String, String>) {
+        if generation != self.partial.generation {
+            return;
+        }
+        self.partial.in_flight = false;
+
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                log::warn!("Partial transcription failed: {}", e);
+                return;
+            }
+        };
+        // The final transcript is already on its way
+        if !self.is_recording() {
+            return;
+        }
+
+        log::debug!("Partial: {}", privacy::redact(&text));
+        self.broadcast_dashboard("groq:partial", json!({
+            "text": text,
+            "timestamp": now_ms(),
+        }));
+
+        let words: Vec<String> = text.split_whitespace().map(String::from).collect();
+
+        // Post-processing can rewrite earlier words, so only paste early without it.
+        let config = self.dictation_config();
+        if config.partial_inject && !config.rewrites_transcript() && !self.note_mode {
+            let stable = words
+                .iter()
+                .zip(&self.partial.last_words)
+                .take_while(|(a, b)| a == b)
+                .count();
+            let done = self.partial.injected.len();
+            if stable > done && words[..done] == self.partial.injected[..] {
+                let delta = words[done..stable].join(" ");
+                let chunk = if done > 0 { format!(" {}", delta) } else { delta };
+                match self.inject_dictation(&chunk, &config) {
+                    Ok(()) => self.partial.injected = words[..stable].to_vec(),
+                    Err(e) => log::error!("Partial injection failed: {}", e),
+                }
+            }
+        }
+
+        self.partial.last_words = words;
+    }
+
+    /// Tint the overlay as dictation nears `max_recording_secs` and stop it
+    /// once the limit is hit, so a forgotten session still gets transcribed.
+    fn check_recording_limit(&mut self) {
+        let Some(secs) = self.recorder.lock().unwrap().recorded_secs() else {
+            return;
+        };
+        // 0 = no limit
+        if self.config.max_recording_secs == 0 {
+            self.visual.limit_warning = 0.0;
+            return;
+        }
+        let max = self.config.max_recording_secs as f64;
+        if secs >= max {
+            log::warn!("Recording reached {}s limit, auto-stopping", self.config.max_recording_secs);
+            self.stop_recording_and_transcribe();
+            return;
+        }
+        let warn = LIMIT_WARN_SECS.min(max * 0.25);
+        self.visual.limit_warning = ((secs - (max - warn)) / warn).clamp(0.0, 1.0) as f32;
+    }
+
+    // ── Meeting mode ─────────────────────────────────────────────────
+
+    /// True while a meeting is being recorded (not just finishing up).
+    fn meeting_recording(&self) -> bool {
+        self.meeting.as_ref().is_some_and(|m| !m.stopping)
+    }
+
+    fn start_meeting(&mut self) {
+        if self.meeting.is_some() || self.is_recording() || self.gemini_session_active() {
+            log::warn!("Cannot start a meeting while another session is active");
+            return;
+        }
+        let notes = match MeetingNotes::create(&self.config.meeting_notes_dir()) {
+            Ok(notes) => notes,
+            Err(e) => {
+                log::error!("Meeting notes unavailable: {}", e);
+                return;
+            }
+        };
+
+        {
+            let mut recorder = self.recorder.lock().unwrap();
+            // Only one chunk is ever buffered; leave room past the interval
+            recorder.set_max_duration(self.config.meeting_chunk_mins.max(1) * 60 * 2);
+            if let Err(e) = recorder.start_recording() {
+                log::error!("Failed to start meeting recording: {}", e);
+                recorder.set_max_duration(self.config.max_recording_secs);
+                return;
+            }
+        }
+
+        log::info!("Meeting started");
+        self.broadcast_dashboard("meeting:start", json!({
+            "path": notes.path().display().to_string(),
+            "timestamp": now_ms(),
+        }));
+        self.meeting = Some(notes);
+        self.recording_mode = None;
+        self.visual.set_state(OverlayState::Meeting);
+        self.announce("Meeting recording started", Politeness::Polite);
+    }
+
+    /// Stop recording and transcribe the final chunk. The notes stay open
+    /// until every outstanding chunk has been written.
+    fn stop_meeting(&mut self) {
+        if !self.meeting_recording() {
+            return;
+        }
+        let wav = {
+            let mut recorder = self.recorder.lock().unwrap();
+            let wav = recorder.stop_recording().ok();
+            recorder.set_max_duration(self.config.max_recording_secs);
+            wav
+        };
+        log::info!("Meeting stopped, finishing transcription");
+        self.transcribe_meeting_chunk(wav);
+        if let Some(ref mut notes) = self.meeting {
+            notes.stopping = true;
+        }
+        self.visual.set_state(OverlayState::Processing);
+        self.finish_meeting_if_done();
+    }
+
+    fn transcribe_meeting_chunk(&mut self, wav: Option<Vec<u8>>) {
+        let Some(ref mut notes) = self.meeting else {
+            return;
+        };
+        let index = notes.cut_chunk();
+        let Some(wav_data) = wav else {
+            // Nothing captured in this chunk; keep the sequence moving
+            notes.finish_chunk(index, Ok(String::new()));
+            return;
+        };
+        self.archive_recording("meeting", &wav_data);
+
+        let transcriber = self.transcriber.clone();
+        let proxy = self.event_proxy.clone();
+        let mut opts = TranscriptionOptions::from_config(&self.config);
+        self.track_usage(&mut opts);
+        self.tokio_rt.spawn(async move {
+            let result = transcriber
+                .transcribe(&wav_data, &opts)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = proxy.send_event(AppEvent::MeetingChunk { index, result });
+        });
+    }
+
+    fn handle_meeting_chunk(&mut self, index: u64, result: Result<Transcript, String>) {
+        let Some(path) = self.meeting.as_ref().map(|m| m.path().display().to_string()) else {
+            return;
+        };
+        match &result {
+            Ok(transcript) => {
+                self.broadcast_dashboard("meeting:chunk", json!({
+                    "index": index,
+                    "text": transcript.text,
+                    "path": path,
+                    "timestamp": now_ms(),
+                }));
+                let history = self.history.as_ref().filter(|_| !self.config.privacy_mode);
+                if let Some(history) = history.filter(|_| !transcript.text.trim().is_empty()) {
+                    let Transcript { text, provider, fallback_from } = transcript;
+                    if let Err(e) = history.add_dictation(provider, *fallback_from, text) {
+                        log::warn!("[History] {}", e);
+                    }
+                }
+            }
+            Err(e) => log::error!("Meeting chunk {} failed: {}", index, e),
+        }
+        let result = result.map(|t| t.text);
+        if let Some(ref mut notes) = self.meeting {
+            notes.finish_chunk(index, result);
+        }
+        self.finish_meeting_if_done();
+    }
+
+    fn finish_meeting_if_done(&mut self) {
+        if !self.meeting.as_ref().is_some_and(|m| m.stopping && !m.outstanding()) {
+            return;
+        }
+        if let Some(notes) = self.meeting.take() {
+            log::info!("Meeting notes complete: {}", notes.path().display());
+            self.broadcast_dashboard("meeting:complete", json!({
+                "path": notes.path().display().to_string(),
+                "timestamp": now_ms(),
+            }));
+        }
+        self.visual.set_state(OverlayState::Done);
+        self.sync_tray();
+        self.request_redraw_all();
     }
 
     fn stop_recording_and_transcribe(&mut self) {
         if !self.is_recording() {
             return;
         }
+        // A meeting is never injected; every stop path ends it properly
+        if self.meeting_recording() {
+            self.stop_meeting();
+            return;
+        }
         log::info!("Stopping recording, starting transcription");
 
         let wav_data = match self.recorder.lock().unwrap().stop_recording() {
@@ -212,51 +852,245 @@ impl App {
             }
         };
 
+        self.archive_recording("dictation", &wav_data);
         self.visual.set_state(OverlayState::Processing);
-        self.broadcast_dashboard("groq:start", json!({ "timestamp": now_ms() }));
+        self.announce("Transcribing", Politeness::Polite);
+        self.dictation_timing = DictationTiming {
+            stopped: Some(std::time::Instant::now()),
+            received: None,
+        };
+        self.broadcast_dashboard("groq:start", json!({
+            "provider": self.transcriber.name(),
+            "timestamp": now_ms(),
+        }));
 
         // Spawn async transcription on tokio runtime
-        let client = self.groq_client.clone();
+        let transcriber = self.transcriber.clone();
         let proxy = self.event_proxy.clone();
-        let model = Some(self.config.model.clone());
-        let language = self.config.effective_language().map(|s| s.to_string());
-        let dictionary = self.config.effective_dictionary().map(|s| s.to_string());
-        let temperature = Some(self.config.temperature);
-        let response_format = Some(self.config.response_format.clone());
-
-        self.tokio_rt.spawn(async move {
-            match client
-                .transcribe_audio_bytes(
-                    &wav_data,
-                    "recording.wav",
-                    model.as_deref(),
-                    language.as_deref(),
-                    dictionary.as_deref(),
-                    response_format.as_deref(),
-                    temperature,
-                )
-                .await
-            {
-                Ok(text) => {
-                    let _ = proxy.send_event(AppEvent::TranscriptionComplete(text));
+        let mut opts = TranscriptionOptions::from_config(&self.dictation_config());
+        let retry_proxy = self.event_proxy.clone();
+        opts.on_retry = Some(RetryListener::new(move |notice| {
+            let _ = retry_proxy.send_event(AppEvent::TranscriptionRetry(notice));
+        }));
+        let chunk_proxy = self.event_proxy.clone();
+        opts.on_chunk = Some(ChunkListener::new(move |progress| {
+            let _ = chunk_proxy.send_event(AppEvent::TranscriptionProgress(progress));
+        }));
+        self.track_usage(&mut opts);
+
+        self.transcription_generation += 1;
+        let generation = self.transcription_generation;
+        self.transcription = Some(self.tokio_rt.spawn(async move {
+            match transcriber.transcribe(&wav_data, &opts).await {
+                Ok(transcript) => {
+                    let _ = proxy.send_event(AppEvent::TranscriptionComplete { generation, transcript });
                 }
                 Err(e) => {
-                    let _ = proxy.send_event(AppEvent::TranscriptionError(e.to_string()));
+                    let _ = proxy.send_event(AppEvent::TranscriptionError { generation, error: e.to_string() });
                 }
             }
-        });
+        }));
+    }
+
+    /// Throw away the current dictation: stop recording without
+    /// transcribing, or abort the pending request. Nothing is injected.
+    /// Meetings are left alone; stopping one keeps its notes.
+    fn cancel_dictation(&mut self) {
+        if self.meeting.is_some() {
+            return;
+        }
+        let mut cancelled = false;
+        if self.is_recording() && self.recording_mode != Some(VoiceMode::GeminiLive) {
+            let _ = self.recorder.lock().unwrap().stop_recording();
+            // Late partial results belong to the discarded recording
+            self.partial.generation += 1;
+            cancelled = true;
+        }
+        if let Some(task) = self.transcription.take() {
+            // Its result event may already be queued
+            self.transcription_generation += 1;
+            task.abort();
+            cancelled = true;
+        }
+        if !cancelled {
+            return;
+        }
+
+        log::info!("Dictation cancelled");
+        self.dictation_timing = DictationTiming::default();
+        self.partial.injected.clear();
+        self.visual.set_state(OverlayState::Idle);
+        self.announce("Dictation cancelled", Politeness::Polite);
+        self.broadcast_dashboard("groq:cancelled", json!({ "timestamp": now_ms() }));
+        self.request_redraw_all();
     }
 
     fn handle_transcription_result(&mut self, text: String) {
-        log::info!("Transcription: {}", text);
+        log::debug!("Transcription: {}", privacy::redact(&text));
+        if self.partial.injected.is_empty() {
+            if let Some(name) = self.spoken_preset(&text) {
+                self.visual.set_state(OverlayState::Idle);
+                if let Err(e) = self.switch_preset(&name) {
+                    log::warn!("[Preset] {}", e);
+                }
+                return;
+            }
+            if let Some(on) = crate::notes::spoken_toggle(&text) {
+                self.visual.set_state(OverlayState::Idle);
+                self.set_note_mode(on);
+                return;
+            }
+        }
+        let config = self.dictation_config();
+        let text = if config.remove_fillers {
+            // Whisper picks the language itself without one, so the locale is no guide
+            crate::text::FillerFilter::new(config.effective_language(), &config.fillers).apply(&text)
+        } else {
+            text
+        };
+        let text = if config.format_numbers {
+            crate::text::NumberFormatter::new(&config.effective_number_locale()).apply(&text)
+        } else {
+            text
+        };
+        let text = if config.code_dictation {
+            crate::text::CodeDictation::new(&config.code_map).apply(&text)
+        } else if config.spoken_commands {
+            crate::text::SpokenCommands::new(&config.command_map).apply(&text)
+        } else {
+            text
+        };
         self.last_transcript = text.clone();
         self.visual.set_state(OverlayState::Done);
 
+        // Skip whatever partial results already pasted
+        let injected = std::mem::take(&mut self.partial.injected);
+        let text = if injected.is_empty() {
+            text
+        } else {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            if words.len() < injected.len() || words[..injected.len()] != injected[..] {
+                log::warn!("Final transcript differs from the partial text already pasted");
+            }
+            match words.get(injected.len()..) {
+                Some(rest) if !rest.is_empty() => format!(" {}", rest.join(" ")),
+                _ => String::new(),
+            }
+        };
+        if text.is_empty() {
+            return;
+        }
+        if self.note_mode {
+            self.save_note(&text);
+            return;
+        }
+        // Partial results already took care of the start of the text, and
+        // code dictation's output must stay exactly as formatted
+        let before = if injected.is_empty() && config.smart_spacing && !config.code_dictation {
+            self.text_before_caret()
+        } else {
+            None
+        };
+        let text = match before {
+            Some(before) => crate::text::fit_to_context(&text, &before),
+            None => text,
+        };
+
         // Inject text into the previously focused window (not the overlay)
-        let target = self.previous_window_id.as_deref();
-        if let Err(e) = self.text_injector.inject_text_to(&text, target) {
+        if let Err(e) = self.inject_dictation(&text, &config) {
+            log::error!("Text injection failed: {}", e);
+            self.show_error(format!("Paste failed: {}", e));
+            return;
+        }
+        let typed = self.last_transcript.clone();
+        self.show_toast(&typed);
+        let words = typed.split_whitespace().count();
+        let summary = format!("Transcribed {} word{}", words, if words == 1 { "" } else { "s" });
+        self.announce(&summary, Politeness::Polite);
+        let timing = std::mem::take(&mut self.dictation_timing);
+        if let Some(received) = timing.received {
+            self.record_metric(metrics::INJECTION, received.elapsed());
+        }
+        if let Some(stopped) = timing.stopped {
+            self.record_metric(metrics::DICTATION, stopped.elapsed());
+        }
+    }
+
+    /// Turn note mode on or off and tell the dashboard.
+    fn set_note_mode(&mut self, on: bool) -> serde_json::Value {
+        let dir = self.config.note_mode_dir();
+        if self.note_mode != on {
+            self.note_mode = on;
+            log::info!("Note mode {} ({})", if on { "on" } else { "off" }, dir.display());
+            self.broadcast_dashboard("note:mode", json!({
+                "enabled": on,
+                "dir": dir.display().to_string(),
+                "timestamp": now_ms(),
+            }));
+        }
+        json!({ "success": true, "note_mode": on, "dir": dir.display().to_string() })
+    }
+
+    /// Append a note-mode transcript to today's notes file.
+    fn save_note(&mut self, text: &str) {
+        let dir = self.config.note_mode_dir();
+        match crate::notes::append(&dir, &self.config.note_template, text) {
+            Ok(path) => {
+                log::info!("Note saved to {}", path.display());
+                self.announce("Note saved", Politeness::Polite);
+                self.broadcast_dashboard("note:saved", json!({
+                    "text": text.trim(),
+                    "path": path.display().to_string(),
+                    "timestamp": now_ms(),
+                }));
+            }
+            Err(e) => {
+                log::error!("Note write failed: {}", e);
+                self.show_error(format!("Note not saved: {}", e));
+            }
+        }
+        self.dictation_timing = DictationTiming::default();
+    }
+
+    /// Paste the last transcript again into whatever window has focus now,
+    /// for when the first paste landed in the wrong place.
+    fn reinject_last(&mut self) -> Result<(), String> {
+        if self.last_transcript.is_empty() {
+            return Err("No transcript to paste again".to_string());
+        }
+        let target = self.text_injector.get_active_window_id();
+        log::info!("Pasting last transcript again");
+        if let Err(e) = self
+            .text_injector
+            .inject_text_to(&self.last_transcript, target.as_deref())
+        {
             log::error!("Text injection failed: {}", e);
+            self.show_error(format!("Paste failed: {}", e));
+            return Err(format!("Paste failed: {}", e));
+        }
+        Ok(())
+    }
+
+    /// The config for the current dictation, with the target application's
+    /// profile applied if one matched.
+    fn dictation_config(&self) -> Config {
+        match self.app_profile {
+            Some(ref name) => self.config.with_app_profile(name),
+            None => self.config.clone(),
+        }
+    }
+
+    /// Paste into the dictation target, using the profile's injection method
+    /// and key delay if they differ from the global ones.
+    fn inject_dictation(&self, text: &str, config: &Config) -> Result<(), String> {
+        let target = self.previous_window_id.as_deref();
+        if config.injection_method == self.config.injection_method
+            && config.type_delay_ms == self.config.type_delay_ms
+        {
+            return self.text_injector.inject_text_to(text, target).map_err(|e| e.to_string());
         }
+        let injector = make_injector(config, &self.tokio_rt)?;
+        injector.inject_text_to(text, target).map_err(|e| e.to_string())
     }
 
     fn toggle_recording(&mut self) {
@@ -274,6 +1108,86 @@ impl App {
         }
     }
 
+    /// Run a mouse gesture's configured action (`click_action` and friends).
+    fn run_gesture(&mut self, event_loop: &ActiveEventLoop, action: &str) {
+        match action {
+            "none" | "" => {}
+            "start" => {
+                if !self.is_recording() {
+                    self.start_recording();
+                }
+            }
+            "stop" => {
+                if self.is_recording() {
+                    self.stop_recording_and_transcribe();
+                } else if self.transcription.is_some() {
+                    // Clicking while it transcribes cancels
+                    self.cancel_dictation();
+                }
+            }
+            "toggle" => {
+                if self.is_recording() {
+                    self.stop_recording_and_transcribe();
+                } else {
+                    self.start_recording();
+                }
+            }
+            "cancel" => self.cancel_dictation(),
+            "mode" => {
+                let other = match self.mode {
+                    VoiceMode::Groq => VoiceMode::GeminiLive,
+                    VoiceMode::GeminiLive => VoiceMode::Groq,
+                };
+                self.handle_tray_action(event_loop, TrayAction::SetMode(other));
+            }
+            "reinject" => {
+                if let Err(e) = self.reinject_last() {
+                    log::info!("[Reinject] {}", e);
+                }
+            }
+            "settings" => self.open_settings(event_loop),
+            "volume_up" | "volume_down" => {
+                let step = if action == "volume_up" { "+5%" } else { "-5%" };
+                if let Err(e) = std::process::Command::new("pactl")
+                    .args(["set-sink-volume", "@DEFAULT_SINK@", step])
+                    .spawn()
+                {
+                    log::warn!("Cannot change the volume (pactl): {}", e);
+                }
+            }
+            other => log::warn!("Unknown mouse gesture action '{}'", other),
+        }
+    }
+
+    /// Dictation hotkey: end any Gemini session, then toggle Groq recording.
+    fn on_toggle_hotkey(&mut self) {
+        if self.meeting_recording() {
+            self.stop_meeting();
+            return;
+        }
+        if self.gemini_session_active() || self.recording_mode == Some(VoiceMode::GeminiLive) {
+            self.disconnect_gemini();
+        }
+        self.mode = VoiceMode::Groq;
+        self.recording_mode = Some(VoiceMode::Groq);
+        self.toggle_recording();
+    }
+
+    /// Gemini hotkey: drop any Groq recording, then toggle the live session.
+    fn on_mode_hotkey(&mut self) {
+        if self.meeting_recording() {
+            log::warn!("Meeting in progress; stop it before starting Gemini");
+            return;
+        }
+        if self.recording_mode == Some(VoiceMode::Groq) && self.is_recording() {
+            let _ = self.recorder.lock().unwrap().stop_recording();
+            self.visual.set_state(OverlayState::Idle);
+        }
+        self.mode = VoiceMode::GeminiLive;
+        self.recording_mode = Some(VoiceMode::GeminiLive);
+        self.toggle_gemini_session();
+    }
+
     // ── Gemini Live methods ──────────────────────────────────────────
 
     /// True if Gemini session is active (connected or connecting, mic streaming)
@@ -302,8 +1216,11 @@ impl App {
             return;
         }
 
-        if self.config.gemini_api_key.is_empty() {
-            log::error!("[Gemini] No API key! Set GEMINI_API_KEY or add gemini_api_key to config.toml");
+        if self.config.gemini_api_key.is_empty() && self.config.gemini_token_url.is_empty() {
+            log::error!("[Gemini] No API key! Set GEMINI_API_KEY or add gemini_api_key (or gemini_token_url) to config.toml");
+            self.announce("Error: No Gemini API key", Politeness::Assertive);
+            self.visual.show_status("No Gemini API key");
+            self.request_redraw_all();
             return;
         }
 
@@ -311,7 +1228,7 @@ impl App {
 
         // Init audio player if needed
         if self.audio_player.is_none() {
-            match AudioPlayer::new() {
+            match AudioPlayer::new(&self.config.output_device) {
                 Ok(player) => self.audio_player = Some(player),
                 Err(e) => {
                     log::error!("Failed to init audio player: {}", e);
@@ -322,17 +1239,56 @@ impl App {
 
         self.gemini_connecting = true;
         self.visual.set_state(OverlayState::Processing);
+        self.visual.show_status("Connecting...");
 
-        let api_key = self.config.gemini_api_key.clone();
-        let voice_name = self.config.voice_name.clone();
-        let system_instruction = self.config.system_instruction.clone();
+        let auth = if self.config.gemini_token_url.is_empty() {
+            GeminiAuth::ApiKey(self.config.gemini_api_key.clone())
+        } else {
+            GeminiAuth::TokenEndpoint(self.config.gemini_token_url.clone())
+        };
+        self.speech_gate = None;
+        if self.config.gemini_local_vad && !self.config.gemini_push_to_talk {
+            match SpeechGate::new(
+                self.config.gemini_vad_threshold,
+                self.config.gemini_prefix_padding_ms,
+                self.config.gemini_silence_ms,
+            ) {
+                Ok(gate) => self.speech_gate = Some(Arc::new(Mutex::new(gate))),
+                Err(e) => log::warn!("[Gemini] {}; using Gemini's own speech detection", e),
+            }
+        }
+        let server_vad = !self.config.gemini_push_to_talk && self.speech_gate.is_none();
+        let setup = GeminiSetup {
+            voice_name: self.config.voice_name.clone(),
+            system_instruction: self.config.system_instruction.clone(),
+            text_only: self.config.gemini_text_only,
+            activity_detection: server_vad.then(|| ActivityDetection {
+                start_sensitivity: self.config.gemini_start_sensitivity.clone(),
+                end_sensitivity: self.config.gemini_end_sensitivity.clone(),
+                prefix_padding_ms: self.config.gemini_prefix_padding_ms,
+                silence_ms: self.config.gemini_silence_ms,
+            }),
+        };
+        self.gemini_text_replies = setup.text_only;
+        self.gemini_ptt = self.config.gemini_push_to_talk;
+        self.gemini_talking.store(false, Ordering::Relaxed);
+        if self.gemini_ptt && self.config.talk_hotkey.trim().is_empty() {
+            log::warn!("[Gemini] Push-to-talk is on but talk_hotkey is empty; Gemini won't hear anything");
+        }
+        let declarations = crate::tools::declarations(&self.tool_context());
+        let model = if setup.text_only { TEXT_MODEL } else { AUDIO_MODEL };
         let proxy = self.event_proxy.clone();
 
         self.tokio_rt.spawn(async move {
             let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel::<GeminiEvent>();
 
-            match GeminiLiveClient::connect(&api_key, &voice_name, &system_instruction, event_tx)
-                .await
+            match GeminiLiveClient::connect(
+                &auth,
+                &setup,
+                declarations,
+                event_tx,
+            )
+            .await
             {
                 Ok(client) => {
                     log::info!("[Gemini] WebSocket connected, starting event bridge");
@@ -344,12 +1300,22 @@ impl App {
                                 GeminiEvent::Ready => AppEvent::GeminiReady,
                                 GeminiEvent::Audio(data) => AppEvent::GeminiAudio(data),
                                 GeminiEvent::Text(text) => AppEvent::GeminiText(text),
+                                GeminiEvent::InputTranscription(text) => AppEvent::GeminiHeard(text),
+                                GeminiEvent::OutputTranscription(text) => AppEvent::GeminiSaid(text),
                                 GeminiEvent::Interrupted => AppEvent::GeminiInterrupted,
                                 GeminiEvent::TurnComplete => AppEvent::GeminiTurnComplete,
                                 GeminiEvent::ToolCall(calls) => AppEvent::GeminiToolCall(calls),
                                 GeminiEvent::ToolCallCancellation(ids) => {
                                     AppEvent::GeminiToolCallCancellation(ids)
                                 }
+                                GeminiEvent::Usage {
+                                    input_tokens,
+                                    output_tokens,
+                                } => AppEvent::Usage(UsageRecord::gemini(
+                                    model,
+                                    input_tokens,
+                                    output_tokens,
+                                )),
                                 GeminiEvent::Error(e) => AppEvent::GeminiError(e),
                                 GeminiEvent::Closed(reason) => AppEvent::GeminiClosed(reason),
                             };
@@ -379,25 +1345,65 @@ impl App {
         log::info!("[Gemini] Starting continuous mic stream");
 
         // Set up streaming callback — sends audio to Gemini in real-time
+        // (with push-to-talk, only while the key is held; with local VAD,
+        // only around speech)
+        let ptt = self.gemini_ptt;
+        let talking = self.gemini_talking.clone();
+        let gate = self.speech_gate.clone();
+        if self.config.archive_recordings && !self.config.privacy_mode && self.session_recording.is_none() {
+            match SessionRecording::create(&self.config.archive_path()) {
+                Ok(recording) => self.session_recording = Some(Arc::new(Mutex::new(recording))),
+                Err(e) => log::warn!("[Archive] {}", e),
+            }
+        }
+        let recording = self.session_recording.clone();
         let streaming_cb: crate::audio::recorder::StreamingCallback =
             Arc::new(move |pcm_s16le: &[u8]| {
-                let guard = GEMINI_CLIENT.lock().unwrap();
-                if let Some(ref c) = *guard {
-                    c.send_audio(pcm_s16le);
+                if ptt && !talking.load(Ordering::Relaxed) {
+                    return;
                 }
-            });
-        self.recorder
+                if let Some(ref gate) = gate {
+                    let events = gate.lock().unwrap().push(pcm_s16le);
+                    let guard = GEMINI_CLIENT.lock().unwrap();
+                    let Some(ref c) = *guard else {
+                        return;
+                    };
+                    for event in events {
+                        match event {
+                            GateEvent::SpeechStart => c.send_activity_start(),
+                            GateEvent::Audio(pcm) => {
+                                c.send_audio(&pcm);
+                                if let Some(ref recording) = recording {
+                                    recording.lock().unwrap().push(&pcm);
+                                }
+                            }
+                            GateEvent::SpeechEnd => c.send_activity_end(),
+                        }
+                    }
+                    return;
+                }
+                let guard = GEMINI_CLIENT.lock().unwrap();
+                if let Some(ref c) = *guard {
+                    c.send_audio(pcm_s16le);
+                    if let Some(ref recording) = recording {
+                        recording.lock().unwrap().push(pcm_s16le);
+                    }
+                }
+            });
+        self.recorder
             .lock()
             .unwrap()
             .set_streaming_callback(Some(streaming_cb));
 
         if let Err(e) = self.recorder.lock().unwrap().start_recording() {
             log::error!("Failed to start recording: {}", e);
+            self.show_error(format!("Microphone: {}", e));
             return;
         }
 
         // Mic is live — user waveform always visible
         self.visual.set_state(OverlayState::Listening);
+        self.announce("Listening", Politeness::Polite);
     }
 
     /// Disconnect from Gemini Live and stop everything.
@@ -420,86 +1426,1288 @@ impl App {
             player.clear();
         }
 
+        self.finish_gemini_turn();
+        if let (Some(history), Some(session)) = (&self.history, self.history_session.take()) {
+            if let Err(e) = history.end_session(session) {
+                log::warn!("[History] {}", e);
+            }
+        }
+        self.gemini_ptt = false;
+        self.speech_gate = None;
+        // The streaming callback holding the other reference is gone
+        if let Some(recording) = self.session_recording.take().and_then(Arc::into_inner) {
+            recording.into_inner().unwrap().finish();
+        }
+        self.gemini_talking.store(false, Ordering::Relaxed);
+        self.talk_released_at = None;
+        if let Some(conversation) = self.conversation.take() {
+            log::info!("Conversation saved to {}", conversation.path().display());
+        }
+
         self.gemini_connecting = false;
         self.recording_mode = None;
         self.visual.set_state(OverlayState::Idle);
     }
 
+    /// Push-to-talk key down: open a user turn in the Gemini session.
+    fn talk_start(&mut self) {
+        self.talk_released_at = None;
+        if !self.gemini_ptt || self.gemini_talking.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(ref client) = *GEMINI_CLIENT.lock().unwrap() {
+            client.send_activity_start();
+        }
+        // Only now let audio through, so it can't overtake activityStart
+        self.gemini_talking.store(true, Ordering::Relaxed);
+        // Talking over Gemini interrupts it
+        if let Some(ref player) = self.audio_player {
+            player.clear();
+        }
+        self.visual.set_state(OverlayState::Listening);
+        self.request_redraw_all();
+    }
+
+    /// Push-to-talk key up (after the grace period): end the user's turn.
+    fn talk_end(&mut self) {
+        self.talk_released_at = None;
+        if !self.gemini_talking.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        if let Some(ref client) = *GEMINI_CLIENT.lock().unwrap() {
+            client.send_activity_end();
+        }
+    }
+
+    /// Screenshot the focused window in the background for the running
+    /// Gemini session; `ScreenCaptured` sends it.
+    fn share_screen(&mut self) {
+        if !self.gemini_session_active() {
+            self.visual.show_status("No Gemini session");
+            self.request_redraw_all();
+            return;
+        }
+        let window = self.text_injector.get_active_window_id();
+        let proxy = self.event_proxy.clone();
+        self.tokio_rt.spawn(async move {
+            let result = screenshot::capture(window.as_deref()).await;
+            let _ = proxy.send_event(AppEvent::ScreenCaptured(result));
+        });
+    }
+
+    /// Hand the transcript of the finished Gemini turn to the dashboard and
+    /// to wherever `gemini_transcript` sends it.
+    fn finish_gemini_turn(&mut self) {
+        let heard = std::mem::take(&mut self.gemini_heard);
+        let said = std::mem::take(&mut self.gemini_said);
+        let (heard, said) = (heard.trim(), said.trim());
+        if heard.is_empty() && said.is_empty() {
+            return;
+        }
+        self.broadcast_dashboard("voice:transcript", json!({
+            "heard": heard,
+            "said": said,
+            "timestamp": now_ms(),
+        }));
+        self.record_history(|history, session| history.add_turn(session, heard, said));
+
+        // Text replies are always typed, like a dictation result
+        let type_reply = self.gemini_text_replies || self.config.gemini_transcript == "type";
+        if type_reply && !said.is_empty() {
+            let target = self.text_injector.get_active_window_id();
+            if let Err(e) = self.text_injector.inject_text_to(said, target.as_deref()) {
+                log::error!("Text injection failed: {}", e);
+                self.show_error(format!("Paste failed: {}", e));
+            }
+        }
+
+        if self.config.gemini_transcript == "log" {
+            if self.conversation.is_none() {
+                match ConversationLog::create(&self.config.meeting_notes_dir()) {
+                    Ok(conversation) => self.conversation = Some(conversation),
+                    Err(e) => log::error!("Conversation log unavailable: {}", e),
+                }
+            }
+            if let Some(ref conversation) = self.conversation {
+                if let Err(e) = conversation.append_turn(heard, said) {
+                    log::error!("Conversation log write failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Check the transcription key and, if set, the Gemini key with a cheap
+    /// request each; results arrive as `AppEvent::KeyChecked`. A missing
+    /// Gemini key isn't reported, since Gemini is optional.
+    fn check_api_keys(&self, transcription: bool, gemini: bool) {
+        if !self.config.check_api_keys {
+            return;
+        }
+        if transcription {
+            let transcriber = self.transcriber.clone();
+            let proxy = self.event_proxy.clone();
+            self.tokio_rt.spawn(async move {
+                let status = transcriber.validate_api_key().await;
+                let service = transcriber.name();
+                let _ = proxy.send_event(AppEvent::KeyChecked { service, status });
+            });
+        }
+        // With a token endpoint the key stays on the backend
+        if gemini
+            && !self.config.gemini_api_key.is_empty()
+            && self.config.gemini_token_url.is_empty()
+        {
+            let key = self.config.gemini_api_key.clone();
+            let proxy = self.event_proxy.clone();
+            self.tokio_rt.spawn(async move {
+                let status = crate::api::gemini::validate_api_key(&key).await;
+                let _ = proxy.send_event(AppEvent::KeyChecked { service: "gemini", status });
+            });
+        }
+    }
+
+    /// Surface a failed key check on the overlay and as a desktop
+    /// notification; either way tell the dashboard.
+    fn on_key_checked(&mut self, service: &'static str, status: KeyStatus) {
+        let message = status.describe(service);
+        self.broadcast_dashboard("keys:checked", json!({
+            "service": service,
+            "status": status.kind(),
+            "message": message,
+            "timestamp": now_ms(),
+        }));
+        if status.is_valid() {
+            log::info!("{}", message);
+            return;
+        }
+        log::warn!("{}", message);
+        self.show_error(message.clone());
+        self.request_redraw_all();
+        let _ = std::process::Command::new("notify-send")
+            .args(["--app-name=maVoice", "--urgency=critical", "maVoice", &message])
+            .spawn();
+    }
+
+    /// Turn privacy mode on or off and save it, like the other tray toggles.
+    fn set_privacy(&mut self, enabled: bool) {
+        let mut new_base = self.base_config.clone();
+        new_base.privacy_mode = enabled;
+        if let Err(e) = new_base.save() {
+            log::error!("Failed to save config: {}", e);
+            return;
+        }
+        self.apply_config(new_base);
+        log::info!("Privacy mode {}", if enabled { "on" } else { "off" });
+        let message = if enabled { "Privacy mode on" } else { "Privacy mode off" };
+        self.visual.show_status(message.to_string());
+        self.announce(message, Politeness::Polite);
+        self.sync_tray();
+    }
+
+    /// Save a finished recording to the archive in the background, with
+    /// `archive_recordings`.
+    fn archive_recording(&self, kind: &'static str, wav: &[u8]) {
+        if !self.config.archive_recordings || self.config.privacy_mode {
+            return;
+        }
+        let dir = self.config.archive_path();
+        let wav = wav.to_vec();
+        self.tokio_rt.spawn_blocking(move || {
+            if let Err(e) = archive::save(&dir, kind, &wav) {
+                log::warn!("[Archive] {}", e);
+            }
+        });
+    }
+
+    /// Apply the archive's retention limits at startup and then hourly.
+    fn maybe_clean_archive(&mut self) {
+        const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+        if !self.config.archive_recordings
+            || self.archive_cleaned_at.is_some_and(|t| t.elapsed() < CLEANUP_INTERVAL)
+        {
+            return;
+        }
+        self.archive_cleaned_at = Some(std::time::Instant::now());
+        let dir = self.config.archive_path();
+        let (max_days, max_mb) = (self.config.archive_max_days, self.config.archive_max_mb);
+        self.tokio_rt.spawn_blocking(move || {
+            if let Err(e) = archive::cleanup(&dir, max_days, max_mb) {
+                log::warn!("[Archive] {}", e);
+            }
+        });
+    }
+
+    /// Report every request made with `opts` as an `AppEvent::Usage`.
+    fn track_usage(&self, opts: &mut TranscriptionOptions) {
+        let proxy = self.event_proxy.clone();
+        opts.on_usage = Some(UsageListener::new(move |usage| {
+            let _ = proxy.send_event(AppEvent::Usage(usage));
+        }));
+    }
+
+    /// Store a request's usage and push the new totals to the dashboard and
+    /// the settings badge.
+    fn record_usage(&mut self, usage: &UsageRecord) {
+        if let Some(ref history) = self.history {
+            if let Err(e) = history.add_usage(usage) {
+                log::warn!("[History] {}", e);
+            }
+        }
+        self.broadcast_dashboard("usage:request", json!({
+            "provider": usage.provider,
+            "model": usage.model,
+            "audioSecs": usage.audio_secs,
+            "tokens": usage.tokens,
+            "costUsd": usage.cost_usd,
+            "timestamp": now_ms(),
+        }));
+        self.publish_usage_totals();
+    }
+
+    /// Broadcast today/week/month usage as `usage:totals` and refresh the
+    /// settings window's badge.
+    fn publish_usage_totals(&mut self) {
+        let Some(ref history) = self.history else {
+            return;
+        };
+        let totals = match history.usage_totals() {
+            Ok(totals) => totals,
+            Err(e) => {
+                log::warn!("[History] {}", e);
+                return;
+            }
+        };
+        let mut payload = json!({ "timestamp": now_ms() });
+        for (period, t) in &totals {
+            payload[*period] = t.to_json();
+        }
+        self.broadcast_dashboard("usage:totals", payload);
+
+        let badge = self
+            .config
+            .usage_badge
+            .then(|| format!("Today: {}", totals[0].1.describe()));
+        if let Some(ref mut settings) = self.settings {
+            settings.set_usage(badge);
+        }
+    }
+
+    /// Write to the history entry of the running Gemini session, starting
+    /// one on first use. Failures are only logged.
+    fn record_history(&mut self, write: impl FnOnce(&History, i64) -> Result<(), String>) {
+        let Some(ref history) = self.history else {
+            return;
+        };
+        if self.config.privacy_mode {
+            return;
+        }
+        let session = match self.history_session {
+            Some(session) => session,
+            None => match history.start_session(self.gemini_text_replies) {
+                Ok(session) => *self.history_session.insert(session),
+                Err(e) => {
+                    log::warn!("[History] {}", e);
+                    return;
+                }
+            },
+        };
+        if let Err(e) = write(history, session) {
+            log::warn!("[History] {}", e);
+        }
+    }
+
+    /// Record how a tool call of the running session ended. Results that
+    /// come in after the session closed are dropped.
+    fn finish_history_tool_call(&self, call_id: &str, status: &str, result: Option<&serde_json::Value>) {
+        if self.config.privacy_mode {
+            return;
+        }
+        if let (Some(history), Some(session)) = (&self.history, self.history_session) {
+            if let Err(e) = history.finish_tool_call(session, call_id, status, result) {
+                log::warn!("[History] {}", e);
+            }
+        }
+    }
+
+    fn needs_confirmation(&self, name: &str) -> bool {
+        let kind = if self.config.custom_tools.iter().any(|t| t.name == name) {
+            Some("custom")
+        } else if MCP_HUB.lock().unwrap().as_ref().is_some_and(|hub| hub.has_tool(name)) {
+            Some("mcp")
+        } else {
+            None
+        };
+        confirm::matches_tool(name, kind, &self.config.confirm_tools)
+            && !confirm::matches_tool(name, kind, &self.config.always_allow_tools)
+    }
+
+    fn tool_context(&self) -> ToolContext {
+        ToolContext {
+            custom_tools: self.config.custom_tools.clone(),
+            mcp: MCP_HUB.lock().unwrap().clone(),
+            memory_enabled: self.config.memory_enabled,
+            memory_db: self.config.memory_db_path(),
+            command_policy: CommandPolicy::from_config(&self.config),
+            search_url: self.config.web_search_url(),
+            file_dirs: self.config.file_dir_paths(),
+            settings: self.config.tools.clone(),
+            streaming: self.config.tool_progress,
+        }
+    }
+
+    /// Launch the configured MCP servers in the background. Any previous
+    /// hub is dropped, which stops its server processes.
+    fn start_mcp(&self) {
+        let generation = MCP_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        MCP_HUB.lock().unwrap().take();
+        let servers = self.config.mcp_servers.clone();
+        if servers.is_empty() {
+            return;
+        }
+        self.tokio_rt.spawn(async move {
+            let hub = McpHub::start(&servers).await;
+            let mut current = MCP_HUB.lock().unwrap();
+            // Checked under the lock so a newer start can't slip in between
+            if MCP_GENERATION.load(Ordering::SeqCst) != generation {
+                log::info!("[MCP] Dropping servers from an outdated config");
+                return;
+            }
+            current.replace(Arc::new(hub));
+        });
+    }
+
+    /// (Re)start the dashboard server on `dashboard_port`, or stop it if
+    /// the dashboard is disabled.
+    fn start_dashboard(&self) {
+        if let Some(server) = DASHBOARD.lock().unwrap().take() {
+            server.shutdown();
+        }
+        if !self.config.dashboard_enabled {
+            log::info!("[Dashboard] Disabled");
+            return;
+        }
+        let port = self.config.dashboard_port;
+        let proxy = self.event_proxy.clone();
+        self.tokio_rt.spawn(async move {
+            let on_command = move |cmd| {
+                let _ = proxy.send_event(AppEvent::Dashboard(cmd));
+            };
+            match DashboardBroadcaster::start(port, on_command).await {
+                Ok(server) => {
+                    if let Some(old) = DASHBOARD.lock().unwrap().replace(server) {
+                        old.shutdown();
+                    }
+                }
+                Err(e) => {
+                    log::warn!("[Dashboard] Failed to start: {}", e);
+                    let _ = std::process::Command::new("notify-send")
+                        .args(["--app-name=maVoice", "maVoice dashboard unavailable", &e])
+                        .spawn();
+                }
+            }
+        });
+    }
+
+    fn start_announcer(&self) {
+        ANNOUNCER.lock().unwrap().take();
+        if !self.config.screen_reader {
+            return;
+        }
+        self.tokio_rt.spawn(async move {
+            match Announcer::connect().await {
+                Ok(announcer) => {
+                    ANNOUNCER.lock().unwrap().replace(Arc::new(announcer));
+                }
+                Err(e) => log::warn!("[AT-SPI] Screen reader announcements unavailable: {}", e),
+            }
+        });
+    }
+
+    /// Have the screen reader speak `text` when `screen_reader` is on.
+    fn announce(&self, text: &str, politeness: Politeness) {
+        let Some(announcer) = ANNOUNCER.lock().unwrap().clone() else {
+            return;
+        };
+        let text = text.to_string();
+        self.tokio_rt.spawn(async move {
+            if let Err(e) = announcer.announce(&text, politeness).await {
+                log::debug!("[AT-SPI] {}", e);
+            }
+        });
+    }
+
+    /// Flash the Error state with `message` and announce it.
+    fn show_error(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        self.announce(&format!("Error: {}", message), Politeness::Assertive);
+        self.visual.show_error(message);
+    }
+
+    /// Connect to the accessibility bus in the background when smart spacing
+    /// or AT-SPI injection is on; drop the connection when both are off.
+    fn start_cursor_context(&self) {
+        CURSOR_CONTEXT.lock().unwrap().take();
+        if !self.config.smart_spacing && !self.config.uses_atspi_injection() {
+            return;
+        }
+        self.tokio_rt.spawn(async move {
+            match CursorContext::connect().await {
+                Ok(ctx) => {
+                    CURSOR_CONTEXT.lock().unwrap().replace(Arc::new(ctx));
+                }
+                Err(e) => log::warn!("[AT-SPI] Cursor context unavailable: {}", e),
+            }
+        });
+    }
+
+    /// The last few characters before the caret in the focused field, if
+    /// the application exposes them. Bounded so a hung application can't
+    /// hold up the paste.
+    fn text_before_caret(&self) -> Option<String> {
+        let ctx = CURSOR_CONTEXT.lock().unwrap().clone()?;
+        self.tokio_rt
+            .block_on(tokio::time::timeout(
+                std::time::Duration::from_millis(150),
+                async move { ctx.text_before_caret(3).await },
+            ))
+            .ok()
+            .flatten()
+    }
+
+    /// Move Gemini's audio to the configured (or new default) output device.
+    /// Outside a session the player is just dropped and made again on the
+    /// next one.
+    /// Register the global hotkeys with the configured backend.
+    fn init_hotkeys(&mut self) {
+        match HotkeyManager::new(
+            &self.config.toggle_hotkey,
+            &self.config.mode_hotkey,
+            &self.config.hotkey_backend,
+        ) {
+            Ok(mut hk) => {
+                if let Err(e) = hk.set_preset_hotkey(&self.config.preset_hotkey) {
+                    log::warn!("{}", e);
+                }
+                if let Err(e) = hk.set_cancel_hotkey(&self.config.cancel_hotkey) {
+                    log::warn!("{}", e);
+                }
+                if let Err(e) = hk.set_reinject_hotkey(&self.config.reinject_hotkey) {
+                    log::warn!("{}", e);
+                }
+                if let Err(e) = hk.set_screen_hotkey(&self.config.screen_hotkey) {
+                    log::warn!("{}", e);
+                }
+                if let Err(e) = hk.set_talk_hotkey(&self.config.talk_hotkey) {
+                    log::warn!("{}", e);
+                }
+                if let Err(e) = hk.set_privacy_hotkey(&self.config.privacy_hotkey) {
+                    log::warn!("{}", e);
+                }
+                self.hotkey_manager = Some(hk);
+            }
+            Err(e) => log::warn!("Global hotkeys unavailable: {}", e),
+        }
+    }
+
+    /// Reopen the microphone after `input_device` changed.
+    fn rebuild_recorder(&mut self) {
+        self.recorder_stale = false;
+        match GroqRecorder::new(&self.config.input_device) {
+            Ok(mut recorder) => {
+                configure_recorder(&mut recorder, &self.config);
+                *self.recorder.lock().unwrap() = recorder;
+                log::info!("[Audio] Input device: {}", self.config.input_device);
+            }
+            Err(e) => log::error!("Failed to switch input device: {}", e),
+        }
+    }
+
+    fn rebuild_audio_player(&mut self) {
+        let Some(old) = self.audio_player.take() else {
+            return;
+        };
+        let old_device = old.device_name().to_string();
+        drop(old);
+        if !self.gemini_session_active() {
+            return;
+        }
+        match AudioPlayer::new(&self.config.output_device) {
+            Ok(player) => {
+                log::info!("[Audio] Output moved from {} to {}", old_device, player.device_name());
+                self.broadcast_dashboard("audio:output", json!({
+                    "device": player.device_name(),
+                    "timestamp": now_ms(),
+                }));
+                self.audio_player = Some(player);
+            }
+            Err(e) => log::error!("Failed to init audio player: {}", e),
+        }
+    }
+
+    /// Tools that change app state run here on the event loop instead of in
+    /// a task; `None` for every other tool.
+    fn run_app_tool(&mut self, call: &FunctionCall) -> Option<serde_json::Value> {
+        match call.name.as_str() {
+            crate::tools::NOTE_MODE_TOOL => {
+                let on = call.args.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+                Some(self.set_note_mode(on))
+            }
+            crate::tools::REMINDER_TOOL => Some(self.set_reminder(&call.args)),
+            _ => None,
+        }
+    }
+
+    /// Run a note mode or reminder call and send its result to Gemini.
+    fn run_approved_app_tool(&mut self, call: FunctionCall) {
+        if let Some(result) = self.run_app_tool(&call) {
+            let _ = self.event_proxy.send_event(AppEvent::ToolResult {
+                call_id: call.id,
+                name: call.name,
+                result,
+                streamed: false,
+            });
+        }
+    }
+
+    /// Schedule a reminder; when it's due a `ReminderDue` event fires it.
+    fn set_reminder(&mut self, args: &serde_json::Value) -> serde_json::Value {
+        let Some(text) = args.get("text").and_then(|v| v.as_str()).filter(|t| !t.trim().is_empty()) else {
+            return json!({ "error": "Missing 'text' parameter" });
+        };
+        let minutes = match args.get("minutes").and_then(|v| v.as_f64()) {
+            Some(m) if m > 0.0 && m <= REMINDER_MAX_MINUTES => m,
+            Some(_) => {
+                return json!({ "error": format!("'minutes' must be between 0 and {}", REMINDER_MAX_MINUTES) });
+            }
+            None => return json!({ "error": "Missing 'minutes' parameter" }),
+        };
+        let delay = std::time::Duration::from_secs_f64(minutes * 60.0);
+
+        self.next_reminder_id += 1;
+        let id = self.next_reminder_id;
+        self.reminders.insert(id, text.trim().to_string());
+        let proxy = self.event_proxy.clone();
+        self.tokio_rt.spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = proxy.send_event(AppEvent::ReminderDue(id));
+        });
+
+        log::debug!("[Reminder] #{} in {:.1} min: {}", id, minutes, privacy::redact(text.trim()));
+        self.broadcast_dashboard("reminder:set", json!({
+            "id": id,
+            "text": text.trim(),
+            "dueAt": now_ms() + delay.as_millis(),
+            "timestamp": now_ms(),
+        }));
+        json!({ "success": true, "id": id, "minutes": minutes })
+    }
+
+    /// Show a due reminder and, with `reminder_speak`, say it: through the
+    /// Gemini session if one is running, else with speech-dispatcher.
+    fn fire_reminder(&mut self, id: u64) {
+        let Some(text) = self.reminders.remove(&id) else {
+            return;
+        };
+        log::debug!("[Reminder] #{} due: {}", id, privacy::redact(&text));
+        let _ = std::process::Command::new("notify-send")
+            .args(["--app-name=maVoice", "--urgency=critical", "Reminder", &text])
+            .spawn();
+        self.broadcast_dashboard("reminder:due", json!({
+            "id": id,
+            "text": text,
+            "timestamp": now_ms(),
+        }));
+        if !self.config.reminder_speak {
+            return;
+        }
+
+        let guard = GEMINI_CLIENT.lock().unwrap();
+        match *guard {
+            Some(ref client) if client.is_open() => {
+                client.send_text(&format!(
+                    "[A reminder you set is due now. Tell me, briefly: {}]",
+                    text
+                ));
+            }
+            _ => {
+                if let Err(e) = std::process::Command::new("spd-say").arg(&text).spawn() {
+                    log::warn!("[Reminder] Cannot speak the reminder (spd-say): {}", e);
+                }
+            }
+        }
+    }
+
     /// Dispatch tool calls to async executors, tracking their IDs.
     fn dispatch_tool_calls(&mut self, calls: Vec<FunctionCall>) {
         for call in calls {
             self.pending_tool_calls.insert(call.id.clone());
+            let ctx = self.tool_context();
+            let app_tool = crate::tools::is_app_tool(&call.name);
+            if app_tool && !ctx.enabled(&call.name) {
+                let _ = self.event_proxy.send_event(AppEvent::ToolResult {
+                    result: json!({ "error": format!("The tool '{}' is disabled", call.name) }),
+                    call_id: call.id,
+                    name: call.name,
+                    streamed: false,
+                });
+                continue;
+            }
+            let needs_confirm = self.needs_confirmation(&call.name);
+            if app_tool && !needs_confirm {
+                self.run_approved_app_tool(call);
+                continue;
+            }
 
             let proxy = self.event_proxy.clone();
             let call_id = call.id.clone();
             let call_name = call.name.clone();
             let call_args = call.args.clone();
+            let streamed = ctx.streaming && crate::tools::streams(&call.name);
+            if needs_confirm {
+                self.broadcast_dashboard("voice:tool_confirm", json!({
+                    "chatId": call_id,
+                    "toolName": call_name,
+                    "timestamp": now_ms(),
+                }));
+            }
 
             self.tokio_rt.spawn(async move {
-                let result = crate::tools::execute(&call_name, &call_args).await;
+                let approval = if needs_confirm {
+                    confirm::ask(&call_name, &call_args).await
+                } else {
+                    Approval::Once
+                };
+                if approval == Approval::Always {
+                    let _ = proxy.send_event(AppEvent::ToolAlwaysAllow(call_name.clone()));
+                }
+                if app_tool && approval != Approval::Denied {
+                    let _ = proxy.send_event(AppEvent::AppToolApproved(call));
+                    return;
+                }
+                let result = match approval {
+                    Approval::Denied => json!({ "error": "The user declined to run this tool" }),
+                    _ if streamed => {
+                        let on_update = |elapsed: std::time::Duration, output: String| {
+                            let _ = proxy.send_event(AppEvent::ToolProgress {
+                                call_id: call_id.clone(),
+                                name: call_name.clone(),
+                                elapsed,
+                                output,
+                            });
+                        };
+                        crate::tools::execute_with_updates(
+                            &call_name,
+                            &call_args,
+                            &ctx,
+                            TOOL_PROGRESS_INTERVAL,
+                            on_update,
+                        )
+                        .await
+                    }
+                    _ => crate::tools::execute(&call_name, &call_args, &ctx, None).await,
+                };
                 let _ = proxy.send_event(AppEvent::ToolResult {
                     call_id,
                     name: call_name,
                     result,
+                    streamed,
                 });
             });
         }
     }
 
-    fn set_skip_taskbar(name: &str) {
-        // Use xdotool to set skip-taskbar by window name (works on X11)
-        let _ = std::process::Command::new("xdotool")
-            .args(["search", "--name", name, "set_window", "--skip-taskbar", "1"])
-            .output();
+    // ── Tray ─────────────────────────────────────────────────────────
+
+    fn tray_state(&self) -> TrayState {
+        TrayState {
+            active: self.is_recording() || self.gemini_session_active(),
+            mode: self.mode,
+            hotkeys_paused: self.hotkeys_paused,
+            meeting: self.meeting.as_ref().is_some_and(|m| !m.stopping),
+            has_transcript: !self.last_transcript.is_empty(),
+            presets: self.base_config.presets.keys().cloned().collect(),
+            active_preset: self.base_config.active_preset.clone(),
+            gemini_text_only: self.config.gemini_text_only,
+            privacy: self.config.privacy_mode,
+        }
     }
 
-    /// Request redraw on both windows
-    fn request_redraw_all(&self) {
-        if let Some(w) = &self.user_window {
-            w.request_redraw();
+    fn sync_tray(&mut self) {
+        let state = self.tray_state();
+        if let Some(ref mut tray) = self.tray {
+            tray.update(state);
         }
-        if let Some(w) = &self.ai_window {
-            w.request_redraw();
+    }
+
+    /// Pause media players when the mic goes live and resume them when it's
+    /// released, if `pause_media` is on.
+    fn sync_media(&mut self) {
+        let listening =
+            self.config.pause_media && (self.is_recording() || self.gemini_session_active());
+        if listening == self.media_paused {
+            return;
+        }
+        self.media_paused = listening;
+        let media = self.media.get_or_insert_with(MediaPause::spawn);
+        if listening {
+            media.pause();
+        } else {
+            media.resume();
         }
     }
 
-    /// Broadcast a JSON event to connected dashboard clients.
-    fn broadcast_dashboard(&self, event_type: &str, payload: serde_json::Value) {
-        if let Some(ref server) = *DASHBOARD.lock().unwrap() {
-            server.broadcast(event_type, payload);
+    /// Turn other audio down while Gemini speaks, if `duck_audio` is on,
+    /// and back up once it has been quiet for [`DUCK_RELEASE_MS`].
+    fn sync_ducking(&mut self) {
+        let playing = self.audio_player.as_ref().is_some_and(|p| p.is_playing());
+        if playing {
+            self.last_speech = Some(std::time::Instant::now());
+        }
+        let speaking = self.config.duck_audio
+            && self
+                .last_speech
+                .is_some_and(|t| t.elapsed() < std::time::Duration::from_millis(DUCK_RELEASE_MS));
+        if speaking == self.ducked {
+            return;
+        }
+        self.ducked = speaking;
+        let ducker = self.ducker.get_or_insert_with(AudioDucker::spawn);
+        if speaking {
+            ducker.duck(self.config.duck_level);
+        } else {
+            ducker.restore();
+        }
+    }
+
+    fn handle_tray_action(&mut self, event_loop: &ActiveEventLoop, action: TrayAction) {
+        match action {
+            TrayAction::ToggleRecording => match self.mode {
+                VoiceMode::Groq => self.on_toggle_hotkey(),
+                VoiceMode::GeminiLive => self.on_mode_hotkey(),
+            },
+            TrayAction::SetMode(mode) => {
+                if mode != self.mode {
+                    if self.gemini_session_active() {
+                        self.disconnect_gemini();
+                    } else if self.meeting_recording() {
+                        self.stop_meeting();
+                    } else if self.is_recording() {
+                        let _ = self.recorder.lock().unwrap().stop_recording();
+                        self.visual.set_state(OverlayState::Idle);
+                    }
+                    log::info!("Mode switched to {:?} from tray", mode);
+                    self.mode = mode;
+                }
+            }
+            TrayAction::ToggleMeeting => {
+                if self.meeting_recording() {
+                    self.stop_meeting();
+                } else {
+                    self.start_meeting();
+                }
+            }
+            TrayAction::Reinject => {
+                if let Err(e) = self.reinject_last() {
+                    log::info!("[Reinject] {}", e);
+                }
+            }
+            TrayAction::SetPreset(name) => {
+                if let Err(e) = self.switch_preset(&name) {
+                    log::warn!("[Preset] {}", e);
+                }
+            }
+            TrayAction::OpenSettings => self.open_settings(event_loop),
+            TrayAction::ExportDebugBundle => {
+                self.tokio_rt.spawn_blocking(|| {
+                    let (title, body) = match crate::debug_bundle::export(None) {
+                        Ok(path) => ("Debug bundle saved", path.display().to_string()),
+                        Err(e) => ("Debug bundle failed", e),
+                    };
+                    let _ = std::process::Command::new("notify-send")
+                        .args(["--app-name=maVoice", title, &body])
+                        .spawn();
+                });
+            }
+            TrayAction::PauseHotkeys(paused) => {
+                log::info!("Global hotkeys {}", if paused { "paused" } else { "resumed" });
+                self.hotkeys_paused = paused;
+            }
+            TrayAction::GeminiTextOnly(enabled) => {
+                let mut new_base = self.base_config.clone();
+                new_base.gemini_text_only = enabled;
+                if let Err(e) = new_base.save() {
+                    log::error!("Failed to save config: {}", e);
+                } else {
+                    self.apply_config(new_base);
+                }
+            }
+            TrayAction::Privacy(enabled) => self.set_privacy(enabled),
+            TrayAction::Quit => {
+                log::info!("Quit requested from tray");
+                if self.gemini_session_active() {
+                    self.disconnect_gemini();
+                }
+                if let Some(ref tray) = self.tray {
+                    tray.shutdown();
+                }
+                event_loop.exit();
+            }
+        }
+        self.sync_tray();
+        self.request_redraw_all();
+    }
+
+    // ── Dashboard control ────────────────────────────────────────────
+
+    fn handle_dashboard_command(&mut self, event_loop: &ActiveEventLoop, cmd: DashboardCommand) {
+        let active = self.is_recording() || self.gemini_session_active();
+        let result = match cmd {
+            DashboardCommand::ToggleRecording => {
+                self.handle_tray_action(event_loop, TrayAction::ToggleRecording);
+                Ok(())
+            }
+            DashboardCommand::StartRecording => {
+                if !active {
+                    self.handle_tray_action(event_loop, TrayAction::ToggleRecording);
+                }
+                Ok(())
+            }
+            DashboardCommand::StopRecording => {
+                if active {
+                    self.handle_tray_action(event_loop, TrayAction::ToggleRecording);
+                }
+                Ok(())
+            }
+            DashboardCommand::SetMode { mode } => match mode.as_str() {
+                "groq" => {
+                    self.handle_tray_action(event_loop, TrayAction::SetMode(VoiceMode::Groq));
+                    Ok(())
+                }
+                "gemini" => {
+                    self.handle_tray_action(event_loop, TrayAction::SetMode(VoiceMode::GeminiLive));
+                    Ok(())
+                }
+                other => Err(format!("Unknown mode: {}", other)),
+            },
+            DashboardCommand::SendText { text } => {
+                let guard = GEMINI_CLIENT.lock().unwrap();
+                match *guard {
+                    Some(ref client) if client.is_open() => {
+                        client.send_text(&text);
+                        self.gemini_turn_ended = Some(std::time::Instant::now());
+                        Ok(())
+                    }
+                    _ => Err("No active Gemini session".to_string()),
+                }
+            }
+            DashboardCommand::ShareScreen => {
+                if self.gemini_session_active() {
+                    self.share_screen();
+                    Ok(())
+                } else {
+                    Err("No active Gemini session".to_string())
+                }
+            }
+            DashboardCommand::SetConfig(patch) => self.apply_config_patch(&patch),
+            DashboardCommand::Reinject => self.reinject_last(),
+            DashboardCommand::SetPreset { name } => self.switch_preset(&name),
+            DashboardCommand::ListHistory => self.send_history(
+                |history| history.sessions(HISTORY_LIST_LIMIT),
+                "history:sessions",
+            ),
+            DashboardCommand::GetUsage => {
+                self.publish_usage_totals();
+                Ok(())
+            }
+            DashboardCommand::GetHistory { id } => {
+                self.send_history(|history| history.session(id), "history:session")
+            }
+        };
+
+        if let Err(e) = result {
+            log::warn!("[Dashboard] Command failed: {}", e);
+            self.broadcast_dashboard("control:error", json!({
+                "error": e,
+                "timestamp": now_ms(),
+            }));
+        }
+    }
+
+    /// Answer a dashboard history query with a `event_type` broadcast.
+    fn send_history(
+        &self,
+        query: impl FnOnce(&History) -> Result<serde_json::Value, String>,
+        event_type: &str,
+    ) -> Result<(), String> {
+        let history = self.history.as_ref().ok_or("History database unavailable")?;
+        let mut payload = query(history)?;
+        payload["timestamp"] = json!(now_ms());
+        self.broadcast_dashboard(event_type, payload);
+        Ok(())
+    }
+
+    /// Pick up edits made to config.toml outside the app. Our own saves
+    /// also land here and are ignored.
+    fn reload_config_from_disk(&mut self) {
+        if Config::is_own_save() {
+            return;
+        }
+        let new_config = match Config::try_load() {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("{}; keeping the current settings", e);
+                return;
+            }
+        };
+        log::info!("config.toml changed on disk, applying");
+        self.apply_config(new_config);
+        self.broadcast_dashboard("config:updated", json!({ "timestamp": now_ms() }));
+    }
+
+    /// Apply a partial config from the dashboard, then save it.
+    /// Tool definitions and approvals can only be changed locally.
+    fn apply_config_patch(&mut self, patch: &serde_json::Value) -> Result<(), String> {
+        if let Some(obj) = patch.as_object() {
+            if let Some(key) = obj.keys().find(|k| REMOTE_LOCKED_CONFIG_KEYS.contains(&k.as_str())) {
+                return Err(format!("'{}' cannot be changed from the dashboard", key));
+            }
+        }
+        let new_config = self.base_config.with_patch(patch)?;
+        new_config.save().map_err(|e| format!("Failed to save config: {}", e))?;
+        self.apply_config(new_config);
+        self.broadcast_dashboard("config:updated", json!({ "timestamp": now_ms() }));
+        Ok(())
+    }
+
+    /// Make `name` the active preset (empty = none), save and apply it.
+    fn switch_preset(&mut self, name: &str) -> Result<(), String> {
+        if !name.is_empty() && !self.base_config.presets.contains_key(name) {
+            return Err(format!("Unknown preset '{}'", name));
+        }
+        let mut new_base = self.base_config.clone();
+        new_base.active_preset = name.to_string();
+        new_base.save().map_err(|e| format!("Failed to save config: {}", e))?;
+        self.apply_config(new_base);
+
+        let label = if name.is_empty() { "Default" } else { name };
+        log::info!("Preset: {}", label);
+        self.visual.show_status(format!("Profile: {}", label));
+        self.request_redraw_all();
+        self.broadcast_dashboard("preset:changed", json!({
+            "name": name,
+            "timestamp": now_ms(),
+        }));
+        let _ = std::process::Command::new("notify-send")
+            .args(["--app-name=maVoice", "--expire-time=2000", "maVoice profile", label])
+            .spawn();
+        Ok(())
+    }
+
+    /// A preset name if `text` is just "<name> profile" or "switch to <name>
+    /// profile", so dictation can switch presets by voice.
+    fn spoken_preset(&self, text: &str) -> Option<String> {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+        let words = words.strip_suffix(&["profile".to_string()])?;
+        let words = words.strip_prefix(&["switch".to_string(), "to".to_string()]).unwrap_or(words);
+        let spoken = words.join(" ");
+        if spoken == "default" {
+            return Some(String::new());
+        }
+        self.base_config
+            .presets
+            .keys()
+            .find(|name| name.to_lowercase() == spoken)
+            .cloned()
+    }
+
+    // ── Settings ─────────────────────────────────────────────────────
+
+    /// Open the settings window, or focus it if already open.
+    fn open_settings(&mut self, event_loop: &ActiveEventLoop) {
+        if self.cli.headless {
+            log::warn!(
+                "[Settings] Not available in headless mode; edit {} instead (changes apply live)",
+                Config::config_path().display()
+            );
+            return;
+        }
+        if let Some(ref settings) = self.settings {
+            settings.focus();
+            return;
+        }
+        match SettingsWindow::open(event_loop, &self.base_config) {
+            Ok(settings) => {
+                self.settings = Some(settings);
+                self.publish_usage_totals();
+            }
+            Err(e) => log::error!("[Settings] {}", e),
+        }
+    }
+
+    fn handle_settings_action(&mut self, action: SettingsAction) {
+        match action {
+            SettingsAction::Save(new_config) => {
+                if let Err(e) = new_config.save() {
+                    log::error!("[Settings] Failed to save config: {}", e);
+                    return;
+                }
+                self.apply_config(new_config);
+            }
+            SettingsAction::Close => {
+                log::info!("[Settings] Window closed");
+                self.settings = None;
+            }
+        }
+    }
+
+    /// Swap in a new on-disk config (its active preset applied), rebuilding
+    /// whatever depends on the changed fields. Gemini voice/instruction
+    /// changes take effect on the next session.
+    fn apply_config(&mut self, new_base: Config) {
+        let new_config = self.cli.apply(new_base.with_active_preset());
+        self.base_config = new_base;
+        let provider_changed = new_config.provider != self.config.provider
+            || new_config.api_key != self.config.api_key
+            || new_config.openai_api_key != self.config.openai_api_key
+            || new_config.upload_format != self.config.upload_format
+            || new_config.http_connect_timeout_secs != self.config.http_connect_timeout_secs
+            || new_config.http_request_timeout_secs != self.config.http_request_timeout_secs
+            || new_config.fallback_providers != self.config.fallback_providers
+            || new_config.model != self.config.model
+            || new_config.openai_model != self.config.openai_model
+            || new_config.candle_model != self.config.candle_model
+            || new_config.candle_device != self.config.candle_device
+            || new_config.offline_fallback != self.config.offline_fallback;
+        if provider_changed {
+            self.transcriber = crate::api::provider::from_config(&new_config);
+            log::info!("Transcription provider: {}", self.transcriber.name());
+        }
+
+        if new_config.injection_method != self.config.injection_method {
+            match make_injector(&new_config, &self.tokio_rt) {
+                Ok(injector) => self.text_injector = injector,
+                Err(e) => log::error!("Failed to switch text injector: {}", e),
+            }
+        }
+        self.text_injector.set_type_delay(new_config.type_delay_ms);
+        self.text_injector.set_rules(&new_config.injection_rules);
+        self.text_injector.set_primary_selection(&new_config.primary_selection);
+
+        // New retention limits apply on the next tick
+        if new_config.archive_dir != self.config.archive_dir
+            || new_config.archive_max_days != self.config.archive_max_days
+            || new_config.archive_max_mb != self.config.archive_max_mb
+        {
+            self.archive_cleaned_at = None;
+        }
+
+        let mcp_changed = new_config.mcp_servers != self.config.mcp_servers;
+        let session_changed = new_config.gemini_text_only != self.config.gemini_text_only
+            || new_config.gemini_push_to_talk != self.config.gemini_push_to_talk
+            || new_config.gemini_local_vad != self.config.gemini_local_vad
+            || new_config.gemini_vad_threshold != self.config.gemini_vad_threshold
+            || new_config.gemini_start_sensitivity != self.config.gemini_start_sensitivity
+            || new_config.gemini_end_sensitivity != self.config.gemini_end_sensitivity
+            || new_config.gemini_prefix_padding_ms != self.config.gemini_prefix_padding_ms
+            || new_config.gemini_silence_ms != self.config.gemini_silence_ms
+            || new_config.tool_progress != self.config.tool_progress;
+        let shaders_changed = new_config.user_shader != self.config.user_shader
+            || new_config.ai_shader != self.config.ai_shader
+            || new_config.reduced_motion != self.config.reduced_motion;
+        let usage_badge_changed = new_config.usage_badge != self.config.usage_badge;
+        let smart_spacing_changed = new_config.smart_spacing != self.config.smart_spacing
+            || new_config.uses_atspi_injection() != self.config.uses_atspi_injection();
+        let screen_reader_changed = new_config.screen_reader != self.config.screen_reader;
+        let dashboard_changed = new_config.dashboard_enabled != self.config.dashboard_enabled
+            || new_config.dashboard_port != self.config.dashboard_port;
+        let output_changed = new_config.output_device != self.config.output_device;
+        let input_changed = new_config.input_device != self.config.input_device;
+        let hotkey_backend_changed = new_config.hotkey_backend != self.config.hotkey_backend;
+        let gemini_key_changed = new_config.gemini_api_key != self.config.gemini_api_key
+            || new_config.gemini_token_url != self.config.gemini_token_url;
+        let transcription_key_changed = new_config.provider != self.config.provider
+            || new_config.api_key != self.config.api_key
+            || new_config.openai_api_key != self.config.openai_api_key;
+
+        // A meeting sets its own cap and restores this one when it stops
+        if new_config.max_recording_secs != self.config.max_recording_secs && self.meeting.is_none() {
+            self.recorder
+                .lock()
+                .unwrap()
+                .set_max_duration(new_config.max_recording_secs);
+        }
+
+        if new_config.overlay_width != self.config.overlay_width
+            || new_config.overlay_height != self.config.overlay_height
+        {
+            if let Some(w) = &self.user_window {
+                let screen_w = w.current_monitor().map_or(1920, |m| m.size().width);
+                let width = match new_config.overlay_width {
+                    0 => screen_w,
+                    px => px.min(screen_w),
+                };
+                let _ = w.request_inner_size(LogicalSize::new(
+                    width as f64,
+                    new_config.overlay_height.max(8) as f64,
+                ));
+            }
         }
+
+        if new_config.preroll_ms != self.config.preroll_ms {
+            self.recorder.lock().unwrap().set_preroll(new_config.preroll_ms);
+        }
+
+        if new_config.denoise != self.config.denoise {
+            self.recorder.lock().unwrap().set_denoise(new_config.denoise);
+        }
+
+        if new_config.normalize_audio != self.config.normalize_audio
+            || new_config.live_agc != self.config.live_agc
+            || new_config.target_dbfs != self.config.target_dbfs
+        {
+            self.recorder.lock().unwrap().set_gain(
+                new_config.normalize_audio,
+                new_config.live_agc,
+                new_config.target_dbfs,
+            );
+        }
+
+        if hotkey_backend_changed {
+            // Release the old backend's grabs before the new one registers
+            self.hotkey_manager = None;
+        } else if let Some(ref mut hk) = self.hotkey_manager {
+            if let Err(e) = hk.rebind(&new_config.toggle_hotkey, &new_config.mode_hotkey) {
+                log::error!("{}", e);
+            }
+            if let Err(e) = hk.set_preset_hotkey(&new_config.preset_hotkey) {
+                log::error!("{}", e);
+            }
+            if let Err(e) = hk.set_cancel_hotkey(&new_config.cancel_hotkey) {
+                log::error!("{}", e);
+            }
+            if let Err(e) = hk.set_reinject_hotkey(&new_config.reinject_hotkey) {
+                log::error!("{}", e);
+            }
+            if let Err(e) = hk.set_screen_hotkey(&new_config.screen_hotkey) {
+                log::error!("{}", e);
+            }
+            if let Err(e) = hk.set_talk_hotkey(&new_config.talk_hotkey) {
+                log::error!("{}", e);
+            }
+            if let Err(e) = hk.set_privacy_hotkey(&new_config.privacy_hotkey) {
+                log::error!("{}", e);
+            }
+        }
+
+        self.config = new_config;
+        if mcp_changed {
+            self.start_mcp();
+        }
+        if smart_spacing_changed {
+            self.start_cursor_context();
+        }
+        if screen_reader_changed {
+            self.start_announcer();
+        }
+        if dashboard_changed {
+            self.start_dashboard();
+        }
+        if output_changed {
+            self.rebuild_audio_player();
+        }
+        if input_changed {
+            if self.is_recording() {
+                log::info!("[Audio] Input device changes after this recording");
+                self.recorder_stale = true;
+            } else {
+                self.rebuild_recorder();
+            }
+        }
+        if hotkey_backend_changed && self.started {
+            self.init_hotkeys();
+        }
+        // Reply modality and voice detection are fixed in the setup message
+        if session_changed && self.gemini_session_active() {
+            log::info!("[Gemini] Session settings changed, reconnecting");
+            self.disconnect_gemini();
+            self.connect_gemini();
+        }
+        self.visual.high_contrast = self.config.high_contrast;
+        self.visual.private = self.config.privacy_mode;
+        privacy::set(self.config.privacy_mode);
+        if shaders_changed && self.gpu.is_some() {
+            self.load_shaders();
+            self.watch_shaders();
+        }
+        if usage_badge_changed {
+            self.publish_usage_totals();
+        }
+        // A key just entered in settings is checked right away
+        self.check_api_keys(transcription_key_changed, gemini_key_changed);
+        log::info!("Config applied");
+    }
+
+    fn set_skip_taskbar(name: &str) {
+        // Use xdotool to set skip-taskbar by window name (works on X11)
+        let _ = std::process::Command::new("xdotool")
+            .args(["search", "--name", name, "set_window", "--skip-taskbar", "1"])
+            .output();
     }
-}
-
-impl ApplicationHandler<AppEvent> for App {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        if self.user_window.is_some() {
-            return;
-        }
 
+    /// Create the overlay windows (strip, orb and the hidden toast) and their
+    /// shared GPU renderers.
+    fn create_overlay(&mut self, event_loop: &ActiveEventLoop) {
         // Detect screen dimensions
         let monitor = event_loop
             .primary_monitor()
             .or_else(|| event_loop.available_monitors().next());
         let (screen_w, screen_h) = monitor
+            .as_ref()
             .map(|m| {
                 let s = m.size();
                 (s.width, s.height)
             })
             .unwrap_or((1920, 1080));
 
-        let strip_w = screen_w as f64;
+        let strip_w = if self.config.overlay_width > 0 {
+            self.config.overlay_width.min(screen_w) as f64
+        } else {
+            screen_w as f64
+        };
 
-        // ── Create USER window (bottom, 64px) ──
-        let user_h = 64.0;
+        self.visual.high_contrast = self.config.high_contrast;
+        self.visual.private = self.config.privacy_mode;
+
+        // ── Create USER window (bottom, 64px unless configured) ──
+        let user_h = self.config.overlay_height.max(8) as f64;
+        // Where it was last dragged to, preferring the primary monitor, else
+        // bottom center
+        let saved = monitor
+            .iter()
+            .cloned()
+            .chain(event_loop.available_monitors())
+            .find_map(|m| {
+                let offset = self.config.overlay_positions.get(&monitor_key(&m))?;
+                let origin = m.position();
+                Some(PhysicalPosition::new(origin.x + offset[0], origin.y + offset[1]))
+            });
+        let user_pos: winit::dpi::Position = match saved {
+            Some(pos) => pos.into(),
+            None => LogicalPosition::new((screen_w as f64 - strip_w) / 2.0, screen_h as f64 - user_h).into(),
+        };
         let user_attrs = Window::default_attributes()
             .with_title("maVoice")
             .with_inner_size(LogicalSize::new(strip_w, user_h))
-            .with_position(LogicalPosition::new(0.0, screen_h as f64 - user_h))
+            .with_position(user_pos)
             .with_decorations(false)
             .with_transparent(true)
             .with_window_level(WindowLevel::AlwaysOnTop)
-            .with_resizable(false);
+            .with_resizable(false)
+            .with_visible(!self.cli.no_overlay);
 
         let user_window = Arc::new(
             event_loop
@@ -518,7 +2726,8 @@ impl ApplicationHandler<AppEvent> for App {
             .with_decorations(false)
             .with_transparent(true)
             .with_window_level(WindowLevel::AlwaysOnTop)
-            .with_resizable(false);
+            .with_resizable(false)
+            .with_visible(!self.cli.no_overlay);
 
         let ai_window = Arc::new(
             event_loop
@@ -526,60 +2735,327 @@ impl ApplicationHandler<AppEvent> for App {
                 .expect("Failed to create AI window"),
         );
 
-        // ── Init shared GPU context (no surface needed — render to texture) ──
+        // ── Create toast window (typed text, shown above the strip on demand) ──
+        let toast_attrs = Window::default_attributes()
+            .with_title("maVoice-Toast")
+            .with_inner_size(LogicalSize::new(TOAST_WIDTH.min(strip_w), TOAST_HEIGHT))
+            .with_decorations(false)
+            .with_transparent(true)
+            .with_window_level(WindowLevel::AlwaysOnTop)
+            .with_resizable(false)
+            .with_active(false)
+            .with_visible(false);
+
+        let toast_window = Arc::new(
+            event_loop
+                .create_window(toast_attrs)
+                .expect("Failed to create toast window"),
+        );
+        // Clicks go through to whatever is underneath
+        let _ = toast_window.set_cursor_hittest(false);
+
+        // ── Init shared GPU context (each renderer picks how it presents) ──
+        let render_path = self.config.render_path.clone();
         let gpu = self
             .tokio_rt
-            .block_on(async { GpuContext::new().await });
+            .block_on(async { GpuContext::new(&render_path).await });
 
         // ── Create renderers ──
         let user_renderer = Renderer::new(
             &gpu,
             user_window.clone(),
-            include_str!("shader.wgsl"),
+            USER_SHADER,
             std::mem::size_of::<UserUniforms>(),
         );
 
         let ai_renderer = Renderer::new(
             &gpu,
             ai_window.clone(),
-            include_str!("ai_shader.wgsl"),
+            AI_SHADER,
             std::mem::size_of::<AiUniforms>(),
         );
 
+        let toast_renderer = Renderer::new(
+            &gpu,
+            toast_window.clone(),
+            TOAST_SHADER,
+            std::mem::size_of::<ToastUniforms>(),
+        );
+
         // Store window IDs for event routing
         self.user_window_id = Some(user_window.id());
         self.ai_window_id = Some(ai_window.id());
+        self.toast_window_id = Some(toast_window.id());
 
         self.user_renderer = Some(user_renderer);
         self.ai_renderer = Some(ai_renderer);
+        self.toast_renderer = Some(toast_renderer);
         self.gpu = Some(gpu);
         self.user_window = Some(user_window);
         self.ai_window = Some(ai_window);
+        self.toast_window = Some(toast_window);
+
+        if !self.config.user_shader.is_empty() || !self.config.ai_shader.is_empty() || self.config.reduced_motion {
+            self.load_shaders();
+            self.watch_shaders();
+        }
 
-        // Skip taskbar for both windows
+        // Skip taskbar for all overlay windows
         Self::set_skip_taskbar("maVoice");
         Self::set_skip_taskbar("maVoice-AI");
-
-        // Init global hotkeys
-        match HotkeyManager::new() {
-            Ok(hk) => self.hotkey_manager = Some(hk),
-            Err(e) => log::warn!("Global hotkeys unavailable: {}", e),
-        }
+        Self::set_skip_taskbar("maVoice-Toast");
 
         log::info!(
             "Windows created: user={}x{} (bottom), AI={}x{} (top center) on {}x{} screen",
             strip_w, user_h, ai_w, ai_h, screen_w, screen_h
         );
+    }
 
-        // Start dashboard WebSocket broadcast server
-        self.tokio_rt.spawn(async {
-            match DashboardBroadcaster::start(3001).await {
-                Ok(server) => {
-                    DASHBOARD.lock().unwrap().replace(server);
+    /// Compile the configured custom shaders, falling back to the built-in
+    /// ones when unset or broken. Reduced motion swaps every visualizer,
+    /// custom ones included, for the static indicator.
+    fn load_shaders(&mut self) {
+        let mut failed = None;
+        let reduced_motion = self.config.reduced_motion;
+        let targets = [
+            (&self.config.user_shader, USER_SHADER, self.user_renderer.as_mut()),
+            (&self.config.ai_shader, AI_SHADER, self.ai_renderer.as_mut()),
+        ];
+        for (path, builtin, renderer) in targets {
+            let Some(renderer) = renderer else {
+                continue;
+            };
+            if reduced_motion {
+                let _ = renderer.set_shader(STATIC_SHADER);
+                continue;
+            }
+            if path.is_empty() {
+                let _ = renderer.set_shader(builtin);
+                continue;
+            }
+            let result = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| renderer.set_shader(&source));
+            match result {
+                Ok(()) => log::info!("Loaded shader {}", path),
+                Err(e) => {
+                    log::error!("Shader {} failed, using the built-in one:\n{}", path, e);
+                    let _ = renderer.set_shader(builtin);
+                    failed = Some(path.clone());
                 }
-                Err(e) => log::warn!("[Dashboard] Failed to start: {}", e),
             }
-        });
+        }
+        if let Some(path) = failed {
+            let name = std::path::Path::new(&path)
+                .file_name()
+                .map_or(path.clone(), |n| n.to_string_lossy().into_owned());
+            self.visual.show_status(format!("Shader error: {}", name));
+        }
+        self.request_redraw_all();
+    }
+
+    /// Reload custom shaders whenever their files are saved.
+    fn watch_shaders(&mut self) {
+        self.shader_watchers.clear();
+        for path in [&self.config.user_shader, &self.config.ai_shader] {
+            if path.is_empty() {
+                continue;
+            }
+            let proxy = self.event_proxy.clone();
+            match crate::system::config_watch::watch(std::path::Path::new(path), move || {
+                let _ = proxy.send_event(AppEvent::ShaderFileChanged);
+            }) {
+                Ok(watcher) => self.shader_watchers.push(watcher),
+                Err(e) => log::warn!("Shader hot-reload unavailable for {}: {}", path, e),
+            }
+        }
+    }
+
+    /// Hide or shrink the strip after `overlay_autohide_secs` with nothing
+    /// to show, and bring it back when something happens (hotkey, tray,
+    /// a status message) or the cursor touches it.
+    fn sync_autohide(&mut self) {
+        if self.user_window.is_none() || self.cli.no_overlay {
+            return;
+        }
+        let idle = self.visual.state == OverlayState::Idle
+            && !self.visual.label_active()
+            && !self.cursor_on_strip
+            && !self.is_dragging;
+        if !idle || self.config.overlay_autohide_secs == 0 {
+            self.idle_since = None;
+            self.show_strip();
+            return;
+        }
+        let since = *self.idle_since.get_or_insert_with(std::time::Instant::now);
+        if self.strip_hidden.is_none()
+            && since.elapsed() >= std::time::Duration::from_secs(self.config.overlay_autohide_secs)
+        {
+            self.hide_strip();
+        }
+    }
+
+    /// Pop the typed text up in the bubble next to the strip: above it, or
+    /// below when the strip sits at the top of the screen.
+    fn show_toast(&mut self, text: &str) {
+        let secs = self.config.injection_toast_secs;
+        if secs == 0 || !self.config.overlay_text || self.cli.no_overlay || text.trim().is_empty() {
+            return;
+        }
+        let (Some(strip), Some(toast)) = (&self.user_window, &self.toast_window) else {
+            return;
+        };
+        self.visual.show_toast(text.trim(), secs as f32);
+
+        if let Ok(pos) = strip.outer_position() {
+            let strip_size = strip.outer_size();
+            let size = toast.outer_size();
+            let gap = (TOAST_GAP * strip.scale_factor()).round() as i32;
+            let x = pos.x + (strip_size.width as i32 - size.width as i32) / 2;
+            let above = pos.y - gap - size.height as i32;
+            let top = strip.current_monitor().map_or(0, |m| m.position().y);
+            let y = if above >= top { above } else { pos.y + strip_size.height as i32 + gap };
+            toast.set_outer_position(PhysicalPosition::new(x, y));
+        }
+        if !self.toast_visible {
+            toast.set_visible(true);
+            self.toast_visible = true;
+        }
+        toast.request_redraw();
+    }
+
+    /// Hide the toast window once its text has faded.
+    fn sync_toast(&mut self) {
+        if !self.toast_visible || self.visual.toast_label().is_some() {
+            return;
+        }
+        if let Some(w) = &self.toast_window {
+            w.set_visible(false);
+        }
+        self.toast_visible = false;
+    }
+
+    fn hide_strip(&mut self) {
+        let Some(w) = &self.user_window else {
+            return;
+        };
+        let invisible = self.config.overlay_autohide_mode == "hide";
+        if invisible {
+            w.set_visible(false);
+        } else {
+            // Keep the bottom edge where it is so the sliver stays at the screen edge
+            let size = w.inner_size();
+            let thin = (AUTOHIDE_STRIP_PX * w.scale_factor()).round().max(1.0) as u32;
+            if let Ok(pos) = w.outer_position() {
+                let drop = size.height.saturating_sub(thin) as i32;
+                w.set_outer_position(PhysicalPosition::new(pos.x, pos.y + drop));
+            }
+            let _ = w.request_inner_size(PhysicalSize::new(size.width, thin));
+        }
+        log::debug!("Overlay auto-hidden ({})", if invisible { "hide" } else { "shrink" });
+        self.strip_hidden = Some(invisible);
+    }
+
+    fn show_strip(&mut self) {
+        let (Some(invisible), Some(w)) = (self.strip_hidden.take(), &self.user_window) else {
+            return;
+        };
+        if invisible {
+            w.set_visible(true);
+        } else {
+            let size = w.inner_size();
+            let full = (self.config.overlay_height.max(8) as f64 * w.scale_factor()).round() as u32;
+            if let Ok(pos) = w.outer_position() {
+                let rise = full.saturating_sub(size.height) as i32;
+                w.set_outer_position(PhysicalPosition::new(pos.x, pos.y - rise));
+            }
+            let _ = w.request_inner_size(PhysicalSize::new(size.width, full));
+        }
+        w.request_redraw();
+    }
+
+    /// Remember where the strip was dragged to, per monitor.
+    fn save_strip_position(&mut self) {
+        let Some(window) = &self.user_window else {
+            return;
+        };
+        let (Ok(pos), Some(monitor)) = (window.outer_position(), window.current_monitor()) else {
+            return;
+        };
+        let origin = monitor.position();
+        let offset = [pos.x - origin.x, pos.y - origin.y];
+        let key = monitor_key(&monitor);
+        if self.base_config.overlay_positions.get(&key) == Some(&offset) {
+            return;
+        }
+        log::info!("Overlay moved to {:?} on {}", offset, key);
+        self.base_config.overlay_positions.insert(key.clone(), offset);
+        self.config.overlay_positions.insert(key, offset);
+        if let Err(e) = self.base_config.save() {
+            log::error!("Failed to save overlay position: {}", e);
+        }
+    }
+
+    /// Request redraw on both windows (and the toast while it's up)
+    fn request_redraw_all(&self) {
+        if let Some(w) = &self.user_window {
+            w.request_redraw();
+        }
+        if let Some(w) = &self.ai_window {
+            w.request_redraw();
+        }
+        if let Some(w) = self.toast_window.as_ref().filter(|_| self.toast_visible) {
+            w.request_redraw();
+        }
+    }
+
+    /// Add a latency sample and broadcast it with the rolling p50/p95 as
+    /// `metrics:<name>`.
+    fn record_metric(&mut self, name: &'static str, took: std::time::Duration) {
+        let mut payload = self.metrics.record(name, took);
+        payload["timestamp"] = json!(now_ms());
+        self.broadcast_dashboard(&format!("metrics:{}", name), payload);
+    }
+
+    /// Broadcast a JSON event to connected dashboard clients.
+    fn broadcast_dashboard(&self, event_type: &str, mut payload: serde_json::Value) {
+        if let Some(ref server) = *DASHBOARD.lock().unwrap() {
+            privacy::redact_payload(&mut payload);
+            server.broadcast(event_type, payload);
+        }
+    }
+}
+
+impl ApplicationHandler<AppEvent> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+
+        if self.cli.headless {
+            log::info!("Headless mode: no overlay windows or GPU");
+        } else {
+            self.create_overlay(event_loop);
+        }
+
+        self.init_hotkeys();
+
+        self.tray = Some(TrayIcon::spawn(self.event_proxy.clone(), self.tray_state()));
+
+        self.start_dashboard();
+        self.start_mcp();
+        self.start_cursor_context();
+        self.start_announcer();
+        self.check_api_keys(true, true);
+
+        let proxy = self.event_proxy.clone();
+        match crate::system::config_watch::watch(&Config::config_path(), move || {
+            let _ = proxy.send_event(AppEvent::ConfigFileChanged);
+        }) {
+            Ok(watcher) => self.config_watcher = Some(watcher),
+            Err(e) => log::warn!("Config hot-reload unavailable: {}", e),
+        }
     }
 
     fn window_event(
@@ -588,15 +3064,30 @@ impl ApplicationHandler<AppEvent> for App {
         window_id: WindowId,
         event: WindowEvent,
     ) {
+        // Settings window handles its own events
+        if let Some(ref mut settings) = self.settings {
+            if settings.id() == window_id {
+                if let Some(action) = settings.handle_event(&event) {
+                    self.handle_settings_action(action);
+                }
+                return;
+            }
+        }
+
         // Route events by window ID
         let is_user_window = Some(window_id) == self.user_window_id;
         let is_ai_window = Some(window_id) == self.ai_window_id;
+        let is_toast_window = Some(window_id) == self.toast_window_id;
 
         match event {
             WindowEvent::CloseRequested => {
                 event_loop.exit();
             }
 
+            WindowEvent::Moved(_) if is_user_window && self.strip_dragged => {
+                self.strip_moved_at = Some(std::time::Instant::now());
+            }
+
             WindowEvent::Resized(size) => {
                 if is_user_window {
                     if let Some(r) = &mut self.user_renderer {
@@ -606,6 +3097,10 @@ impl ApplicationHandler<AppEvent> for App {
                     if let Some(r) = &mut self.ai_renderer {
                         r.resize(size.width, size.height);
                     }
+                } else if is_toast_window {
+                    if let Some(r) = &mut self.toast_renderer {
+                        r.resize(size.width, size.height);
+                    }
                 }
             }
 
@@ -627,7 +3122,31 @@ impl ApplicationHandler<AppEvent> for App {
 
                 // ── Render user window ──
                 if is_user_window {
+                    // Elapsed-time bar while dictating (meetings run uncapped)
+                    let (rec_secs, rec_limit) = if self.visual.state == OverlayState::Recording {
+                        let secs = self.recorder.lock().unwrap().recorded_secs().unwrap_or(0.0);
+                        (secs as f32, self.config.max_recording_secs as f32)
+                    } else {
+                        (0.0, 0.0)
+                    };
+                    let label = self
+                        .visual
+                        .user_label(&self.last_transcript)
+                        .filter(|_| self.config.overlay_text)
+                        .map(|(text, alpha)| TextOverlay {
+                            text,
+                            color: self.visual.color,
+                            alpha,
+                            placement: TextPlacement::Top,
+                        });
+                    let backdrop = if self.config.high_contrast {
+                        HIGH_CONTRAST_BACKDROP * self.visual.effective_intensity().min(1.0)
+                    } else {
+                        0.0
+                    };
                     if let Some(r) = &mut self.user_renderer {
+                        r.set_text(label);
+                        r.set_backdrop(backdrop);
                         let uniforms = UserUniforms {
                             resolution: [r.width as f32, r.height as f32],
                             time: elapsed,
@@ -635,6 +3154,9 @@ impl ApplicationHandler<AppEvent> for App {
                             levels: self.visual.effective_levels(),
                             color: self.visual.color,
                             mode: self.visual.mode,
+                            rec_secs,
+                            rec_limit,
+                            _pad: [0.0; 2],
                         };
                         r.render_bytes(bytemuck::bytes_of(&uniforms));
                     }
@@ -642,7 +3164,24 @@ impl ApplicationHandler<AppEvent> for App {
 
                 // ── Render AI window ──
                 if is_ai_window {
+                    let label = self
+                        .visual
+                        .ai_label()
+                        .filter(|_| self.config.overlay_text)
+                        .map(|(text, alpha)| TextOverlay {
+                            text,
+                            color: self.visual.ai_color,
+                            alpha,
+                            placement: TextPlacement::Bottom,
+                        });
+                    let backdrop = if self.config.high_contrast {
+                        HIGH_CONTRAST_BACKDROP * self.visual.effective_ai_intensity().min(1.0)
+                    } else {
+                        0.0
+                    };
                     if let Some(r) = &mut self.ai_renderer {
+                        r.set_text(label);
+                        r.set_backdrop(backdrop);
                         let uniforms = AiUniforms {
                             resolution: [r.width as f32, r.height as f32],
                             time: elapsed,
@@ -655,6 +3194,28 @@ impl ApplicationHandler<AppEvent> for App {
                     }
                 }
 
+                // ── Render toast window ──
+                if is_toast_window {
+                    let toast = self.visual.toast_label();
+                    let alpha = toast.as_ref().map_or(0.0, |(_, alpha)| *alpha);
+                    if let Some(r) = &mut self.toast_renderer {
+                        r.set_text(toast.map(|(text, alpha)| TextOverlay {
+                            text,
+                            color: [1.0; 3],
+                            alpha,
+                            placement: TextPlacement::Center,
+                        }));
+                        let uniforms = ToastUniforms {
+                            resolution: [r.width as f32, r.height as f32],
+                            alpha,
+                            _pad: 0.0,
+                            color: self.visual.state_color(OverlayState::Done),
+                            _pad2: 0.0,
+                        };
+                        r.render_bytes(bytemuck::bytes_of(&uniforms));
+                    }
+                }
+
                 // Request next frame if active (either channel)
                 let ai_playing = self
                     .audio_player
@@ -665,6 +3226,7 @@ impl ApplicationHandler<AppEvent> for App {
                     || self.visual.intensity > 0.001
                     || self.visual.ai_intensity > 0.001
                     || ai_playing
+                    || self.visual.label_active()
                 {
                     self.request_redraw_all();
                 }
@@ -691,9 +3253,14 @@ impl ApplicationHandler<AppEvent> for App {
                         self.click_state.count += 1;
                         self.click_state.timer = Some(std::time::Instant::now());
                     }
+                    MouseButton::Middle => {
+                        let action = self.config.middle_click_action.clone();
+                        self.run_gesture(event_loop, &action);
+                    }
                     MouseButton::Right => {
                         // Right-click drag
                         self.is_dragging = true;
+                        self.strip_dragged = true;
                         if let Some(w) = &self.user_window {
                             let _ = w.drag_window();
                         }
@@ -702,6 +3269,36 @@ impl ApplicationHandler<AppEvent> for App {
                 }
             }
 
+            WindowEvent::CursorEntered { .. } if is_user_window => {
+                self.cursor_on_strip = true;
+            }
+
+            WindowEvent::CursorLeft { .. } if is_user_window => {
+                self.cursor_on_strip = false;
+            }
+
+            WindowEvent::MouseWheel { delta, .. } if is_user_window => {
+                let steps = match delta {
+                    // signum() of 0.0 is 1; a horizontal scroll isn't a step
+                    MouseScrollDelta::LineDelta(_, y) if y == 0.0 => 0,
+                    MouseScrollDelta::LineDelta(_, y) => y.signum() as i32,
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        self.click_state.scroll += pos.y;
+                        let steps = (self.click_state.scroll / SCROLL_STEP_PX).trunc();
+                        self.click_state.scroll -= steps * SCROLL_STEP_PX;
+                        steps as i32
+                    }
+                };
+                let action = if steps > 0 {
+                    self.config.scroll_up_action.clone()
+                } else {
+                    self.config.scroll_down_action.clone()
+                };
+                for _ in 0..steps.unsigned_abs() {
+                    self.run_gesture(event_loop, &action);
+                }
+            }
+
             // --- Keyboard handling (user window only) ---
             WindowEvent::KeyboardInput { event, .. } if is_user_window => {
                 if event.state != ElementState::Pressed {
@@ -714,6 +3311,7 @@ impl ApplicationHandler<AppEvent> for App {
                             self.stop_recording_and_transcribe();
                         }
                     }
+                    Key::Named(NamedKey::Escape) => self.cancel_dictation(),
                     Key::Named(NamedKey::Alt) => {
                         self.alt_state.count += 1;
                         self.alt_state.timer = Some(std::time::Instant::now());
@@ -726,23 +3324,32 @@ impl ApplicationHandler<AppEvent> for App {
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        // Process click timer (280ms window for double-click)
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.recorder_stale && !self.is_recording() {
+            self.rebuild_recorder();
+        }
+
+        // Process click timer (double_click_ms window for double-click)
         if let Some(timer) = self.click_state.timer {
-            if timer.elapsed().as_millis() >= 280 {
+            if timer.elapsed().as_millis() >= u128::from(self.config.double_click_ms) {
                 let count = self.click_state.count;
                 self.click_state.count = 0;
                 self.click_state.timer = None;
 
                 if count == 1 {
-                    if self.is_recording() {
-                        self.stop_recording_and_transcribe();
+                    let action = self.config.click_action.clone();
+                    self.run_gesture(event_loop, &action);
+                } else if count >= 2 {
+                    let action = self.config.double_click_action.clone();
+                    let was_recording = self.is_recording();
+                    self.run_gesture(event_loop, &action);
+                    // Swallow the stray clicks of a slow double-click
+                    if !was_recording && self.is_recording() {
+                        self.click_state.cooldown_until = Some(
+                            std::time::Instant::now()
+                                + std::time::Duration::from_millis(self.config.click_cooldown_ms),
+                        );
                     }
-                } else if count >= 2 && !self.is_recording() {
-                    self.start_recording();
-                    self.click_state.cooldown_until = Some(
-                        std::time::Instant::now() + std::time::Duration::from_millis(500),
-                    );
                 }
             }
         }
@@ -760,57 +3367,223 @@ impl ApplicationHandler<AppEvent> for App {
             }
         }
 
+        // Follow output device changes (headset plugged in or removed)
+        if self.audio_player.as_ref().is_some_and(|p| p.is_stale()) {
+            self.rebuild_audio_player();
+        }
+
+        // VAD auto-stop for Groq dictation
+        if self.config.vad_silence_ms > 0
+            && self.recording_mode != Some(VoiceMode::GeminiLive)
+            && !self.gemini_session_active()
+            && self.meeting.is_none()
+        {
+            let silence = self.recorder.lock().unwrap().silence_ms();
+            if silence.is_some_and(|ms| ms >= self.config.vad_silence_ms) {
+                log::info!("VAD: {}ms of silence, auto-stopping", self.config.vad_silence_ms);
+                self.stop_recording_and_transcribe();
+            }
+        }
+
+        self.maybe_clean_archive();
+
+        // Hard cap on dictation length: warn on the overlay, then auto-stop
+        if self.recording_mode != Some(VoiceMode::GeminiLive)
+            && !self.gemini_session_active()
+            && self.meeting.is_none()
+        {
+            self.check_recording_limit();
+        }
+
+        // Meeting mode: cut and transcribe a chunk every few minutes
+        if self.meeting_recording() {
+            let interval = std::time::Duration::from_secs(self.config.meeting_chunk_mins.max(1) * 60);
+            if self.meeting.as_ref().is_some_and(|m| m.chunk_due(interval)) {
+                let wav = self.recorder.lock().unwrap().drain_wav();
+                self.transcribe_meeting_chunk(wav);
+            }
+        }
+
+        // Live partial transcripts while dictating
+        if self.config.partial_interval_ms > 0
+            && self.recording_mode != Some(VoiceMode::GeminiLive)
+            && self.is_recording()
+            && self.meeting.is_none()
+        {
+            self.maybe_request_partial();
+        }
+
         // Check global hotkeys
         if let Some(ref hk) = self.hotkey_manager {
             let poll = hk.poll();
-            if poll.toggle_fired {
-                if self.gemini_session_active() || self.recording_mode == Some(VoiceMode::GeminiLive) {
-                    self.disconnect_gemini();
+            if !self.hotkeys_paused {
+                if poll.toggle_fired {
+                    self.on_toggle_hotkey();
                 }
-                self.mode = VoiceMode::Groq;
-                self.recording_mode = Some(VoiceMode::Groq);
-                self.toggle_recording();
-            }
-            if poll.mode_switch_fired {
-                if self.recording_mode == Some(VoiceMode::Groq) && self.is_recording() {
-                    let _ = self.recorder.lock().unwrap().stop_recording();
-                    self.visual.set_state(OverlayState::Idle);
+                if poll.mode_switch_fired {
+                    self.on_mode_hotkey();
+                }
+                if poll.preset_fired {
+                    let next = self.base_config.next_preset();
+                    if let Err(e) = self.switch_preset(&next) {
+                        log::warn!("[Preset] {}", e);
+                    }
+                }
+                if poll.cancel_fired {
+                    self.cancel_dictation();
+                }
+                if poll.reinject_fired {
+                    if let Err(e) = self.reinject_last() {
+                        log::info!("[Reinject] {}", e);
+                    }
+                }
+                if poll.screen_fired {
+                    self.share_screen();
+                }
+                if poll.privacy_fired {
+                    self.set_privacy(!self.config.privacy_mode);
+                }
+                if poll.talk_pressed {
+                    self.talk_start();
+                }
+                if poll.talk_released {
+                    self.talk_released_at = Some(std::time::Instant::now());
                 }
-                self.mode = VoiceMode::GeminiLive;
-                self.recording_mode = Some(VoiceMode::GeminiLive);
-                self.toggle_gemini_session();
             }
         }
 
-        // Drive animation — request redraw when anything is visible
-        if self.visual.state != OverlayState::Idle
+        if self
+            .talk_released_at
+            .is_some_and(|t| t.elapsed() >= std::time::Duration::from_millis(TALK_RELEASE_GRACE_MS))
+        {
+            self.talk_end();
+        }
+
+        self.sync_tray();
+        self.sync_media();
+        self.sync_ducking();
+        self.sync_autohide();
+        self.sync_toast();
+
+        // Drive animation — request redraw when anything is visible, paced
+        // to max_fps so high-refresh displays don't keep a core busy
+        let animating = self.visual.state != OverlayState::Idle
             || self.visual.intensity > 0.001
             || self.visual.ai_intensity > 0.001
-        {
+            || self.visual.label_active();
+        if !animating {
+            event_loop.set_control_flow(ControlFlow::Wait);
+        } else if self.config.max_fps == 0 {
             self.request_redraw_all();
+            event_loop.set_control_flow(ControlFlow::Wait);
+        } else {
+            let now = std::time::Instant::now();
+            if now >= self.next_frame {
+                self.request_redraw_all();
+                let frame = std::time::Duration::from_secs_f64(1.0 / self.config.max_fps as f64);
+                // After a stall, restart the cadence instead of catching up
+                self.next_frame = (self.next_frame + frame).max(now + frame / 2);
+            }
+            event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
         }
 
         // Reset drag state
         self.is_dragging = false;
+
+        // Save the strip's position once a drag has settled
+        if self
+            .strip_moved_at
+            .is_some_and(|t| t.elapsed() >= std::time::Duration::from_millis(STRIP_SAVE_DELAY_MS))
+        {
+            self.strip_moved_at = None;
+            self.strip_dragged = false;
+            self.save_strip_position();
+        }
+
+        // Without windows nothing else wakes the loop to poll hotkeys and timers
+        if self.cli.headless {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(
+                std::time::Instant::now() + std::time::Duration::from_millis(HEADLESS_TICK_MS),
+            ));
+        }
     }
 
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: AppEvent) {
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: AppEvent) {
         match event {
-            AppEvent::TranscriptionComplete(text) => {
+            AppEvent::TranscriptionComplete { generation, .. } | AppEvent::TranscriptionError { generation, .. }
+                if generation != self.transcription_generation =>
+            {
+                log::info!("Dropping result of a cancelled transcription");
+            }
+            AppEvent::TranscriptionComplete {
+                transcript: Transcript { text, provider, fallback_from },
+                ..
+            } => {
+                self.transcription = None;
+                let received = std::time::Instant::now();
+                self.dictation_timing.received = Some(received);
+                if let Some(stopped) = self.dictation_timing.stopped {
+                    self.record_metric(metrics::TRANSCRIPTION, received - stopped);
+                }
                 self.broadcast_dashboard("groq:complete", json!({
                     "text": text,
+                    "provider": provider,
+                    "fallbackFrom": fallback_from,
                     "timestamp": now_ms(),
                 }));
+                if let Some(history) = self.history.as_ref().filter(|_| !self.config.privacy_mode) {
+                    if let Err(e) = history.add_dictation(provider, fallback_from, &text) {
+                        log::warn!("[History] {}", e);
+                    }
+                }
                 self.handle_transcription_result(text);
                 self.request_redraw_all();
             }
-            AppEvent::TranscriptionError(err) => {
+            AppEvent::PartialTranscription { generation, result } => {
+                self.handle_partial_result(generation, result);
+            }
+            AppEvent::ConfigFileChanged => self.reload_config_from_disk(),
+            AppEvent::ShaderFileChanged => self.load_shaders(),
+            AppEvent::MeetingChunk { index, result } => {
+                self.handle_meeting_chunk(index, result);
+            }
+            AppEvent::TranscriptionRetry(notice) => {
+                self.broadcast_dashboard("groq:retry", json!({
+                    "attempt": notice.attempt,
+                    "maxAttempts": notice.max_attempts,
+                    "delayMs": notice.delay.as_millis() as u64,
+                    "reason": notice.reason,
+                    "timestamp": now_ms(),
+                }));
+                if matches!(self.visual.state, OverlayState::Processing | OverlayState::Retrying) {
+                    self.visual.set_state(OverlayState::Retrying);
+                    self.request_redraw_all();
+                }
+            }
+            AppEvent::Usage(usage) => self.record_usage(&usage),
+            AppEvent::KeyChecked { service, status } => self.on_key_checked(service, status),
+            AppEvent::TranscriptionProgress(progress) => {
+                self.broadcast_dashboard("groq:chunk", json!({
+                    "index": progress.index,
+                    "done": progress.done,
+                    "total": progress.total,
+                    "ok": progress.ok,
+                    "timestamp": now_ms(),
+                }));
+                if self.transcription.is_some() {
+                    self.visual.show_status(format!("Chunk {}/{}", progress.done, progress.total));
+                    self.request_redraw_all();
+                }
+            }
+            AppEvent::TranscriptionError { error: err, .. } => {
+                self.transcription = None;
+                self.dictation_timing = DictationTiming::default();
                 log::error!("Transcription error: {}", err);
                 self.broadcast_dashboard("groq:error", json!({
                     "error": err,
                     "timestamp": now_ms(),
                 }));
-                self.visual.set_state(OverlayState::Idle);
+                self.show_error(err);
                 self.request_redraw_all();
             }
 
@@ -819,6 +3592,7 @@ impl ApplicationHandler<AppEvent> for App {
             AppEvent::GeminiReady => {
                 log::info!("[Gemini] Ready — session established, starting mic");
                 self.gemini_connecting = false;
+                self.gemini_turn_ended = None;
                 self.broadcast_dashboard("voice:open", json!({ "timestamp": now_ms() }));
                 self.start_gemini_mic();
                 self.request_redraw_all();
@@ -830,6 +3604,9 @@ impl ApplicationHandler<AppEvent> for App {
                 }
                 if self.visual.state != OverlayState::AISpeaking {
                     log::info!("[Gemini] AI speaking — audio arriving");
+                    if let Some(ended) = self.gemini_turn_ended.take() {
+                        self.record_metric(metrics::FIRST_AUDIO, ended.elapsed());
+                    }
                     self.broadcast_dashboard("voice:speaking", json!({ "timestamp": now_ms() }));
                     self.visual.set_state(OverlayState::AISpeaking);
                 }
@@ -837,15 +3614,32 @@ impl ApplicationHandler<AppEvent> for App {
             }
 
             AppEvent::GeminiText(text) => {
-                log::info!("[Gemini] Text: {}", text);
+                log::debug!("[Gemini] Text: {}", privacy::redact(&text));
+                if self.gemini_text_replies {
+                    self.gemini_said.push_str(&text);
+                }
+                self.visual.push_ai_text(&text);
+                self.request_redraw_all();
                 self.broadcast_dashboard("voice:text", json!({
                     "text": text,
                     "timestamp": now_ms(),
                 }));
             }
 
+            AppEvent::GeminiHeard(text) => {
+                // Pieces keep coming while the user talks; the last one
+                // before the reply marks the end of the turn
+                if self.visual.state != OverlayState::AISpeaking {
+                    self.gemini_turn_ended = Some(std::time::Instant::now());
+                }
+                self.gemini_heard.push_str(&text);
+            }
+
+            AppEvent::GeminiSaid(text) => self.gemini_said.push_str(&text),
+
             AppEvent::GeminiInterrupted => {
                 log::info!("[Gemini] Interrupted (barge-in)");
+                self.finish_gemini_turn();
                 self.broadcast_dashboard("voice:interrupted", json!({ "timestamp": now_ms() }));
                 if let Some(ref player) = self.audio_player {
                     player.clear();
@@ -856,6 +3650,11 @@ impl ApplicationHandler<AppEvent> for App {
 
             AppEvent::GeminiTurnComplete => {
                 log::info!("[Gemini] Turn complete — back to listening");
+                if let Some(ref player) = self.audio_player {
+                    player.flush();
+                }
+                self.finish_gemini_turn();
+                self.visual.end_ai_turn();
                 self.broadcast_dashboard("voice:listening", json!({ "timestamp": now_ms() }));
                 self.visual.set_state(OverlayState::Listening);
                 self.request_redraw_all();
@@ -871,6 +3670,9 @@ impl ApplicationHandler<AppEvent> for App {
                         "input": call.args,
                         "timestamp": ts,
                     }));
+                    self.record_history(|history, session| {
+                        history.add_tool_call(session, &call.id, &call.name, &call.args)
+                    });
                 }
                 self.dispatch_tool_calls(calls);
             }
@@ -879,6 +3681,39 @@ impl ApplicationHandler<AppEvent> for App {
                 log::info!("[Gemini] Tool call cancellation: {:?}", ids);
                 for id in &ids {
                     self.pending_tool_calls.remove(id);
+                    self.finish_history_tool_call(id, "cancelled", None);
+                }
+            }
+
+            AppEvent::ToolProgress {
+                call_id,
+                name,
+                elapsed,
+                output,
+            } => {
+                // Updates for a cancelled call are dropped like its result
+                if self.pending_tool_calls.contains(&call_id) {
+                    self.broadcast_dashboard("voice:tool_progress", json!({
+                        "chatId": call_id,
+                        "toolName": name,
+                        "elapsedSecs": elapsed.as_secs(),
+                        "output": output,
+                        "timestamp": now_ms(),
+                    }));
+                    let response = FunctionResponse {
+                        id: call_id,
+                        name,
+                        response: json!({
+                            "status": "still running",
+                            "elapsed_secs": elapsed.as_secs(),
+                            "new_output": output,
+                        }),
+                        will_continue: true,
+                        scheduling: Some("WHEN_IDLE"),
+                    };
+                    if let Some(ref client) = *GEMINI_CLIENT.lock().unwrap() {
+                        client.send_tool_response(vec![response]);
+                    }
                 }
             }
 
@@ -886,19 +3721,33 @@ impl ApplicationHandler<AppEvent> for App {
                 call_id,
                 name,
                 result,
+                streamed,
             } => {
                 // Only send response if the call wasn't cancelled
                 if self.pending_tool_calls.remove(&call_id) {
                     log::info!("[Tool] {} completed (id={})", name, call_id);
+                    self.finish_history_tool_call(&call_id, "done", Some(&result));
                     self.broadcast_dashboard("voice:tool_result", json!({
                         "chatId": call_id,
                         "toolName": name,
                         "timestamp": now_ms(),
                     }));
+                    if name == "write_file" {
+                        self.broadcast_dashboard("voice:file_write", json!({
+                            "chatId": call_id,
+                            "path": result.get("path"),
+                            "bytes": result.get("bytes"),
+                            "mode": result.get("mode"),
+                            "error": result.get("error"),
+                            "timestamp": now_ms(),
+                        }));
+                    }
                     let response = FunctionResponse {
                         id: call_id,
                         name,
                         response: result,
+                        will_continue: false,
+                        scheduling: streamed.then_some("WHEN_IDLE"),
                     };
                     let guard = GEMINI_CLIENT.lock().unwrap();
                     if let Some(ref client) = *guard {
@@ -916,6 +3765,7 @@ impl ApplicationHandler<AppEvent> for App {
                     "timestamp": now_ms(),
                 }));
                 self.disconnect_gemini();
+                self.show_error(err);
                 self.request_redraw_all();
             }
 
@@ -928,6 +3778,49 @@ impl ApplicationHandler<AppEvent> for App {
                 self.disconnect_gemini();
                 self.request_redraw_all();
             }
+
+            AppEvent::ScreenCaptured(result) => {
+                match result {
+                    Ok(shot) => {
+                        let sent = match *GEMINI_CLIENT.lock().unwrap() {
+                            Some(ref client) if client.is_open() => {
+                                log::info!("[Gemini] Sharing screenshot ({} KB)", shot.data.len() / 1024);
+                                client.send_image(shot.mime_type, shot.data);
+                                true
+                            }
+                            _ => false,
+                        };
+                        if sent {
+                            self.broadcast_dashboard("voice:screen", json!({ "timestamp": now_ms() }));
+                            self.visual.show_status("Screen shared");
+                        } else {
+                            log::info!("[Gemini] Session ended before the screenshot was ready");
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("[Gemini] {}", e);
+                        self.visual.show_status("Screenshot failed");
+                    }
+                }
+                self.request_redraw_all();
+            }
+
+            AppEvent::ReminderDue(id) => self.fire_reminder(id),
+
+            AppEvent::AppToolApproved(call) => self.run_approved_app_tool(call),
+
+            AppEvent::ToolAlwaysAllow(name) => {
+                if !self.config.always_allow_tools.contains(&name) {
+                    self.config.always_allow_tools.push(name.clone());
+                    self.base_config.always_allow_tools.push(name.clone());
+                    if let Err(e) = self.base_config.save() {
+                        log::error!("Failed to save config: {}", e);
+                    }
+                    log::info!("[Tool] {} is now always allowed", name);
+                }
+            }
+            AppEvent::Tray(action) => self.handle_tray_action(event_loop, action),
+            AppEvent::Dashboard(cmd) => self.handle_dashboard_command(event_loop, cmd),
         }
     }
 }
@@ -0,0 +1,173 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hound::{SampleFormat, WavSpec, WavWriter};
+
+use crate::meeting::utc_date_time;
+
+/// Sample rate of the PCM streamed to Gemini
+const SESSION_SAMPLE_RATE: u32 = 16_000;
+/// The `kind`s recordings are saved under
+const KINDS: &[&str] = &["dictation", "meeting", "gemini"];
+
+/// Save a finished recording as `<kind>-<date>-<time>.wav` in `dir`.
+/// Returns the file's path.
+pub fn save(dir: &Path, kind: &str, wav: &[u8]) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let path = unique_path(dir, kind);
+    std::fs::write(&path, wav).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    log::info!("[Archive] Saved {}", path.display());
+    Ok(path)
+}
+
+/// A Gemini session's mic audio (not Gemini's replies), written to disk as it's streamed since
+/// sessions can run for hours.
+pub struct SessionRecording {
+    path: PathBuf,
+    writer: Option<WavWriter<BufWriter<File>>>,
+}
+
+impl SessionRecording {
+    /// Start `gemini-<date>-<time>.wav` in `dir`.
+    pub fn create(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        let path = unique_path(dir, "gemini");
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: SESSION_SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let writer = WavWriter::create(&path, spec)
+            .map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+        Ok(Self {
+            path,
+            writer: Some(writer),
+        })
+    }
+
+    /// Append a chunk of 16-bit little-endian PCM. Audio streamed while
+    /// privacy mode is on is left out.
+    pub fn push(&mut self, pcm_s16le: &[u8]) {
+        if crate::privacy::enabled() {
+            return;
+        }
+        let Some(ref mut writer) = self.writer else {
+            return;
+        };
+        for sample in pcm_s16le.chunks_exact(2) {
+            if let Err(e) = writer.write_sample(i16::from_le_bytes([sample[0], sample[1]])) {
+                log::warn!("[Archive] Cannot write {}: {}", self.path.display(), e);
+                self.writer = None;
+                return;
+            }
+        }
+    }
+
+    /// Write the header and close the file. Sessions where nothing was
+    /// streamed leave no file behind.
+    pub fn finish(mut self) {
+        let Some(writer) = self.writer.take() else {
+            return;
+        };
+        let empty = writer.len() == 0;
+        if let Err(e) = writer.finalize() {
+            log::warn!("[Archive] Cannot finish {}: {}", self.path.display(), e);
+        } else if empty {
+            let _ = std::fs::remove_file(&self.path);
+        } else {
+            log::info!("[Archive] Saved {}", self.path.display());
+        }
+    }
+}
+
+/// Delete archived recordings older than `max_days`, then the oldest ones
+/// until the rest fit in `max_mb`. 0 turns either limit off. Only files
+/// named by [`unique_path`] count, since `dir` may hold other audio.
+/// Returns how many files were removed.
+pub fn cleanup(dir: &Path, max_days: u64, max_mb: u64) -> Result<usize, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Cannot read {}: {}", dir.display(), e)),
+    };
+    let mut files: Vec<(PathBuf, SystemTime, u64)> = entries
+        .filter_map(Result::ok)
+        .filter(|e| is_archived(&e.file_name().to_string_lossy()))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((e.path(), meta.modified().ok()?, meta.len()))
+        })
+        .collect();
+    // Oldest first
+    files.sort_by_key(|(_, modified, _)| *modified);
+
+    let mut removed = 0;
+    let mut remove = |path: &Path| match std::fs::remove_file(path) {
+        Ok(()) => {
+            removed += 1;
+            true
+        }
+        Err(e) => {
+            log::warn!("[Archive] Cannot delete {}: {}", path.display(), e);
+            false
+        }
+    };
+
+    if max_days > 0 {
+        let cutoff = SystemTime::now() - Duration::from_secs(max_days * 86_400);
+        files.retain(|(path, modified, _)| *modified >= cutoff || !remove(path));
+    }
+    if max_mb > 0 {
+        let limit = max_mb * 1024 * 1024;
+        let mut total: u64 = files.iter().map(|(_, _, len)| len).sum();
+        for (path, _, len) in &files {
+            if total <= limit {
+                break;
+            }
+            if remove(path) {
+                total -= len;
+            }
+        }
+    }
+    if removed > 0 {
+        log::info!("[Archive] Removed {} old recordings from {}", removed, dir.display());
+    }
+    Ok(removed)
+}
+
+/// Whether `name` is `<kind>-<YYYY-MM-DD>-<HHMMSS>[-<n>].wav` for one of
+/// [`KINDS`].
+fn is_archived(name: &str) -> bool {
+    let Some(stem) = name.strip_suffix(".wav") else {
+        return false;
+    };
+    let Some(rest) = KINDS.iter().find_map(|kind| stem.strip_prefix(kind)?.strip_prefix('-')) else {
+        return false;
+    };
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let parts: Vec<&str> = rest.split('-').collect();
+    let lengths = [4, 2, 2, 6];
+    (parts.len() == 4 || parts.len() == 5)
+        && parts.iter().all(|p| digits(p))
+        && parts.iter().zip(lengths).all(|(p, len)| p.len() == len)
+}
+
+/// `<kind>-<date>-<HHMMSS>.wav` in `dir`, numbered if that name is taken.
+fn unique_path(dir: &Path, kind: &str) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (date, time) = utc_date_time(now);
+    let stem = format!("{}-{}-{}{:02}", kind, date, time.replace(':', ""), now % 60);
+    let mut path = dir.join(format!("{}.wav", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.wav", stem, n));
+        n += 1;
+    }
+    path
+}
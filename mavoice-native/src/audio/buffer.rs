@@ -0,0 +1,281 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+/// Seconds of audio kept in RAM before the rest spills to a temp file.
+const MEMORY_SECS: usize = 120;
+
+/// Fixed-capacity ring of the most recent samples (level metering, pre-roll).
+pub struct LevelRing {
+    buf: Vec<f32>,
+    pos: usize,
+    filled: bool,
+}
+
+impl LevelRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buf: vec![0.0; capacity],
+            pos: 0,
+            filled: false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pos = 0;
+        self.filled = false;
+    }
+
+    pub fn push(&mut self, data: &[f32]) {
+        for &s in data {
+            self.buf[self.pos] = s;
+            self.pos += 1;
+            if self.pos == self.buf.len() {
+                self.pos = 0;
+                self.filled = true;
+            }
+        }
+    }
+
+    /// The buffered samples, oldest first.
+    pub fn recent(&self) -> Vec<f32> {
+        if self.filled {
+            let mut out = Vec::with_capacity(self.buf.len());
+            out.extend_from_slice(&self.buf[self.pos..]);
+            out.extend_from_slice(&self.buf[..self.pos]);
+            out
+        } else {
+            self.buf[..self.pos].to_vec()
+        }
+    }
+}
+
+/// Temp file holding the older part of a long recording as raw f32 LE.
+struct Spill {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    /// Samples fully written; anything after this in the file is ignored.
+    samples: usize,
+}
+
+impl Spill {
+    fn create() -> std::io::Result<Self> {
+        // Prefer the per-user runtime dir; the file is created 0600 either way.
+        let dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let path = dir.join(format!("mavoice-{}-{}.f32", std::process::id(), nanos));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)?;
+        log::info!("Recording spills to {}", path.display());
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path,
+            samples: 0,
+        })
+    }
+
+    fn write(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.writer.write_all(&bytes)?;
+        self.samples += samples.len();
+        Ok(())
+    }
+
+    fn read(&mut self) -> Result<Vec<f32>, String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("Spill flush failed: {}", e))?;
+        let bytes = std::fs::read(&self.path).map_err(|e| format!("Spill read failed: {}", e))?;
+        Ok(bytes
+            .chunks_exact(4)
+            .take(self.samples)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+enum SpillMsg {
+    /// Append these samples
+    Write(Vec<f32>),
+    /// Send back everything spilled so far
+    Read(mpsc::Sender<Result<Vec<f32>, String>>),
+    /// Drop the recording and its file
+    Reset,
+}
+
+/// Owns the spill file so creating and writing it never happens on the
+/// audio callback. Audio that can't be written is kept in memory here.
+fn spill_writer(rx: mpsc::Receiver<SpillMsg>) {
+    let mut spill: Option<Spill> = None;
+    let mut kept: Vec<f32> = Vec::new();
+    let mut failed = false;
+    for msg in rx {
+        match msg {
+            SpillMsg::Write(samples) => {
+                if spill.is_none() && !failed {
+                    match Spill::create() {
+                        Ok(s) => spill = Some(s),
+                        Err(e) => {
+                            log::warn!("Cannot spill recording to disk, keeping it in memory: {}", e);
+                            failed = true;
+                        }
+                    }
+                }
+                match spill.as_mut().filter(|_| !failed) {
+                    Some(s) => {
+                        if let Err(e) = s.write(&samples) {
+                            // Whatever made it to the file past `s.samples` is
+                            // ignored on read-back
+                            log::warn!("Spill write failed, keeping recording in memory: {}", e);
+                            failed = true;
+                            kept.extend_from_slice(&samples);
+                        }
+                    }
+                    None => kept.extend_from_slice(&samples),
+                }
+            }
+            SpillMsg::Read(reply) => {
+                let result = match spill.as_mut() {
+                    Some(s) => s.read(),
+                    None => Ok(Vec::new()),
+                }
+                .map(|mut samples| {
+                    samples.extend_from_slice(&kept);
+                    samples
+                });
+                let _ = reply.send(result);
+            }
+            SpillMsg::Reset => {
+                spill = None;
+                kept = Vec::new();
+                failed = false;
+            }
+        }
+    }
+}
+
+/// Recording storage with a hard length cap. The first `MEMORY_SECS` stay in
+/// memory; older audio is handed to a writer thread that moves it to a
+/// private temp file, so a long recording doesn't keep growing the heap.
+pub struct RecordingBuffer {
+    mem: Vec<f32>,
+    /// `None` if the writer thread couldn't start; everything stays in memory
+    spill: Option<mpsc::Sender<SpillMsg>>,
+    /// Samples handed to the writer thread
+    spilled: usize,
+    memory_samples: usize,
+    max_samples: usize,
+    total: usize,
+    truncated: bool,
+}
+
+impl RecordingBuffer {
+    pub fn new(sample_rate: u32, max_secs: u64) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let spill = match std::thread::Builder::new()
+            .name("recording-spill".into())
+            .spawn(move || spill_writer(rx))
+        {
+            Ok(_) => Some(tx),
+            Err(e) => {
+                log::warn!("Cannot start the spill writer, long recordings stay in memory: {}", e);
+                None
+            }
+        };
+        Self {
+            mem: Vec::new(),
+            spill,
+            spilled: 0,
+            memory_samples: sample_rate as usize * MEMORY_SECS,
+            max_samples: sample_rate as usize * max_secs as usize,
+            total: 0,
+            truncated: false,
+        }
+    }
+
+    pub fn set_max_samples(&mut self, max_samples: usize) {
+        self.max_samples = max_samples;
+    }
+
+    /// Drop all audio, including any spill file.
+    pub fn clear(&mut self) {
+        self.mem = Vec::new();
+        if self.spilled > 0 {
+            if let Some(ref tx) = self.spill {
+                let _ = tx.send(SpillMsg::Reset);
+            }
+        }
+        self.spilled = 0;
+        self.total = 0;
+        self.truncated = false;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Samples recorded so far, in memory and on disk.
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    pub fn push(&mut self, data: &[f32]) {
+        let room = self.max_samples.saturating_sub(self.total);
+        let take = data.len().min(room);
+        if take < data.len() && !self.truncated {
+            log::warn!("Recording reached its maximum length; further audio is dropped");
+            self.truncated = true;
+        }
+        self.mem.extend_from_slice(&data[..take]);
+        self.total += take;
+
+        if self.mem.len() >= self.memory_samples {
+            if let Some(ref tx) = self.spill {
+                let chunk = std::mem::take(&mut self.mem);
+                let len = chunk.len();
+                match tx.send(SpillMsg::Write(chunk)) {
+                    Ok(()) => self.spilled += len,
+                    // The writer is gone; keep the audio here
+                    Err(mpsc::SendError(SpillMsg::Write(chunk))) => {
+                        self.mem = chunk;
+                        self.spill = None;
+                    }
+                    Err(_) => self.spill = None,
+                }
+            }
+        }
+    }
+
+    /// Everything recorded so far, reading back any spilled audio.
+    pub fn samples(&mut self) -> Result<Vec<f32>, String> {
+        let mut out = Vec::with_capacity(self.total);
+        if self.spilled > 0 {
+            let tx = self.spill.as_ref().ok_or("The spill writer stopped")?;
+            let (reply_tx, reply_rx) = mpsc::channel();
+            tx.send(SpillMsg::Read(reply_tx))
+                .map_err(|_| "The spill writer stopped".to_string())?;
+            let spilled = reply_rx
+                .recv()
+                .map_err(|_| "The spill writer stopped".to_string())??;
+            out.extend_from_slice(&spilled);
+        }
+        out.extend_from_slice(&self.mem);
+        Ok(out)
+    }
+}
@@ -0,0 +1,227 @@
+/// RMS below this (~-40 dBFS) counts as silence when looking for a cut point.
+const SILENCE_RMS: f32 = 0.01;
+
+/// Analysis frame for silence detection.
+const FRAME_MS: u32 = 20;
+
+/// Chunking parameters, all in seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkPlan {
+    /// Preferred chunk length.
+    pub target_secs: f32,
+    /// How far either side of the target to look for a silent cut point.
+    pub search_secs: f32,
+    /// Overlap applied only when no silence was found and we had to cut mid-speech.
+    pub overlap_secs: f32,
+}
+
+/// A slice of the source recording, as sample indices into the decoded PCM.
+#[derive(Debug, Clone, Copy)]
+pub struct AudioChunk {
+    pub index: usize,
+    pub start: usize,
+    pub end: usize,
+    /// True if this chunk ended on detected silence rather than a hard cut.
+    pub clean_cut: bool,
+}
+
+/// Split mono PCM into chunks near `plan.target_secs`, cutting at the quietest
+/// point within the search window so words aren't split mid-sample.
+pub fn split_on_silence(samples: &[f32], sample_rate: u32, plan: ChunkPlan) -> Vec<AudioChunk> {
+    let rate = sample_rate as f32;
+    let target = (plan.target_secs * rate) as usize;
+    let search = ((plan.search_secs * rate) as usize).min(target / 2);
+    let overlap = ((plan.overlap_secs * rate) as usize).min(target / 4);
+    let frame = ((sample_rate * FRAME_MS) / 1000).max(1) as usize;
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < samples.len() {
+        let remaining = samples.len() - start;
+        if target == 0 || remaining <= target + search {
+            chunks.push(AudioChunk {
+                index: chunks.len(),
+                start,
+                end: samples.len(),
+                clean_cut: true,
+            });
+            break;
+        }
+
+        let ideal = start + target;
+        let lo = ideal - search;
+        let hi = (ideal + search).min(samples.len());
+
+        let (cut, rms) = quietest_frame(samples, lo, hi, frame).unwrap_or((ideal, f32::MAX));
+
+        if rms <= SILENCE_RMS {
+            chunks.push(AudioChunk {
+                index: chunks.len(),
+                start,
+                end: cut,
+                clean_cut: true,
+            });
+            start = cut;
+        } else {
+            log::debug!(
+                "No silence near {:.1}s (min RMS {:.3}), hard cut with overlap",
+                ideal as f32 / rate,
+                rms
+            );
+            chunks.push(AudioChunk {
+                index: chunks.len(),
+                start,
+                end: ideal,
+                clean_cut: false,
+            });
+            start = ideal - overlap;
+        }
+    }
+
+    chunks
+}
+
+/// Find the frame with the lowest RMS in `[lo, hi)`, stepping by half a frame.
+/// Returns the sample index at the centre of that frame and its RMS.
+fn quietest_frame(samples: &[f32], lo: usize, hi: usize, frame: usize) -> Option<(usize, f32)> {
+    let hop = (frame / 2).max(1);
+    let mut best: Option<(usize, f32)> = None;
+    let mut pos = lo;
+
+    while pos + frame <= hi {
+        let window = &samples[pos..pos + frame];
+        let rms = (window.iter().map(|&x| x * x).sum::<f32>() / frame as f32).sqrt();
+        if best.is_none_or(|(_, b)| rms < b) {
+            best = Some((pos + frame / 2, rms));
+        }
+        pos += hop;
+    }
+
+    best
+}
+
+/// Words at either end of an overlapped cut that may be half-heard and
+/// transcribed differently by the two chunks.
+const STITCH_SLACK: usize = 2;
+
+/// Longest repeated run looked for; two seconds of speech is well under this.
+const STITCH_MAX_WORDS: usize = 16;
+
+/// Append the transcript of the next chunk to `text`. After a hard cut
+/// (`overlapped`) both chunks contain the same couple of seconds, so the
+/// longest run of words ending `text` that also starts `next` is kept only
+/// once, allowing for a few cut-off words on either side of it. Needs at
+/// least two matching words; otherwise the texts are just joined.
+pub fn stitch(text: &mut String, next: &str, overlapped: bool) {
+    let next = next.trim();
+    if next.is_empty() {
+        return;
+    }
+    if text.is_empty() {
+        text.push_str(next);
+        return;
+    }
+    if !overlapped {
+        text.push(' ');
+        text.push_str(next);
+        return;
+    }
+
+    let prev_words: Vec<&str> = text.split_whitespace().collect();
+    let next_words: Vec<&str> = next.split_whitespace().collect();
+    let key = |w: &str| -> String {
+        w.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let prev_keys: Vec<String> = prev_words.iter().map(|w| key(w)).collect();
+    let next_keys: Vec<String> = next_words.iter().map(|w| key(w)).collect();
+
+    // (run length, dropped from the end of text, dropped from the start of next)
+    let mut best: Option<(usize, usize, usize)> = None;
+    for drop_prev in 0..=STITCH_SLACK.min(prev_keys.len()) {
+        let prev_end = prev_keys.len() - drop_prev;
+        for skip_next in 0..=STITCH_SLACK.min(next_keys.len()) {
+            let max_run = STITCH_MAX_WORDS
+                .min(prev_end)
+                .min(next_keys.len() - skip_next);
+            let run = (2..=max_run).rev().find(|&k| {
+                prev_keys[prev_end - k..prev_end] == next_keys[skip_next..skip_next + k]
+                    && next_keys[skip_next..skip_next + k].iter().all(|w| !w.is_empty())
+            });
+            if let Some(k) = run {
+                let better = best.is_none_or(|(bk, bp, bn)| {
+                    k > bk || (k == bk && drop_prev + skip_next < bp + bn)
+                });
+                if better {
+                    best = Some((k, drop_prev, skip_next));
+                }
+            }
+        }
+    }
+
+    match best {
+        Some((run, drop_prev, skip_next)) => {
+            log::debug!(
+                "Stitched chunks on {} repeated words (dropped {} + {})",
+                run,
+                drop_prev,
+                skip_next
+            );
+            // Byte offset just past the last word kept from `text`
+            let last = prev_words[prev_words.len() - drop_prev - 1];
+            let cut = last.as_ptr() as usize - text.as_ptr() as usize + last.len();
+            let rest = &next_words[skip_next + run..];
+            text.truncate(cut);
+            if !rest.is_empty() {
+                text.push(' ');
+                text.push_str(&rest.join(" "));
+            }
+        }
+        None => {
+            text.push(' ');
+            text.push_str(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stitched(text: &str, next: &str, overlapped: bool) -> String {
+        let mut text = text.to_string();
+        stitch(&mut text, next, overlapped);
+        text
+    }
+
+    #[test]
+    fn overlap_is_kept_once() {
+        assert_eq!(
+            stitched("we should meet at the office", "at the office tomorrow morning", true),
+            "we should meet at the office tomorrow morning"
+        );
+    }
+
+    #[test]
+    fn repeated_words_use_the_longest_run() {
+        assert_eq!(stitched("I said no no no", "no no no way", true), "I said no no no way");
+    }
+
+    #[test]
+    fn cut_off_word_is_dropped() {
+        assert_eq!(
+            stitched("please send the report to marketing tod", "to marketing today and then", true),
+            "please send the report to marketing today and then"
+        );
+    }
+
+    #[test]
+    fn one_word_or_no_overlap_just_joins() {
+        assert_eq!(stitched("go home", "home now", true), "go home home now");
+        assert_eq!(stitched("go home", "home now", false), "go home home now");
+        assert_eq!(stitched("", "home now", true), "home now");
+    }
+}
@@ -0,0 +1,62 @@
+use nnnoiseless::DenoiseState;
+
+/// RNNoise works on 48 kHz audio in 10ms frames.
+const RNNOISE_RATE: u32 = 48_000;
+
+/// Streaming RNNoise noise suppression for the capture path.
+///
+/// Input at other rates is brought to 48 kHz by an integer factor (16 kHz ->
+/// x3) with linear interpolation and back by averaging, which is plenty for
+/// speech. Output lags input by up to one frame.
+pub struct Denoiser {
+    state: Box<DenoiseState<'static>>,
+    factor: usize,
+    last: f32,
+    frame_in: Vec<f32>,
+    frame_out: Vec<f32>,
+    out48: Vec<f32>,
+}
+
+impl Denoiser {
+    /// `None` if `sample_rate` doesn't divide 48 kHz evenly.
+    pub fn new(sample_rate: u32) -> Option<Self> {
+        if sample_rate == 0 || RNNOISE_RATE % sample_rate != 0 {
+            log::warn!("Noise suppression needs a rate dividing 48 kHz, got {} Hz", sample_rate);
+            return None;
+        }
+        Some(Self {
+            state: DenoiseState::new(),
+            factor: (RNNOISE_RATE / sample_rate) as usize,
+            last: 0.0,
+            frame_in: Vec::with_capacity(DenoiseState::FRAME_SIZE),
+            frame_out: vec![0.0; DenoiseState::FRAME_SIZE],
+            out48: Vec::new(),
+        })
+    }
+
+    /// Denoise normalized samples, returning whatever output is ready.
+    pub fn process(&mut self, data: &[f32]) -> Vec<f32> {
+        for &s in data {
+            // Upsample; RNNoise expects 16-bit range floats
+            for k in 1..=self.factor {
+                let t = k as f32 / self.factor as f32;
+                let v = self.last + (s - self.last) * t;
+                self.frame_in.push(v * i16::MAX as f32);
+                if self.frame_in.len() == DenoiseState::FRAME_SIZE {
+                    self.state.process_frame(&mut self.frame_out, &self.frame_in);
+                    self.out48.extend(self.frame_out.iter().map(|v| v / i16::MAX as f32));
+                    self.frame_in.clear();
+                }
+            }
+            self.last = s;
+        }
+
+        let usable = self.out48.len() - self.out48.len() % self.factor;
+        let out = self.out48[..usable]
+            .chunks_exact(self.factor)
+            .map(|c| c.iter().sum::<f32>() / self.factor as f32)
+            .collect();
+        self.out48.drain(..usable);
+        out
+    }
+}
@@ -0,0 +1,89 @@
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+
+use super::wav::decode_wav;
+
+/// Container used when uploading recordings for transcription.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UploadFormat {
+    #[default]
+    Wav,
+    /// Lossless, typically ~2x smaller than 16-bit WAV for speech.
+    Flac,
+}
+
+impl UploadFormat {
+    /// Parse the `upload_format` config value; unknown values fall back to WAV.
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "flac" => UploadFormat::Flac,
+            "wav" => UploadFormat::Wav,
+            other => {
+                log::warn!("Unknown upload_format '{}', using wav", other);
+                UploadFormat::Wav
+            }
+        }
+    }
+}
+
+/// Audio ready to attach to a multipart upload.
+pub struct UploadAudio {
+    pub bytes: Vec<u8>,
+    pub file_name: String,
+    pub mime: &'static str,
+}
+
+/// Re-encode a WAV recording in `format`. If encoding fails the original WAV
+/// is sent instead, so a codec problem never costs a transcription.
+pub fn prepare_upload(wav: &[u8], file_name: &str, format: UploadFormat) -> UploadAudio {
+    let wav_upload = || UploadAudio {
+        bytes: wav.to_vec(),
+        file_name: file_name.to_string(),
+        mime: "audio/wav",
+    };
+
+    match format {
+        UploadFormat::Wav => wav_upload(),
+        UploadFormat::Flac => match wav_to_flac(wav) {
+            Ok(bytes) => {
+                log::info!(
+                    "FLAC: {:.1} KB -> {:.1} KB",
+                    wav.len() as f64 / 1024.0,
+                    bytes.len() as f64 / 1024.0
+                );
+                UploadAudio {
+                    bytes,
+                    file_name: std::path::Path::new(file_name)
+                        .with_extension("flac")
+                        .to_string_lossy()
+                        .into_owned(),
+                    mime: "audio/flac",
+                }
+            }
+            Err(e) => {
+                log::warn!("FLAC encoding failed, uploading WAV: {}", e);
+                wav_upload()
+            }
+        },
+    }
+}
+
+/// Encode a WAV file as 16-bit mono FLAC.
+fn wav_to_flac(wav: &[u8]) -> Result<Vec<u8>, String> {
+    let (samples, rate) = decode_wav(wav)?;
+    let pcm: Vec<i32> = samples
+        .iter()
+        .map(|&s| (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i32)
+        .collect();
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| format!("Invalid FLAC config: {:?}", e))?;
+    let source = flacenc::source::MemSource::from_samples(&pcm, 1, 16, rate as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("{:?}", e))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream.write(&mut sink).map_err(|e| format!("{:?}", e))?;
+    Ok(sink.as_slice().to_vec())
+}
@@ -0,0 +1,78 @@
+/// Loudest gain either stage will apply (+30 dB), so near-silence isn't
+/// blown up into hiss.
+const MAX_GAIN: f32 = 31.6;
+
+/// Peak ceiling after normalization (-1 dBFS).
+const PEAK_CEILING: f32 = 0.89;
+
+/// Frames quieter than this (-55 dBFS RMS) are treated as silence and don't
+/// count toward the loudness estimate.
+const SILENCE_RMS: f32 = 0.0018;
+
+/// Analysis frame for the offline pass (20ms at 16 kHz).
+const FRAME: usize = 320;
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|&x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Scale a finished recording so its speech sits at `target_dbfs` RMS,
+/// without pushing any peak above -1 dBFS. Returns the gain applied.
+pub fn normalize(samples: &mut [f32], target_dbfs: f32) -> f32 {
+    let active: Vec<f32> = samples
+        .chunks(FRAME)
+        .map(rms)
+        .filter(|&r| r > SILENCE_RMS)
+        .collect();
+    if active.is_empty() {
+        return 1.0;
+    }
+    let speech_rms =
+        (active.iter().map(|r| r * r).sum::<f32>() / active.len() as f32).sqrt();
+    let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+
+    let gain = (db_to_linear(target_dbfs) / speech_rms)
+        .min(PEAK_CEILING / peak.max(f32::EPSILON))
+        .min(MAX_GAIN);
+    for s in samples.iter_mut() {
+        *s *= gain;
+    }
+    gain
+}
+
+/// Slow automatic gain control for live audio (Gemini streaming), where the
+/// whole recording isn't available up front.
+pub struct Agc {
+    target: f32,
+    gain: f32,
+}
+
+impl Agc {
+    pub fn new(target_dbfs: f32) -> Self {
+        Self {
+            target: db_to_linear(target_dbfs),
+            gain: 1.0,
+        }
+    }
+
+    /// Adjust gain from this block's level and apply it in place. Gain drops
+    /// quickly on loud input and rises slowly, and holds during silence.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let level = rms(samples);
+        if level > SILENCE_RMS {
+            let wanted = (self.target / level).min(MAX_GAIN);
+            let rate = if wanted < self.gain { 0.5 } else { 0.05 };
+            self.gain += (wanted - self.gain) * rate;
+        }
+        for s in samples.iter_mut() {
+            *s = (*s * self.gain).clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -1,4 +1,11 @@
+pub mod buffer;
+pub mod chunking;
+pub mod denoise;
+pub mod encode;
+pub mod gain;
 pub mod player;
 pub mod recorder;
+pub mod vad;
+pub mod wav;
 pub use player::AudioPlayer;
 pub use recorder::GroqRecorder;
@@ -1,11 +1,21 @@
 use cpal::traits::*;
-use cpal::{Device, SampleRate, Stream, StreamConfig};
+use cpal::{Device, SampleRate, Stream, StreamConfig, StreamError};
+use rubato::{FftFixedIn, Resampler};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+/// Gemini's output sample rate.
+const GEMINI_RATE: u32 = 24_000;
+
 /// Minimum buffered samples before playback starts (~170ms at 24kHz).
 const BUFFER_THRESHOLD: usize = 4080;
 
+/// How often a player on the default device checks whether it moved.
+const DEVICE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Input frames per resampler call (~20ms at 24kHz).
+const RESAMPLE_CHUNK: usize = 480;
+
 /// Audio player for Gemini Live PCM output (24kHz mono s16le).
 ///
 /// Uses a shared ring buffer: the main thread enqueues decoded PCM data,
@@ -19,20 +29,33 @@ pub struct AudioPlayer {
     playing: Arc<AtomicBool>,
     /// Whether we're currently buffering (waiting to reach threshold before playback)
     buffering: Arc<AtomicBool>,
+    /// Converts to the device rate when it can't play 24kHz
+    resampler: Option<Mutex<Resample>>,
+    /// Name of the device playing
+    device_name: String,
+    /// Set when the stream should be rebuilt on another device
+    stale: Arc<AtomicBool>,
+    /// Stops the device watcher
+    closed: Arc<AtomicBool>,
 }
 
 impl AudioPlayer {
-    pub fn new() -> Result<Self, String> {
+    /// Play on the output device whose name contains `requested`, or the
+    /// default device if it's empty or not found.
+    pub fn new(requested: &str) -> Result<Self, String> {
         log::info!("Initializing audio player for Gemini output");
 
         let host = cpal::default_host();
-        let output_device = host
-            .default_output_device()
-            .ok_or("No output device available")?;
-        log::info!(
-            "Output device: {}",
-            output_device.name().unwrap_or_default()
-        );
+        let (output_device, follows_default) = match find_output_device(&host, requested) {
+            Some(device) => (device, false),
+            None => (
+                host.default_output_device()
+                    .ok_or("No output device available")?,
+                true,
+            ),
+        };
+        let device_name = output_device.name().unwrap_or_default();
+        log::info!("Output device: {}", device_name);
 
         // Try 24kHz mono first (Gemini's native output rate), fallback to device default
         let (config, needs_resample) = Self::pick_output_config(&output_device)?;
@@ -48,12 +71,21 @@ impl AudioPlayer {
         let recent_output = Arc::new(Mutex::new(Vec::<f32>::with_capacity(2048)));
         let playing = Arc::new(AtomicBool::new(false));
         let buffering = Arc::new(AtomicBool::new(true));
+        let stale = Arc::new(AtomicBool::new(false));
+        let stale_clone = stale.clone();
 
         let buf_clone = buffer.clone();
         let recent_clone = recent_output.clone();
         let playing_clone = playing.clone();
         let buffering_clone = buffering.clone();
         let out_channels = config.channels as usize;
+        // Same ~170ms at the device rate
+        let threshold = BUFFER_THRESHOLD * config.sample_rate.0 as usize / GEMINI_RATE as usize;
+        let resampler = if needs_resample {
+            Some(Mutex::new(Resample::new(config.sample_rate.0)?))
+        } else {
+            None
+        };
 
         let stream = output_device
             .build_output_stream(
@@ -64,7 +96,7 @@ impl AudioPlayer {
 
                     // Buffering mode: output silence until we have enough data
                     if buffering_clone.load(Ordering::Relaxed) {
-                        if buf.len() >= BUFFER_THRESHOLD {
+                        if buf.len() >= threshold {
                             buffering_clone.store(false, Ordering::Relaxed);
                             log::debug!("Playback buffer filled ({} samples), starting drain", buf.len());
                         } else {
@@ -113,7 +145,12 @@ impl AudioPlayer {
                         recent.drain(..excess);
                     }
                 },
-                |err| log::error!("Output stream error: {err}"),
+                move |err| {
+                    log::error!("Output stream error: {err}");
+                    if matches!(err, StreamError::DeviceNotAvailable) {
+                        stale_clone.store(true, Ordering::Relaxed);
+                    }
+                },
                 None,
             )
             .map_err(|e| e.to_string())?;
@@ -121,23 +158,43 @@ impl AudioPlayer {
         stream.play().map_err(|e| e.to_string())?;
         log::info!("Audio player started");
 
+        let closed = Arc::new(AtomicBool::new(false));
+        if follows_default {
+            watch_devices(device_name.clone(), requested.to_string(), stale.clone(), closed.clone());
+        }
+
         Ok(Self {
             _stream: stream,
             buffer,
             recent_output,
             playing,
             buffering,
+            resampler,
+            device_name,
+            stale,
+            closed,
         })
     }
 
+    /// Whether the stream should be rebuilt: its device went away, or it
+    /// plays the default device and a better one is available now (the
+    /// default changed, or the configured device was plugged in).
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(Ordering::Relaxed)
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
     /// Pick output config: prefer 24kHz mono, fall back to device default.
     fn pick_output_config(device: &Device) -> Result<(StreamConfig, bool), String> {
         let supports_24k = device
             .supported_output_configs()
             .map(|mut it| {
                 it.any(|c| {
-                    c.min_sample_rate() <= SampleRate(24_000)
-                        && c.max_sample_rate() >= SampleRate(24_000)
+                    c.min_sample_rate() <= SampleRate(GEMINI_RATE)
+                        && c.max_sample_rate() >= SampleRate(GEMINI_RATE)
                 })
             })
             .unwrap_or(false);
@@ -146,13 +203,13 @@ impl AudioPlayer {
             Ok((
                 StreamConfig {
                     channels: 1,
-                    sample_rate: SampleRate(24_000),
+                    sample_rate: SampleRate(GEMINI_RATE),
                     buffer_size: cpal::BufferSize::Default,
                 },
                 false,
             ))
         } else {
-            // Use device default — we'll need to resample
+            // Use device default; enqueue resamples to its rate
             let def = device.default_output_config().map_err(|e| e.to_string())?;
             log::warn!(
                 "24kHz not supported, using device default: {} Hz, {} ch",
@@ -164,7 +221,9 @@ impl AudioPlayer {
         }
     }
 
-    /// Enqueue raw PCM data from Gemini (24kHz mono s16le bytes).
+    /// Enqueue raw PCM data from Gemini (24kHz mono s16le bytes),
+    /// resampled to the device rate if it differs. The output callback
+    /// copies the mono signal to every device channel.
     /// Thread-safe — can be called from any thread.
     pub fn enqueue(&self, pcm_24khz_s16le: &[u8]) {
         // Convert s16le bytes → f32 samples
@@ -176,13 +235,32 @@ impl AudioPlayer {
             })
             .collect();
 
-        let mut buf = self.buffer.lock().unwrap();
-        buf.extend_from_slice(&samples);
+        match self.resampler {
+            Some(ref resampler) => {
+                let mut resampler = resampler.lock().unwrap();
+                let mut buf = self.buffer.lock().unwrap();
+                resampler.push(&samples, &mut buf);
+            }
+            None => self.buffer.lock().unwrap().extend_from_slice(&samples),
+        }
+    }
+
+    /// Queue the audio still held by the resampler at the end of a turn,
+    /// which would otherwise play at the start of the next one.
+    pub fn flush(&self) {
+        if let Some(ref resampler) = self.resampler {
+            let mut resampler = resampler.lock().unwrap();
+            let mut buf = self.buffer.lock().unwrap();
+            resampler.flush(&mut buf);
+        }
     }
 
     /// Flush the playback buffer (for barge-in interruption).
     /// Resets buffering state so next response starts fresh.
     pub fn clear(&self) {
+        if let Some(ref resampler) = self.resampler {
+            resampler.lock().unwrap().reset();
+        }
         self.buffer.lock().unwrap().clear();
         self.recent_output.lock().unwrap().clear();
         self.buffering.store(true, Ordering::Relaxed);
@@ -237,3 +315,132 @@ impl AudioPlayer {
         levels
     }
 }
+
+impl Drop for AudioPlayer {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Check every [`DEVICE_CHECK_INTERVAL`] whether a player on the default
+/// device `playing` should move: the default is now another device, or an
+/// output matching `requested` has appeared. Device enumeration can be
+/// slow, so this runs on its own thread rather than the event loop.
+fn watch_devices(playing: String, requested: String, stale: Arc<AtomicBool>, closed: Arc<AtomicBool>) {
+    let requested = requested.trim().to_lowercase();
+    let spawned = std::thread::Builder::new()
+        .name("output-devices".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(DEVICE_CHECK_INTERVAL);
+            if closed.load(Ordering::Relaxed) || stale.load(Ordering::Relaxed) {
+                return;
+            }
+            let host = cpal::default_host();
+            let default = host.default_output_device().and_then(|d| d.name().ok());
+            let moved = default.as_ref().is_some_and(|name| *name != playing);
+            let appeared = !requested.is_empty()
+                && host.output_devices().is_ok_and(|mut devices| {
+                    devices.any(|d| d.name().is_ok_and(|n| n.to_lowercase().contains(&requested)))
+                });
+            if moved || appeared {
+                log::info!("Output device changed, moving playback off {}", playing);
+                stale.store(true, Ordering::Relaxed);
+                return;
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Cannot watch output devices: {}", e);
+    }
+}
+
+/// The output device whose name contains `name`, if any.
+fn find_output_device(host: &cpal::Host, name: &str) -> Option<Device> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let wanted = name.to_lowercase();
+    let devices = match host.output_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            log::warn!("Cannot list output devices: {}", e);
+            return None;
+        }
+    };
+    let mut available = Vec::new();
+    for device in devices {
+        let device_name = device.name().unwrap_or_default();
+        if device_name.to_lowercase().contains(&wanted) {
+            return Some(device);
+        }
+        available.push(device_name);
+    }
+    log::warn!(
+        "No output device matching '{}', using the default. Available: {}",
+        name,
+        available.join(", ")
+    );
+    None
+}
+
+/// Streaming 24kHz → device-rate conversion. Input that doesn't fill a
+/// whole chunk waits for the next call (or [`Resample::flush`]).
+struct Resample {
+    resampler: FftFixedIn<f32>,
+    pending: Vec<f32>,
+    /// Audio went in since the last reset, so the delay line holds some
+    primed: bool,
+}
+
+impl Resample {
+    fn new(rate: u32) -> Result<Self, String> {
+        let resampler = FftFixedIn::new(GEMINI_RATE as usize, rate as usize, RESAMPLE_CHUNK, 2, 1)
+            .map_err(|e| format!("Cannot resample to {} Hz: {}", rate, e))?;
+        Ok(Self {
+            resampler,
+            pending: Vec::with_capacity(RESAMPLE_CHUNK * 2),
+            primed: false,
+        })
+    }
+
+    /// Resample `samples` after whatever is pending and append the result to `out`.
+    fn push(&mut self, samples: &[f32], out: &mut Vec<f32>) {
+        self.pending.extend_from_slice(samples);
+        self.primed |= !samples.is_empty();
+        let mut used = 0;
+        while self.pending.len() - used >= self.resampler.input_frames_next() {
+            let n = self.resampler.input_frames_next();
+            let input = [&self.pending[used..used + n]];
+            match self.resampler.process(&input[..], None) {
+                Ok(frames) => out.extend_from_slice(&frames[0]),
+                Err(e) => log::warn!("Resampling failed: {}", e),
+            }
+            used += n;
+        }
+        self.pending.drain(..used);
+    }
+
+    /// Resample the pending input and the resampler's own delay line, then
+    /// start over for the next stream.
+    fn flush(&mut self, out: &mut Vec<f32>) {
+        if !self.primed {
+            return;
+        }
+        if !self.pending.is_empty() {
+            let input = [&self.pending[..]];
+            if let Ok(frames) = self.resampler.process_partial(Some(&input[..]), None) {
+                out.extend_from_slice(&frames[0]);
+            }
+        }
+        if let Ok(frames) = self.resampler.process_partial::<&[f32]>(None, None) {
+            out.extend_from_slice(&frames[0]);
+        }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.pending.clear();
+        self.primed = false;
+        self.resampler.reset();
+    }
+}
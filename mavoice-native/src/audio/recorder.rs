@@ -1,34 +1,139 @@
 use cpal::traits::*;
 use cpal::{Device, SampleFormat, SampleRate, Stream, StreamConfig};
-use crossbeam_channel::{unbounded, Receiver, Sender};
-use hound::{SampleFormat as HoundSampleFormat, WavSpec, WavWriter};
-use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use super::buffer::{LevelRing, RecordingBuffer};
+use super::denoise::Denoiser;
+use super::gain::{normalize, Agc};
+use super::vad::EnergyVad;
+
 /// Callback that receives raw s16le PCM chunks for real-time streaming (Gemini mode).
 pub type StreamingCallback = Arc<dyn Fn(&[u8]) + Send + Sync>;
 
+/// Streaming chunk size (~100ms = 1600 samples at 16kHz)
+const STREAM_CHUNK_SIZE: usize = 1600;
+
+/// Samples kept for the level meter (~64ms at 16kHz).
+const LEVEL_WINDOW: usize = 1024;
+
+/// Default recording cap until `set_max_duration` is called.
+const DEFAULT_MAX_SECS: u64 = 600;
+
+/// Everything the cpal input callback writes into, shared by all sample formats.
+struct CaptureSink {
+    audio_buf: Arc<Mutex<RecordingBuffer>>,
+    levels: Arc<Mutex<LevelRing>>,
+    streaming_cb: Arc<Mutex<Option<StreamingCallback>>>,
+    chunk_accum: Vec<f32>,
+    vad: Arc<Mutex<EnergyVad>>,
+    recording: Arc<AtomicBool>,
+    preroll: Arc<Mutex<Option<LevelRing>>>,
+    denoise: Arc<Mutex<Option<Denoiser>>>,
+    agc: Arc<Mutex<Option<Agc>>>,
+}
+
+impl CaptureSink {
+    /// Handle one buffer of normalized mono samples from the device.
+    fn push(&mut self, data: &[f32]) {
+        // Everything downstream (recording, streaming, levels) sees the
+        // cleaned signal.
+        let cleaned;
+        let data = match self.denoise.lock().unwrap().as_mut() {
+            Some(d) => {
+                cleaned = d.process(data);
+                &cleaned[..]
+            }
+            None => data,
+        };
+        if data.is_empty() {
+            return;
+        }
+
+        {
+            // Checked under the pre-roll lock so no buffer falls between the
+            // pre-roll and the recording when `start_recording` flips the flag.
+            let mut preroll = self.preroll.lock().unwrap();
+            if !self.recording.load(Ordering::Acquire) {
+                // Standby: only the last few seconds are kept
+                if let Some(ring) = preroll.as_mut() {
+                    ring.push(data);
+                }
+                self.chunk_accum.clear();
+                return;
+            }
+        }
+
+        self.levels.lock().unwrap().push(data);
+        self.vad.lock().unwrap().process(data);
+
+        // Gemini streams live and never needs the recording, so only keep
+        // audio when there's no streaming callback.
+        let cb = self.streaming_cb.lock().unwrap();
+        if cb.is_none() {
+            self.audio_buf.lock().unwrap().push(data);
+        }
+
+        // Accumulate for streaming callback
+        if let Some(ref callback) = *cb {
+            self.chunk_accum.extend_from_slice(data);
+            while self.chunk_accum.len() >= STREAM_CHUNK_SIZE {
+                if let Some(agc) = self.agc.lock().unwrap().as_mut() {
+                    agc.process(&mut self.chunk_accum[..STREAM_CHUNK_SIZE]);
+                }
+                // Convert f32 → s16le bytes
+                let mut s16_bytes = Vec::with_capacity(STREAM_CHUNK_SIZE * 2);
+                for &sample in &self.chunk_accum[..STREAM_CHUNK_SIZE] {
+                    let s16 = (sample * i16::MAX as f32)
+                        .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                    s16_bytes.extend_from_slice(&s16.to_le_bytes());
+                }
+                callback(&s16_bytes);
+                self.chunk_accum.drain(..STREAM_CHUNK_SIZE);
+            }
+        }
+    }
+}
+
 pub struct GroqRecorder {
     device: Device,
     config: StreamConfig,
     stream: Option<Stream>,
-    audio_buffer: Arc<Mutex<Vec<f32>>>,
-    sample_sender: Sender<f32>,
-    _sample_receiver: Receiver<f32>,
+    audio_buffer: Arc<Mutex<RecordingBuffer>>,
+    /// Most recent samples for the visualizer.
+    levels: Arc<Mutex<LevelRing>>,
     /// Optional callback for real-time audio streaming (fires ~10x/sec with s16le chunks).
     streaming_callback: Arc<Mutex<Option<StreamingCallback>>>,
+    /// Voice activity detector fed from the capture callback (for auto-stop).
+    vad: Arc<Mutex<EnergyVad>>,
+    /// Whether captured audio goes to the recording. With pre-roll enabled the
+    /// stream stays open between recordings and this flips instead.
+    recording: Arc<AtomicBool>,
+    /// Most recent audio captured while not recording; `None` when pre-roll is off.
+    preroll: Arc<Mutex<Option<LevelRing>>>,
+    /// RNNoise stage applied to captured audio; `None` when disabled.
+    denoise: Arc<Mutex<Option<Denoiser>>>,
+    /// Target RMS for normalizing finished recordings; `None` leaves them as captured.
+    normalize_dbfs: Option<f32>,
+    /// Live gain control for streamed audio; `None` when disabled.
+    agc: Arc<Mutex<Option<Agc>>>,
 }
 
 impl GroqRecorder {
-    pub fn new() -> Result<Self, String> {
+    /// Open the input device whose name contains `device_name`
+    /// (case-insensitive), or the system default if it's empty or not found.
+    pub fn new(device_name: &str) -> Result<Self, String> {
         log::info!("Initializing Groq-compatible audio recorder");
 
         let host = cpal::default_host();
         log::info!("Audio host: {}", host.id().name());
 
-        let input_device = host
-            .default_input_device()
-            .ok_or("No input device available")?;
+        let input_device = match find_input_device(&host, device_name) {
+            Some(device) => device,
+            None => host
+                .default_input_device()
+                .ok_or("No input device available")?,
+        };
         log::info!(
             "Using device: {}",
             input_device.name().unwrap_or_default()
@@ -67,32 +172,67 @@ impl GroqRecorder {
             config.channels
         );
 
-        let audio_buffer = Arc::new(Mutex::new(Vec::<f32>::new()));
-        let (tx, rx) = unbounded();
+        let audio_buffer = Arc::new(Mutex::new(RecordingBuffer::new(
+            config.sample_rate.0,
+            DEFAULT_MAX_SECS,
+        )));
 
         Ok(Self {
             device: input_device,
             config,
             stream: None,
             audio_buffer,
-            sample_sender: tx,
-            _sample_receiver: rx,
+            levels: Arc::new(Mutex::new(LevelRing::new(LEVEL_WINDOW))),
             streaming_callback: Arc::new(Mutex::new(None)),
+            vad: Arc::new(Mutex::new(EnergyVad::new())),
+            recording: Arc::new(AtomicBool::new(false)),
+            preroll: Arc::new(Mutex::new(None)),
+            denoise: Arc::new(Mutex::new(None)),
+            normalize_dbfs: None,
+            agc: Arc::new(Mutex::new(None)),
         })
     }
 
     pub fn start_recording(&mut self) -> Result<(), String> {
-        if self.stream.is_some() {
+        if self.is_recording() {
             return Err("Already recording".into());
         }
 
         log::info!("Starting recording");
         self.audio_buffer.lock().unwrap().clear();
+        self.levels.lock().unwrap().clear();
+        self.vad.lock().unwrap().reset();
 
-        let audio_buf = self.audio_buffer.clone();
-        let tx = self.sample_sender.clone();
-        let streaming_cb = self.streaming_callback.clone();
+        if self.stream.is_some() {
+            // Standby stream is already open: seed the recording with the
+            // pre-roll so the first syllables aren't clipped.
+            let mut preroll = self.preroll.lock().unwrap();
+            if let Some(ring) = preroll.as_mut() {
+                let pre = ring.recent();
+                ring.clear();
+                if self.streaming_callback.lock().unwrap().is_none() {
+                    log::info!("Pre-roll: {} samples", pre.len());
+                    self.audio_buffer.lock().unwrap().push(&pre);
+                }
+            }
+            self.recording.store(true, Ordering::Release);
+            drop(preroll);
+            log::info!("Recording started successfully");
+            return Ok(());
+        }
 
+        self.recording.store(true, Ordering::Release);
+        if let Err(e) = self.open_stream() {
+            self.recording.store(false, Ordering::Release);
+            return Err(e);
+        }
+        log::info!("Recording started successfully");
+        Ok(())
+    }
+
+    /// Open and start the input stream. Whether its audio is recorded is
+    /// decided per buffer by the `recording` flag.
+    fn open_stream(&mut self) -> Result<(), String> {
         let sample_format = self
             .device
             .default_input_config()
@@ -102,57 +242,36 @@ impl GroqRecorder {
         log::info!("Sample format: {:?}", sample_format);
         let err_fn = |err| log::error!("Stream error: {err}");
 
-        // Accumulator for streaming chunks (~100ms = 1600 samples at 16kHz)
-        const STREAM_CHUNK_SIZE: usize = 1600;
+        let mut sink = CaptureSink {
+            audio_buf: self.audio_buffer.clone(),
+            levels: self.levels.clone(),
+            streaming_cb: self.streaming_callback.clone(),
+            chunk_accum: Vec::with_capacity(STREAM_CHUNK_SIZE),
+            vad: self.vad.clone(),
+            recording: self.recording.clone(),
+            preroll: self.preroll.clone(),
+            denoise: self.denoise.clone(),
+            agc: self.agc.clone(),
+        };
 
         self.stream = Some(match sample_format {
-            SampleFormat::F32 => {
-                let mut chunk_accum: Vec<f32> = Vec::with_capacity(STREAM_CHUNK_SIZE);
-                self.device
-                    .build_input_stream(
-                        &self.config,
-                        move |data: &[f32], _| {
-                            for &s in data {
-                                let _ = tx.send(s);
-                            }
-                            audio_buf.lock().unwrap().extend_from_slice(data);
-
-                            // Accumulate for streaming callback
-                            let cb = streaming_cb.lock().unwrap();
-                            if cb.is_some() {
-                                chunk_accum.extend_from_slice(data);
-                                while chunk_accum.len() >= STREAM_CHUNK_SIZE {
-                                    // Convert f32 → s16le bytes
-                                    let mut s16_bytes =
-                                        Vec::with_capacity(STREAM_CHUNK_SIZE * 2);
-                                    for &sample in &chunk_accum[..STREAM_CHUNK_SIZE] {
-                                        let s16 = (sample * i16::MAX as f32)
-                                            .clamp(i16::MIN as f32, i16::MAX as f32)
-                                            as i16;
-                                        s16_bytes.extend_from_slice(&s16.to_le_bytes());
-                                    }
-                                    if let Some(ref callback) = *cb {
-                                        callback(&s16_bytes);
-                                    }
-                                    chunk_accum.drain(..STREAM_CHUNK_SIZE);
-                                }
-                            }
-                        },
-                        err_fn,
-                        None,
-                    )
-                    .map_err(|e| e.to_string())?
-            }
+            SampleFormat::F32 => self
+                .device
+                .build_input_stream(
+                    &self.config,
+                    move |data: &[f32], _| sink.push(data),
+                    err_fn,
+                    None,
+                )
+                .map_err(|e| e.to_string())?,
             SampleFormat::I16 => self
                 .device
                 .build_input_stream(
                     &self.config,
                     move |data: &[i16], _| {
-                        for &s in data {
-                            let f = s as f32 / i16::MAX as f32;
-                            let _ = tx.send(f);
-                            audio_buf.lock().unwrap().push(f);
-                        }
+                        let samples: Vec<f32> =
+                            data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                        sink.push(&samples);
                     },
                     err_fn,
                     None,
@@ -163,11 +282,11 @@ impl GroqRecorder {
                 .build_input_stream(
                     &self.config,
                     move |data: &[u16], _| {
-                        for &s in data {
-                            let f = (s as f32 / u16::MAX as f32) * 2.0 - 1.0;
-                            let _ = tx.send(f);
-                            audio_buf.lock().unwrap().push(f);
-                        }
+                        let samples: Vec<f32> = data
+                            .iter()
+                            .map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0)
+                            .collect();
+                        sink.push(&samples);
                     },
                     err_fn,
                     None,
@@ -181,42 +300,35 @@ impl GroqRecorder {
             .unwrap()
             .play()
             .map_err(|e| e.to_string())?;
-
-        log::info!("Recording started successfully");
         Ok(())
     }
 
     pub fn stop_recording(&mut self) -> Result<Vec<u8>, String> {
-        if self.stream.is_none() {
+        if !self.is_recording() {
             return Err("Not recording".into());
         }
         log::info!("Stopping recording and generating WAV");
-        self.stream.take(); // drop = stop
+        self.recording.store(false, Ordering::Release);
+        if self.preroll.lock().unwrap().is_none() {
+            self.stream.take(); // drop = stop
+        }
 
-        let samples = self.audio_buffer.lock().unwrap().clone();
+        let mut samples = {
+            let mut buf = self.audio_buffer.lock().unwrap();
+            let samples = buf.samples();
+            buf.clear();
+            samples?
+        };
         if samples.is_empty() {
             return Err("No audio captured".into());
         }
-
-        let mut wav_bytes = Vec::<u8>::new();
-        {
-            let spec = WavSpec {
-                channels: 1,
-                sample_rate: self.config.sample_rate.0,
-                bits_per_sample: 16,
-                sample_format: HoundSampleFormat::Int,
-            };
-            let mut writer =
-                WavWriter::new(Cursor::new(&mut wav_bytes), spec).map_err(|e| e.to_string())?;
-
-            for &s in &samples {
-                let s16 =
-                    (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-                writer.write_sample(s16).map_err(|e| e.to_string())?;
-            }
-            writer.finalize().unwrap();
+        if let Some(target) = self.normalize_dbfs {
+            let gain = normalize(&mut samples, target);
+            log::info!("Normalized to {} dBFS (gain {:.1} dB)", target, 20.0 * gain.log10());
         }
 
+        let wav_bytes = super::wav::encode_wav(&samples, self.config.sample_rate.0)?;
+
         log::info!(
             "Generated {:.1} KB WAV ({} samples @ {} Hz)",
             wav_bytes.len() as f32 / 1024.0,
@@ -226,8 +338,114 @@ impl GroqRecorder {
         Ok(wav_bytes)
     }
 
+    /// Take everything captured so far as WAV and keep recording into an
+    /// empty buffer. `None` when not recording or nothing was captured.
+    pub fn drain_wav(&mut self) -> Option<Vec<u8>> {
+        if !self.is_recording() {
+            return None;
+        }
+        let mut samples = {
+            let mut buf = self.audio_buffer.lock().unwrap();
+            if buf.is_empty() {
+                return None;
+            }
+            let samples = buf.samples();
+            buf.clear();
+            samples.ok()?
+        };
+        if let Some(target) = self.normalize_dbfs {
+            normalize(&mut samples, target);
+        }
+        super::wav::encode_wav(&samples, self.config.sample_rate.0).ok()
+    }
+
+    /// Cap how much audio one recording keeps; anything past it is dropped.
+    /// 0 keeps everything.
+    pub fn set_max_duration(&mut self, secs: u64) {
+        let max_samples = match secs {
+            0 => usize::MAX,
+            secs => self.config.sample_rate.0 as usize * secs as usize,
+        };
+        self.audio_buffer.lock().unwrap().set_max_samples(max_samples);
+    }
+
+    /// WAV of everything captured so far without stopping, plus its length
+    /// in seconds. `None` when not recording or nothing has been captured.
+    pub fn snapshot_wav(&self) -> Option<(Vec<u8>, f64)> {
+        if !self.is_recording() {
+            return None;
+        }
+        let mut samples = {
+            let mut buf = self.audio_buffer.lock().unwrap();
+            if buf.is_empty() {
+                return None;
+            }
+            buf.samples().ok()?
+        };
+        if let Some(target) = self.normalize_dbfs {
+            normalize(&mut samples, target);
+        }
+        let rate = self.config.sample_rate.0;
+        let wav = super::wav::encode_wav(&samples, rate).ok()?;
+        Some((wav, samples.len() as f64 / rate as f64))
+    }
+
+    /// Seconds of audio kept in the current recording, or `None` when not recording.
+    pub fn recorded_secs(&self) -> Option<f64> {
+        if !self.is_recording() {
+            return None;
+        }
+        let len = self.audio_buffer.lock().unwrap().len();
+        Some(len as f64 / self.config.sample_rate.0 as f64)
+    }
+
     pub fn is_recording(&self) -> bool {
-        self.stream.is_some()
+        self.recording.load(Ordering::Acquire)
+    }
+
+    /// Keep the last `ms` of microphone audio while idle and prepend it to
+    /// the next recording. This holds the input stream open between
+    /// recordings; 0 turns it off and releases the microphone.
+    pub fn set_preroll(&mut self, ms: u64) {
+        let samples = self.config.sample_rate.0 as usize * ms as usize / 1000;
+        if samples == 0 {
+            *self.preroll.lock().unwrap() = None;
+            if !self.is_recording() {
+                self.stream.take();
+            }
+            return;
+        }
+
+        *self.preroll.lock().unwrap() = Some(LevelRing::new(samples));
+        if self.stream.is_none() {
+            match self.open_stream() {
+                Ok(()) => log::info!("Pre-roll: keeping last {}ms of audio", ms),
+                Err(e) => {
+                    log::warn!("Pre-roll disabled, could not open input: {}", e);
+                    *self.preroll.lock().unwrap() = None;
+                }
+            }
+        }
+    }
+
+    /// Turn RNNoise suppression of fan and keyboard noise on or off.
+    pub fn set_denoise(&self, enabled: bool) {
+        let mut denoise = self.denoise.lock().unwrap();
+        if !enabled {
+            *denoise = None;
+        } else if denoise.is_none() {
+            *denoise = Denoiser::new(self.config.sample_rate.0);
+            if denoise.is_some() {
+                log::info!("Noise suppression enabled");
+            }
+        }
+    }
+
+    /// Normalize finished recordings to `target_dbfs` and/or apply live gain
+    /// control to streamed audio. Either can be off independently.
+    pub fn set_gain(&mut self, normalize: bool, live_agc: bool, target_dbfs: f32) {
+        self.normalize_dbfs = normalize.then_some(target_dbfs);
+        *self.agc.lock().unwrap() = live_agc.then(|| Agc::new(target_dbfs));
     }
 
     /// Set a callback for real-time audio streaming (Gemini mode).
@@ -236,21 +454,23 @@ impl GroqRecorder {
         *self.streaming_callback.lock().unwrap() = callback;
     }
 
+    /// Milliseconds of silence since the user last spoke in this recording,
+    /// or `None` if no speech has been detected yet.
+    pub fn silence_ms(&self) -> Option<u64> {
+        if !self.is_recording() {
+            return None;
+        }
+        self.vad.lock().unwrap().silence_ms()
+    }
+
     /// Get real-time audio levels for visualization (4 pseudo-frequency bands)
     pub fn get_audio_levels(&self) -> [f32; 4] {
         if !self.is_recording() {
             return [0.0; 4];
         }
 
-        let buffer = self.audio_buffer.lock().unwrap();
-        let samples = &*buffer;
-
-        // Use last 1024 samples (~64ms at 16kHz) for real-time response
-        let recent: &[f32] = if samples.len() > 1024 {
-            &samples[samples.len() - 1024..]
-        } else {
-            samples
-        };
+        // Last ~64ms for real-time response
+        let recent = self.levels.lock().unwrap().recent();
 
         if recent.is_empty() {
             return [0.0; 4];
@@ -288,3 +508,33 @@ impl GroqRecorder {
         levels
     }
 }
+
+/// First input device whose name contains `name`, ignoring case.
+fn find_input_device(host: &cpal::Host, name: &str) -> Option<Device> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let wanted = name.to_lowercase();
+    let devices = match host.input_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            log::warn!("Cannot list input devices: {}", e);
+            return None;
+        }
+    };
+    let mut available = Vec::new();
+    for device in devices {
+        let device_name = device.name().unwrap_or_default();
+        if device_name.to_lowercase().contains(&wanted) {
+            return Some(device);
+        }
+        available.push(device_name);
+    }
+    log::warn!(
+        "No input device matching '{}', using the default. Available: {}",
+        name,
+        available.join(", ")
+    );
+    None
+}
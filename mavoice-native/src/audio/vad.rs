@@ -0,0 +1,185 @@
+use std::time::Instant;
+
+/// Absolute RMS floor for speech (~-34 dBFS), so a silent room never counts as voice.
+const MIN_SPEECH_RMS: f32 = 0.02;
+
+/// Speech must exceed the tracked noise floor by this factor.
+const NOISE_MULTIPLIER: f32 = 3.0;
+
+/// Energy-based voice activity detector.
+///
+/// Tracks an adaptive noise floor (fast to fall, slow to rise) and marks a
+/// buffer as speech when its RMS clears both the floor and a fixed minimum.
+pub struct EnergyVad {
+    noise_floor: f32,
+    last_speech: Option<Instant>,
+}
+
+impl EnergyVad {
+    pub fn new() -> Self {
+        Self {
+            noise_floor: MIN_SPEECH_RMS / NOISE_MULTIPLIER,
+            last_speech: None,
+        }
+    }
+
+    /// Forget any detected speech (call at the start of each recording).
+    pub fn reset(&mut self) {
+        self.last_speech = None;
+    }
+
+    /// Feed one capture buffer. Returns true if it contained speech.
+    pub fn process(&mut self, samples: &[f32]) -> bool {
+        if samples.is_empty() {
+            return false;
+        }
+
+        let rms = (samples.iter().map(|&x| x * x).sum::<f32>() / samples.len() as f32).sqrt();
+
+        if rms < self.noise_floor {
+            self.noise_floor += (rms - self.noise_floor) * 0.5;
+        } else {
+            self.noise_floor += (rms - self.noise_floor) * 0.002;
+        }
+
+        let is_speech = rms > MIN_SPEECH_RMS.max(self.noise_floor * NOISE_MULTIPLIER);
+        if is_speech {
+            self.last_speech = Some(Instant::now());
+        }
+        is_speech
+    }
+
+    /// Milliseconds since speech was last heard, or `None` if no speech yet.
+    pub fn silence_ms(&self) -> Option<u64> {
+        self.last_speech.map(|t| t.elapsed().as_millis() as u64)
+    }
+}
+
+/// Samples per Silero frame at 16kHz (32ms)
+const SILERO_FRAME: usize = 512;
+const SILERO_FRAME_MS: u64 = 32;
+
+/// Speech ends once the probability drops this far below the threshold,
+/// so it doesn't flicker around the threshold.
+const SILERO_HYSTERESIS: f32 = 0.15;
+
+/// What a [`SpeechGate`] lets through.
+#[derive(Debug)]
+pub enum GateEvent {
+    SpeechStart,
+    /// 16kHz mono s16le audio inside speech, including the padding before it
+    Audio(Vec<u8>),
+    SpeechEnd,
+}
+
+/// Passes streamed audio on only around speech, as judged by the Silero
+/// VAD model run locally.
+///
+/// Audio from before speech is held back in a short ring so the start of a
+/// word isn't lost; speech ends after `silence_ms` of frames below the
+/// threshold. Everything else, including background conversation, never
+/// leaves the machine. Needs the `silero` feature.
+pub struct SpeechGate {
+    #[cfg(feature = "silero")]
+    vad: voice_activity_detector::VoiceActivityDetector,
+    threshold: f32,
+    pending: Vec<i16>,
+    preroll: std::collections::VecDeque<Vec<i16>>,
+    preroll_frames: usize,
+    hangover_frames: usize,
+    quiet_frames: usize,
+    speaking: bool,
+}
+
+impl SpeechGate {
+    /// `threshold` is the speech probability (0..1) that opens the gate;
+    /// lower is more sensitive.
+    #[cfg(not(feature = "silero"))]
+    pub fn new(_threshold: f32, _prefix_padding_ms: u64, _silence_ms: u64) -> Result<Self, String> {
+        Err("Local speech detection needs a build with the \"silero\" feature".to_string())
+    }
+
+    /// `threshold` is the speech probability (0..1) that opens the gate;
+    /// lower is more sensitive.
+    #[cfg(feature = "silero")]
+    pub fn new(threshold: f32, prefix_padding_ms: u64, silence_ms: u64) -> Result<Self, String> {
+        let vad = voice_activity_detector::VoiceActivityDetector::builder()
+            .sample_rate(16_000)
+            .chunk_size(SILERO_FRAME)
+            .build()
+            .map_err(|e| format!("Cannot load the Silero VAD model: {}", e))?;
+        Ok(Self {
+            vad,
+            threshold: threshold.clamp(0.05, 0.95),
+            pending: Vec::with_capacity(SILERO_FRAME * 2),
+            preroll: std::collections::VecDeque::new(),
+            preroll_frames: prefix_padding_ms.div_ceil(SILERO_FRAME_MS) as usize,
+            hangover_frames: (silence_ms / SILERO_FRAME_MS).max(1) as usize,
+            quiet_frames: 0,
+            speaking: false,
+        })
+    }
+
+    /// Feed 16kHz mono s16le audio; returns what should be sent on.
+    pub fn push(&mut self, pcm_s16le: &[u8]) -> Vec<GateEvent> {
+        self.pending.extend(
+            pcm_s16le
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]])),
+        );
+
+        let mut events = Vec::new();
+        let mut audio: Vec<i16> = Vec::new();
+        while self.pending.len() >= SILERO_FRAME {
+            let frame: Vec<i16> = self.pending.drain(..SILERO_FRAME).collect();
+            let probability = self.predict(&frame);
+
+            if !self.speaking {
+                if probability >= self.threshold {
+                    self.speaking = true;
+                    self.quiet_frames = 0;
+                    events.push(GateEvent::SpeechStart);
+                    audio.extend(self.preroll.drain(..).flatten());
+                    audio.extend_from_slice(&frame);
+                } else {
+                    self.preroll.push_back(frame);
+                    while self.preroll.len() > self.preroll_frames {
+                        self.preroll.pop_front();
+                    }
+                }
+                continue;
+            }
+
+            audio.extend_from_slice(&frame);
+            if probability < self.threshold - SILERO_HYSTERESIS {
+                self.quiet_frames += 1;
+                if self.quiet_frames >= self.hangover_frames {
+                    self.speaking = false;
+                    events.push(GateEvent::Audio(to_bytes(&std::mem::take(&mut audio))));
+                    events.push(GateEvent::SpeechEnd);
+                }
+            } else {
+                self.quiet_frames = 0;
+            }
+        }
+        if !audio.is_empty() {
+            events.push(GateEvent::Audio(to_bytes(&audio)));
+        }
+        events
+    }
+
+    #[cfg(feature = "silero")]
+    fn predict(&mut self, frame: &[i16]) -> f32 {
+        self.vad.predict(frame.iter().copied())
+    }
+
+    /// Never called: without the model there is no gate to feed
+    #[cfg(not(feature = "silero"))]
+    fn predict(&mut self, _frame: &[i16]) -> f32 {
+        0.0
+    }
+}
+
+fn to_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
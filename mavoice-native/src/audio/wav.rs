@@ -0,0 +1,68 @@
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::io::Cursor;
+
+/// Encode mono f32 samples as a 16-bit PCM WAV file.
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    let mut wav_bytes = Vec::<u8>::with_capacity(44 + samples.len() * 2);
+    {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut writer =
+            WavWriter::new(Cursor::new(&mut wav_bytes), spec).map_err(|e| e.to_string())?;
+
+        for &s in samples {
+            let s16 = (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            writer.write_sample(s16).map_err(|e| e.to_string())?;
+        }
+        writer.finalize().map_err(|e| e.to_string())?;
+    }
+    Ok(wav_bytes)
+}
+
+/// Decode a WAV file into mono f32 samples (channels are averaged).
+/// Returns `(samples, sample_rate)`.
+pub fn decode_wav(bytes: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    let reader = WavReader::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?,
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    let mono = if channels == 1 {
+        interleaved
+    } else {
+        interleaved
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect()
+    };
+
+    Ok((mono, spec.sample_rate))
+}
+
+/// Duration of a WAV file in seconds, read from the header only.
+pub fn wav_duration_secs(bytes: &[u8]) -> Option<f64> {
+    let reader = WavReader::new(Cursor::new(bytes)).ok()?;
+    let rate = reader.spec().sample_rate;
+    if rate == 0 {
+        return None;
+    }
+    Some(reader.duration() as f64 / rate as f64)
+}
@@ -0,0 +1,246 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::api::provider::{self, TranscriptionOptions, TranscriptionProvider, UploadListener};
+use crate::audio::encode::{prepare_upload, UploadFormat};
+use crate::audio::wav::{decode_wav, wav_duration_secs};
+use crate::audio::{AudioPlayer, GroqRecorder};
+use crate::config::Config;
+use crate::metrics::percentile;
+
+/// The built-in benchmark clip, 16 kHz mono
+const BENCH_CLIP: &[u8] = include_bytes!("../assets/bench.wav");
+/// What the built-in clip says
+const BENCH_SENTENCE: &str =
+    "Please schedule the quarterly review for Thursday at three thirty and send the agenda to the whole team.";
+/// Sample rate the player takes
+const PLAYER_RATE: u32 = 24_000;
+/// Kept recording after playback ends, for the device buffers to drain
+const LOOPBACK_TAIL: Duration = Duration::from_millis(250);
+
+/// `--bench` settings from the command line.
+pub struct BenchOptions {
+    pub runs: usize,
+    /// WAV to use instead of the built-in clip
+    pub file: Option<PathBuf>,
+    /// Play the clip on the speakers and record it with the microphone
+    pub loopback: bool,
+    /// Type each transcript into the focused window
+    pub inject: bool,
+}
+
+/// Timings of one run. Stages that weren't part of the run are `None`.
+struct Run {
+    capture: Option<Duration>,
+    encode: Duration,
+    /// Sending the audio; `None` for local providers
+    upload: Option<Duration>,
+    /// From the end of the upload (or the start, for local providers) to
+    /// the transcript
+    response: Duration,
+    inject: Option<Duration>,
+}
+
+impl Run {
+    fn total(&self) -> Duration {
+        self.capture.unwrap_or_default()
+            + self.upload.unwrap_or_default()
+            + self.response
+            + self.inject.unwrap_or_default()
+    }
+}
+
+/// Send the benchmark clip through the configured pipeline `runs` times
+/// and print per-stage latencies.
+pub fn run(config: &Config, opts: &BenchOptions) -> Result<(), String> {
+    let rt = Arc::new(
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Cannot start the runtime: {}", e))?,
+    );
+    let clip = match opts.file {
+        Some(ref path) => std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?,
+        None => BENCH_CLIP.to_vec(),
+    };
+    let transcriber = provider::from_config(config);
+    if !transcriber.has_api_key() {
+        return Err(format!("No {} API key set", transcriber.name()));
+    }
+    let injector = if opts.inject {
+        Some(crate::app::make_injector(config, &rt)?)
+    } else {
+        None
+    };
+    let mut loopback = if opts.loopback {
+        let recorder = GroqRecorder::new(&config.input_device)?;
+        let player = AudioPlayer::new(&config.output_device)?;
+        Some((recorder, player))
+    } else {
+        None
+    };
+    let format = UploadFormat::from_config(&config.upload_format);
+    let mut transcription_opts = TranscriptionOptions::from_config(config);
+    // A retried request reports again; the last upload is the one answered
+    let uploaded: Arc<Mutex<Option<Instant>>> = Arc::default();
+    let upload_slot = uploaded.clone();
+    transcription_opts.on_upload = Some(UploadListener::new(move || {
+        *upload_slot.lock().unwrap() = Some(Instant::now());
+    }));
+
+    println!(
+        "{} ({}), {:.1}s clip, {} run{}{}",
+        transcriber.name(),
+        config.transcription_model(),
+        wav_duration_secs(&clip).unwrap_or(0.0),
+        opts.runs,
+        if opts.runs == 1 { "" } else { "s" },
+        if opts.loopback { ", speaker → microphone" } else { "" },
+    );
+    if injector.is_some() {
+        println!("Typing into the focused window in 3 s…");
+        std::thread::sleep(Duration::from_secs(3));
+    }
+
+    let mut runs = Vec::with_capacity(opts.runs);
+    let mut last_text = String::new();
+    for i in 1..=opts.runs {
+        let (audio, capture) = match loopback {
+            Some((ref mut recorder, ref player)) => {
+                let (audio, took) = capture_loopback(&clip, recorder, player)?;
+                (audio, Some(took))
+            }
+            None => (clip.clone(), None),
+        };
+
+        // Providers encode again inside the request; this is only to show its share
+        let started = Instant::now();
+        let _ = prepare_upload(&audio, "bench.wav", format);
+        let encode = started.elapsed();
+
+        *uploaded.lock().unwrap() = None;
+        let started = Instant::now();
+        let transcript = rt
+            .block_on(transcriber.transcribe(&audio, &transcription_opts))
+            .map_err(|e| format!("Run {}: {}", i, e))?;
+        let finished = Instant::now();
+        let upload_end = *uploaded.lock().unwrap();
+        let upload = upload_end.map(|t| t - started);
+        let response = finished - upload_end.unwrap_or(started);
+
+        let inject = match injector {
+            Some(ref injector) => {
+                let started = Instant::now();
+                injector
+                    .inject_text_to(&format!("{} ", transcript.text.trim()), None)
+                    .map_err(|e| format!("Run {}: injection failed: {}", i, e))?;
+                Some(started.elapsed())
+            }
+            None => None,
+        };
+
+        let run = Run { capture, encode, upload, response, inject };
+        println!(
+            "run {:>2}: {}",
+            i,
+            [
+                run.capture.map(|d| format!("capture {}", ms(d))),
+                Some(format!("encode {}", ms(run.encode))),
+                run.upload.map(|d| format!("upload {}", ms(d))),
+                Some(format!("response {}", ms(run.response))),
+                run.inject.map(|d| format!("inject {}", ms(d))),
+                Some(format!("total {}", ms(run.total()))),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ")
+        );
+        last_text = transcript.text;
+        runs.push(run);
+    }
+
+    println!();
+    println!("{:<11} {:>8} {:>8} {:>8} {:>8}", "stage", "min", "p50", "p95", "mean");
+    let stages: [(&str, Vec<Option<Duration>>); 6] = [
+        ("capture", runs.iter().map(|r| r.capture).collect()),
+        ("encode", runs.iter().map(|r| Some(r.encode)).collect()),
+        ("upload", runs.iter().map(|r| r.upload).collect()),
+        ("response", runs.iter().map(|r| Some(r.response)).collect()),
+        ("inject", runs.iter().map(|r| r.inject).collect()),
+        ("total", runs.iter().map(|r| Some(r.total())).collect()),
+    ];
+    for (stage, samples) in stages {
+        let mut sorted: Vec<u64> = samples.into_iter().flatten().map(|d| d.as_millis() as u64).collect();
+        if sorted.is_empty() {
+            continue;
+        }
+        sorted.sort_unstable();
+        let mean = sorted.iter().sum::<u64>() / sorted.len() as u64;
+        println!(
+            "{:<11} {:>5} ms {:>5} ms {:>5} ms {:>5} ms",
+            stage,
+            sorted[0],
+            percentile(&sorted, 50),
+            percentile(&sorted, 95),
+            mean
+        );
+    }
+    println!();
+    println!("Transcript: {}", last_text.trim());
+    if opts.file.is_none() {
+        println!("Expected:   {}", BENCH_SENTENCE);
+    }
+    Ok(())
+}
+
+/// Play `clip` and record it; returns the recording and how long it took
+/// from the end of playback to the finished WAV (draining the device
+/// buffers, stopping the recorder and encoding).
+fn capture_loopback(clip: &[u8], recorder: &mut GroqRecorder, player: &AudioPlayer) -> Result<(Vec<u8>, Duration), String> {
+    let (samples, rate) = decode_wav(clip)?;
+    let pcm: Vec<u8> = resample_linear(&samples, rate, PLAYER_RATE)
+        .iter()
+        .flat_map(|&s| ((s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16).to_le_bytes())
+        .collect();
+
+    recorder.start_recording()?;
+    player.enqueue(&pcm);
+    player.flush();
+    let deadline = Instant::now() + Duration::from_secs_f64(samples.len() as f64 / rate as f64 + 5.0);
+    while !player.is_playing() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    while player.is_playing() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let played = Instant::now();
+    std::thread::sleep(LOOPBACK_TAIL);
+
+    let audio = recorder.stop_recording()?;
+    Ok((audio, played.elapsed()))
+}
+
+/// Linear interpolation; plenty for playing speech back through a speaker.
+fn resample_linear(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = from as f64 / to as f64;
+    let len = (samples.len() as f64 / step) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = samples[idx];
+            let b = samples.get(idx + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+fn ms(d: Duration) -> String {
+    format!("{} ms", d.as_millis())
+}
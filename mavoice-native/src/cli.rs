@@ -0,0 +1,106 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+/// Command-line overrides. They apply to this session only and are never
+/// written back to the config file.
+#[derive(Debug, Clone, Default, Parser)]
+#[command(version, about = "Voice dictation overlay and Gemini Live assistant")]
+pub struct Cli {
+    /// Start in dictation ("groq") or voice assistant ("gemini") mode
+    #[arg(long, value_parser = ["groq", "gemini"])]
+    pub mode: Option<String>,
+
+    /// Record from the input device whose name contains this text
+    #[arg(long, value_name = "NAME")]
+    pub device: Option<String>,
+
+    /// Play Gemini's voice on the output device whose name contains this text
+    #[arg(long, value_name = "NAME")]
+    pub output_device: Option<String>,
+
+    /// Transcription backend: "groq", "openai" or "candle"
+    #[arg(long, value_parser = ["groq", "openai", "candle"])]
+    pub provider: Option<String>,
+
+    /// Transcription model
+    #[arg(long)]
+    pub model: Option<String>,
+
+    /// Spoken language code, e.g. "en" or "de"
+    #[arg(long)]
+    pub language: Option<String>,
+
+    /// Don't show the overlay windows (hotkeys, tray and dashboard still work)
+    #[arg(long)]
+    pub no_overlay: bool,
+
+    /// Run without overlay windows or a GPU context, e.g. as a systemd
+    /// service on a minimal window manager. Control it with hotkeys, the
+    /// tray or the dashboard.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Print API usage and estimated cost for today, this week and this
+    /// month, then exit
+    #[arg(long)]
+    pub usage: bool,
+
+    /// Zip recent logs, the config (API keys removed) and environment info
+    /// for a bug report, then exit. Written to ~/mavoice-debug-<date>.zip
+    /// unless a path is given.
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+    pub debug_bundle: Option<PathBuf>,
+
+    /// Transcribe a test clip this many times (default 5) with the current
+    /// settings, print how long each stage took, then exit
+    #[arg(long, value_name = "RUNS", num_args = 0..=1, default_missing_value = "5")]
+    pub bench: Option<usize>,
+
+    /// Benchmark with this WAV instead of the built-in clip
+    #[arg(long, value_name = "PATH", requires = "bench")]
+    pub bench_file: Option<PathBuf>,
+
+    /// Benchmark: play the clip on the speakers and record it with the
+    /// microphone, to include capture
+    #[arg(long, requires = "bench")]
+    pub bench_loopback: bool,
+
+    /// Benchmark: type each transcript into the focused window
+    #[arg(long, requires = "bench")]
+    pub bench_inject: bool,
+
+    /// Read and save this config file instead of ~/.config/mavoice/config.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+impl Cli {
+    /// `config` with the flags given on the command line applied.
+    pub fn apply(&self, mut config: Config) -> Config {
+        if let Some(ref mode) = self.mode {
+            config.mode = mode.clone();
+        }
+        if let Some(ref device) = self.device {
+            config.input_device = device.clone();
+        }
+        if let Some(ref device) = self.output_device {
+            config.output_device = device.clone();
+        }
+        if let Some(ref provider) = self.provider {
+            config.provider = provider.clone();
+        }
+        if let Some(ref model) = self.model {
+            if config.provider == "openai" {
+                config.openai_model = model.clone();
+            } else {
+                config.model = model.clone();
+            }
+        }
+        if let Some(ref language) = self.language {
+            config.language = language.clone();
+        }
+        config
+    }
+}
@@ -1,65 +1,688 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::system::secrets;
+
+/// Config file given with `--config`, replacing the default location.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// What [`Config::save`] last wrote, so the file watcher can tell our own
+/// saves from edits.
+static LAST_SAVED: Mutex<Option<String>> = Mutex::new(None);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    /// Keep API keys in the Secret Service keyring instead of this file
+    pub use_keyring: bool,
+    /// Keys that were read from the keyring, so clearing one in Settings
+    /// removes it there too (and a failed lookup never deletes anything)
+    #[serde(skip)]
+    keyring_loaded: Vec<&'static str>,
+    /// Transcription backend: "groq", "openai" or "candle" (local Whisper)
+    pub provider: String,
+    /// Providers to try in order when `provider` fails, e.g. ["openai"]
+    pub fallback_providers: Vec<String>,
+    /// Transcribe with the local model ("candle") when the transcription
+    /// servers can't be reached
+    pub offline_fallback: bool,
     pub api_key: String,
+    pub openai_api_key: String,
+    pub openai_model: String,
+    /// Whisper model run locally with provider "candle": a Hugging Face repo
+    /// such as "openai/whisper-small", or a directory with its files
+    pub candle_model: String,
+    /// Where the local model runs: "auto" (CUDA if built in and present),
+    /// "cpu" or "cuda"
+    pub candle_device: String,
     pub model: String,
     pub language: String,
-    pub dictionary: String,
+    /// Vocabulary prompt: one string, or a table keyed by language code
+    pub dictionary: Dictionary,
     pub temperature: f32,
     pub response_format: String,
+    /// Upload container: "wav" or "flac" (smaller; falls back to WAV on error)
+    pub upload_format: String,
+    /// Attempts per transcription request, counting the first (1 = no retries)
+    pub retry_max_attempts: u32,
+    /// Wait before the first retry; doubles for each retry after
+    pub retry_base_delay_ms: u64,
+    /// Give up on connecting to the transcription API after this long
+    pub http_connect_timeout_secs: u64,
+    /// Give up on a whole transcription request (upload + response) after this long
+    pub http_request_timeout_secs: u64,
+    /// Chunks of a long recording (over 5 minutes) uploaded at the same time
+    pub chunk_concurrency: usize,
+    /// Show today's API usage and estimated cost in the settings window
+    pub usage_badge: bool,
+    /// Check the API keys with a cheap request at startup and when they
+    /// change, and warn if one is rejected
+    pub check_api_keys: bool,
     pub gemini_api_key: String,
+    /// Backend that hands out short-lived Gemini tokens, used instead of
+    /// `gemini_api_key` when set (POSTed before every session)
+    pub gemini_token_url: String,
     pub mode: String,
     pub voice_name: String,
     pub system_instruction: String,
+    /// What to do with the transcript of Gemini's spoken replies: "off",
+    /// "type" into the focused app, or "log" the conversation to a markdown
+    /// file in `meeting_dir`
+    pub gemini_transcript: String,
+    /// Gemini replies with text, typed into the focused app, instead of
+    /// speaking
+    pub gemini_text_only: bool,
+    /// Gemini's voice activity detection: "high" starts a turn on quieter
+    /// sounds, "low" needs clearer speech (noisy rooms)
+    pub gemini_start_sensitivity: String,
+    /// "high" ends your turn at shorter pauses, "low" waits longer
+    pub gemini_end_sensitivity: String,
+    /// Audio kept from before detected speech
+    pub gemini_prefix_padding_ms: u64,
+    /// Silence that ends your turn
+    pub gemini_silence_ms: u64,
+    /// Turn Gemini's voice detection off; hold `talk_hotkey` while speaking
+    pub gemini_push_to_talk: bool,
+    /// Detect speech locally (Silero VAD) and only stream audio around it,
+    /// instead of streaming the mic continuously to Gemini's detection
+    pub gemini_local_vad: bool,
+    /// Speech probability (0-1) that opens the local VAD; lower is more sensitive
+    pub gemini_vad_threshold: f32,
+    /// Auto-stop Groq recording after this much silence following speech (0 = off)
+    pub vad_silence_ms: u64,
+    /// Dictation auto-stops and transcribes at this length (0 = no limit)
+    pub max_recording_secs: u64,
+    /// Microphone to record from, matched against device names (empty =
+    /// system default).
+    pub input_device: String,
+    /// Speakers or headset for Gemini's voice, matched against device names
+    /// (empty = system default, followed when it changes). Applied live.
+    pub output_device: String,
+    /// Keep this much mic audio from before the hotkey and prepend it to the
+    /// recording (0 = off). Keeps the microphone open while idle.
+    pub preroll_ms: u64,
+    /// RNNoise suppression of background noise before transcription/streaming
+    pub denoise: bool,
+    /// Scale finished recordings so speech sits at `target_dbfs` before upload
+    pub normalize_audio: bool,
+    /// Automatic gain control on audio streamed to Gemini
+    pub live_agc: bool,
+    /// Loudness target (speech RMS) for normalization and live gain
+    pub target_dbfs: f32,
+    /// Pause playing MPRIS media players while the mic is live (needs playerctl)
+    pub pause_media: bool,
+    /// Turn other audio streams down while Gemini speaks (needs pactl)
+    pub duck_audio: bool,
+    /// Volume of other streams while ducked, in percent of their own
+    pub duck_level: u32,
+    /// Meeting mode: transcribe and append to the notes file every N minutes
+    pub meeting_chunk_mins: u64,
+    /// Where meeting notes are written (empty = ~/Documents/maVoice)
+    pub meeting_dir: String,
+    /// Where note mode writes its daily `<date>.md` files (empty =
+    /// ~/Documents/maVoice/notes)
+    pub note_dir: String,
+    /// One note-mode entry; `{date}`, `{time}` (UTC) and `{text}` are filled in
+    pub note_template: String,
+    /// Keep every dictation, meeting chunk and Gemini session as a WAV file.
+    /// Only the mic side of a Gemini session is kept, not Gemini's replies.
+    pub archive_recordings: bool,
+    /// Where archived recordings go (empty = ~/.local/share/mavoice/recordings)
+    pub archive_dir: String,
+    /// Delete archived recordings older than this (0 = keep forever). Only
+    /// files named like the archive's own are ever deleted.
+    pub archive_max_days: u64,
+    /// Delete the oldest archived recordings beyond this total size (0 = no limit)
+    pub archive_max_mb: u64,
+    /// Store nothing: no history, no recording archive, no transcripts on
+    /// the dashboard or in the log. The overlay is drawn in rose while on.
+    pub privacy_mode: bool,
+    /// Say due reminders out loud: through Gemini during a session, else
+    /// with speech-dispatcher (`spd-say`)
+    pub reminder_speak: bool,
+    /// Re-transcribe the recording this often while dictating for live partials (0 = off)
+    pub partial_interval_ms: u64,
+    /// Paste words from partial results once two partials agree on them
+    pub partial_inject: bool,
+    /// Draw transcripts, Gemini's text and status messages on the overlay
+    pub overlay_text: bool,
+    /// Show what was typed in a bubble above the strip for this many
+    /// seconds after each dictation (0 = off)
+    pub injection_toast_secs: u64,
+    /// Replace the animated waveform and orb with a still indicator in the
+    /// state color
+    pub reduced_motion: bool,
+    /// High-contrast state colors on a dark plate
+    pub high_contrast: bool,
+    /// Frame rate cap for the overlay animation (0 = redraw as fast as possible)
+    pub max_fps: u32,
+    /// Waveform strip width in pixels (0 = full screen width)
+    pub overlay_width: u32,
+    /// Waveform strip height in pixels
+    pub overlay_height: u32,
+    /// Hide the strip after this many idle seconds (0 = always shown)
+    pub overlay_autohide_secs: u64,
+    /// How it hides: "shrink" to a sliver that comes back on hover, or
+    /// "hide" completely until the next recording or message
+    pub overlay_autohide_mode: String,
+    /// Strip position per monitor name, as `[x, y]` from the monitor's
+    /// top-left corner. Saved when the strip is dragged.
+    pub overlay_positions: BTreeMap<String, [i32; 2]>,
+    /// How overlay frames reach the screen: "surface" presents through a
+    /// transparent wgpu surface, "readback" copies each frame to the CPU and
+    /// blits it (needed for NVIDIA on X11), "auto" picks. Read at startup.
+    pub render_path: String,
+    /// WGSL file replacing the waveform strip shader (empty = built-in).
+    /// Reloaded when the file changes.
+    pub user_shader: String,
+    /// WGSL file replacing the Gemini orb shader (empty = built-in)
+    pub ai_shader: String,
+    /// Global hotkey for Groq dictation, e.g. "ctrl+shift+Comma"
+    pub toggle_hotkey: String,
+    /// Global hotkey for the Gemini Live session
+    pub mode_hotkey: String,
+    /// Where global hotkeys are read: "auto" (evdev on Wayland), "global"
+    /// (X11 grabs) or "evdev" (/dev/input, needs the `input` group).
+    pub hotkey_backend: String,
+    /// Text injection backend: "auto", "xdotool", "wtype", "ydotool", "ibus",
+    /// "virtual-keyboard", "kwin", "portal" or "type" (key by key, no paste)
+    pub injection_method: String,
+    /// Pause between keys for the "type" injection method
+    pub type_delay_ms: u64,
+    /// Put transcripts in the PRIMARY selection (middle-click paste): "off",
+    /// "also" (besides injecting) or "only" (instead of injecting)
+    pub primary_selection: String,
+    /// Interpret "period", "new line", "scratch that", ... in dictation as commands
+    pub spoken_commands: bool,
+    /// Extra or overridden spoken commands: phrase -> text or `{action}` (empty disables)
+    pub command_map: HashMap<String, String>,
+    /// Turn spoken symbols and "camel case ..." into code instead of
+    /// applying the spoken commands; usually set in terminal/editor profiles
+    pub code_dictation: bool,
+    /// Extra or overridden code phrases: phrase -> symbol or `{case}` (empty disables)
+    pub code_map: HashMap<String, String>,
+    /// Strip "um", "uh", "you know" and stutters from dictation
+    pub remove_fillers: bool,
+    /// Filler words per language code, replacing the built-in list for
+    /// that language
+    pub fillers: HashMap<String, Vec<String>>,
+    /// Write spoken numbers, amounts, times and dates as digits
+    pub format_numbers: bool,
+    /// Locale for formatted numbers, e.g. "en-GB" (empty = from `language`,
+    /// then the desktop locale)
+    pub number_locale: String,
+    /// Space and capitalize dictation to fit the text before the cursor
+    /// (read over AT-SPI)
+    pub smart_spacing: bool,
+    /// Announce state changes ("Recording started", errors) to screen
+    /// readers such as Orca over AT-SPI
+    pub screen_reader: bool,
+    /// User-defined tools exposed to Gemini Live (`[[custom_tools]]` tables)
+    pub custom_tools: Vec<CustomToolConfig>,
+    /// MCP servers whose tools are offered to Gemini Live (`[[mcp_servers]]` tables)
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Tools that need approval before running (exact names, `prefix*`, or
+    /// `mcp:*` / `custom:*` for every MCP or custom tool)
+    pub confirm_tools: Vec<String>,
+    /// Tools approved permanently via "Always allow"
+    pub always_allow_tools: Vec<String>,
+    /// Let Gemini keep talking while `run_command` and `ask_claude` run, and
+    /// send it their output every few seconds (non-blocking function calls)
+    pub tool_progress: bool,
+    /// Per-tool switches and timeouts keyed by tool name, e.g.
+    /// `[tools] ask_claude = { enabled = false }`
+    pub tools: BTreeMap<String, ToolSettings>,
+    /// Binaries `run_command` may run (empty = any not in `run_command_deny`)
+    pub run_command_allow: Vec<String>,
+    /// Binaries `run_command` refuses, also behind `sudo`, `env`, `xargs`, ...
+    pub run_command_deny: Vec<String>,
+    /// Run commands in this directory and refuse paths outside it (empty = no jail)
+    pub run_command_dir: String,
+    pub run_command_timeout_secs: u64,
+    /// Bytes of stdout and of stderr returned to Gemini
+    pub run_command_max_output: usize,
+    /// Virtual memory limit per command in MiB (0 = none)
+    pub run_command_max_memory_mb: u64,
+    /// Offer Gemini the `search_memory` and `remember` tools
+    pub memory_enabled: bool,
+    /// SQLite database the memory tools use, created on first use; an
+    /// existing ShieldCortex `memories.db` works too (empty = not set up)
+    pub memory_db: String,
+    /// Search engine for the `web_search` tool, with `{query}` where the
+    /// query goes; JSON results (SearXNG `format=json`) are returned as a
+    /// list, other pages as text (empty = no `web_search` tool)
+    pub web_search_url: String,
+    /// Directories the `read_file` and `write_file` tools may use; relative
+    /// paths start in the first (empty = no file tools)
+    pub file_dirs: Vec<String>,
+    /// Serve the dashboard WebSocket and page on `dashboard_port`
+    pub dashboard_enabled: bool,
+    /// Local port of the dashboard (`ws://127.0.0.1:<port>`)
+    pub dashboard_port: u16,
+    /// Per-application overrides keyed by window class, e.g.
+    /// `[profiles."Gnome-terminal"]`
+    pub profiles: HashMap<String, AppProfile>,
+    /// How text is delivered per window class pattern (`*` wildcards), e.g.
+    /// `[injection_rules."*keepass*"]`
+    pub injection_rules: HashMap<String, InjectionRule>,
+    /// Named sets of config overrides, e.g. `[presets.coding]`, switched by
+    /// hotkey, tray or voice
+    pub presets: BTreeMap<String, toml::Table>,
+    /// Preset applied on top of this file (empty = none)
+    pub active_preset: String,
+    /// Hotkey cycling through presets (empty = none)
+    pub preset_hotkey: String,
+    /// Hotkey discarding the current recording or pending transcription (empty = none)
+    pub cancel_hotkey: String,
+    /// Overlay mouse gestures. Each is one of "none", "start", "stop"
+    /// (stop recording, or cancel a pending transcription), "toggle",
+    /// "cancel", "mode", "reinject", "settings", "volume_up", "volume_down"
+    pub click_action: String,
+    pub double_click_action: String,
+    pub middle_click_action: String,
+    pub scroll_up_action: String,
+    pub scroll_down_action: String,
+    /// Second click within this long makes a double-click
+    pub double_click_ms: u64,
+    /// Clicks ignored this long after a double-click starts recording
+    pub click_cooldown_ms: u64,
+    /// Hotkey pasting the last transcript into the focused window again (empty = none)
+    pub reinject_hotkey: String,
+    /// Hotkey sending a screenshot of the focused window to the running
+    /// Gemini session (empty = none)
+    pub screen_hotkey: String,
+    /// Hold to speak in a Gemini session when `gemini_push_to_talk` is on (empty = none)
+    pub talk_hotkey: String,
+    /// Hotkey toggling `privacy_mode` (empty = none)
+    pub privacy_hotkey: String,
+}
+
+/// Custom vocabulary sent as the transcription prompt. A table keeps each
+/// language's terms out of the others' prompts:
+///
+/// ```toml
+/// [dictionary]
+/// en = "kubectl, systemctl"
+/// de = "Kubernetes, Grüße"
+/// default = "maVoice"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Dictionary {
+    /// The same prompt for every language
+    Global(String),
+    /// Prompts keyed by language code; `default` covers the others
+    PerLanguage(BTreeMap<String, String>),
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Dictionary::Global(String::new())
+    }
+}
+
+impl Dictionary {
+    /// The prompt for `language` ("en", "de-DE", ...), or for unknown ones.
+    /// Empty entries are skipped, so "default" still applies to them.
+    pub fn for_language(&self, language: Option<&str>) -> Option<&str> {
+        let map = match self {
+            Dictionary::Global(prompt) => {
+                return (!prompt.trim().is_empty()).then_some(prompt.as_str());
+            }
+            Dictionary::PerLanguage(map) => map,
+        };
+        let lookup = |key: &str| {
+            map.iter()
+                .find(|(k, prompt)| k.eq_ignore_ascii_case(key) && !prompt.trim().is_empty())
+                .map(|(_, prompt)| prompt.as_str())
+        };
+        let lang = language.unwrap_or_default();
+        let primary = lang.split(['-', '_']).next().unwrap_or_default();
+        [lang, primary, "default"]
+            .into_iter()
+            .filter(|key| !key.is_empty())
+            .find_map(lookup)
+    }
+}
+
+/// `path` with a leading `~/` expanded, or `None` if it is empty.
+fn expand_home(path: &str) -> Option<PathBuf> {
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => Some(home.join(rest)),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+/// Locale of the desktop session ("de-DE" for `LANG=de_DE.UTF-8`).
+fn desktop_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .map(|v| v.split(['.', '@']).next().unwrap_or_default().replace('_', "-"))
+        .filter(|locale| !locale.is_empty() && locale != "C" && locale != "POSIX")
+}
+
+/// Language of the desktop session ("de" for `LANG=de_DE.UTF-8`), used to
+/// pick a dictionary entry while Whisper detects the language itself.
+fn locale_language() -> Option<String> {
+    desktop_locale().map(|locale| locale.split('-').next().unwrap_or_default().to_lowercase())
+}
+
+/// A tool declared in config.toml and run as an external command.
+///
+/// Exactly one of `command` (run via `bash -c`) or `exec` (argv, no shell)
+/// should be set. `{{name}}` placeholders are replaced with the matching
+/// argument; in `command` the value is shell-quoted first. The full argument
+/// object is also written to stdin as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomToolConfig {
+    pub name: String,
+    pub description: String,
+    /// JSON schema for the arguments (Gemini `parameters` object)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exec: Vec<String>,
+    #[serde(default = "default_tool_timeout")]
+    pub timeout_secs: u64,
+}
+
+/// Overrides for one tool (built-in, custom or MCP) in the `[tools]` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolSettings {
+    /// Declare the tool to Gemini at all
+    pub enabled: bool,
+    /// Replaces the tool's own timeout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Default for ToolSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: None,
+        }
+    }
+}
+
+/// Overrides applied while dictating into a particular application.
+/// Unset fields keep the global value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppProfile {
+    pub model: Option<String>,
+    pub language: Option<String>,
+    pub dictionary: Option<Dictionary>,
+    pub spoken_commands: Option<bool>,
+    /// Merged over the global `command_map`
+    pub command_map: HashMap<String, String>,
+    pub code_dictation: Option<bool>,
+    /// Merged over the global `code_map`
+    pub code_map: HashMap<String, String>,
+    pub injection_method: Option<String>,
+    pub type_delay_ms: Option<u64>,
+}
+
+/// Delivery of dictation into windows whose class matches a pattern.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InjectionRule {
+    /// "paste" (the session's injection method), "type" (key by key),
+    /// "clipboard" (copy only) or "atspi" (inserted at the caret); unset
+    /// means "paste"
+    pub method: Option<String>,
+    /// Add a space after each transcript
+    pub trailing_space: bool,
+    /// Start each transcript with a capital letter
+    pub capitalize: bool,
+}
+
+/// A Model Context Protocol server launched as a child process (stdio transport).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Prefix for this server's tools, e.g. "fs" -> "fs__read_file"
+    pub name: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+fn default_tool_timeout() -> u64 {
+    30
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            use_keyring: true,
+            keyring_loaded: Vec::new(),
+            provider: "groq".to_string(),
+            fallback_providers: Vec::new(),
+            offline_fallback: false,
             api_key: String::new(),
+            openai_api_key: String::new(),
+            openai_model: "whisper-1".to_string(),
+            candle_model: "openai/whisper-small".to_string(),
+            candle_device: "auto".to_string(),
             model: "whisper-large-v3-turbo".to_string(),
             language: "en".to_string(),
-            dictionary: String::new(),
+            dictionary: Dictionary::default(),
             temperature: 0.0,
             response_format: "json".to_string(),
+            upload_format: "wav".to_string(),
+            retry_max_attempts: 3,
+            retry_base_delay_ms: 500,
+            http_connect_timeout_secs: 10,
+            http_request_timeout_secs: 120,
+            chunk_concurrency: 3,
+            usage_badge: false,
+            check_api_keys: true,
             gemini_api_key: String::new(),
+            gemini_token_url: String::new(),
             mode: "groq".to_string(),
             voice_name: "Aoede".to_string(),
             system_instruction: "You are maVoice, a warm and helpful desktop voice assistant running on Linux. \
                 Keep responses concise and conversational. You have access to tools: you can search the web \
                 via Google Search, search and save memories, run shell commands on the user's machine, and \
                 delegate complex tasks to Claude. Use tools proactively when they'd help answer a question.".to_string(),
+            gemini_transcript: "off".to_string(),
+            gemini_text_only: false,
+            gemini_start_sensitivity: "high".to_string(),
+            gemini_end_sensitivity: "low".to_string(),
+            gemini_prefix_padding_ms: 100,
+            gemini_silence_ms: 500,
+            gemini_push_to_talk: false,
+            gemini_local_vad: false,
+            gemini_vad_threshold: 0.5,
+            vad_silence_ms: 0,
+            input_device: String::new(),
+            output_device: String::new(),
+            max_recording_secs: 600,
+            preroll_ms: 0,
+            denoise: false,
+            normalize_audio: false,
+            live_agc: false,
+            target_dbfs: -20.0,
+            pause_media: false,
+            duck_audio: false,
+            duck_level: 30,
+            meeting_chunk_mins: 5,
+            meeting_dir: String::new(),
+            note_dir: String::new(),
+            note_template: "- **{time}** {text}\n".to_string(),
+            archive_recordings: false,
+            archive_dir: String::new(),
+            archive_max_days: 30,
+            archive_max_mb: 2000,
+            privacy_mode: false,
+            reminder_speak: true,
+            partial_interval_ms: 0,
+            partial_inject: false,
+            overlay_text: true,
+            injection_toast_secs: 3,
+            reduced_motion: false,
+            high_contrast: false,
+            max_fps: 60,
+            overlay_width: 0,
+            overlay_height: 64,
+            overlay_autohide_secs: 0,
+            overlay_autohide_mode: "shrink".to_string(),
+            overlay_positions: BTreeMap::new(),
+            render_path: "auto".to_string(),
+            user_shader: String::new(),
+            ai_shader: String::new(),
+            toggle_hotkey: "ctrl+shift+Comma".to_string(),
+            mode_hotkey: "ctrl+shift+Period".to_string(),
+            hotkey_backend: "auto".to_string(),
+            injection_method: "auto".to_string(),
+            type_delay_ms: 12,
+            primary_selection: "off".to_string(),
+            spoken_commands: false,
+            command_map: HashMap::new(),
+            code_dictation: false,
+            code_map: HashMap::new(),
+            remove_fillers: false,
+            fillers: HashMap::new(),
+            format_numbers: false,
+            number_locale: String::new(),
+            smart_spacing: false,
+            screen_reader: false,
+            custom_tools: Vec::new(),
+            mcp_servers: Vec::new(),
+            confirm_tools: ["run_command", "write_file", "mcp:*", "custom:*"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            always_allow_tools: Vec::new(),
+            tool_progress: true,
+            tools: BTreeMap::new(),
+            run_command_allow: Vec::new(),
+            run_command_deny: Vec::new(),
+            run_command_dir: String::new(),
+            run_command_timeout_secs: 30,
+            run_command_max_output: 4000,
+            run_command_max_memory_mb: 0,
+            memory_enabled: true,
+            memory_db: String::new(),
+            web_search_url: String::new(),
+            file_dirs: Vec::new(),
+            dashboard_enabled: true,
+            dashboard_port: 3001,
+            profiles: HashMap::new(),
+            injection_rules: HashMap::new(),
+            presets: BTreeMap::new(),
+            active_preset: String::new(),
+            preset_hotkey: String::new(),
+            cancel_hotkey: "ctrl+shift+Backspace".to_string(),
+            reinject_hotkey: String::new(),
+            click_action: "stop".to_string(),
+            double_click_action: "start".to_string(),
+            middle_click_action: "settings".to_string(),
+            scroll_up_action: "none".to_string(),
+            scroll_down_action: "none".to_string(),
+            double_click_ms: 280,
+            click_cooldown_ms: 500,
+            screen_hotkey: String::new(),
+            talk_hotkey: String::new(),
+            privacy_hotkey: String::new(),
         }
     }
 }
 
 impl Config {
     pub fn config_path() -> PathBuf {
+        if let Some(path) = CONFIG_PATH.get() {
+            return path.clone();
+        }
         let config_dir = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("~/.config"))
             .join("mavoice");
         config_dir.join("config.toml")
     }
 
+    /// Use `path` instead of `~/.config/mavoice/config.toml` for this run.
+    /// Must be called before the first load.
+    pub fn set_config_path(path: PathBuf) {
+        let _ = CONFIG_PATH.set(path);
+    }
+
+    /// Directory for meeting notes, resolving the empty default.
+    pub fn meeting_notes_dir(&self) -> PathBuf {
+        if !self.meeting_dir.is_empty() {
+            return PathBuf::from(&self.meeting_dir);
+        }
+        dirs::document_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("maVoice")
+    }
+
+    /// Directory for note-mode files, resolving the empty default.
+    pub fn note_mode_dir(&self) -> PathBuf {
+        expand_home(&self.note_dir).unwrap_or_else(|| self.meeting_notes_dir().join("notes"))
+    }
+
+    /// Directory for archived recordings, resolving the empty default.
+    pub fn archive_path(&self) -> PathBuf {
+        expand_home(&self.archive_dir).unwrap_or_else(|| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("~/.local/share"))
+                .join("mavoice")
+                .join("recordings")
+        })
+    }
+
+    /// The memory database, or `None` if unset.
+    pub fn memory_db_path(&self) -> Option<PathBuf> {
+        expand_home(&self.memory_db)
+    }
+
+    /// `file_dirs` with `~` expanded.
+    pub fn file_dir_paths(&self) -> Vec<PathBuf> {
+        self.file_dirs.iter().filter_map(|d| expand_home(d)).collect()
+    }
+
+    /// `web_search_url`, or `None` if unset.
+    pub fn web_search_url(&self) -> Option<String> {
+        let url = self.web_search_url.trim();
+        (!url.is_empty()).then(|| url.to_string())
+    }
+
+    /// Timeout for `run_command`: from `[tools]`, else `run_command_timeout_secs`.
+    pub fn run_command_timeout(&self) -> std::time::Duration {
+        let secs = self
+            .tools
+            .get("run_command")
+            .and_then(|t| t.timeout_secs)
+            .unwrap_or(self.run_command_timeout_secs);
+        std::time::Duration::from_secs(secs.max(1))
+    }
+
+    /// The `run_command` jail, or `None` if unset.
+    pub fn run_command_dir_path(&self) -> Option<PathBuf> {
+        expand_home(&self.run_command_dir)
+    }
+
     pub fn load() -> Self {
         let path = Self::config_path();
 
         if path.exists() {
-            match std::fs::read_to_string(&path) {
-                Ok(contents) => match toml::from_str::<Config>(&contents) {
-                    Ok(config) => {
-                        log::info!("Loaded config from {}", path.display());
-                        return config.with_env_fallback();
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to parse config: {}. Using defaults.", e);
-                    }
-                },
-                Err(e) => {
-                    log::warn!("Failed to read config: {}. Using defaults.", e);
-                }
+            match Self::try_load() {
+                Ok(config) => return config,
+                Err(e) => log::warn!("{}. Using defaults.", e),
             }
         }
 
@@ -69,17 +692,111 @@ impl Config {
         config
     }
 
+    /// Read and parse the config file, with keys filled from the keyring
+    /// and environment. Errors leave the caller to pick a fallback.
+    pub fn try_load() -> Result<Self, String> {
+        let path = Self::config_path();
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config: {}", e))?;
+        let config = toml::from_str::<Config>(&contents)
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+        log::info!("Loaded config from {}", path.display());
+        Ok(config.with_keyring().with_env_fallback())
+    }
+
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let path = Self::config_path();
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let contents = toml::to_string_pretty(self)?;
-        std::fs::write(&path, contents)?;
+        let contents = toml::to_string_pretty(&self.without_stored_secrets())?;
+        std::fs::write(&path, &contents)?;
+        if let Ok(mut last) = LAST_SAVED.lock() {
+            *last = Some(contents);
+        }
         log::info!("Saved config to {}", path.display());
         Ok(())
     }
 
+    /// Whether the config file holds exactly what this process last saved.
+    pub fn is_own_save() -> bool {
+        let Ok(contents) = std::fs::read_to_string(Self::config_path()) else {
+            return false;
+        };
+        LAST_SAVED
+            .lock()
+            .is_ok_and(|last| last.as_deref() == Some(contents.as_str()))
+    }
+
+    fn secret_fields(&mut self) -> [(&'static str, &mut String); 3] {
+        [
+            ("groq_api_key", &mut self.api_key),
+            ("openai_api_key", &mut self.openai_api_key),
+            ("gemini_api_key", &mut self.gemini_api_key),
+        ]
+    }
+
+    /// Fill empty API keys from the keyring. Keys still in plaintext are
+    /// moved there and the file is rewritten without them. Without a
+    /// working keyring the file is left alone, so loading never writes it.
+    fn with_keyring(mut self) -> Self {
+        if !self.use_keyring {
+            return self;
+        }
+        let mut migrated = false;
+        let mut loaded = Vec::new();
+        for (name, value) in self.secret_fields() {
+            if value.is_empty() {
+                if let Some(secret) = secrets::get(name) {
+                    *value = secret;
+                    loaded.push(name);
+                }
+            } else {
+                match secrets::set(name, value) {
+                    Ok(()) => migrated = true,
+                    Err(e) => log::debug!("Keeping {} in config.toml: {}", name, e),
+                }
+                loaded.push(name);
+            }
+        }
+        self.keyring_loaded = loaded;
+        if migrated {
+            log::info!("Moved plaintext API keys to the keyring");
+            if let Err(e) = self.save() {
+                log::warn!("Failed to rewrite config after migration: {}", e);
+            }
+        }
+        self
+    }
+
+    /// Copy for writing to disk: keys the keyring accepted are blanked, and
+    /// keys that were cleared are removed from it. Without a keyring (e.g.
+    /// headless) keys stay in the file.
+    fn without_stored_secrets(&self) -> Self {
+        let mut out = self.clone();
+        if !self.use_keyring {
+            return out;
+        }
+        let loaded = self.keyring_loaded.clone();
+        for (name, value) in out.secret_fields() {
+            let result = if value.is_empty() {
+                if !loaded.contains(&name) {
+                    continue;
+                }
+                secrets::delete(name)
+            } else if secrets::get(name).as_deref() == Some(value.as_str()) {
+                Ok(())
+            } else {
+                secrets::set(name, value)
+            };
+            match result {
+                Ok(()) => value.clear(),
+                Err(e) => log::warn!("Keeping {} in config.toml: {}", name, e),
+            }
+        }
+        out
+    }
+
     /// Use env vars as fallback if config keys are empty
     fn with_env_fallback(mut self) -> Self {
         if self.api_key.is_empty() {
@@ -87,6 +804,11 @@ impl Config {
                 self.api_key = key;
             }
         }
+        if self.openai_api_key.is_empty() {
+            if let Ok(key) = std::env::var("OPENAI_API_KEY") {
+                self.openai_api_key = key;
+            }
+        }
         if self.gemini_api_key.is_empty() {
             if let Ok(key) = std::env::var("GEMINI_API_KEY") {
                 self.gemini_api_key = key;
@@ -95,6 +817,131 @@ impl Config {
         self
     }
 
+    /// Copy of this config with the keys in `patch` (a JSON object) overwritten.
+    /// Unknown keys and type mismatches are rejected.
+    pub fn with_patch(&self, patch: &serde_json::Value) -> Result<Self, String> {
+        let patch = patch.as_object().ok_or("Config patch must be an object")?;
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        let fields = value.as_object_mut().ok_or("Config is not an object")?;
+        for (key, v) in patch {
+            if !fields.contains_key(key) {
+                return Err(format!("Unknown config key: {}", key));
+            }
+            fields.insert(key.clone(), v.clone());
+        }
+        serde_json::from_value(value).map_err(|e| format!("Invalid config: {}", e))
+    }
+
+    /// The effective config: this one with `active_preset` applied. An
+    /// unknown or invalid preset is ignored with a warning.
+    pub fn with_active_preset(&self) -> Config {
+        if self.active_preset.is_empty() {
+            return self.clone();
+        }
+        let Some(table) = self.presets.get(&self.active_preset) else {
+            log::warn!("Unknown preset '{}'", self.active_preset);
+            return self.clone();
+        };
+        let mut patch = match serde_json::to_value(table) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Preset '{}' is invalid: {}", self.active_preset, e);
+                return self.clone();
+            }
+        };
+        // A preset can't redefine the preset list itself
+        if let Some(obj) = patch.as_object_mut() {
+            obj.remove("presets");
+            obj.remove("active_preset");
+        }
+        self.with_patch(&patch).unwrap_or_else(|e| {
+            log::warn!("Preset '{}' is invalid: {}", self.active_preset, e);
+            self.clone()
+        })
+    }
+
+    /// The preset after the active one, wrapping around through "none".
+    pub fn next_preset(&self) -> String {
+        let mut names = self.presets.keys();
+        if self.active_preset.is_empty() {
+            return names.next().cloned().unwrap_or_default();
+        }
+        names
+            .skip_while(|n| **n != self.active_preset)
+            .nth(1)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Key of the profile matching one of a window's classes (case-insensitive).
+    pub fn app_profile_for(&self, classes: &[String]) -> Option<String> {
+        self.profiles
+            .keys()
+            .find(|key| classes.iter().any(|c| c.eq_ignore_ascii_case(key)))
+            .cloned()
+    }
+
+    /// Whether any injection rule inserts text over AT-SPI.
+    pub fn uses_atspi_injection(&self) -> bool {
+        self.injection_rules
+            .values()
+            .any(|rule| rule.method.as_deref() == Some("atspi"))
+    }
+
+    /// This config with the overrides of profile `name` applied.
+    pub fn with_app_profile(&self, name: &str) -> Config {
+        let mut config = self.clone();
+        let Some(profile) = self.profiles.get(name) else {
+            return config;
+        };
+        if let Some(ref model) = profile.model {
+            match config.provider.as_str() {
+                "openai" => config.openai_model = model.clone(),
+                "candle" => config.candle_model = model.clone(),
+                _ => config.model = model.clone(),
+            }
+        }
+        if let Some(ref language) = profile.language {
+            config.language = language.clone();
+        }
+        if let Some(ref dictionary) = profile.dictionary {
+            config.dictionary = dictionary.clone();
+        }
+        if let Some(spoken) = profile.spoken_commands {
+            config.spoken_commands = spoken;
+        }
+        config
+            .command_map
+            .extend(profile.command_map.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if let Some(code) = profile.code_dictation {
+            config.code_dictation = code;
+        }
+        config
+            .code_map
+            .extend(profile.code_map.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if let Some(ref method) = profile.injection_method {
+            config.injection_method = method.clone();
+        }
+        if let Some(delay) = profile.type_delay_ms {
+            config.type_delay_ms = delay;
+        }
+        config
+    }
+
+    /// Model name for the selected transcription provider.
+    pub fn transcription_model(&self) -> &str {
+        self.model_for(&self.provider)
+    }
+
+    /// Model name for transcription provider `provider`.
+    pub fn model_for(&self, provider: &str) -> &str {
+        match provider {
+            "openai" => &self.openai_model,
+            "candle" => &self.candle_model,
+            _ => &self.model,
+        }
+    }
+
     pub fn effective_language(&self) -> Option<&str> {
         if self.language.is_empty() {
             None
@@ -103,11 +950,39 @@ impl Config {
         }
     }
 
-    pub fn effective_dictionary(&self) -> Option<&str> {
-        if self.dictionary.is_empty() {
-            None
-        } else {
-            Some(&self.dictionary)
+    /// Locale for formatted numbers: `number_locale`, else the dictation
+    /// language, else the desktop's.
+    pub fn effective_number_locale(&self) -> String {
+        if !self.number_locale.is_empty() {
+            return self.number_locale.clone();
+        }
+        // A bare "en" says nothing about the region; the desktop may
+        if let Some(desktop) = desktop_locale() {
+            let lang = desktop.split('-').next().unwrap_or_default();
+            if self.language.is_empty() || self.language.eq_ignore_ascii_case(lang) {
+                return desktop;
+            }
         }
+        self.language.clone()
+    }
+
+    /// The configured language, or the desktop locale's when Whisper
+    /// detects the language itself.
+    pub fn dictation_language(&self) -> Option<String> {
+        match self.effective_language() {
+            Some(lang) => Some(lang.to_string()),
+            None => locale_language(),
+        }
+    }
+
+    /// Dictionary prompt for the dictation language.
+    pub fn effective_dictionary(&self) -> Option<&str> {
+        self.dictionary.for_language(self.dictation_language().as_deref())
+    }
+
+    /// Whether post-processing can change words after they were first
+    /// heard, which rules out pasting partial results early.
+    pub fn rewrites_transcript(&self) -> bool {
+        self.spoken_commands || self.code_dictation || self.format_numbers || self.remove_fillers
     }
 }
@@ -0,0 +1,63 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::meeting::utc_date_time;
+
+/// Markdown log of one Gemini Live session, built from the transcriptions
+/// of what the user said and what Gemini answered.
+pub struct ConversationLog {
+    path: PathBuf,
+}
+
+impl ConversationLog {
+    /// Create `conversation-<date>-<time>.md` in `dir` with a heading.
+    pub fn create(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (date, time) = utc_date_time(now);
+        let path = dir.join(format!("conversation-{}-{}.md", date, time.replace(':', "")));
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        writeln!(file, "# Gemini conversation {} {} UTC\n", date, time)
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+
+        log::info!("Conversation log: {}", path.display());
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one exchange. Either side may be empty (tool-only turns,
+    /// interruptions before Gemini answered).
+    pub fn append_turn(&self, heard: &str, said: &str) -> Result<(), String> {
+        let mut entry = String::new();
+        if !heard.is_empty() {
+            entry.push_str(&format!("**You:** {}\n\n", heard));
+        }
+        if !said.is_empty() {
+            entry.push_str(&format!("**Gemini:** {}\n\n", said));
+        }
+        if entry.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Cannot open {}: {}", self.path.display(), e))?;
+        file.write_all(entry.as_bytes())
+            .map_err(|e| format!("Cannot write {}: {}", self.path.display(), e))
+    }
+}
@@ -0,0 +1,248 @@
+// Built-in maVoice dashboard: live events, transcripts and latency over the
+// same WebSocket the app broadcasts on.
+(() => {
+  const MAX_ROWS = 200;
+  const $ = (id) => document.getElementById(id);
+
+  let ws = null;
+  let transcribeStart = null;
+  const latencies = [];
+  const toolStarts = new Map();
+  let openSession = null;
+
+  function time(ts) {
+    return new Date(ts || Date.now()).toLocaleTimeString();
+  }
+
+  function prepend(list, node) {
+    list.prepend(node);
+    while (list.children.length > MAX_ROWS) list.lastChild.remove();
+  }
+
+  function row(text, meta, cls) {
+    const div = document.createElement("div");
+    div.className = "row" + (cls ? " " + cls : "");
+    div.textContent = text;
+    if (meta) {
+      const m = document.createElement("div");
+      m.className = "meta";
+      m.textContent = meta;
+      div.appendChild(m);
+    }
+    return div;
+  }
+
+  function setState(label) {
+    $("state").textContent = label;
+  }
+
+  function ms(n) {
+    return n < 1000 ? `${n} ms` : `${(n / 1000).toFixed(2)} s`;
+  }
+
+  function handle(type, p) {
+    const line = document.createElement("div");
+    line.textContent = `${time(p.timestamp)}  ${type}  ${JSON.stringify(p)}`;
+    prepend($("events"), line);
+
+    switch (type) {
+      case "groq:start":
+        transcribeStart = p.timestamp;
+        setState(`transcribing (${p.provider || "groq"})`);
+        break;
+      case "groq:complete": {
+        const took = transcribeStart ? p.timestamp - transcribeStart : null;
+        transcribeStart = null;
+        if (took !== null) {
+          latencies.push(took);
+          $("lat-last").textContent = ms(took);
+          $("lat-avg").textContent = ms(Math.round(latencies.reduce((a, b) => a + b, 0) / latencies.length));
+        }
+        const via = p.provider ? ` · ${p.provider}` : "";
+        prepend($("transcripts"), row(p.text, `dictation${via} · ${time(p.timestamp)}${took !== null ? " · " + ms(took) : ""}`));
+        setState("idle");
+        break;
+      }
+      case "groq:chunk":
+        setState(`transcribing (${p.done}/${p.total} chunks)`);
+        break;
+      case "groq:retry":
+        setState(`retrying (${p.attempt}/${p.maxAttempts})`);
+        break;
+      case "meeting:start": setState("meeting"); break;
+      case "meeting:chunk":
+        if (p.text) prepend($("transcripts"), row(p.text, `meeting · ${time(p.timestamp)}`));
+        break;
+      case "meeting:complete":
+        prepend($("transcripts"), row(p.path, `meeting notes saved · ${time(p.timestamp)}`));
+        setState("idle");
+        break;
+      case "note:mode":
+        prepend($("transcripts"), row(p.enabled ? `saving dictation to ${p.dir}` : "dictation is pasted again", `note mode ${p.enabled ? "on" : "off"} · ${time(p.timestamp)}`));
+        break;
+      case "note:saved":
+        prepend($("transcripts"), row(p.text, `note · ${time(p.timestamp)}`));
+        break;
+      case "reminder:set":
+        prepend($("transcripts"), row(p.text, `reminder at ${time(p.dueAt)} · set ${time(p.timestamp)}`));
+        break;
+      case "reminder:due":
+        prepend($("transcripts"), row(p.text, `reminder · ${time(p.timestamp)}`));
+        break;
+      case "preset:changed":
+        prepend($("transcripts"), row(p.name || "default", `profile · ${time(p.timestamp)}`));
+        break;
+      case "groq:error":
+        prepend($("transcripts"), row(p.error, `error · ${time(p.timestamp)}`, "err"));
+        setState("idle");
+        break;
+      case "voice:open": setState("gemini: listening"); break;
+      case "voice:listening": setState("gemini: listening"); break;
+      case "voice:speaking": setState("gemini: speaking"); break;
+      case "voice:interrupted": setState("gemini: interrupted"); break;
+      case "voice:close":
+        setState("idle");
+        send("history:list");
+        break;
+      case "voice:text":
+        prepend($("transcripts"), row(p.text, `gemini · ${time(p.timestamp)}`));
+        break;
+      case "voice:transcript":
+        if (p.heard) prepend($("transcripts"), row(p.heard, `you (to gemini) · ${time(p.timestamp)}`));
+        if (p.said) prepend($("transcripts"), row(p.said, `gemini (spoken) · ${time(p.timestamp)}`));
+        break;
+      case "voice:tool_call":
+        toolStarts.set(p.chatId, p.timestamp);
+        prepend($("tools"), row(p.toolName, `called · ${time(p.timestamp)}`));
+        break;
+      case "voice:tool_confirm":
+        prepend($("tools"), row(p.toolName, `waiting for approval · ${time(p.timestamp)}`));
+        break;
+      case "voice:tool_progress":
+        prepend($("tools"), row(p.toolName, `running ${p.elapsedSecs}s · ${time(p.timestamp)}${p.output ? " · " + p.output.trim().split("\n").pop() : ""}`));
+        break;
+      case "voice:tool_result": {
+        const start = toolStarts.get(p.chatId);
+        toolStarts.delete(p.chatId);
+        const took = start ? p.timestamp - start : null;
+        if (took !== null) $("tool-last").textContent = ms(took);
+        prepend($("tools"), row(p.toolName, `done · ${time(p.timestamp)}${took !== null ? " · " + ms(took) : ""}`));
+        break;
+      }
+      case "voice:file_write":
+        if (p.error) prepend($("tools"), row(p.error, `write_file refused · ${time(p.timestamp)}`, "err"));
+        else prepend($("tools"), row(p.path, `write_file · ${p.mode} ${p.bytes} bytes · ${time(p.timestamp)}`));
+        break;
+      case "metrics:transcription":
+      case "metrics:injection":
+      case "metrics:first_audio": {
+        const el = $(`m-${type.slice("metrics:".length)}`);
+        el.textContent = `${ms(p.p50)} / ${ms(p.p95)}`;
+        el.title = `last ${ms(p.ms)} · ${p.count} samples`;
+        break;
+      }
+      case "history:sessions":
+        renderSessions(p.sessions || []);
+        break;
+      case "history:session":
+        renderConversation(p);
+        break;
+      case "usage:totals":
+        for (const period of ["today", "week", "month"]) {
+          const t = p[period];
+          if (!t) continue;
+          const el = $(`usage-${period}`);
+          el.textContent = `$${t.costUsd.toFixed(2)}`;
+          el.title = `${t.requests} requests · ${(t.audioSecs / 60).toFixed(1)} min audio · ${t.tokens} tokens`;
+        }
+        break;
+      case "keys:checked":
+        if (p.status !== "valid") prepend($("transcripts"), row(p.message, `api key · ${time(p.timestamp)}`, "err"));
+        break;
+      case "control:error":
+        prepend($("transcripts"), row(p.error, `control · ${time(p.timestamp)}`, "err"));
+        break;
+    }
+  }
+
+  function renderSessions(sessions) {
+    const list = $("sessions");
+    list.replaceChildren();
+    for (const s of sessions) {
+      const counts = `${s.turns} turns${s.toolCalls ? ` · ${s.toolCalls} tools` : ""}`;
+      const div = row(s.preview || "(no speech)", `${new Date(s.startedAt).toLocaleString()} · ${counts}`);
+      if (s.id === openSession) div.classList.add("sel");
+      div.onclick = () => {
+        openSession = s.id;
+        for (const r of list.children) r.classList.remove("sel");
+        div.classList.add("sel");
+        send("history:get", { id: s.id });
+      };
+      list.appendChild(div);
+    }
+    if (!sessions.length) list.appendChild(row("No Gemini sessions yet", null));
+  }
+
+  function renderConversation(session) {
+    if (session.id !== openSession) return;
+    const view = $("conversation");
+    view.replaceChildren();
+    for (const e of session.entries || []) {
+      if (e.kind === "turn") {
+        if (e.heard) view.appendChild(row(e.heard, `you · ${time(e.at)}`, "you"));
+        if (e.said) view.appendChild(row(e.said, `gemini · ${time(e.at)}`));
+      } else {
+        const div = row(e.name, `tool ${e.status} · ${time(e.at)}`);
+        const args = document.createElement("div");
+        args.className = "args";
+        args.textContent = JSON.stringify(e.args, null, 2) +
+          (e.result != null ? "\n→ " + JSON.stringify(e.result, null, 2) : "");
+        div.appendChild(args);
+        view.appendChild(div);
+      }
+    }
+  }
+
+  function send(type, payload) {
+    if (ws && ws.readyState === WebSocket.OPEN) {
+      ws.send(JSON.stringify(payload === undefined ? { type } : { type, payload }));
+    }
+  }
+
+  function connect() {
+    ws = new WebSocket(`ws://${location.host}/`);
+    ws.onopen = () => {
+      $("conn").textContent = "connected";
+      $("conn").className = "pill on";
+      send("history:list");
+      send("usage:get");
+    };
+    ws.onclose = () => {
+      $("conn").textContent = "disconnected";
+      $("conn").className = "pill off";
+      setTimeout(connect, 2000);
+    };
+    ws.onmessage = (msg) => {
+      try {
+        const { type, payload } = JSON.parse(msg.data);
+        handle(type, payload || {});
+      } catch (e) {
+        console.warn("bad event", e);
+      }
+    };
+  }
+
+  $("toggle").onclick = () => send("recording:toggle");
+  $("mode").onchange = (e) => send("mode:set", { mode: e.target.value });
+  const sendText = () => {
+    const text = $("text").value.trim();
+    if (text) {
+      send("gemini:text", { text });
+      $("text").value = "";
+    }
+  };
+  $("send").onclick = sendText;
+  $("text").onkeydown = (e) => { if (e.key === "Enter") sendText(); };
+
+  connect();
+})();
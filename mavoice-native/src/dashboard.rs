@@ -1,46 +1,146 @@
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
 use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
 use tokio_tungstenite::tungstenite::Message;
 
 const BROADCAST_CAPACITY: usize = 256;
+/// Recent events replayed to each new client, so a dashboard opened
+/// mid-session starts with the latest transcripts and state.
+const REPLAY_CAPACITY: usize = 100;
 
-/// Lightweight WebSocket broadcast server for the claudegram dashboard.
+/// Events that aren't replayed: partial transcripts are stale by the time
+/// anyone connects and would push the real events out, and history
+/// answers are for whoever asked.
+const NOT_REPLAYED: &[&str] = &["groq:partial", "history:session", "history:sessions"];
+/// Binding is retried while the port is in use, e.g. by a previous
+/// instance that is still shutting down.
+const BIND_ATTEMPTS: u32 = 3;
+const BIND_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Built-in dashboard page, served over plain HTTP on the WebSocket port.
+const INDEX_HTML: &str = include_str!("dashboard.html");
+const DASHBOARD_JS: &str = include_str!("dashboard.js");
+
+/// Commands a dashboard client can send, in the same `{ "type", "payload" }`
+/// envelope as broadcast events.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum DashboardCommand {
+    #[serde(rename = "recording:toggle")]
+    ToggleRecording,
+    #[serde(rename = "recording:start")]
+    StartRecording,
+    #[serde(rename = "recording:stop")]
+    StopRecording,
+    /// `{ "mode": "groq" | "gemini" }`
+    #[serde(rename = "mode:set")]
+    SetMode { mode: String },
+    /// Send a text turn to the live Gemini session.
+    #[serde(rename = "gemini:text")]
+    SendText { text: String },
+    /// Send a screenshot of the focused window to the live Gemini session.
+    #[serde(rename = "gemini:screen")]
+    ShareScreen,
+    /// Partial config: an object of `config.toml` keys to overwrite.
+    #[serde(rename = "config:set")]
+    SetConfig(Value),
+    /// Paste the last transcript into the focused window again.
+    #[serde(rename = "transcript:reinject")]
+    Reinject,
+    /// `{ "name": "<preset>" }`; empty name clears the preset.
+    #[serde(rename = "preset:set")]
+    SetPreset { name: String },
+    /// Recent Gemini sessions, answered with `history:sessions`.
+    #[serde(rename = "history:list")]
+    ListHistory,
+    /// Usage totals for today, this week and this month, answered with
+    /// `usage:totals`.
+    #[serde(rename = "usage:get")]
+    GetUsage,
+    /// `{ "id": <session> }`; answered with `history:session`.
+    #[serde(rename = "history:get")]
+    GetHistory { id: i64 },
+}
+
+impl DashboardCommand {
+    /// The command's `type`, for logging without its payload (text turns
+    /// and config patches, which may hold API keys).
+    pub fn name(&self) -> &'static str {
+        match self {
+            DashboardCommand::ToggleRecording => "recording:toggle",
+            DashboardCommand::StartRecording => "recording:start",
+            DashboardCommand::StopRecording => "recording:stop",
+            DashboardCommand::SetMode { .. } => "mode:set",
+            DashboardCommand::SendText { .. } => "gemini:text",
+            DashboardCommand::ShareScreen => "gemini:screen",
+            DashboardCommand::SetConfig(_) => "config:set",
+            DashboardCommand::Reinject => "transcript:reinject",
+            DashboardCommand::SetPreset { .. } => "preset:set",
+            DashboardCommand::ListHistory => "history:list",
+            DashboardCommand::GetUsage => "usage:get",
+            DashboardCommand::GetHistory { .. } => "history:get",
+        }
+    }
+}
+
+type CommandHandler = Arc<dyn Fn(DashboardCommand) + Send + Sync>;
+
+/// Lightweight WebSocket server for the claudegram dashboard.
 ///
 /// Accepts clients on `ws://127.0.0.1:{port}` and fans out JSON events
-/// via a `tokio::sync::broadcast` channel. Clients are read-only — any
-/// incoming messages are ignored.
+/// via a `tokio::sync::broadcast` channel. Incoming messages are parsed as
+/// [`DashboardCommand`]s and passed to the handler given to `start`.
+/// Plain HTTP requests on the same port get the built-in dashboard page.
+/// The last [`REPLAY_CAPACITY`] events are replayed to each new client.
+/// Browser clients are only accepted from localhost origins, so arbitrary
+/// web pages can't drive the app.
 pub struct DashboardBroadcaster {
     tx: broadcast::Sender<String>,
+    /// Locked while sending so a client subscribing in between can't miss
+    /// or double an event.
+    replay: Arc<Mutex<VecDeque<String>>>,
     running: Arc<AtomicBool>,
+    accept_task: tokio::task::JoinHandle<()>,
 }
 
 impl DashboardBroadcaster {
-    /// Start the broadcast server in a background tokio task.
-    pub async fn start(port: u16) -> Result<Self, String> {
-        let listener = TcpListener::bind(format!("127.0.0.1:{}", port))
-            .await
-            .map_err(|e| format!("Failed to bind port {}: {}", port, e))?;
+    /// Start the server in a background tokio task. `on_command` is called
+    /// (from a tokio task) for every command received from a client.
+    pub async fn start(
+        port: u16,
+        on_command: impl Fn(DashboardCommand) + Send + Sync + 'static,
+    ) -> Result<Self, String> {
+        let listener = bind(port).await?;
 
-        log::info!("[Dashboard] Server listening on ws://127.0.0.1:{}", port);
+        log::info!("[Dashboard] Server listening on ws://127.0.0.1:{} (page: http://127.0.0.1:{}/)", port, port);
 
         let (tx, _) = broadcast::channel(BROADCAST_CAPACITY);
         let running = Arc::new(AtomicBool::new(true));
+        let replay = Arc::new(Mutex::new(VecDeque::with_capacity(REPLAY_CAPACITY)));
 
         let accept_tx = tx.clone();
+        let accept_replay = replay.clone();
         let accept_running = running.clone();
+        let on_command: CommandHandler = Arc::new(on_command);
 
-        tokio::spawn(async move {
+        let accept_task = tokio::spawn(async move {
             while accept_running.load(Ordering::Relaxed) {
                 match listener.accept().await {
                     Ok((stream, addr)) => {
-                        log::info!("[Dashboard] Client connected: {}", addr);
-                        let client_rx = accept_tx.subscribe();
-                        tokio::spawn(handle_client(stream, client_rx));
+                        let (backlog, client_rx) = {
+                            let replay = accept_replay.lock().unwrap();
+                            (replay.iter().cloned().collect(), accept_tx.subscribe())
+                        };
+                        tokio::spawn(route_connection(stream, addr, backlog, client_rx, on_command.clone()));
                     }
                     Err(e) => {
                         if accept_running.load(Ordering::Relaxed) {
@@ -51,35 +151,135 @@ impl DashboardBroadcaster {
             }
         });
 
-        Ok(Self { tx, running })
+        Ok(Self { tx, replay, running, accept_task })
     }
 
     /// Broadcast a JSON event to all connected dashboard clients.
     ///
     /// Format: `{ "type": "<event_type>", "payload": { ... } }`
     ///
-    /// Non-blocking. Silently drops if no clients are connected.
+    /// Non-blocking. Events sent while no clients are connected are only
+    /// kept for replay, except for [`NOT_REPLAYED`] ones.
     pub fn broadcast(&self, event_type: &str, payload: Value) {
         let msg = json!({
             "type": event_type,
             "payload": payload,
-        });
+        })
+        .to_string();
+        if !NOT_REPLAYED.contains(&event_type) {
+            let mut replay = self.replay.lock().unwrap();
+            if replay.len() == REPLAY_CAPACITY {
+                replay.pop_front();
+            }
+            replay.push_back(msg.clone());
+        }
         // Ignore send errors (no active receivers)
-        let _ = self.tx.send(msg.to_string());
+        let _ = self.tx.send(msg);
     }
 
-    /// Shut down the server.
+    /// Shut down the server and free the port. Connected clients are
+    /// dropped once their next write fails.
     pub fn shutdown(&self) {
         self.running.store(false, Ordering::Relaxed);
+        self.accept_task.abort();
+    }
+}
+
+async fn bind(port: u16) -> Result<TcpListener, String> {
+    let mut attempt = 1;
+    loop {
+        match TcpListener::bind(format!("127.0.0.1:{}", port)).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && attempt < BIND_ATTEMPTS => {
+                log::warn!(
+                    "[Dashboard] Port {} in use, retrying in {}s ({}/{})",
+                    port,
+                    BIND_RETRY_DELAY.as_secs(),
+                    attempt,
+                    BIND_ATTEMPTS
+                );
+                attempt += 1;
+                tokio::time::sleep(BIND_RETRY_DELAY).await;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                return Err(format!(
+                    "Port {} is already in use; set dashboard_port to a free port",
+                    port
+                ));
+            }
+            Err(e) => return Err(format!("Failed to bind port {}: {}", port, e)),
+        }
+    }
+}
+
+/// Peek at the request head: WebSocket upgrades go to `handle_client`,
+/// anything else is answered as a static file request.
+async fn route_connection(
+    mut stream: tokio::net::TcpStream,
+    addr: std::net::SocketAddr,
+    backlog: Vec<String>,
+    rx: broadcast::Receiver<String>,
+    on_command: CommandHandler,
+) {
+    let mut buf = [0u8; 4096];
+    let mut len = 0;
+    // The head usually arrives in one segment; give slow clients a few tries.
+    for _ in 0..20 {
+        len = match stream.peek(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") || len == buf.len() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let head = String::from_utf8_lossy(&buf[..len]).to_ascii_lowercase();
+    if head.contains("upgrade: websocket") {
+        log::info!("[Dashboard] Client connected: {}", addr);
+        handle_client(stream, backlog, rx, on_command).await;
+    } else {
+        // Consume the request so closing doesn't reset the connection.
+        let _ = stream.read(&mut buf).await;
+        serve_static(&mut stream, &head).await;
     }
 }
 
-/// Handle a single dashboard WebSocket client.
+/// Answer a plain HTTP request with one of the embedded dashboard files.
+async fn serve_static(stream: &mut tokio::net::TcpStream, head: &str) {
+    let mut request_line = head.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let path = request_line.next().unwrap_or("/");
+    let path = path.split('?').next().unwrap_or("/");
+
+    let (status, content_type, body) = match (method, path) {
+        ("get", "/") | ("get", "/index.html") => ("200 OK", "text/html; charset=utf-8", INDEX_HTML),
+        ("get", "/dashboard.js") => ("200 OK", "application/javascript; charset=utf-8", DASHBOARD_JS),
+        ("get", _) => ("404 Not Found", "text/plain; charset=utf-8", "Not found"),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", "Method not allowed"),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Handle a single dashboard WebSocket client: replay `backlog`, then
+/// forward live broadcasts.
 async fn handle_client(
     stream: tokio::net::TcpStream,
+    backlog: Vec<String>,
     mut rx: broadcast::Receiver<String>,
+    on_command: CommandHandler,
 ) {
-    let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+    let ws_stream = match tokio_tungstenite::accept_hdr_async(stream, check_origin).await {
         Ok(ws) => ws,
         Err(e) => {
             log::warn!("[Dashboard] WebSocket handshake failed: {}", e);
@@ -89,19 +289,30 @@ async fn handle_client(
 
     let (mut ws_write, mut ws_read) = ws_stream.split();
 
-    // Read task: drain incoming messages (we don't use them, but must consume
-    // to keep the connection alive and handle close/ping frames).
+    // Read task: parse commands; also keeps close/ping frames handled.
     let mut read_task = tokio::spawn(async move {
         while let Some(msg) = ws_read.next().await {
             match msg {
+                Ok(Message::Text(text)) => match serde_json::from_str::<DashboardCommand>(&text) {
+                    Ok(cmd) => {
+                        log::info!("[Dashboard] Command: {}", cmd.name());
+                        on_command(cmd);
+                    }
+                    Err(e) => log::warn!("[Dashboard] Ignoring invalid command: {}", e),
+                },
                 Ok(Message::Close(_)) | Err(_) => break,
                 _ => {} // ignore everything else
             }
         }
     });
 
-    // Write task: forward broadcasts to this client.
+    // Write task: replay recent events, then forward broadcasts to this client.
     let mut write_task = tokio::spawn(async move {
+        for text in backlog {
+            if ws_write.send(Message::Text(text.into())).await.is_err() {
+                return;
+            }
+        }
         loop {
             match rx.recv().await {
                 Ok(text) => {
@@ -130,3 +341,33 @@ async fn handle_client(
 
     log::info!("[Dashboard] Client disconnected");
 }
+
+/// Handshake callback: reject browser connections from non-local origins.
+/// Clients that send no `Origin` header (CLI tools, scripts) are allowed.
+#[allow(clippy::result_large_err)]
+fn check_origin(req: &Request, resp: Response) -> Result<Response, ErrorResponse> {
+    let origin = req.headers().get("origin").and_then(|o| o.to_str().ok());
+    match origin {
+        None => Ok(resp),
+        Some(o) if is_local_origin(o) => Ok(resp),
+        Some(o) => {
+            log::warn!("[Dashboard] Rejected connection from origin {}", o);
+            let mut err = ErrorResponse::new(Some("Forbidden origin".to_string()));
+            *err.status_mut() = StatusCode::FORBIDDEN;
+            Err(err)
+        }
+    }
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let rest = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .unwrap_or("");
+    let host = if rest.starts_with('[') {
+        rest.split(']').next().map(|h| &h[1..]).unwrap_or("")
+    } else {
+        rest.split([':', '/']).next().unwrap_or("")
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
@@ -0,0 +1,157 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::config::Config;
+use crate::logfile;
+use crate::meeting::utc_date_time;
+
+/// Session variables that decide which backends get picked
+const ENV_VARS: &[&str] = &[
+    "XDG_SESSION_TYPE",
+    "XDG_CURRENT_DESKTOP",
+    "DESKTOP_SESSION",
+    "WAYLAND_DISPLAY",
+    "DISPLAY",
+    "GDK_BACKEND",
+    "QT_QPA_PLATFORM",
+    "YDOTOOL_SOCKET",
+    "RUST_LOG",
+];
+
+/// Helpers maVoice shells out to, depending on the setup
+const TOOLS: &[&str] = &[
+    "xdotool", "xclip", "xsel", "wtype", "wl-copy", "ydotool", "ydotoold", "ibus", "playerctl",
+    "pactl", "import", "notify-send", "spd-say",
+];
+
+/// Zip the recent logs, the config with its secrets blanked and a summary
+/// of the environment into `dest` (default: `~/mavoice-debug-<date>-<time>.zip`)
+/// for attaching to a bug report. Returns the file's path.
+pub fn export(dest: Option<&Path>) -> Result<PathBuf, String> {
+    let path = dest.map(Path::to_path_buf).unwrap_or_else(default_path);
+    let file = File::create(&path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let write_err = |e: &dyn std::fmt::Display| format!("Cannot write {}: {}", path.display(), e);
+
+    for log in logfile::log_files() {
+        let Ok(contents) = std::fs::read(&log) else {
+            continue;
+        };
+        let name = log.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        zip.start_file(format!("logs/{}", name), options).map_err(|e| write_err(&e))?;
+        zip.write_all(&contents).map_err(|e| write_err(&e))?;
+    }
+
+    zip.start_file("config.toml", options).map_err(|e| write_err(&e))?;
+    zip.write_all(redacted_config().as_bytes()).map_err(|e| write_err(&e))?;
+
+    zip.start_file("environment.txt", options).map_err(|e| write_err(&e))?;
+    zip.write_all(environment().as_bytes()).map_err(|e| write_err(&e))?;
+
+    zip.finish().map_err(|e| write_err(&e))?;
+    log::info!("Debug bundle written to {}", path.display());
+    Ok(path)
+}
+
+fn default_path() -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (date, time) = utc_date_time(now);
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(format!("mavoice-debug-{}-{}.zip", date, time.replace(':', "")))
+}
+
+/// The config file as written, with API keys, tokens and the environment
+/// and headers of MCP servers replaced.
+fn redacted_config() -> String {
+    let path = Config::config_path();
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => return format!("# Cannot read {}: {}\n", path.display(), e),
+    };
+    match contents.parse::<toml::Table>() {
+        Ok(mut table) => {
+            redact_table(&mut table);
+            toml::to_string_pretty(&table).unwrap_or_default()
+        }
+        Err(e) => format!("# {} does not parse: {}\n", path.display(), e),
+    }
+}
+
+fn redact_table(table: &mut toml::Table) {
+    for (key, value) in table.iter_mut() {
+        let secret = key.ends_with("_key")
+            || ["token", "secret", "password"].iter().any(|s| key.contains(s));
+        if matches!(key.as_str(), "env" | "headers") {
+            if let toml::Value::Table(t) = value {
+                for v in t.values_mut() {
+                    *v = toml::Value::String("<redacted>".into());
+                }
+            }
+        } else if secret {
+            if value.as_str().is_some_and(|s| !s.is_empty()) {
+                *value = toml::Value::String("<redacted>".into());
+            }
+        } else if let toml::Value::Table(t) = value {
+            redact_table(t);
+        } else if let toml::Value::Array(items) = value {
+            for item in items {
+                if let toml::Value::Table(t) = item {
+                    redact_table(t);
+                }
+            }
+        }
+    }
+}
+
+fn environment() -> String {
+    let mut out = String::new();
+    let mut line = |s: String| {
+        out.push_str(&s);
+        out.push('\n');
+    };
+    line(format!("maVoice {}", env!("CARGO_PKG_VERSION")));
+    line(format!("candle: {}", cfg!(feature = "candle")));
+    line(format!("cuda: {}", cfg!(feature = "cuda")));
+    line(format!("silero: {}", cfg!(feature = "silero")));
+    let os = std::fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("PRETTY_NAME="))
+                .map(|v| v.trim_matches('"').to_string())
+        })
+        .unwrap_or_else(|| "unknown".into());
+    line(format!("OS: {}", os));
+    let kernel = std::fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+    line(format!("Kernel: {}", kernel.trim()));
+    line(String::new());
+    for var in ENV_VARS {
+        line(format!("{}={}", var, std::env::var(var).unwrap_or_default()));
+    }
+    line(String::new());
+    for tool in TOOLS {
+        line(format!("{}: {}", tool, if in_path(tool) { "found" } else { "missing" }));
+    }
+    let groups = std::process::Command::new("id")
+        .arg("-nG")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    line(format!("Groups: {}", groups));
+    out
+}
+
+fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
@@ -0,0 +1,342 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+
+use crate::usage::{UsageRecord, UsageTotals};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        id         INTEGER PRIMARY KEY,
+        started_at INTEGER NOT NULL,
+        ended_at   INTEGER,
+        text_only  INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS turns (
+        id         INTEGER PRIMARY KEY,
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        at         INTEGER NOT NULL,
+        heard      TEXT NOT NULL,
+        said       TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tool_calls (
+        id          INTEGER PRIMARY KEY,
+        session_id  INTEGER NOT NULL REFERENCES sessions(id),
+        call_id     TEXT NOT NULL,
+        name        TEXT NOT NULL,
+        args        TEXT NOT NULL,
+        result      TEXT,
+        status      TEXT NOT NULL DEFAULT 'running',
+        called_at   INTEGER NOT NULL,
+        finished_at INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS usage (
+        id         INTEGER PRIMARY KEY,
+        at         INTEGER NOT NULL,
+        provider   TEXT NOT NULL,
+        model      TEXT NOT NULL,
+        audio_secs REAL NOT NULL,
+        tokens     INTEGER NOT NULL,
+        cost_usd   REAL NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS dictations (
+        id            INTEGER PRIMARY KEY,
+        at            INTEGER NOT NULL,
+        provider      TEXT NOT NULL,
+        text          TEXT NOT NULL,
+        fallback_from TEXT
+    );
+    CREATE INDEX IF NOT EXISTS turns_session ON turns(session_id);
+    CREATE INDEX IF NOT EXISTS usage_at ON usage(at);
+    CREATE INDEX IF NOT EXISTS tool_calls_session ON tool_calls(session_id);
+";
+
+/// Add `column` to a table created by an older version, if it's missing.
+fn add_column(conn: &Connection, table: &str, column: &str, kind: &str) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|e| format!("Cannot read the {} table: {}", table, e))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| format!("Cannot read the {} table: {}", table, e))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, kind))
+            .map_err(|e| format!("Cannot add {}.{}: {}", table, column, e))?;
+    }
+    Ok(())
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// SQLite store of Gemini Live sessions (the transcribed turns and every
+/// tool call with its arguments and result, for the dashboard's
+/// conversation view), of dictations with the provider that transcribed
+/// them, and of API usage.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// `~/.local/share/mavoice/history.db`
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("~/.local/share"))
+            .join("mavoice")
+            .join("history.db")
+    }
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Cannot set up history DB: {}", e))?;
+        add_column(&conn, "dictations", "fallback_from", "TEXT")?;
+        log::info!("History DB: {}", path.display());
+        Ok(Self { conn })
+    }
+
+    pub fn start_session(&self, text_only: bool) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO sessions (started_at, text_only) VALUES (?1, ?2)",
+                params![now_ms(), text_only],
+            )
+            .map_err(|e| format!("Cannot start session: {}", e))?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn end_session(&self, session: i64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE sessions SET ended_at = ?2 WHERE id = ?1",
+                params![session, now_ms()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Cannot end session: {}", e))
+    }
+
+    pub fn add_turn(&self, session: i64, heard: &str, said: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO turns (session_id, at, heard, said) VALUES (?1, ?2, ?3, ?4)",
+                params![session, now_ms(), heard, said],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Cannot save turn: {}", e))
+    }
+
+    pub fn add_tool_call(
+        &self,
+        session: i64,
+        call_id: &str,
+        name: &str,
+        args: &Value,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO tool_calls (session_id, call_id, name, args, called_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![session, call_id, name, args.to_string(), now_ms()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Cannot save tool call: {}", e))
+    }
+
+    /// Record how a tool call ended: `status` is "done" (with its result)
+    /// or "cancelled".
+    pub fn finish_tool_call(
+        &self,
+        session: i64,
+        call_id: &str,
+        status: &str,
+        result: Option<&Value>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE tool_calls SET status = ?3, result = ?4, finished_at = ?5 \
+                 WHERE session_id = ?1 AND call_id = ?2",
+                params![session, call_id, status, result.map(Value::to_string), now_ms()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Cannot update tool call: {}", e))
+    }
+
+    /// The most recent sessions, newest first, with their first thing said
+    /// as a preview.
+    pub fn sessions(&self, limit: usize) -> Result<Value, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT s.id, s.started_at, s.ended_at, s.text_only, \
+                   (SELECT COUNT(*) FROM turns t WHERE t.session_id = s.id), \
+                   (SELECT COUNT(*) FROM tool_calls c WHERE c.session_id = s.id), \
+                   (SELECT t.heard FROM turns t WHERE t.session_id = s.id AND t.heard != '' \
+                    ORDER BY t.id LIMIT 1) \
+                 FROM sessions s \
+                 WHERE EXISTS (SELECT 1 FROM turns t WHERE t.session_id = s.id) \
+                    OR EXISTS (SELECT 1 FROM tool_calls c WHERE c.session_id = s.id) \
+                 ORDER BY s.id DESC LIMIT ?1",
+            )
+            .map_err(|e| format!("History query failed: {}", e))?;
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                Ok(json!({
+                    "id": row.get::<_, i64>(0)?,
+                    "startedAt": row.get::<_, i64>(1)?,
+                    "endedAt": row.get::<_, Option<i64>>(2)?,
+                    "textOnly": row.get::<_, bool>(3)?,
+                    "turns": row.get::<_, i64>(4)?,
+                    "toolCalls": row.get::<_, i64>(5)?,
+                    "preview": row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                }))
+            })
+            .map_err(|e| format!("History query failed: {}", e))?;
+        let sessions: Vec<Value> = rows.filter_map(|r| r.ok()).collect();
+        Ok(json!({ "sessions": sessions }))
+    }
+
+    /// One session's turns and tool calls, interleaved in time order.
+    pub fn session(&self, session: i64) -> Result<Value, String> {
+        let started: Option<(i64, Option<i64>)> = self
+            .conn
+            .query_row(
+                "SELECT started_at, ended_at FROM sessions WHERE id = ?1",
+                [session],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("History query failed: {}", e))?;
+        let Some((started_at, ended_at)) = started else {
+            return Err(format!("No session {}", session));
+        };
+
+        let mut entries: Vec<(i64, Value)> = Vec::new();
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT at, heard, said FROM turns WHERE session_id = ?1 ORDER BY id")
+            .map_err(|e| format!("History query failed: {}", e))?;
+        let turns = stmt
+            .query_map([session], |row| {
+                let at: i64 = row.get(0)?;
+                Ok((at, json!({
+                    "kind": "turn",
+                    "at": at,
+                    "heard": row.get::<_, String>(1)?,
+                    "said": row.get::<_, String>(2)?,
+                })))
+            })
+            .map_err(|e| format!("History query failed: {}", e))?;
+        entries.extend(turns.filter_map(|r| r.ok()));
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT called_at, name, args, result, status, finished_at \
+                 FROM tool_calls WHERE session_id = ?1 ORDER BY id",
+            )
+            .map_err(|e| format!("History query failed: {}", e))?;
+        let calls = stmt
+            .query_map([session], |row| {
+                let at: i64 = row.get(0)?;
+                let args: String = row.get(2)?;
+                let result: Option<String> = row.get(3)?;
+                Ok((at, json!({
+                    "kind": "tool",
+                    "at": at,
+                    "name": row.get::<_, String>(1)?,
+                    "args": serde_json::from_str::<Value>(&args).unwrap_or(Value::String(args)),
+                    "result": result.map(|r| serde_json::from_str::<Value>(&r).unwrap_or(Value::String(r))),
+                    "status": row.get::<_, String>(4)?,
+                    "finishedAt": row.get::<_, Option<i64>>(5)?,
+                })))
+            })
+            .map_err(|e| format!("History query failed: {}", e))?;
+        entries.extend(calls.filter_map(|r| r.ok()));
+
+        // Stable, so a turn and a tool call from the same millisecond keep
+        // turns first
+        entries.sort_by_key(|(at, _)| *at);
+        Ok(json!({
+            "id": session,
+            "startedAt": started_at,
+            "endedAt": ended_at,
+            "entries": entries.into_iter().map(|(_, e)| e).collect::<Vec<_>>(),
+        }))
+    }
+
+    /// Record a dictation; `fallback_from` is the configured provider when
+    /// another one (or the offline model) stood in for it.
+    pub fn add_dictation(&self, provider: &str, fallback_from: Option<&str>, text: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO dictations (at, provider, text, fallback_from) VALUES (?1, ?2, ?3, ?4)",
+                params![now_ms(), provider, text, fallback_from],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Cannot save dictation: {}", e))
+    }
+
+    pub fn add_usage(&self, usage: &UsageRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO usage (at, provider, model, audio_secs, tokens, cost_usd) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    now_ms(),
+                    usage.provider,
+                    usage.model,
+                    usage.audio_secs,
+                    usage.tokens as i64,
+                    usage.cost_usd
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Cannot save usage: {}", e))
+    }
+
+    /// Usage since the start of today, this week (from Monday) and this
+    /// month, in local time.
+    pub fn usage_totals(&self) -> Result<[(&'static str, UsageTotals); 3], String> {
+        let periods = [
+            ("today", "'start of day'"),
+            ("week", "'start of day', 'weekday 0', '-6 days'"),
+            ("month", "'start of month'"),
+        ];
+        let mut totals = [("", UsageTotals::default()); 3];
+        for (slot, (name, modifiers)) in totals.iter_mut().zip(periods) {
+            let sql = format!(
+                "SELECT COUNT(*), COALESCE(SUM(audio_secs), 0), COALESCE(SUM(tokens), 0), \
+                   COALESCE(SUM(cost_usd), 0) \
+                 FROM usage \
+                 WHERE at >= strftime('%s', 'now', 'localtime', {}, 'utc') * 1000",
+                modifiers
+            );
+            let period = self
+                .conn
+                .query_row(&sql, [], |row| {
+                    Ok(UsageTotals {
+                        requests: row.get::<_, i64>(0)? as u64,
+                        audio_secs: row.get(1)?,
+                        tokens: row.get::<_, i64>(2)? as u64,
+                        cost_usd: row.get(3)?,
+                    })
+                })
+                .map_err(|e| format!("Usage query failed: {}", e))?;
+            *slot = (name, period);
+        }
+        Ok(totals)
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Start a new file once the current one reaches this size
+const MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept next to the current one (`mavoice.log.1` is the newest)
+const KEEP: usize = 3;
+
+/// Log to stderr and to `~/.local/state/mavoice/mavoice.log`. Without a
+/// writable state directory only stderr is used.
+pub fn init() {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    match RotatingLog::open(log_path()) {
+        Ok(file) => {
+            builder.target(env_logger::Target::Pipe(Box::new(Tee(file))));
+        }
+        Err(e) => eprintln!("Logging to stderr only: {}", e),
+    }
+    builder.init();
+}
+
+/// `~/.local/state/mavoice/mavoice.log`
+pub fn log_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(|| PathBuf::from("~/.local/state"))
+        .join("mavoice")
+        .join("mavoice.log")
+}
+
+/// The log and its rotated predecessors that exist, newest first.
+pub fn log_files() -> Vec<PathBuf> {
+    let path = log_path();
+    std::iter::once(path.clone())
+        .chain((1..=KEEP).map(|i| rotated(&path, i)))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Appends to the log file, moving it to `.1` (and older ones up) when it
+/// grows past [`MAX_BYTES`].
+struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    len: u64,
+}
+
+impl RotatingLog {
+    fn open(path: PathBuf) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { path, file, len })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for i in (1..KEEP).rev() {
+            let from = rotated(&self.path, i);
+            if from.exists() {
+                std::fs::rename(&from, rotated(&self.path, i + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated(&self.path, 1))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > MAX_BYTES {
+            // Keep writing to the old file rather than losing the record
+            let _ = self.rotate();
+        }
+        let n = self.file.write(buf)?;
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Every record goes to stderr and the log file. A failing file never
+/// stops logging to stderr.
+struct Tee(RotatingLog);
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _ = io::stderr().write_all(buf);
+        let _ = self.0.write_all(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = self.0.flush();
+        io::stderr().flush()
+    }
+}
@@ -1,18 +1,69 @@
 mod api;
 mod app;
+mod archive;
 mod audio;
+mod bench;
+mod cli;
 mod config;
+mod conversation;
 mod dashboard;
+mod debug_bundle;
+mod history;
+mod logfile;
+mod meeting;
+mod metrics;
+mod notes;
+mod privacy;
 mod renderer;
+mod settings;
 mod state_machine;
 mod system;
+mod text;
 mod tools;
+mod usage;
 
+use clap::Parser;
 use std::sync::Arc;
 use winit::event_loop::EventLoop;
 
 fn main() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let cli = cli::Cli::parse();
+    if let Some(ref path) = cli.config {
+        config::Config::set_config_path(path.clone());
+    }
+
+    if cli.usage {
+        print_usage();
+        return;
+    }
+    if let Some(ref dest) = cli.debug_bundle {
+        let dest = (!dest.as_os_str().is_empty()).then_some(dest.as_path());
+        match debug_bundle::export(dest) {
+            Ok(path) => println!("{}", path.display()),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    logfile::init();
+
+    if let Some(runs) = cli.bench {
+        let config = cli.apply(config::Config::load().with_active_preset());
+        let opts = bench::BenchOptions {
+            runs: runs.max(1),
+            file: cli.bench_file.clone(),
+            loopback: cli.bench_loopback,
+            inject: cli.bench_inject,
+        };
+        if let Err(e) = bench::run(&config, &opts) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
     log::info!("maVoice native v{}", env!("CARGO_PKG_VERSION"));
 
@@ -25,14 +76,41 @@ fn main() {
     );
 
     // Create event loop with custom AppEvent
-    let event_loop = EventLoop::<app::AppEvent>::with_user_event()
-        .build()
-        .expect("Failed to create event loop");
+    let event_loop = match EventLoop::<app::AppEvent>::with_user_event().build() {
+        Ok(event_loop) => event_loop,
+        Err(e) => {
+            // winit needs a display server connection even with no windows
+            log::error!("Failed to create event loop: {}", e);
+            eprintln!(
+                "Cannot connect to a display server ({}). maVoice needs DISPLAY or WAYLAND_DISPLAY set, also with --headless.",
+                e
+            );
+            std::process::exit(1);
+        }
+    };
 
     let proxy = event_loop.create_proxy();
 
-    let mut app = app::App::new(tokio_rt, proxy);
+    let mut app = app::App::new(tokio_rt, proxy, cli);
 
     log::info!("Starting event loop");
     event_loop.run_app(&mut app).expect("Event loop failed");
 }
+
+/// `--usage`: totals from the history database.
+fn print_usage() {
+    let totals = history::History::open(&history::History::default_path())
+        .and_then(|history| history.usage_totals());
+    match totals {
+        Ok(totals) => {
+            for (period, t) in totals {
+                println!("{:<6} {}", period, t.describe());
+            }
+            println!("Costs are estimates from list prices.");
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
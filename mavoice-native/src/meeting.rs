@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A long-form recording transcribed in chunks and appended to a markdown
+/// file. Chunks are transcribed concurrently, so results are buffered and
+/// written back in recording order.
+pub struct MeetingNotes {
+    path: PathBuf,
+    started: Instant,
+    /// Offset of the chunk currently being recorded
+    chunk_started: Duration,
+    next_index: u64,
+    next_write: u64,
+    /// Finished chunks waiting for an earlier one, keyed by index
+    done: BTreeMap<u64, Result<String, String>>,
+    /// Chunk start offsets, keyed by index
+    offsets: BTreeMap<u64, Duration>,
+    /// Recording has stopped; only outstanding chunks remain
+    pub stopping: bool,
+}
+
+impl MeetingNotes {
+    /// Create `meeting-<date>.md` in `dir` with a heading.
+    pub fn create(dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (date, time) = utc_date_time(now);
+        let path = dir.join(format!("meeting-{}-{}.md", date, time.replace(':', "")));
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        writeln!(file, "# Meeting {} {} UTC\n", date, time)
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+
+        log::info!("Meeting notes: {}", path.display());
+        Ok(Self {
+            path,
+            started: Instant::now(),
+            chunk_started: Duration::ZERO,
+            next_index: 0,
+            next_write: 0,
+            done: BTreeMap::new(),
+            offsets: BTreeMap::new(),
+            stopping: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// True once the current chunk has run for `interval`.
+    pub fn chunk_due(&self, interval: Duration) -> bool {
+        self.started.elapsed() - self.chunk_started >= interval
+    }
+
+    /// Close the current chunk and return its index for the transcription.
+    pub fn cut_chunk(&mut self) -> u64 {
+        let index = self.next_index;
+        self.offsets.insert(index, self.chunk_started);
+        self.next_index += 1;
+        self.chunk_started = self.started.elapsed();
+        index
+    }
+
+    /// Record a chunk's transcript and append every chunk that is now in order.
+    pub fn finish_chunk(&mut self, index: u64, result: Result<String, String>) {
+        self.done.insert(index, result);
+        while let Some(result) = self.done.remove(&self.next_write) {
+            let offset = self
+                .offsets
+                .remove(&self.next_write)
+                .unwrap_or_default()
+                .as_secs();
+            let body = match result {
+                Ok(text) if text.trim().is_empty() => None,
+                Ok(text) => Some(text.trim().to_string()),
+                Err(e) => Some(format!("_(transcription failed: {})_", e)),
+            };
+            if let Some(body) = body {
+                let entry = format!(
+                    "**[{:02}:{:02}:{:02}]** {}\n\n",
+                    offset / 3600,
+                    offset / 60 % 60,
+                    offset % 60,
+                    body
+                );
+                if let Err(e) = self.append(&entry) {
+                    log::error!("Meeting notes write failed: {}", e);
+                }
+            }
+            self.next_write += 1;
+        }
+    }
+
+    /// Chunks cut but not yet written.
+    pub fn outstanding(&self) -> bool {
+        self.next_write < self.next_index
+    }
+
+    fn append(&self, text: &str) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(text.as_bytes())
+    }
+}
+
+/// ("YYYY-MM-DD", "HH:MM") in UTC for a Unix timestamp.
+pub(crate) fn utc_date_time(secs: u64) -> (String, String) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        format!("{:04}-{:02}-{:02}", year, month, day),
+        format!("{:02}:{:02}", rem / 3600, rem / 60 % 60),
+    )
+}
@@ -0,0 +1,57 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+/// Samples kept per metric for the rolling percentiles.
+const WINDOW: usize = 100;
+
+/// Stop of a dictation recording → final transcript received.
+pub const TRANSCRIPTION: &str = "transcription";
+/// Final transcript received → text pasted into the target window.
+pub const INJECTION: &str = "injection";
+/// Stop of a dictation recording → text pasted (the whole wait).
+pub const DICTATION: &str = "dictation";
+/// End of the user's Gemini turn → first audio of the reply.
+pub const FIRST_AUDIO: &str = "first_audio";
+
+/// Timestamps of the dictation in flight, from which the latencies are taken.
+#[derive(Debug, Default)]
+pub struct DictationTiming {
+    pub stopped: Option<Instant>,
+    pub received: Option<Instant>,
+}
+
+/// Rolling latency samples per metric, in milliseconds.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    samples: HashMap<&'static str, VecDeque<u64>>,
+}
+
+impl Metrics {
+    /// Add a sample and return the `metrics:<name>` payload: the sample
+    /// plus p50/p95 over the last [`WINDOW`] samples.
+    pub fn record(&mut self, name: &'static str, took: Duration) -> Value {
+        let ms = took.as_millis() as u64;
+        let samples = self.samples.entry(name).or_default();
+        if samples.len() == WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(ms);
+
+        let mut sorted: Vec<u64> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        json!({
+            "ms": ms,
+            "p50": percentile(&sorted, 50),
+            "p95": percentile(&sorted, 95),
+            "count": sorted.len(),
+        })
+    }
+}
+
+/// Nearest-rank percentile of an ascending, non-empty slice.
+pub(crate) fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
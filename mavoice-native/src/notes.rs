@@ -0,0 +1,56 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::meeting::utc_date_time;
+
+/// Append a dictated note to today's `<date>.md` in `dir`, creating the
+/// file with a heading first. `template` may use `{date}`, `{time}` and
+/// `{text}`; times are UTC like meeting notes. Returns the file's path.
+pub fn append(dir: &Path, template: &str, text: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (date, time) = utc_date_time(now);
+    let path = dir.join(format!("{}.md", date));
+    let new = !path.exists();
+
+    let mut entry = template
+        .replace("{date}", &date)
+        .replace("{time}", &time)
+        .replace("{text}", text.trim());
+    if !entry.ends_with('\n') {
+        entry.push('\n');
+    }
+    if new {
+        entry = format!("# Notes {}\n\n{}", date, entry);
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    file.write_all(entry.as_bytes())
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// "start note mode" / "stop note mode" (also "note mode on/off"), as
+/// `Some(on)`; anything else is `None`.
+pub fn spoken_toggle(text: &str) -> Option<bool> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+    match words.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["start", "note", "mode"] | ["note", "mode", "on"] => Some(true),
+        ["stop", "note", "mode"] | ["note", "mode", "off"] | ["end", "note", "mode"] => Some(false),
+        _ => None,
+    }
+}
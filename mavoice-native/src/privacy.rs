@@ -0,0 +1,43 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::Value;
+
+/// Dashboard payload fields carrying what was said or typed
+const CONTENT_FIELDS: &[&str] = &["text", "heard", "said", "input", "output"];
+
+/// Privacy mode (`privacy_mode`). A global so the API clients can keep
+/// transcripts out of their logs without threading the config through.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// `text` for a log line, or only its length in privacy mode.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    if enabled() {
+        Cow::Owned(format!("<{} chars>", text.chars().count()))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Blank the transcript fields of a dashboard event in privacy mode.
+pub fn redact_payload(payload: &mut Value) {
+    if !enabled() {
+        return;
+    }
+    let Some(fields) = payload.as_object_mut() else {
+        return;
+    };
+    for key in CONTENT_FIELDS {
+        if let Some(value) = fields.get_mut(*key) {
+            *value = Value::Null;
+        }
+    }
+}
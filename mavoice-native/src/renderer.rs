@@ -1,8 +1,11 @@
 use bytemuck::{Pod, Zeroable};
+use font8x8::{UnicodeFonts, BASIC_FONTS, LATIN_FONTS};
 use std::sync::Arc;
 use wgpu::util::DeviceExt;
 
-/// Uniform buffer layout for user shader — 48 bytes, matches shader.wgsl
+/// Uniform buffer layout for user shader — 64 bytes, matches shader.wgsl.
+/// Custom shaders (`user_shader`) bind this as-is, so fields are only ever
+/// appended, never moved.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct UserUniforms {
@@ -12,9 +15,13 @@ pub struct UserUniforms {
     pub levels: [f32; 4],     // 16 bytes (offset 16)
     pub color: [f32; 3],      // 12 bytes (offset 32)
     pub mode: f32,            // 4 bytes  (offset 44)
-}                             // total: 48 bytes
+    pub rec_secs: f32,        // 4 bytes  (offset 48) seconds recorded so far
+    pub rec_limit: f32,       // 4 bytes  (offset 52) auto-stop length, 0 = no bar
+    pub _pad: [f32; 2],       // 8 bytes  (offset 56)
+}                             // total: 64 bytes
 
-/// Uniform buffer layout for AI shader — 48 bytes, matches ai_shader.wgsl
+/// Uniform buffer layout for AI shader — 48 bytes, matches ai_shader.wgsl.
+/// Same stability rule as [`UserUniforms`].
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct AiUniforms {
@@ -26,16 +33,65 @@ pub struct AiUniforms {
     pub _pad: f32,            // 4 bytes  (offset 44)
 }                             // total: 48 bytes
 
+/// Uniform buffer layout for the transcript toast — 32 bytes, matches
+/// toast_shader.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct ToastUniforms {
+    pub resolution: [f32; 2], // 8 bytes  (offset 0)
+    pub alpha: f32,           // 4 bytes  (offset 8)
+    pub _pad: f32,            // 4 bytes  (offset 12)
+    pub color: [f32; 3],      // 12 bytes (offset 16) border tint
+    pub _pad2: f32,           // 4 bytes  (offset 28)
+}                             // total: 32 bytes
+
+/// Where a [`TextOverlay`] sits in the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextPlacement {
+    /// One line along the top edge (user strip)
+    Top,
+    /// Up to three wrapped lines along the bottom edge (AI window)
+    Bottom,
+    /// Up to three wrapped lines, centered both ways (transcript toast)
+    Center,
+}
+
+/// Text drawn over the shader output. Composited on the CPU with an 8x8
+/// bitmap font (doubled) while blitting, so it costs nothing on the GPU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOverlay {
+    pub text: String,
+    /// Linear RGB, like the shader colors
+    pub color: [f32; 3],
+    pub alpha: f32,
+    pub placement: TextPlacement,
+}
+
+/// Glyph scale: 8x8 cells drawn as 16x16 pixels
+const TEXT_SCALE: usize = 2;
+const GLYPH_PX: usize = 8 * TEXT_SCALE;
+const TEXT_MARGIN: usize = 6;
+
+/// Vendor id of NVIDIA adapters, whose X11 driver only offers opaque surfaces
+const NVIDIA_VENDOR: u32 = 0x10DE;
+
+/// Draws the text layer over the shader on the direct surface path
+const TEXT_SHADER: &str = include_str!("text_shader.wgsl");
+
 /// Shared GPU resources — created once, shared between both renderers
 pub struct GpuContext {
     pub device: Arc<wgpu::Device>,
     pub queue: Arc<wgpu::Queue>,
     pub start_time: std::time::Instant,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    /// Try presenting through a wgpu surface before falling back to readback
+    direct: bool,
 }
 
 impl GpuContext {
-    pub async fn new() -> Self {
-        // Use default backends — we don't need a surface for render-to-texture
+    /// `render_path` is "auto", "surface" or "readback", see `Config::render_path`.
+    pub async fn new(render_path: &str) -> Self {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::VULKAN | wgpu::Backends::GL,
             ..Default::default()
@@ -44,13 +100,15 @@ impl GpuContext {
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::LowPower,
-                compatible_surface: None, // No surface needed — we render to texture
+                // Surfaces are made per window later; readback needs none
+                compatible_surface: None,
                 force_fallback_adapter: false,
             })
             .await
             .expect("No suitable GPU adapter found");
 
-        log::info!("GPU adapter: {:?}", adapter.get_info().name);
+        let info = adapter.get_info();
+        log::info!("GPU adapter: {:?}", info.name);
 
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
@@ -69,10 +127,21 @@ impl GpuContext {
             })
             .expect("Failed to create device");
 
+        let wayland = std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t == "wayland")
+            || std::env::var_os("WAYLAND_DISPLAY").is_some();
+        let direct = match render_path {
+            "surface" => true,
+            "readback" => false,
+            _ => wayland || info.vendor != NVIDIA_VENDOR,
+        };
+
         Self {
             device: Arc::new(device),
             queue: Arc::new(queue),
             start_time: std::time::Instant::now(),
+            instance,
+            adapter,
+            direct,
         }
     }
 
@@ -81,23 +150,296 @@ impl GpuContext {
     }
 }
 
-/// Per-window renderer — renders shader to texture, blits to window via softbuffer.
-/// Bypasses wgpu surface compositing (which is Opaque on NVIDIA X11) by writing
-/// ARGB pixels directly to the X11 window's 32-bit backing pixmap.
+/// How finished frames reach the window.
+enum Output {
+    /// Straight to a wgpu surface with a transparent alpha mode (Wayland,
+    /// Mesa on X11). No copies leave the GPU.
+    Surface {
+        surface: wgpu::Surface<'static>,
+        config: wgpu::SurfaceConfiguration,
+        /// Created the first time there's text to draw
+        text_layer: Option<TextLayer>,
+    },
+    /// Render to texture, read it back and blit with softbuffer. Bypasses
+    /// wgpu surface compositing (which is Opaque on NVIDIA X11) by writing
+    /// ARGB pixels directly to the X11 window's 32-bit backing pixmap.
+    Readback {
+        render_texture: wgpu::Texture,
+        render_view: wgpu::TextureView,
+        readback_buffer: wgpu::Buffer,
+        _sb_context: softbuffer::Context<Arc<winit::window::Window>>,
+        sb_surface: softbuffer::Surface<Arc<winit::window::Window>, Arc<winit::window::Window>>,
+    },
+}
+
+impl Output {
+    fn surface(
+        gpu: &GpuContext,
+        window: Arc<winit::window::Window>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        let surface = gpu.instance.create_surface(window).map_err(|e| e.to_string())?;
+        if !gpu.adapter.is_surface_supported(&surface) {
+            return Err("adapter can't present to this window".into());
+        }
+        let caps = surface.get_capabilities(&gpu.adapter);
+        let alpha_mode = [
+            wgpu::CompositeAlphaMode::PreMultiplied,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+        ]
+        .into_iter()
+        .find(|mode| caps.alpha_modes.contains(mode))
+        .ok_or_else(|| format!("surface is opaque (alpha modes {:?})", caps.alpha_modes))?;
+        // Shaders apply gamma themselves, like the Rgba8Unorm readback target
+        let format = caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| !f.is_srgb())
+            .ok_or("no linear surface format")?;
+        // Frames are paced by the event loop; don't block each window on vblank
+        let present_mode = [wgpu::PresentMode::Mailbox, wgpu::PresentMode::Immediate]
+            .into_iter()
+            .find(|mode| caps.present_modes.contains(mode))
+            .unwrap_or(wgpu::PresentMode::Fifo);
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width,
+            height,
+            present_mode,
+            desired_maximum_frame_latency: 2,
+            alpha_mode,
+            view_formats: vec![],
+        };
+        surface.configure(&gpu.device, &config);
+        Ok(Output::Surface {
+            surface,
+            config,
+            text_layer: None,
+        })
+    }
+
+    fn readback(gpu: &GpuContext, window: Arc<winit::window::Window>, width: u32, height: u32) -> Self {
+        let (render_texture, render_view, readback_buffer) = readback_targets(&gpu.device, width, height);
+
+        // Softbuffer context + surface for X11 ARGB presentation
+        let sb_context =
+            softbuffer::Context::new(window.clone()).expect("Failed to create softbuffer context");
+        let sb_surface =
+            softbuffer::Surface::new(&sb_context, window).expect("Failed to create softbuffer surface");
+
+        Output::Readback {
+            render_texture,
+            render_view,
+            readback_buffer,
+            _sb_context: sb_context,
+            sb_surface,
+        }
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            Output::Surface { config, .. } => config.format,
+            Output::Readback { .. } => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+/// Render target texture and the buffer it's copied into for the CPU.
+fn readback_targets(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+) -> (wgpu::Texture, wgpu::TextureView, wgpu::Buffer) {
+    let render_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("render-target"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let render_view = render_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Readback buffer for texture → CPU transfer
+    let bytes_per_row = Renderer::aligned_bytes_per_row(width);
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback-buffer"),
+        size: (bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    (render_texture, render_view, readback_buffer)
+}
+
+/// The text overlay as a texture, for the surface path where there's no CPU
+/// blit to draw it into. Redrawn only when the text changes; the fade is a
+/// uniform.
+struct TextLayer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    texture: wgpu::Texture,
+    alpha_buffer: wgpu::Buffer,
+    /// What's in `texture`, at full alpha
+    drawn: Option<TextOverlay>,
+    width: u32,
+    height: u32,
+}
+
+impl TextLayer {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("text-layer"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let alpha_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("text-alpha"),
+            contents: bytemuck::cast_slice(&[0.0f32; 4]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("text-bind-group-layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text-bind-group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: alpha_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("text-pipeline-layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let pipeline = Renderer::create_pipeline(device, &pipeline_layout, format, TEXT_SHADER);
+
+        Self {
+            pipeline,
+            bind_group,
+            texture,
+            alpha_buffer,
+            drawn: None,
+            width,
+            height,
+        }
+    }
+
+    fn update(&mut self, queue: &wgpu::Queue, overlay: &TextOverlay) {
+        let alpha = overlay.alpha.clamp(0.0, 1.0);
+        queue.write_buffer(&self.alpha_buffer, 0, bytemuck::cast_slice(&[alpha, 0.0, 0.0, 0.0]));
+
+        let opaque = TextOverlay {
+            alpha: 1.0,
+            ..overlay.clone()
+        };
+        if self.drawn.as_ref() == Some(&opaque) {
+            return;
+        }
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut pixels = vec![0u32; width * height];
+        draw_text(&mut pixels, width, height, &opaque);
+        // 0xAARRGGBB → RGBA bytes
+        let bytes: Vec<u8> = pixels
+            .iter()
+            .flat_map(|px| {
+                let [a, r, g, b] = px.to_be_bytes();
+                [r, g, b, a]
+            })
+            .collect();
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &bytes,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(self.width * 4),
+                rows_per_image: Some(self.height),
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.drawn = Some(opaque);
+    }
+}
+
+/// Per-window renderer — runs the shader and presents the result either
+/// directly through a wgpu surface or via texture readback and softbuffer.
 pub struct Renderer {
     // GPU resources
     pipeline: wgpu::RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    uniform_size: usize,
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
-    // Render-to-texture target
-    render_texture: wgpu::Texture,
-    render_view: wgpu::TextureView,
-    readback_buffer: wgpu::Buffer,
-    // Softbuffer for X11 ARGB compositing
-    _sb_context: softbuffer::Context<Arc<winit::window::Window>>,
-    sb_surface: softbuffer::Surface<Arc<winit::window::Window>, Arc<winit::window::Window>>,
+    output: Output,
+    /// Drawn over the next frames until replaced
+    text: Option<TextOverlay>,
+    /// Opacity of the black plate behind the shader (high contrast)
+    backdrop: f32,
     // Dimensions
     pub width: u32,
     pub height: u32,
@@ -114,48 +456,22 @@ impl Renderer {
         let width = size.width.max(1);
         let height = size.height.max(1);
 
-        let format = wgpu::TextureFormat::Rgba8Unorm;
-
-        // Create render target texture
-        let render_texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("render-target"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
-            view_formats: &[],
-        });
-        let render_view = render_texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Readback buffer for texture → CPU transfer
-        let bytes_per_row = Self::aligned_bytes_per_row(width);
-        let readback_size = (bytes_per_row * height) as u64;
-        let readback_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("readback-buffer"),
-            size: readback_size,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
-
-        // Softbuffer context + surface for X11 ARGB presentation
-        let sb_context =
-            softbuffer::Context::new(window.clone()).expect("Failed to create softbuffer context");
-        let sb_surface =
-            softbuffer::Surface::new(&sb_context, window.clone()).expect("Failed to create softbuffer surface");
-
-        // Shader module
-        let shader = gpu
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("shader"),
-                source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-            });
+        let direct = if gpu.direct {
+            match Output::surface(gpu, window.clone(), width, height) {
+                Ok(output) => {
+                    log::info!("Presenting directly to a wgpu surface");
+                    Some(output)
+                }
+                Err(e) => {
+                    log::info!("Direct presentation unavailable ({}), using readback", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let output = direct.unwrap_or_else(|| Output::readback(gpu, window, width, height));
+        let format = output.format();
 
         // Uniform buffer
         let uniform_data = vec![0u8; uniform_size];
@@ -204,70 +520,91 @@ impl Renderer {
                     immediate_size: 0,
                 });
 
-        // Render pipeline — premultiplied alpha blending
-        let pipeline = gpu
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[],
-                    compilation_options: Default::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: Some("fs_main"),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format,
-                        blend: Some(wgpu::BlendState {
-                            color: wgpu::BlendComponent {
-                                src_factor: wgpu::BlendFactor::One,
-                                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                                operation: wgpu::BlendOperation::Add,
-                            },
-                            alpha: wgpu::BlendComponent {
-                                src_factor: wgpu::BlendFactor::One,
-                                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                                operation: wgpu::BlendOperation::Add,
-                            },
-                        }),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                    compilation_options: Default::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleStrip,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState::default(),
-                multiview_mask: None,
-                cache: None,
-            });
+        let pipeline = Self::create_pipeline(&gpu.device, &pipeline_layout, format, shader_source);
 
         Self {
             pipeline,
+            pipeline_layout,
+            format,
+            uniform_size,
             uniform_buffer,
             bind_group,
             device: gpu.device.clone(),
             queue: gpu.queue.clone(),
-            render_texture,
-            render_view,
-            readback_buffer,
-            _sb_context: sb_context,
-            sb_surface,
+            output,
+            text: None,
+            backdrop: 0.0,
             width,
             height,
         }
     }
 
+    /// Render pipeline for `shader_source` — premultiplied alpha blending
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+        shader_source: &str,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview_mask: None,
+            cache: None,
+        })
+    }
+
+    /// Swap in a different shader. It's validated first, since wgpu panics
+    /// on invalid modules; on error the current pipeline is kept.
+    pub fn set_shader(&mut self, source: &str) -> Result<(), String> {
+        validate_shader(source, self.uniform_size)?;
+        self.pipeline = Self::create_pipeline(&self.device, &self.pipeline_layout, self.format, source);
+        Ok(())
+    }
+
     /// Bytes per row aligned to wgpu's COPY_BYTES_PER_ROW_ALIGNMENT (256)
     fn aligned_bytes_per_row(width: u32) -> u32 {
         let unaligned = width * 4;
@@ -282,40 +619,43 @@ impl Renderer {
         self.width = width;
         self.height = height;
 
-        // Recreate render texture
-        self.render_texture = self.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("render-target"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
-            view_formats: &[],
-        });
-        self.render_view = self
-            .render_texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Recreate readback buffer
-        let bytes_per_row = Self::aligned_bytes_per_row(width);
-        let readback_size = (bytes_per_row * height) as u64;
-        self.readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("readback-buffer"),
-            size: readback_size,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
+        match &mut self.output {
+            Output::Surface {
+                surface,
+                config,
+                text_layer,
+            } => {
+                config.width = width;
+                config.height = height;
+                surface.configure(&self.device, config);
+                *text_layer = None;
+            }
+            Output::Readback {
+                render_texture,
+                render_view,
+                readback_buffer,
+                sb_surface,
+                ..
+            } => {
+                (*render_texture, *render_view, *readback_buffer) =
+                    readback_targets(&self.device, width, height);
+                let _ = sb_surface.resize(
+                    std::num::NonZeroU32::new(width).unwrap(),
+                    std::num::NonZeroU32::new(height).unwrap(),
+                );
+            }
+        }
+    }
 
-        // Resize softbuffer
-        let _ = self.sb_surface.resize(
-            std::num::NonZeroU32::new(width).unwrap(),
-            std::num::NonZeroU32::new(height).unwrap(),
-        );
+    /// Set or clear the text drawn over subsequent frames.
+    pub fn set_text(&mut self, text: Option<TextOverlay>) {
+        self.text = text;
+    }
+
+    /// Put a black plate of this opacity behind the shader output, so its
+    /// colors keep their contrast over any background (0 = transparent).
+    pub fn set_backdrop(&mut self, alpha: f32) {
+        self.backdrop = alpha.clamp(0.0, 1.0);
     }
 
     pub fn render_bytes(&mut self, uniform_bytes: &[u8]) {
@@ -323,31 +663,86 @@ impl Renderer {
         self.queue
             .write_buffer(&self.uniform_buffer, 0, uniform_bytes);
 
+        if matches!(self.output, Output::Surface { .. }) {
+            self.render_surface();
+        } else {
+            self.render_readback();
+        }
+    }
+
+    fn render_surface(&mut self) {
+        let Output::Surface {
+            surface,
+            config,
+            text_layer,
+        } = &mut self.output
+        else {
+            return;
+        };
+        let frame = match surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                surface.configure(&self.device, config);
+                return;
+            }
+            Err(e) => {
+                log::warn!("Skipping frame: {}", e);
+                return;
+            }
+        };
+        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let text_layer = match self.text {
+            Some(ref text) => {
+                let layer = text_layer
+                    .get_or_insert_with(|| TextLayer::new(&self.device, config.format, config.width, config.height));
+                layer.update(&self.queue, text);
+                Some(&*layer)
+            }
+            None => None,
+        };
+
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("render-encoder"),
             });
-
-        // Render shader to texture
         {
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("render-pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.render_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
-                        store: wgpu::StoreOp::Store,
-                    },
-                    depth_slice: None,
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-                multiview_mask: None,
+            let mut pass = begin_pass(&mut encoder, &view, self.backdrop);
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.draw(0..4, 0..1);
+            if let Some(layer) = text_layer {
+                pass.set_pipeline(&layer.pipeline);
+                pass.set_bind_group(0, &layer.bind_group, &[]);
+                pass.draw(0..4, 0..1);
+            }
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+    }
+
+    fn render_readback(&mut self) {
+        let Output::Readback {
+            render_texture,
+            render_view,
+            readback_buffer,
+            sb_surface,
+            ..
+        } = &mut self.output
+        else {
+            return;
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("render-encoder"),
             });
 
+        // Render shader to texture
+        {
+            let mut pass = begin_pass(&mut encoder, render_view, self.backdrop);
             pass.set_pipeline(&self.pipeline);
             pass.set_bind_group(0, &self.bind_group, &[]);
             pass.draw(0..4, 0..1);
@@ -357,13 +752,13 @@ impl Renderer {
         let bytes_per_row = Self::aligned_bytes_per_row(self.width);
         encoder.copy_texture_to_buffer(
             wgpu::TexelCopyTextureInfo {
-                texture: &self.render_texture,
+                texture: render_texture,
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
             wgpu::TexelCopyBufferInfo {
-                buffer: &self.readback_buffer,
+                buffer: readback_buffer,
                 layout: wgpu::TexelCopyBufferLayout {
                     offset: 0,
                     bytes_per_row: Some(bytes_per_row),
@@ -380,7 +775,7 @@ impl Renderer {
         self.queue.submit(std::iter::once(encoder.finish()));
 
         // Map readback buffer and blit to softbuffer
-        let buffer_slice = self.readback_buffer.slice(..);
+        let buffer_slice = readback_buffer.slice(..);
         let (tx, rx) = std::sync::mpsc::channel();
         buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
             let _ = tx.send(result);
@@ -397,12 +792,12 @@ impl Renderer {
             let stride = bytes_per_row as usize;
 
             // Resize softbuffer to match render dimensions, then blit
-            let _ = self.sb_surface.resize(
+            let _ = sb_surface.resize(
                 std::num::NonZeroU32::new(self.width).unwrap(),
                 std::num::NonZeroU32::new(self.height).unwrap(),
             );
             // Write to softbuffer — RGBA premultiplied → packed u32 (0xAARRGGBB for softbuffer)
-            if let Ok(mut buffer) = self.sb_surface.buffer_mut() {
+            if let Ok(mut buffer) = sb_surface.buffer_mut() {
                 for y in 0..height {
                     let row_start = y * stride;
                     for x in 0..width {
@@ -417,10 +812,190 @@ impl Renderer {
                         buffer[y * width + x] = (a << 24) | (r << 16) | (g << 8) | b;
                     }
                 }
+                if let Some(ref text) = self.text {
+                    draw_text(&mut buffer, width, height, text);
+                }
                 let _ = buffer.present();
             }
             drop(data);
         }
-        self.readback_buffer.unmap();
+        readback_buffer.unmap();
     }
 }
+
+/// Start a pass that clears to black at `backdrop` opacity (premultiplied,
+/// so 0 is fully transparent).
+fn begin_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    backdrop: f32,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("render-pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color {
+                    a: f64::from(backdrop),
+                    ..wgpu::Color::BLACK
+                }),
+                store: wgpu::StoreOp::Store,
+            },
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+        multiview_mask: None,
+    })
+}
+
+/// Check that `source` is valid WGSL with `vs_main`/`fs_main` entry points
+/// that reads nothing but the uniform block at group 0, binding 0, no larger
+/// than the `uniform_size` bytes we upload.
+fn validate_shader(source: &str, uniform_size: usize) -> Result<(), String> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(source))?;
+
+    for (name, stage) in [("vs_main", naga::ShaderStage::Vertex), ("fs_main", naga::ShaderStage::Fragment)] {
+        if !module.entry_points.iter().any(|ep| ep.name == name && ep.stage == stage) {
+            return Err(format!("Missing entry point {} ({:?})", name, stage));
+        }
+    }
+
+    for (_, var) in module.global_variables.iter() {
+        let Some(ref binding) = var.binding else {
+            continue;
+        };
+        if binding.group != 0 || binding.binding != 0 || var.space != naga::AddressSpace::Uniform {
+            return Err(format!(
+                "Only var<uniform> at @group(0) @binding(0) is bound, found {:?}",
+                binding
+            ));
+        }
+        let size = module.types[var.ty].inner.size(module.to_ctx()) as usize;
+        if size > uniform_size {
+            return Err(format!(
+                "Uniforms are {} bytes but only {} are provided",
+                size, uniform_size
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Word-wrap `text` into lines of at most `max_chars`, keeping the last
+/// `max_lines` (the newest words of a growing transcript) and marking the cut.
+fn layout_text(text: &str, max_chars: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        // Hard-split words longer than a line
+        let chars: Vec<char> = word.chars().collect();
+        for piece in chars.chunks(max_chars.max(1)) {
+            let piece: String = piece.iter().collect();
+            let len = line.chars().count();
+            if len > 0 && len + 1 + piece.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&piece);
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    if lines.len() > max_lines {
+        lines.drain(..lines.len() - max_lines);
+        let first: String = lines[0].chars().skip(3).collect();
+        lines[0] = format!("...{}", first);
+    }
+    lines
+}
+
+/// Composite `overlay` into a premultiplied 0xAARRGGBB buffer.
+fn draw_text(buffer: &mut [u32], width: usize, height: usize, overlay: &TextOverlay) {
+    let alpha = overlay.alpha.clamp(0.0, 1.0);
+    if alpha <= 0.0 || overlay.text.is_empty() {
+        return;
+    }
+    let max_chars = width.saturating_sub(2 * TEXT_MARGIN) / GLYPH_PX;
+    let (max_lines, top) = match overlay.placement {
+        TextPlacement::Top => (1, TEXT_MARGIN),
+        TextPlacement::Bottom | TextPlacement::Center => {
+            let lines = 3.min(height.saturating_sub(TEXT_MARGIN) / (GLYPH_PX + 2)).max(1);
+            (lines, 0)
+        }
+    };
+    let lines = layout_text(&overlay.text, max_chars, max_lines);
+    let line_h = GLYPH_PX + 2;
+    let top = match overlay.placement {
+        TextPlacement::Top => top,
+        TextPlacement::Bottom => height.saturating_sub(TEXT_MARGIN + lines.len() * line_h),
+        TextPlacement::Center => height.saturating_sub(lines.len() * line_h) / 2,
+    };
+
+    // Light tint of the state color, in the same sRGB space the shader writes
+    let rgb = overlay.color.map(|c| (c + (1.0 - c) * 0.6).clamp(0.0, 1.0).powf(1.0 / 2.2));
+
+    for (row, line) in lines.iter().enumerate() {
+        let chars = line.chars().count();
+        let left = width.saturating_sub(chars * GLYPH_PX) / 2;
+        let y = top + row * line_h;
+        for (i, ch) in line.chars().enumerate() {
+            let glyph = BASIC_FONTS
+                .get(ch)
+                .or_else(|| LATIN_FONTS.get(ch))
+                .or_else(|| BASIC_FONTS.get('?'))
+                .unwrap_or([0; 8]);
+            let x = left + i * GLYPH_PX;
+            // Drop shadow first, then the glyph
+            draw_glyph(buffer, width, height, &glyph, (x + 1, y + 1), [0.0; 3], alpha * 0.7);
+            draw_glyph(buffer, width, height, &glyph, (x, y), rgb, alpha);
+        }
+    }
+}
+
+fn draw_glyph(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    glyph: &[u8; 8],
+    (x0, y0): (usize, usize),
+    rgb: [f32; 3],
+    alpha: f32,
+) {
+    for (gy, bits) in glyph.iter().enumerate() {
+        for gx in 0..8 {
+            // font8x8 rows are LSB-first: bit 0 is the leftmost pixel
+            if bits & (1 << gx) == 0 {
+                continue;
+            }
+            for sy in 0..TEXT_SCALE {
+                for sx in 0..TEXT_SCALE {
+                    let x = x0 + gx * TEXT_SCALE + sx;
+                    let y = y0 + gy * TEXT_SCALE + sy;
+                    if x < width && y < height {
+                        let px = &mut buffer[y * width + x];
+                        *px = blend(*px, rgb, alpha);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Source-over of a straight-alpha color onto a premultiplied pixel.
+fn blend(dst: u32, rgb: [f32; 3], alpha: f32) -> u32 {
+    let inv = 1.0 - alpha;
+    let channel = |shift: u32, src: f32| -> u32 {
+        let d = ((dst >> shift) & 0xFF) as f32;
+        ((src * 255.0 * alpha + d * inv).round() as u32).min(255) << shift
+    };
+    channel(24, 1.0) | channel(16, rgb[0]) | channel(8, rgb[1]) | channel(0, rgb[2])
+}
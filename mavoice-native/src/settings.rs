@@ -0,0 +1,765 @@
+use std::sync::Arc;
+
+// egui-wgpu brings its own wgpu; use its re-export so the settings window
+// doesn't have to track the overlay renderer's wgpu version.
+use egui_wgpu::wgpu;
+use winit::dpi::LogicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowId};
+
+use crate::config::{Config, Dictionary};
+use crate::system::hotkeys::parse_hotkey;
+
+const PROVIDERS: &[&str] = &["groq", "openai", "candle"];
+const CANDLE_DEVICES: &[&str] = &["auto", "cpu", "cuda"];
+const VOICES: &[&str] = &["Puck", "Charon", "Kore", "Fenrir", "Aoede"];
+const INJECTION_METHODS: &[&str] = &["auto", "xdotool", "wtype", "ydotool", "ibus", "virtual-keyboard", "kwin", "portal", "type"];
+const PRIMARY_SELECTION: &[&str] = &["off", "also", "only"];
+const GEMINI_TRANSCRIPTS: &[&str] = &["off", "type", "log"];
+const SENSITIVITIES: &[&str] = &["high", "low"];
+const GESTURE_ACTIONS: &[&str] = &[
+    "none", "start", "stop", "toggle", "cancel", "mode", "reinject", "settings", "volume_up", "volume_down",
+];
+
+/// What the user asked the settings window to do.
+pub enum SettingsAction {
+    /// Persist and apply this config.
+    Save(Config),
+    /// Close the window without saving.
+    Close,
+}
+
+/// Small egui window for editing `Config` — opened from the overlay.
+pub struct SettingsWindow {
+    window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    egui_ctx: egui::Context,
+    egui_state: egui_winit::State,
+    egui_renderer: egui_wgpu::Renderer,
+    /// Working copy edited by the form; only applied on Save.
+    draft: Config,
+    status: Option<String>,
+    /// Usage badge text, when `usage_badge` is on
+    usage: Option<String>,
+}
+
+impl SettingsWindow {
+    pub fn open(event_loop: &ActiveEventLoop, config: &Config) -> Result<Self, String> {
+        let attrs = Window::default_attributes()
+            .with_title("maVoice Settings")
+            .with_inner_size(LogicalSize::new(520.0, 640.0))
+            .with_min_inner_size(LogicalSize::new(420.0, 400.0));
+        let window = Arc::new(
+            event_loop
+                .create_window(attrs)
+                .map_err(|e| format!("Failed to create settings window: {}", e))?,
+        );
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::VULKAN | wgpu::Backends::GL,
+            ..Default::default()
+        });
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|e| format!("Failed to create settings surface: {}", e))?;
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            compatible_surface: Some(&surface),
+            force_fallback_adapter: false,
+        }))
+        .map_err(|e| format!("No adapter for settings window: {}", e))?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("mavoice-settings-device"),
+            ..Default::default()
+        }))
+        .map_err(|e| format!("Failed to create settings device: {}", e))?;
+
+        let size = window.inner_size();
+        let caps = surface.get_capabilities(&adapter);
+        let format = caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| !f.is_srgb())
+            .unwrap_or(caps.formats[0]);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &surface_config);
+
+        let egui_ctx = egui::Context::default();
+        let egui_state = egui_winit::State::new(
+            egui_ctx.clone(),
+            egui::ViewportId::ROOT,
+            &window,
+            Some(window.scale_factor() as f32),
+            None,
+            Some(device.limits().max_texture_dimension_2d as usize),
+        );
+        let egui_renderer = egui_wgpu::Renderer::new(&device, format, None, 1, false);
+
+        log::info!("[Settings] Window opened");
+
+        Ok(Self {
+            window,
+            surface,
+            surface_config,
+            device,
+            queue,
+            egui_ctx,
+            egui_state,
+            egui_renderer,
+            draft: config.clone(),
+            status: None,
+            usage: None,
+        })
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Bring an already-open settings window to the front.
+    pub fn focus(&self) {
+        self.window.focus_window();
+    }
+
+    /// Set (or clear) the usage summary shown next to the buttons.
+    pub fn set_usage(&mut self, usage: Option<String>) {
+        if usage != self.usage {
+            self.usage = usage;
+            self.window.request_redraw();
+        }
+    }
+
+    /// Route a window event into egui. Returns an action when the user saves or closes.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> Option<SettingsAction> {
+        match event {
+            WindowEvent::CloseRequested => return Some(SettingsAction::Close),
+            WindowEvent::Resized(size) => {
+                if size.width > 0 && size.height > 0 {
+                    self.surface_config.width = size.width;
+                    self.surface_config.height = size.height;
+                    self.surface.configure(&self.device, &self.surface_config);
+                }
+                self.window.request_redraw();
+            }
+            WindowEvent::RedrawRequested => return self.redraw(),
+            _ => {}
+        }
+
+        let response = self.egui_state.on_window_event(&self.window, event);
+        if response.repaint {
+            self.window.request_redraw();
+        }
+        None
+    }
+
+    fn redraw(&mut self) -> Option<SettingsAction> {
+        let raw_input = self.egui_state.take_egui_input(&self.window);
+        let mut action = None;
+
+        let draft = &mut self.draft;
+        let status = &mut self.status;
+        let usage = self.usage.as_deref();
+        let full_output = self.egui_ctx.run(raw_input, |ctx| {
+            action = Self::ui(ctx, draft, status, usage);
+        });
+
+        self.egui_state
+            .handle_platform_output(&self.window, full_output.platform_output);
+
+        let paint_jobs = self
+            .egui_ctx
+            .tessellate(full_output.shapes, full_output.pixels_per_point);
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [self.surface_config.width, self.surface_config.height],
+            pixels_per_point: full_output.pixels_per_point,
+        };
+
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(e) => {
+                log::warn!("[Settings] Surface error: {}", e);
+                self.surface.configure(&self.device, &self.surface_config);
+                self.window.request_redraw();
+                return action;
+            }
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        for (id, delta) in &full_output.textures_delta.set {
+            self.egui_renderer
+                .update_texture(&self.device, &self.queue, *id, delta);
+        }
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("settings-encoder"),
+            });
+        let extra_cmds = self.egui_renderer.update_buffers(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen,
+        );
+
+        {
+            let pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("settings-pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            self.egui_renderer
+                .render(&mut pass.forget_lifetime(), &paint_jobs, &screen);
+        }
+
+        self.queue
+            .submit(extra_cmds.into_iter().chain(std::iter::once(encoder.finish())));
+        frame.present();
+
+        for id in &full_output.textures_delta.free {
+            self.egui_renderer.free_texture(id);
+        }
+
+        if self.egui_ctx.has_requested_repaint() {
+            self.window.request_redraw();
+        }
+
+        action
+    }
+
+    /// Draw the settings form. Returns an action if a button was pressed.
+    fn ui(
+        ctx: &egui::Context,
+        draft: &mut Config,
+        status: &mut Option<String>,
+        usage: Option<&str>,
+    ) -> Option<SettingsAction> {
+        let mut action = None;
+
+        egui::TopBottomPanel::bottom("settings-buttons").show(ctx, |ui| {
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    match Self::validate(draft) {
+                        Ok(()) => {
+                            *status = Some("Saved".to_string());
+                            draft.confirm_tools.retain(|t| !t.is_empty());
+                            draft.always_allow_tools.retain(|t| !t.is_empty());
+                            action = Some(SettingsAction::Save(draft.clone()));
+                        }
+                        Err(e) => *status = Some(e),
+                    }
+                }
+                if ui.button("Revert").clicked() {
+                    *draft = Config::load();
+                    *status = Some("Reloaded from disk".to_string());
+                }
+                if ui.button("Close").clicked() {
+                    action = Some(SettingsAction::Close);
+                }
+                if let Some(ref msg) = status {
+                    ui.label(msg.as_str());
+                }
+                if let Some(usage) = usage {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(egui::RichText::new(usage).small().weak());
+                    });
+                }
+            });
+            ui.add_space(6.0);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("Transcription");
+                egui::Grid::new("transcription").num_columns(2).show(ui, |ui| {
+                    ui.label("Provider");
+                    egui::ComboBox::from_id_salt("provider")
+                        .selected_text(draft.provider.as_str())
+                        .show_ui(ui, |ui| {
+                            for p in PROVIDERS {
+                                ui.selectable_value(&mut draft.provider, p.to_string(), *p);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Groq API key");
+                    ui.add(egui::TextEdit::singleline(&mut draft.api_key).password(true));
+                    ui.end_row();
+
+                    ui.label("Groq model");
+                    ui.text_edit_singleline(&mut draft.model);
+                    ui.end_row();
+
+                    ui.label("OpenAI API key");
+                    ui.add(egui::TextEdit::singleline(&mut draft.openai_api_key).password(true));
+                    ui.end_row();
+
+                    ui.label("Key storage");
+                    ui.checkbox(&mut draft.use_keyring, "System keyring (Secret Service)");
+                    ui.end_row();
+
+                    ui.label("OpenAI model");
+                    ui.text_edit_singleline(&mut draft.openai_model);
+                    ui.end_row();
+
+                    ui.label("Local model (candle)");
+                    ui.text_edit_singleline(&mut draft.candle_model);
+                    ui.end_row();
+
+                    ui.label("Local device");
+                    egui::ComboBox::from_id_salt("candle_device")
+                        .selected_text(draft.candle_device.as_str())
+                        .show_ui(ui, |ui| {
+                            for d in CANDLE_DEVICES {
+                                ui.selectable_value(&mut draft.candle_device, d.to_string(), *d);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Offline fallback");
+                    ui.checkbox(&mut draft.offline_fallback, "Use the local model when the server is unreachable");
+                    ui.end_row();
+
+                    ui.label("Language");
+                    ui.text_edit_singleline(&mut draft.language);
+                    ui.end_row();
+
+                    ui.label("Temperature");
+                    ui.add(egui::Slider::new(&mut draft.temperature, 0.0..=1.0));
+                    ui.end_row();
+
+                    ui.label("Parallel chunks");
+                    ui.add(egui::DragValue::new(&mut draft.chunk_concurrency).range(1..=8));
+                    ui.end_row();
+
+                    ui.label("Usage");
+                    ui.checkbox(&mut draft.usage_badge, "Show today's usage and cost here");
+                    ui.end_row();
+
+                    ui.label("Dashboard");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut draft.dashboard_enabled, "Enabled, port");
+                        ui.add_enabled(
+                            draft.dashboard_enabled,
+                            egui::DragValue::new(&mut draft.dashboard_port).range(1024..=65535),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label("Upload format");
+                    egui::ComboBox::from_id_salt("upload_format")
+                        .selected_text(draft.upload_format.as_str())
+                        .show_ui(ui, |ui| {
+                            for f in ["wav", "flac"] {
+                                ui.selectable_value(&mut draft.upload_format, f.to_string(), f);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Silence auto-stop (ms)");
+                    ui.add(egui::DragValue::new(&mut draft.vad_silence_ms).range(0..=10_000));
+                    ui.end_row();
+
+                    ui.label("Max recording (s, 0 = no limit)");
+                    ui.add(egui::DragValue::new(&mut draft.max_recording_secs).range(0..=3600));
+                    ui.end_row();
+
+                    ui.label("Pre-roll (ms, keeps mic open)");
+                    ui.add(egui::DragValue::new(&mut draft.preroll_ms).range(0..=3000));
+                    ui.end_row();
+
+                    ui.label("Noise suppression");
+                    ui.checkbox(&mut draft.denoise, "RNNoise");
+                    ui.end_row();
+
+                    ui.label("Media");
+                    ui.checkbox(&mut draft.pause_media, "Pause players while listening");
+                    ui.end_row();
+
+                    ui.label("Ducking");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut draft.duck_audio, "Lower other audio while Gemini speaks");
+                        ui.add_enabled(
+                            draft.duck_audio,
+                            egui::DragValue::new(&mut draft.duck_level).range(0..=100).suffix("%"),
+                        );
+                    });
+                    ui.end_row();
+
+                    ui.label("Gain");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut draft.normalize_audio, "Normalize uploads");
+                        ui.checkbox(&mut draft.live_agc, "Live AGC (Gemini)");
+                    });
+                    ui.end_row();
+
+                    ui.label("Target level (dBFS)");
+                    ui.add(egui::Slider::new(&mut draft.target_dbfs, -40.0..=-6.0));
+                    ui.end_row();
+
+                    ui.label("Meeting chunk (min)");
+                    ui.add(egui::DragValue::new(&mut draft.meeting_chunk_mins).range(1..=60));
+                    ui.end_row();
+
+                    ui.label("Meeting notes folder");
+                    ui.add(egui::TextEdit::singleline(&mut draft.meeting_dir).hint_text("~/Documents/maVoice"));
+                    ui.end_row();
+
+                    ui.label("Archive recordings");
+                    ui.checkbox(&mut draft.archive_recordings, "Save every recording as WAV");
+                    ui.end_row();
+
+                    ui.label("Archive folder");
+                    ui.add(egui::TextEdit::singleline(&mut draft.archive_dir).hint_text("~/.local/share/mavoice/recordings"));
+                    ui.end_row();
+
+                    ui.label("Keep recordings");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut draft.archive_max_days).range(0..=3650).suffix(" days"));
+                        ui.add(egui::DragValue::new(&mut draft.archive_max_mb).range(0..=1_000_000).suffix(" MB"));
+                    });
+                    ui.end_row();
+
+                    ui.label("Privacy mode");
+                    ui.checkbox(&mut draft.privacy_mode, "Store no history, recordings or transcripts");
+                    ui.end_row();
+
+                    ui.label("Live partials every (ms)");
+                    ui.add(egui::DragValue::new(&mut draft.partial_interval_ms).range(0..=10_000));
+                    ui.end_row();
+
+                    ui.label("Paste partials early");
+                    ui.checkbox(&mut draft.partial_inject, "Type words as soon as they stabilize");
+                    ui.end_row();
+
+                    ui.label("Spoken commands");
+                    ui.checkbox(&mut draft.spoken_commands, "\"period\", \"new line\", \"scratch that\", …");
+                    ui.end_row();
+
+                    ui.label("Fillers");
+                    ui.checkbox(&mut draft.remove_fillers, "Remove \"um\", \"uh\" and stutters");
+                    ui.end_row();
+
+                    ui.label("Numbers");
+                    ui.checkbox(&mut draft.format_numbers, "\"twenty five dollars\" → $25, dates and times");
+                    ui.end_row();
+
+                    ui.label("Smart spacing");
+                    ui.checkbox(&mut draft.smart_spacing, "Fit spaces and capitals to the text before the cursor");
+                    ui.end_row();
+
+                    ui.label("Screen reader");
+                    ui.checkbox(&mut draft.screen_reader, "Announce recording, results and errors (Orca)");
+                    ui.end_row();
+
+                    ui.label("Code dictation");
+                    ui.checkbox(&mut draft.code_dictation, "\"camel case foo bar\", \"open paren\", \"arrow\", …");
+                    ui.end_row();
+                });
+                match draft.dictionary {
+                    Dictionary::Global(ref mut prompt) => {
+                        ui.label("Dictionary (custom vocabulary prompt)");
+                        ui.add(egui::TextEdit::multiline(prompt).desired_rows(3));
+                    }
+                    // Edit the entry the current language would use
+                    Dictionary::PerLanguage(ref mut map) => {
+                        let key = if draft.language.is_empty() {
+                            "default".to_string()
+                        } else {
+                            draft.language.to_lowercase()
+                        };
+                        ui.label(format!("Dictionary for \"{}\" (custom vocabulary prompt)", key));
+                        ui.add(egui::TextEdit::multiline(map.entry(key).or_default()).desired_rows(3));
+                    }
+                }
+
+                ui.separator();
+                ui.heading("Hotkeys");
+                egui::Grid::new("hotkeys").num_columns(2).show(ui, |ui| {
+                    ui.label("Dictation");
+                    ui.text_edit_singleline(&mut draft.toggle_hotkey);
+                    ui.end_row();
+
+                    ui.label("Gemini Live");
+                    ui.text_edit_singleline(&mut draft.mode_hotkey);
+                    ui.end_row();
+
+                    ui.label("Next profile");
+                    ui.text_edit_singleline(&mut draft.preset_hotkey);
+                    ui.end_row();
+
+                    ui.label("Cancel dictation");
+                    ui.text_edit_singleline(&mut draft.cancel_hotkey);
+                    ui.end_row();
+
+                    ui.label("Paste last again");
+                    ui.text_edit_singleline(&mut draft.reinject_hotkey);
+                    ui.end_row();
+
+                    ui.label("Share screen with Gemini");
+                    ui.text_edit_singleline(&mut draft.screen_hotkey);
+                    ui.end_row();
+
+                    ui.label("Gemini push-to-talk");
+                    ui.text_edit_singleline(&mut draft.talk_hotkey);
+                    ui.end_row();
+
+                    ui.label("Privacy mode");
+                    ui.text_edit_singleline(&mut draft.privacy_hotkey);
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.heading("Mouse");
+                egui::Grid::new("gestures").num_columns(2).show(ui, |ui| {
+                    let gestures = [
+                        ("Click", "click", &mut draft.click_action),
+                        ("Double-click", "double_click", &mut draft.double_click_action),
+                        ("Middle-click", "middle_click", &mut draft.middle_click_action),
+                        ("Scroll up", "scroll_up", &mut draft.scroll_up_action),
+                        ("Scroll down", "scroll_down", &mut draft.scroll_down_action),
+                    ];
+                    for (label, id, action) in gestures {
+                        ui.label(label);
+                        egui::ComboBox::from_id_salt(id)
+                            .selected_text(action.as_str())
+                            .show_ui(ui, |ui| {
+                                for a in GESTURE_ACTIONS {
+                                    ui.selectable_value(action, a.to_string(), *a);
+                                }
+                            });
+                        ui.end_row();
+                    }
+
+                    ui.label("Double-click window (ms)");
+                    ui.add(egui::DragValue::new(&mut draft.double_click_ms).range(100..=1000));
+                    ui.end_row();
+
+                    ui.label("Click cooldown (ms)");
+                    ui.add(egui::DragValue::new(&mut draft.click_cooldown_ms).range(0..=2000));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.heading("Text injection");
+                egui::Grid::new("injection").num_columns(2).show(ui, |ui| {
+                    ui.label("Method");
+                    egui::ComboBox::from_id_salt("injection")
+                        .selected_text(draft.injection_method.as_str())
+                        .show_ui(ui, |ui| {
+                            for m in INJECTION_METHODS {
+                                ui.selectable_value(&mut draft.injection_method, m.to_string(), *m);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Key delay (ms, \"type\")");
+                    ui.add(egui::DragValue::new(&mut draft.type_delay_ms).range(0..=500));
+                    ui.end_row();
+
+                    ui.label("Primary selection");
+                    egui::ComboBox::from_id_salt("primary_selection")
+                        .selected_text(draft.primary_selection.as_str())
+                        .show_ui(ui, |ui| {
+                            for m in PRIMARY_SELECTION {
+                                ui.selectable_value(&mut draft.primary_selection, m.to_string(), *m);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Overlay");
+                    ui.checkbox(&mut draft.overlay_text, "Show transcript and status text");
+                    ui.end_row();
+
+                    ui.label("Typed text bubble (s, 0 = off)");
+                    ui.add(egui::DragValue::new(&mut draft.injection_toast_secs).range(0..=30));
+                    ui.end_row();
+
+                    ui.label("Accessibility");
+                    ui.vertical(|ui| {
+                        ui.checkbox(&mut draft.reduced_motion, "Reduced motion (still indicator)");
+                        ui.checkbox(&mut draft.high_contrast, "High-contrast colors");
+                    });
+                    ui.end_row();
+
+                    ui.label("Max FPS (0 = unlimited)");
+                    ui.add(egui::DragValue::new(&mut draft.max_fps).range(0..=240));
+                    ui.end_row();
+
+                    ui.label("Auto-hide after (s, 0 = off)");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut draft.overlay_autohide_secs).range(0..=3600));
+                        egui::ComboBox::from_id_salt("autohide_mode")
+                            .selected_text(draft.overlay_autohide_mode.as_str())
+                            .show_ui(ui, |ui| {
+                                for m in ["shrink", "hide"] {
+                                    ui.selectable_value(&mut draft.overlay_autohide_mode, m.to_string(), m);
+                                }
+                            });
+                    });
+                    ui.end_row();
+
+                    ui.label("Strip shader");
+                    ui.add(egui::TextEdit::singleline(&mut draft.user_shader).hint_text("built-in"));
+                    ui.end_row();
+
+                    ui.label("Orb shader");
+                    ui.add(egui::TextEdit::singleline(&mut draft.ai_shader).hint_text("built-in"));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.heading("Gemini Live");
+                egui::Grid::new("gemini").num_columns(2).show(ui, |ui| {
+                    ui.label("Gemini API key");
+                    ui.add(egui::TextEdit::singleline(&mut draft.gemini_api_key).password(true));
+                    ui.end_row();
+
+                    ui.label("Token endpoint");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.gemini_token_url)
+                            .hint_text("none (use the API key)"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Voice");
+                    egui::ComboBox::from_id_salt("voice")
+                        .selected_text(draft.voice_name.as_str())
+                        .show_ui(ui, |ui| {
+                            for v in VOICES {
+                                ui.selectable_value(&mut draft.voice_name, v.to_string(), *v);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Spoken replies");
+                    egui::ComboBox::from_id_salt("gemini_transcript")
+                        .selected_text(draft.gemini_transcript.as_str())
+                        .show_ui(ui, |ui| {
+                            for t in GEMINI_TRANSCRIPTS {
+                                ui.selectable_value(&mut draft.gemini_transcript, t.to_string(), *t);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Text replies");
+                    ui.checkbox(&mut draft.gemini_text_only, "Type replies instead of speaking");
+                    ui.end_row();
+
+                    ui.label("Push-to-talk");
+                    ui.checkbox(&mut draft.gemini_push_to_talk, "Hold the push-to-talk key while speaking");
+                    ui.end_row();
+
+                    ui.label("Local speech detection");
+                    ui.checkbox(&mut draft.gemini_local_vad, "Only stream audio around speech (Silero)");
+                    ui.end_row();
+
+                    ui.label("Local detection threshold");
+                    ui.add(egui::Slider::new(&mut draft.gemini_vad_threshold, 0.1..=0.9));
+                    ui.end_row();
+
+                    ui.label("Speech start sensitivity");
+                    egui::ComboBox::from_id_salt("start_sensitivity")
+                        .selected_text(draft.gemini_start_sensitivity.as_str())
+                        .show_ui(ui, |ui| {
+                            for s in SENSITIVITIES {
+                                ui.selectable_value(&mut draft.gemini_start_sensitivity, s.to_string(), *s);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Speech end sensitivity");
+                    egui::ComboBox::from_id_salt("end_sensitivity")
+                        .selected_text(draft.gemini_end_sensitivity.as_str())
+                        .show_ui(ui, |ui| {
+                            for s in SENSITIVITIES {
+                                ui.selectable_value(&mut draft.gemini_end_sensitivity, s.to_string(), *s);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("End of turn silence (ms)");
+                    ui.add(egui::DragValue::new(&mut draft.gemini_silence_ms).range(100..=5000));
+                    ui.end_row();
+                });
+                ui.label("System instruction");
+                ui.add(egui::TextEdit::multiline(&mut draft.system_instruction).desired_rows(4));
+
+                ui.separator();
+                ui.heading("Tools");
+                egui::Grid::new("tools").num_columns(2).show(ui, |ui| {
+                    ui.label("Ask before running");
+                    list_edit(ui, &mut draft.confirm_tools);
+                    ui.end_row();
+
+                    ui.label("Always allowed");
+                    list_edit(ui, &mut draft.always_allow_tools);
+                    ui.end_row();
+                });
+            });
+        });
+
+        action
+    }
+
+    /// Reject drafts that would leave the app in a broken state.
+    fn validate(draft: &Config) -> Result<(), String> {
+        parse_hotkey(&draft.toggle_hotkey)?;
+        parse_hotkey(&draft.mode_hotkey)?;
+        if draft.toggle_hotkey == draft.mode_hotkey {
+            return Err("Dictation and Gemini hotkeys must differ".to_string());
+        }
+        for spec in [
+            &draft.preset_hotkey,
+            &draft.cancel_hotkey,
+            &draft.reinject_hotkey,
+            &draft.screen_hotkey,
+            &draft.talk_hotkey,
+            &draft.privacy_hotkey,
+        ] {
+            if !spec.trim().is_empty() {
+                parse_hotkey(spec)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Edit a list of names as one comma-separated line.
+fn list_edit(ui: &mut egui::Ui, items: &mut Vec<String>) {
+    let mut text = items.join(", ");
+    if ui.text_edit_singleline(&mut text).changed() {
+        // Empty entries are kept while typing so a trailing comma survives the round trip.
+        *items = text.split(',').map(|t| t.trim().to_string()).collect();
+    }
+}
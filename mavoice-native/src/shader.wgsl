@@ -8,6 +8,9 @@ struct Uniforms {
     levels: vec4<f32>,
     color: vec3<f32>,
     mode: f32,
+    rec_secs: f32,
+    rec_limit: f32,
+    _pad: vec2<f32>,
 }
 
 @group(0) @binding(0) var<uniform> u: Uniforms;
@@ -80,6 +83,24 @@ fn user_processing(uv: vec2<f32>, w: f32, h: f32) -> f32 {
     return alpha;
 }
 
+// ── Elapsed-time bar — thin line along the bottom edge ───────────
+// Filled up to the time recorded, a faint track for what's left before
+// auto-stop, split into one-minute segments.
+fn elapsed_bar(uv: vec2<f32>, w: f32, h: f32) -> f32 {
+    if u.rec_limit <= 0.0 {
+        return 0.0;
+    }
+    let line = 1.0 - smoothstep(0.6, 1.4, abs(uv.y - (h - 3.0)));
+    let filled = uv.x <= w * clamp(u.rec_secs / u.rec_limit, 0.0, 1.0);
+    var alpha = select(0.10, 0.45, filled);
+
+    let seg = w * 60.0 / u.rec_limit;
+    if seg > 12.0 && (uv.x % seg) > seg - 2.0 {
+        alpha = 0.0;
+    }
+    return alpha * line;
+}
+
 // ── Composite ────────────────────────────────────────────────────
 
 @fragment
@@ -101,7 +122,8 @@ fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
     let bot_fade = smoothstep(0.0, edge_margin, h - uv.y);
     let edge = top_fade * bot_fade;
 
-    let alpha = clamp(user_alpha * edge, 0.0, 1.0);
+    let bar = elapsed_bar(uv, w, h) * min(u.intensity * 1.5, 1.0);
+    let alpha = clamp(max(user_alpha * edge, bar), 0.0, 1.0);
 
     // Apply sRGB gamma then premultiply (X11 ARGB compositing)
     let srgb = pow(clamp(u.color, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / 2.2));
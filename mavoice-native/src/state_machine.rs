@@ -4,18 +4,54 @@ pub enum OverlayState {
     Idle,
     Recording,   // Mode A: buffering for Groq
     Processing,  // Mode A: waiting for Groq API
+    Retrying,    // Mode A: request failed, waiting to retry
     Done,        // Mode A: transcription complete
     Listening,   // Mode B: streaming to Gemini, user speaking
     AISpeaking,  // Mode B: Gemini responding with audio
+    Meeting,     // Long-form meeting recording, transcribed in chunks
+    Error,       // Something failed; red flash, then back to Idle
 }
 
 /// Color palette
 const COLOR_IDLE: [f32; 3] = [0.0, 0.0, 0.0];
 const COLOR_RECORDING: [f32; 3] = [1.0, 0.51, 0.24];    // warm amber
 const COLOR_PROCESSING: [f32; 3] = [0.9, 0.76, 0.31];   // golden
+const COLOR_LIMIT: [f32; 3] = [0.93, 0.2, 0.2];         // red, recording near its cap
+const COLOR_RETRYING: [f32; 3] = [0.94, 0.42, 0.35];    // coral
 const COLOR_DONE: [f32; 3] = [0.31, 0.86, 0.51];        // emerald
 const COLOR_LISTENING: [f32; 3] = [0.024, 0.714, 0.831]; // cyan #06B6D4
+const COLOR_MEETING: [f32; 3] = [0.66, 0.42, 0.95];      // violet
 const COLOR_AI_SPEAKING: [f32; 3] = [0.337, 0.467, 0.969]; // soft blue #5677F7
+const COLOR_ERROR: [f32; 3] = [0.96, 0.16, 0.22];       // signal red
+const COLOR_PRIVATE: [f32; 3] = [0.95, 0.45, 0.62];     // rose, mic live in privacy mode
+
+/// High-contrast palette (linear RGB): saturated, far apart in hue, and at
+/// least 4.5:1 against the black plate drawn behind it (WCAG AA), most
+/// above 7:1.
+const HC_RECORDING: [f32; 3] = [1.0, 0.45, 0.0];   // orange   11.6:1
+const HC_PROCESSING: [f32; 3] = [1.0, 1.0, 0.0];   // yellow   19.6:1
+const HC_LIMIT: [f32; 3] = [1.0, 0.0, 0.0];        // red       5.3:1
+const HC_RETRYING: [f32; 3] = [1.0, 0.35, 0.35];   // pink     10.8:1
+const HC_DONE: [f32; 3] = [0.0, 1.0, 0.2];         // green    15.6:1
+const HC_LISTENING: [f32; 3] = [0.0, 1.0, 1.0];    // cyan     16.7:1
+const HC_MEETING: [f32; 3] = [1.0, 0.2, 1.0];      // magenta   9.6:1
+const HC_AI_SPEAKING: [f32; 3] = [0.4, 0.6, 1.0];  // sky      12.7:1
+const HC_ERROR: [f32; 3] = HC_LIMIT;
+const HC_PRIVATE: [f32; 3] = [1.0, 1.0, 1.0];      // white    21.0:1
+
+/// How long the Error state holds before returning to Idle
+const ERROR_HOLD_SECS: f32 = 2.5;
+
+/// How long a status message stays on the strip
+const STATUS_SECS: f32 = 3.0;
+/// How long Gemini's text stays up after its last update
+const AI_TEXT_SECS: f32 = 6.0;
+
+/// Opacity for a label shown `age` seconds ago for `hold` seconds, fading
+/// out over the last half second.
+fn label_fade(age: f32, hold: f32) -> f32 {
+    ((hold - age) / 0.5).clamp(0.0, 1.0)
+}
 
 impl OverlayState {
     /// User waveform color (bottom line)
@@ -24,9 +60,26 @@ impl OverlayState {
             OverlayState::Idle => COLOR_IDLE,
             OverlayState::Recording => COLOR_RECORDING,
             OverlayState::Processing => COLOR_PROCESSING,
+            OverlayState::Retrying => COLOR_RETRYING,
             OverlayState::Done => COLOR_DONE,
             OverlayState::Listening => COLOR_LISTENING,
             OverlayState::AISpeaking => COLOR_LISTENING, // stays cyan when AI responds
+            OverlayState::Meeting => COLOR_MEETING,
+            OverlayState::Error => COLOR_ERROR,
+        }
+    }
+
+    /// User waveform color in high-contrast mode
+    pub fn high_contrast_color(&self) -> [f32; 3] {
+        match self {
+            OverlayState::Idle => COLOR_IDLE,
+            OverlayState::Recording => HC_RECORDING,
+            OverlayState::Processing => HC_PROCESSING,
+            OverlayState::Retrying => HC_RETRYING,
+            OverlayState::Done => HC_DONE,
+            OverlayState::Listening | OverlayState::AISpeaking => HC_LISTENING,
+            OverlayState::Meeting => HC_MEETING,
+            OverlayState::Error => HC_ERROR,
         }
     }
 }
@@ -40,12 +93,30 @@ pub struct VisualState {
     pub intensity: f32,
     pub color: [f32; 3],
     pub mode: f32, // 0.0 = waveform, 1.0 = processing
+    /// 0.0..1.0 as a recording approaches its maximum length
+    pub limit_warning: f32,
     // AI channel (top bubble)
     pub ai_levels: [f32; 4],
     pub ai_intensity: f32,
     pub ai_color: [f32; 3],
+    /// Use the high-contrast palette (`high_contrast`)
+    pub high_contrast: bool,
+    /// Privacy mode: the live states get their own color
+    pub private: bool,
     // Timing
     pub done_start: Option<std::time::Instant>,
+    pub error_start: Option<std::time::Instant>,
+    /// What went wrong, while in the Error state
+    pub error_message: Option<String>,
+    /// Short-lived message for the user strip ("No API key", "Connecting...")
+    status: Option<(String, std::time::Instant)>,
+    /// Gemini's text for the current or last turn
+    ai_text: String,
+    ai_text_at: Option<std::time::Instant>,
+    /// The next text chunk starts a new turn
+    ai_turn_done: bool,
+    /// Transcript just typed, when it was shown and for how many seconds
+    toast: Option<(String, std::time::Instant, f32)>,
 }
 
 impl VisualState {
@@ -56,10 +127,106 @@ impl VisualState {
             intensity: 0.0,
             color: COLOR_IDLE,
             mode: 0.0,
+            limit_warning: 0.0,
             ai_levels: [0.0; 4],
             ai_intensity: 0.0,
             ai_color: COLOR_AI_SPEAKING,
+            high_contrast: false,
+            private: false,
             done_start: None,
+            error_start: None,
+            error_message: None,
+            status: None,
+            ai_text: String::new(),
+            ai_text_at: None,
+            ai_turn_done: false,
+            toast: None,
+        }
+    }
+
+    /// Show a status message on the user strip for a few seconds.
+    pub fn show_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), std::time::Instant::now()));
+    }
+
+    /// Show the transcript that was just typed in the toast bubble for
+    /// `secs` seconds.
+    pub fn show_toast(&mut self, text: impl Into<String>, secs: f32) {
+        self.toast = Some((text.into(), std::time::Instant::now(), secs));
+    }
+
+    /// The toast's text and opacity, while it's up.
+    pub fn toast_label(&self) -> Option<(String, f32)> {
+        let (text, at, secs) = self.toast.as_ref()?;
+        let alpha = label_fade(at.elapsed().as_secs_f32(), *secs);
+        (alpha > 0.0).then(|| (text.clone(), alpha))
+    }
+
+    /// Append a chunk of Gemini's text response.
+    pub fn push_ai_text(&mut self, chunk: &str) {
+        if self.ai_turn_done {
+            self.ai_text.clear();
+            self.ai_turn_done = false;
+        }
+        self.ai_text.push_str(chunk);
+        self.ai_text_at = Some(std::time::Instant::now());
+    }
+
+    /// Gemini finished its turn; its text lingers, then the next turn replaces it.
+    pub fn end_ai_turn(&mut self) {
+        self.ai_turn_done = true;
+    }
+
+    /// Text for the user strip and its opacity: the error, the transcript
+    /// just pasted (unless the toast shows it), or a status message.
+    pub fn user_label(&self, last_transcript: &str) -> Option<(String, f32)> {
+        match self.state {
+            OverlayState::Error => {
+                let text = self.error_message.clone()?;
+                Some((text, (self.error_fade() * 3.0).min(1.0)))
+            }
+            OverlayState::Done if !last_transcript.is_empty() && self.toast_label().is_none() => {
+                let age = self.done_start?.elapsed().as_secs_f32();
+                Some((last_transcript.to_string(), label_fade(age, 2.0)))
+            }
+            OverlayState::Retrying => Some(("Retrying...".to_string(), 1.0)),
+            _ => {
+                let (text, at) = self.status.as_ref()?;
+                let alpha = label_fade(at.elapsed().as_secs_f32(), STATUS_SECS);
+                (alpha > 0.0).then(|| (text.clone(), alpha))
+            }
+        }
+    }
+
+    /// Gemini's latest text and its opacity.
+    pub fn ai_label(&self) -> Option<(String, f32)> {
+        if self.ai_text.is_empty() {
+            return None;
+        }
+        let age = self.ai_text_at?.elapsed().as_secs_f32();
+        let alpha = label_fade(age, AI_TEXT_SECS);
+        (alpha > 0.0).then(|| (self.ai_text.clone(), alpha))
+    }
+
+    /// A label is still on screen, so frames must keep coming to fade it.
+    pub fn label_active(&self) -> bool {
+        let status = self
+            .status
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed().as_secs_f32() < STATUS_SECS);
+        status || self.ai_label().is_some() || self.toast_label().is_some()
+    }
+
+    /// The color `state` is drawn in, in the current palette.
+    pub fn state_color(&self, state: OverlayState) -> [f32; 3] {
+        let live = matches!(state, OverlayState::Recording | OverlayState::Listening | OverlayState::AISpeaking);
+        if self.private && live {
+            return if self.high_contrast { HC_PRIVATE } else { COLOR_PRIVATE };
+        }
+        if self.high_contrast {
+            state.high_contrast_color()
+        } else {
+            state.user_color()
         }
     }
 
@@ -70,9 +237,31 @@ impl VisualState {
         }
         log::debug!("State: {:?} -> {:?}", self.state, new_state);
         self.state = new_state;
+        self.limit_warning = 0.0;
         if new_state == OverlayState::Done {
             self.done_start = Some(std::time::Instant::now());
         }
+        if new_state == OverlayState::Error {
+            self.error_start = Some(std::time::Instant::now());
+        } else {
+            self.error_start = None;
+            self.error_message = None;
+        }
+    }
+
+    /// Flash the Error state with a short description of the failure.
+    pub fn show_error(&mut self, message: impl Into<String>) {
+        self.set_state(OverlayState::Error);
+        // Restart the hold if another error arrives during it
+        self.error_start = Some(std::time::Instant::now());
+        self.error_message = Some(message.into());
+    }
+
+    /// 1.0 right after an error, fading to 0.0 over the hold.
+    fn error_fade(&self) -> f32 {
+        self.error_start.map_or(0.0, |start| {
+            (1.0 - start.elapsed().as_secs_f32() / ERROR_HOLD_SECS).max(0.0)
+        })
     }
 
     /// Per-frame update — returns true if a redraw is needed.
@@ -86,7 +275,7 @@ impl VisualState {
         // ── User channel (bottom waveform) ──
         let user_active = matches!(
             self.state,
-            OverlayState::Recording | OverlayState::Listening
+            OverlayState::Recording | OverlayState::Listening | OverlayState::Meeting
         );
 
         // Smooth user audio levels — fast attack, moderate decay
@@ -111,14 +300,18 @@ impl VisualState {
             self.intensity = 0.0;
         }
 
-        // Smooth user color
-        let tc = self.state.user_color();
+        // Smooth user color, shifting toward red as the recording limit nears
+        let base = self.state_color(self.state);
+        let limit = if self.high_contrast { HC_LIMIT } else { COLOR_LIMIT };
+        let w = self.limit_warning.clamp(0.0, 1.0);
         for i in 0..3 {
-            self.color[i] += (tc[i] - self.color[i]) * 0.08;
+            let tc = base[i] + (limit[i] - base[i]) * w;
+            self.color[i] += (tc - self.color[i]) * 0.08;
         }
 
         // Mode: 0.0 for waveform, 1.0 for processing orbs
-        let target_mode = if self.state == OverlayState::Processing { 1.0 } else { 0.0 };
+        let processing = matches!(self.state, OverlayState::Processing | OverlayState::Retrying);
+        let target_mode = if processing { 1.0 } else { 0.0 };
         self.mode += (target_mode - self.mode) * 0.15;
 
         // ── AI channel (top bubble) ──
@@ -147,8 +340,9 @@ impl VisualState {
         }
 
         // Smooth AI color (stays blue)
+        let ai_target = if self.high_contrast { HC_AI_SPEAKING } else { COLOR_AI_SPEAKING };
         for i in 0..3 {
-            self.ai_color[i] += (COLOR_AI_SPEAKING[i] - self.ai_color[i]) * 0.08;
+            self.ai_color[i] += (ai_target[i] - self.ai_color[i]) * 0.08;
         }
 
         // ── Done state auto-reset ──
@@ -161,6 +355,11 @@ impl VisualState {
             }
         }
 
+        // ── Error state auto-reset ──
+        if self.state == OverlayState::Error && self.error_fade() <= 0.0 {
+            self.set_state(OverlayState::Idle);
+        }
+
         // Also handle mode-switch flash (Listening/Recording with done_start)
         if matches!(self.state, OverlayState::Listening | OverlayState::Recording) {
             if let Some(start) = self.done_start {
@@ -185,11 +384,13 @@ impl VisualState {
             OverlayState::Recording | OverlayState::Listening => {
                 self.levels.map(|l| l.max(0.18))
             }
+            // Hours-long session: quieter floor so the strip isn't distracting
+            OverlayState::Meeting => self.levels.map(|l| l.max(0.08)),
             OverlayState::AISpeaking => {
                 // Dim user waveform to subtle breathing
                 self.levels.map(|l| l.max(0.05))
             }
-            OverlayState::Processing => [0.0; 4],
+            OverlayState::Processing | OverlayState::Retrying => [0.0; 4],
             OverlayState::Done => {
                 if let Some(start) = self.done_start {
                     let fade = (1.0 - start.elapsed().as_secs_f32() / 1.5).max(0.0);
@@ -198,6 +399,11 @@ impl VisualState {
                     [0.0; 4]
                 }
             }
+            // Jagged, decaying line
+            OverlayState::Error => {
+                let fade = self.error_fade();
+                [0.55 * fade, 0.2 * fade, 0.7 * fade, 0.35 * fade]
+            }
             OverlayState::Idle => [0.0; 4],
         }
     }
@@ -220,6 +426,7 @@ impl VisualState {
     pub fn effective_intensity(&self) -> f32 {
         match self.state {
             OverlayState::Recording | OverlayState::Listening => self.intensity * 0.85,
+            OverlayState::Meeting => self.intensity * 0.6,
             OverlayState::AISpeaking => self.intensity * 0.3, // dim user while AI speaks
             OverlayState::Done => {
                 if let Some(start) = self.done_start {
@@ -229,6 +436,12 @@ impl VisualState {
                     0.0
                 }
             }
+            // Bright flash that settles into a fading hold
+            OverlayState::Error => {
+                let fade = self.error_fade();
+                let flash = ((fade - 0.85) / 0.15).max(0.0);
+                self.intensity * (0.5 * fade + 0.5 * flash)
+            }
             _ => self.intensity,
         }
     }
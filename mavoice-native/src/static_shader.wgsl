@@ -0,0 +1,44 @@
+// maVoice static shader — reduced motion replacement for both visualizers.
+// A solid pill (strip) or dot (orb) in the state color that fades in and
+// out with intensity but never moves. Reads only the fields the user and
+// AI uniform blocks share, so it binds to either.
+
+struct Uniforms {
+    resolution: vec2<f32>,
+    time: f32,
+    intensity: f32,
+    levels: vec4<f32>,
+    color: vec3<f32>,
+    _pad: f32,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(i32(vi & 1u)) * 2.0 - 1.0;
+    let y = f32(i32(vi >> 1u)) * 2.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+// Distance to a horizontal capsule centered on the origin
+fn capsule(p: vec2<f32>, half_len: f32, r: f32) -> f32 {
+    let q = vec2<f32>(max(abs(p.x) - half_len, 0.0), p.y);
+    return length(q) - r;
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let w = u.resolution.x;
+    let h = u.resolution.y;
+    let center = vec2<f32>(w * 0.5, h * 0.55);
+
+    // Wide windows (the strip) get a bar, square-ish ones (the orb) a dot
+    let r = min(h * 0.15, 30.0);
+    let half_len = max(w * 0.5 - h, 0.0) * 0.3;
+    let d = capsule(frag_coord.xy - center, half_len, r);
+
+    let alpha = clamp(0.5 - d, 0.0, 1.0) * clamp(u.intensity * 1.5, 0.0, 1.0);
+    let srgb = pow(clamp(u.color, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / 2.2));
+    return vec4<f32>(srgb * alpha, alpha);
+}
@@ -0,0 +1,387 @@
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Structure, Value};
+use zbus::{Connection, MatchRule, MessageStream};
+
+/// Where every AT-SPI application keeps its root object
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const REGISTRY: &str = "org.a11y.atspi.Registry";
+/// `ATSPI_ROLE_APPLICATION`
+const ROLE_APPLICATION: u32 = 75;
+
+/// Connect to the accessibility bus, which is separate from the session bus.
+async fn connect_bus() -> Result<Connection, String> {
+    let session = Connection::session()
+        .await
+        .map_err(|e| format!("No D-Bus session: {}", e))?;
+    let address: String = session
+        .call_method(
+            Some("org.a11y.Bus"),
+            "/org/a11y/bus",
+            Some("org.a11y.Bus"),
+            "GetAddress",
+            &(),
+        )
+        .await
+        .and_then(|reply| reply.body().deserialize())
+        .map_err(|e| format!("Accessibility bus unavailable: {}", e))?;
+    zbus::connection::Builder::address(address.as_str())
+        .map_err(|e| format!("Bad accessibility bus address {}: {}", address, e))?
+        .build()
+        .await
+        .map_err(|e| format!("Cannot connect to the accessibility bus: {}", e))
+}
+
+/// An accessible object: its application's bus name and object path.
+#[derive(Debug, Clone)]
+struct Accessible {
+    bus_name: String,
+    path: OwnedObjectPath,
+}
+
+/// Follows keyboard focus over AT-SPI so the text before the caret of the
+/// focused field can be read when dictation is pasted, or dictation
+/// inserted at the caret directly.
+///
+/// AT-SPI has no "what has focus" call; the focused object is whatever last
+/// reported `object:state-changed:focused`. Applications that don't expose
+/// accessibility (some terminals, Qt apps without
+/// `QT_LINUX_ACCESSIBILITY_ALWAYS_ON`) simply yield no context.
+pub struct CursorContext {
+    conn: Connection,
+    focused: Arc<Mutex<Option<Accessible>>>,
+}
+
+impl CursorContext {
+    /// Connect to the accessibility bus and start following focus changes.
+    pub async fn connect() -> Result<Self, String> {
+        let conn = connect_bus().await?;
+
+        // Applications only emit events someone registered for
+        let registered = conn
+            .call_method(
+                Some("org.a11y.atspi.Registry"),
+                "/org/a11y/atspi/registry",
+                Some("org.a11y.atspi.Registry"),
+                "RegisterEvent",
+                &("object:state-changed:focused",),
+            )
+            .await;
+        if let Err(e) = registered {
+            log::debug!("[AT-SPI] RegisterEvent failed: {}", e);
+        }
+
+        let rule = MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .interface("org.a11y.atspi.Event.Object")
+            .and_then(|b| b.member("StateChanged"))
+            .map_err(|e| format!("Bad match rule: {}", e))?
+            .build();
+        let mut stream = MessageStream::for_match_rule(rule, &conn, None)
+            .await
+            .map_err(|e| format!("Cannot follow focus: {}", e))?;
+
+        let focused = Arc::new(Mutex::new(None));
+        let tracked = focused.clone();
+        tokio::spawn(async move {
+            while let Some(Ok(msg)) = stream.next().await {
+                let header = msg.header();
+                let (Some(sender), Some(path)) = (header.sender(), header.path()) else {
+                    continue;
+                };
+                let body = msg.body();
+                let Ok(event) = body.deserialize::<Structure>() else {
+                    continue;
+                };
+                // (kind, detail1, detail2, any_data, ...): detail1 is 1 on
+                // gaining the state and 0 on losing it
+                let (Some(Value::Str(kind)), Some(Value::I32(gained))) =
+                    (event.fields().first(), event.fields().get(1))
+                else {
+                    continue;
+                };
+                if kind.as_str() != "focused" {
+                    continue;
+                }
+                let accessible = Accessible {
+                    bus_name: sender.to_string(),
+                    path: path.clone().into(),
+                };
+                let mut focused = tracked.lock().unwrap();
+                if *gained == 1 {
+                    *focused = Some(accessible);
+                } else if focused
+                    .as_ref()
+                    .is_some_and(|f| f.bus_name == accessible.bus_name && f.path == accessible.path)
+                {
+                    *focused = None;
+                }
+            }
+            log::info!("[AT-SPI] Focus stream ended");
+        });
+
+        log::info!("[AT-SPI] Following focus for cursor context");
+        Ok(Self { conn, focused })
+    }
+
+    /// Up to `n` characters before the caret in the focused field, or
+    /// `None` if nothing focused exposes the Text interface.
+    pub async fn text_before_caret(&self, n: i32) -> Option<String> {
+        let target = self.focused.lock().unwrap().clone()?;
+        let caret = self.caret(&target).await?;
+        self.conn
+            .call_method(
+                Some(target.bus_name.as_str()),
+                target.path.as_str(),
+                Some("org.a11y.atspi.Text"),
+                "GetText",
+                &((caret - n).max(0), caret),
+            )
+            .await
+            .and_then(|reply| reply.body().deserialize::<String>())
+            .map_err(|e| log::debug!("[AT-SPI] Cannot read text: {}", e))
+            .ok()
+    }
+
+    /// Insert `text` at the caret of the focused field through its
+    /// EditableText interface, without keys or the clipboard.
+    pub async fn insert_at_caret(&self, text: &str) -> Result<(), String> {
+        let target = self
+            .focused
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("No accessible text field has focus")?;
+        let caret = self.caret(&target).await.ok_or("The focused field has no caret")?;
+        let length = i32::try_from(text.len()).map_err(|_| "Text too long".to_string())?;
+        let inserted: bool = self
+            .conn
+            .call_method(
+                Some(target.bus_name.as_str()),
+                target.path.as_str(),
+                Some("org.a11y.atspi.EditableText"),
+                "InsertText",
+                &(caret, text, length),
+            )
+            .await
+            .and_then(|reply| reply.body().deserialize())
+            .map_err(|e| format!("The focused field is not editable over AT-SPI: {}", e))?;
+        if !inserted {
+            return Err("The focused field refused the text".to_string());
+        }
+        log::info!("[AT-SPI] Inserted {} chars at the caret", text.chars().count());
+        Ok(())
+    }
+
+    /// Caret offset in `target`'s Text interface.
+    async fn caret(&self, target: &Accessible) -> Option<i32> {
+        let caret: OwnedValue = self
+            .conn
+            .call_method(
+                Some(target.bus_name.as_str()),
+                target.path.as_str(),
+                Some("org.freedesktop.DBus.Properties"),
+                "Get",
+                &("org.a11y.atspi.Text", "CaretOffset"),
+            )
+            .await
+            .and_then(|reply| reply.body().deserialize())
+            .map_err(|e| log::debug!("[AT-SPI] No caret: {}", e))
+            .ok()?;
+        i32::try_from(caret).ok().filter(|c| *c >= 0)
+    }
+}
+
+/// How urgently a screen reader should speak an announcement, as in
+/// ARIA live regions.
+#[derive(Debug, Clone, Copy)]
+pub enum Politeness {
+    /// After whatever it is saying now
+    Polite = 1,
+    /// Interrupting it (errors)
+    Assertive = 2,
+}
+
+/// Speaks state changes ("Recording started", errors) through the user's
+/// screen reader.
+///
+/// maVoice draws its overlay with wgpu and has no accessible widgets, so it
+/// registers a bare application object with the AT-SPI registry and sends
+/// `object:announcement` events from it. Orca 45+ reads those out like an
+/// ARIA live region, in the voice and at the rate the user set up.
+pub struct Announcer {
+    conn: Connection,
+}
+
+impl Announcer {
+    /// Connect to the accessibility bus and register as an application.
+    pub async fn connect() -> Result<Self, String> {
+        let conn = connect_bus().await?;
+        let bus_name = conn
+            .unique_name()
+            .map(|n| n.to_string())
+            .ok_or("No name on the accessibility bus")?;
+
+        let server = conn.object_server();
+        server
+            .at(ROOT_PATH, RootAccessible { bus_name: bus_name.clone() })
+            .await
+            .map_err(|e| format!("Cannot export the accessible root: {}", e))?;
+        server
+            .at(ROOT_PATH, ApplicationInfo { id: 0 })
+            .await
+            .map_err(|e| format!("Cannot export the application: {}", e))?;
+
+        // Screen readers only look at applications the registry knows
+        let root = ObjectPath::try_from(ROOT_PATH).map_err(|e| e.to_string())?;
+        let embedded = conn
+            .call_method(
+                Some(REGISTRY),
+                ROOT_PATH,
+                Some("org.a11y.atspi.Socket"),
+                "Embed",
+                &((bus_name.as_str(), root),),
+            )
+            .await;
+        if let Err(e) = embedded {
+            log::debug!("[AT-SPI] Embed failed: {}", e);
+        }
+
+        log::info!("[AT-SPI] Announcing state changes to screen readers");
+        Ok(Self { conn })
+    }
+
+    /// Have the screen reader speak `text`.
+    pub async fn announce(&self, text: &str, politeness: Politeness) -> Result<(), String> {
+        // (kind, detail1, detail2, any_data, properties) like every AT-SPI
+        // event; detail1 is the politeness
+        let properties: HashMap<&str, Value> = HashMap::new();
+        self.conn
+            .emit_signal(
+                None::<&str>,
+                ROOT_PATH,
+                "org.a11y.atspi.Event.Object",
+                "Announcement",
+                &("", politeness as i32, 0i32, Value::from(text), properties),
+            )
+            .await
+            .map_err(|e| format!("Announcement failed: {}", e))
+    }
+}
+
+/// The one accessible object maVoice has: its application root.
+struct RootAccessible {
+    bus_name: String,
+}
+
+fn registry_root() -> (String, OwnedObjectPath) {
+    (REGISTRY.to_string(), OwnedObjectPath::try_from(ROOT_PATH).unwrap())
+}
+
+#[zbus::interface(name = "org.a11y.atspi.Accessible")]
+impl RootAccessible {
+    #[zbus(property)]
+    fn name(&self) -> String {
+        "maVoice".to_string()
+    }
+
+    #[zbus(property)]
+    fn description(&self) -> String {
+        "Voice dictation overlay".to_string()
+    }
+
+    #[zbus(property)]
+    fn parent(&self) -> (String, OwnedObjectPath) {
+        registry_root()
+    }
+
+    #[zbus(property)]
+    fn child_count(&self) -> i32 {
+        0
+    }
+
+    #[zbus(property)]
+    fn locale(&self) -> String {
+        String::new()
+    }
+
+    #[zbus(property)]
+    fn accessible_id(&self) -> String {
+        String::new()
+    }
+
+    fn get_role(&self) -> u32 {
+        ROLE_APPLICATION
+    }
+
+    fn get_role_name(&self) -> String {
+        "application".to_string()
+    }
+
+    fn get_localized_role_name(&self) -> String {
+        "application".to_string()
+    }
+
+    /// No states set (the two 32-bit words of the state set)
+    fn get_state(&self) -> Vec<u32> {
+        vec![0, 0]
+    }
+
+    fn get_attributes(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+
+    fn get_application(&self) -> (String, OwnedObjectPath) {
+        (self.bus_name.clone(), OwnedObjectPath::try_from(ROOT_PATH).unwrap())
+    }
+
+    fn get_children(&self) -> Vec<(String, OwnedObjectPath)> {
+        Vec::new()
+    }
+
+    fn get_index_in_parent(&self) -> i32 {
+        -1
+    }
+
+    fn get_interfaces(&self) -> Vec<String> {
+        vec!["org.a11y.atspi.Accessible".to_string(), "org.a11y.atspi.Application".to_string()]
+    }
+}
+
+struct ApplicationInfo {
+    /// Assigned by the registry
+    id: i32,
+}
+
+#[zbus::interface(name = "org.a11y.atspi.Application")]
+impl ApplicationInfo {
+    #[zbus(property)]
+    fn toolkit_name(&self) -> String {
+        "maVoice".to_string()
+    }
+
+    #[zbus(property)]
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    #[zbus(property)]
+    fn atspi_version(&self) -> String {
+        "2.1".to_string()
+    }
+
+    #[zbus(property)]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[zbus(property)]
+    fn set_id(&mut self, id: i32) {
+        self.id = id;
+    }
+
+    fn get_locale(&self, _kind: u32) -> String {
+        std::env::var("LANG").unwrap_or_default()
+    }
+}
@@ -0,0 +1,53 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Editors write in bursts (truncate + write, or temp file + rename); wait
+/// for this much quiet before reporting a change.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Call `on_change` whenever `path` (config.toml, custom shaders) is modified. The parent directory is
+/// watched so atomic saves that replace the file are seen too. Dropping the
+/// returned watcher stops watching.
+pub fn watch(
+    path: &Path,
+    on_change: impl Fn() + Send + 'static,
+) -> Result<notify::RecommendedWatcher, String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent directory", path.display()))?;
+    let name = path.file_name().map(|n| n.to_os_string());
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else {
+            return;
+        };
+        let relevant = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) && event.paths.iter().any(|p| p.file_name() == name.as_deref());
+        if relevant {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| format!("File watcher failed: {}", e))?;
+
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Cannot watch {}: {}", dir.display(), e))?;
+
+    std::thread::Builder::new()
+        .name("file-watch".into())
+        .spawn(move || {
+            while rx.recv().is_ok() {
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+                on_change();
+            }
+        })
+        .map_err(|e| format!("File watcher thread failed: {}", e))?;
+
+    log::info!("Watching {} for changes", path.display());
+    Ok(watcher)
+}
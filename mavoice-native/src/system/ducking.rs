@@ -0,0 +1,153 @@
+use std::process::Command;
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+
+use serde_json::Value;
+
+enum DuckCommand {
+    /// Lower other streams to this percentage of their volume
+    Duck(u32),
+    Restore,
+}
+
+/// A stream that was turned down, with its per-channel volumes before.
+struct Ducked {
+    index: u64,
+    volumes: Vec<u64>,
+}
+
+/// Turns other PulseAudio/PipeWire streams down while Gemini speaks and
+/// back up after.
+///
+/// Uses `pactl` (pipewire-pulse works too) on a worker thread so the event
+/// loop never waits on the sound server. Only the streams it lowered are
+/// restored, to the exact volume they had; dropping the ducker restores
+/// them before it returns, so quitting mid-reply leaves no music quiet.
+pub struct AudioDucker {
+    tx: Option<Sender<DuckCommand>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl AudioDucker {
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("ducking".into())
+            .spawn(move || {
+                let mut ducked: Vec<Ducked> = Vec::new();
+                for cmd in rx {
+                    match cmd {
+                        DuckCommand::Duck(level) => {
+                            if ducked.is_empty() {
+                                ducked = duck_others(level);
+                            }
+                        }
+                        DuckCommand::Restore => {
+                            for stream in ducked.drain(..) {
+                                if let Err(e) = set_volume(stream.index, &stream.volumes) {
+                                    log::warn!("[Ducking] Failed to restore stream {}: {}", stream.index, e);
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn ducking thread");
+        Self {
+            tx: Some(tx),
+            worker: Some(worker),
+        }
+    }
+
+    /// Lower every other playing stream to `level` percent of its volume.
+    pub fn duck(&self, level: u32) {
+        self.send(DuckCommand::Duck(level));
+    }
+
+    pub fn restore(&self) {
+        self.send(DuckCommand::Restore);
+    }
+
+    fn send(&self, cmd: DuckCommand) {
+        if let Some(ref tx) = self.tx {
+            let _ = tx.send(cmd);
+        }
+    }
+}
+
+impl Drop for AudioDucker {
+    fn drop(&mut self) {
+        self.restore();
+        // Closing the channel ends the worker once it has restored
+        self.tx.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Lower every sink input not belonging to this process and return what
+/// to restore.
+fn duck_others(level: u32) -> Vec<Ducked> {
+    let inputs = match pactl(&["-f", "json", "list", "sink-inputs"]) {
+        Ok(out) => out,
+        Err(e) => {
+            log::warn!("[Ducking] Cannot list streams: {}", e);
+            return Vec::new();
+        }
+    };
+    let inputs: Vec<Value> = match serde_json::from_str(&inputs) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            log::warn!("[Ducking] Unexpected pactl output (needs pactl 16+): {}", e);
+            return Vec::new();
+        }
+    };
+
+    let own_pid = std::process::id().to_string();
+    let mut ducked = Vec::new();
+    for input in &inputs {
+        let Some(index) = input["index"].as_u64() else {
+            continue;
+        };
+        let pid = &input["properties"]["application.process.id"];
+        if pid.as_str() == Some(own_pid.as_str()) || input["corked"].as_bool() == Some(true) {
+            continue;
+        }
+        let volumes: Vec<u64> = input["volume"]
+            .as_object()
+            .map(|channels| channels.values().filter_map(|c| c["value"].as_u64()).collect())
+            .unwrap_or_default();
+        if volumes.is_empty() {
+            continue;
+        }
+        let lowered: Vec<u64> = volumes.iter().map(|v| v * u64::from(level.min(100)) / 100).collect();
+        match set_volume(index, &lowered) {
+            Ok(()) => ducked.push(Ducked { index, volumes }),
+            Err(e) => log::warn!("[Ducking] Failed to lower stream {}: {}", index, e),
+        }
+    }
+    if !ducked.is_empty() {
+        log::info!("[Ducking] Lowered {} stream(s) to {}%", ducked.len(), level);
+    }
+    ducked
+}
+
+fn set_volume(index: u64, volumes: &[u64]) -> Result<(), String> {
+    let index = index.to_string();
+    let volumes: Vec<String> = volumes.iter().map(|v| v.to_string()).collect();
+    let mut args = vec!["set-sink-input-volume", index.as_str()];
+    args.extend(volumes.iter().map(String::as_str));
+    pactl(&args).map(|_| ())
+}
+
+fn pactl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .output()
+        .map_err(|e| format!("pactl not available: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,224 @@
+use evdev::{Device, InputEventKind, Key};
+use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// Global hotkeys read straight from `/dev/input`, for Wayland compositors
+/// where global-hotkey's X11 grab never sees the keys.
+///
+/// Needs read access to the keyboard event devices (the `input` group). Keys
+/// are only observed, not grabbed, so the focused app still receives them.
+/// Keyboards plugged in after startup aren't picked up.
+pub struct EvdevHotkeys {
+    bindings: Arc<Mutex<Vec<HotKey>>>,
+    /// (hotkey id, pressed) from the reader threads
+    events: Receiver<(u32, bool)>,
+}
+
+impl EvdevHotkeys {
+    /// Start a reader thread for every keyboard that can be opened.
+    pub fn new() -> Result<Self, String> {
+        let bindings = Arc::new(Mutex::new(Vec::new()));
+        let (tx, events) = mpsc::channel();
+
+        let mut keyboards = 0;
+        for (path, device) in evdev::enumerate() {
+            if !is_keyboard(&device) {
+                continue;
+            }
+            log::info!(
+                "[evdev] Watching {} ({})",
+                device.name().unwrap_or("keyboard"),
+                path.display()
+            );
+            let bindings = bindings.clone();
+            let tx = tx.clone();
+            std::thread::Builder::new()
+                .name("evdev-hotkeys".into())
+                .spawn(move || read_keys(device, bindings, tx))
+                .map_err(|e| format!("Failed to spawn evdev reader: {}", e))?;
+            keyboards += 1;
+        }
+
+        if keyboards == 0 {
+            return Err(
+                "No readable keyboards in /dev/input (add your user to the 'input' group)".into(),
+            );
+        }
+        Ok(Self { bindings, events })
+    }
+
+    pub fn register(&self, hotkey: HotKey) -> Result<(), String> {
+        if evdev_key(hotkey.key).is_none() {
+            return Err(format!("{:?} isn't supported by the evdev backend", hotkey.key));
+        }
+        let mut bindings = self.bindings.lock().unwrap();
+        if !bindings.contains(&hotkey) {
+            bindings.push(hotkey);
+        }
+        Ok(())
+    }
+
+    pub fn unregister(&self, hotkey: HotKey) {
+        self.bindings.lock().unwrap().retain(|hk| *hk != hotkey);
+    }
+
+    /// Hotkey presses and releases since the last call, as (id, pressed).
+    pub fn drain(&self) -> Vec<(u32, bool)> {
+        self.events.try_iter().collect()
+    }
+}
+
+fn is_keyboard(device: &Device) -> bool {
+    device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(Key::KEY_A) && keys.contains(Key::KEY_ENTER))
+}
+
+fn read_keys(mut device: Device, bindings: Arc<Mutex<Vec<HotKey>>>, tx: Sender<(u32, bool)>) {
+    let mut mods = Modifiers::empty();
+    // Keys currently holding a hotkey down, with its id
+    let mut held: Vec<(Key, u32)> = Vec::new();
+    loop {
+        let events = match device.fetch_events() {
+            Ok(events) => events,
+            Err(e) => {
+                log::warn!("[evdev] Stopped reading {}: {}", device.name().unwrap_or("keyboard"), e);
+                return;
+            }
+        };
+        for event in events {
+            let InputEventKind::Key(key) = event.kind() else {
+                continue;
+            };
+            // 0 = release, 1 = press, 2 = autorepeat
+            let pressed = event.value() != 0;
+            if let Some(modifier) = modifier(key) {
+                mods.set(modifier, pressed);
+                continue;
+            }
+            match event.value() {
+                1 => {
+                    let fired = bindings
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .find(|hk| hk.mods == mods && evdev_key(hk.key) == Some(key))
+                        .map(HotKey::id);
+                    if let Some(id) = fired {
+                        held.push((key, id));
+                        if tx.send((id, true)).is_err() {
+                            return;
+                        }
+                    }
+                }
+                // Releases go to whatever this key pressed, even if the
+                // modifiers came up first
+                0 => {
+                    while let Some(i) = held.iter().position(|(k, _)| *k == key) {
+                        let (_, id) = held.swap_remove(i);
+                        if tx.send((id, false)).is_err() {
+                            return;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn modifier(key: Key) -> Option<Modifiers> {
+    match key {
+        Key::KEY_LEFTCTRL | Key::KEY_RIGHTCTRL => Some(Modifiers::CONTROL),
+        Key::KEY_LEFTSHIFT | Key::KEY_RIGHTSHIFT => Some(Modifiers::SHIFT),
+        Key::KEY_LEFTALT | Key::KEY_RIGHTALT => Some(Modifiers::ALT),
+        Key::KEY_LEFTMETA | Key::KEY_RIGHTMETA => Some(Modifiers::SUPER),
+        _ => None,
+    }
+}
+
+/// The evdev key for a hotkey's key code, by physical position (US layout).
+fn evdev_key(code: Code) -> Option<Key> {
+    let key = match code {
+        Code::KeyA => Key::KEY_A,
+        Code::KeyB => Key::KEY_B,
+        Code::KeyC => Key::KEY_C,
+        Code::KeyD => Key::KEY_D,
+        Code::KeyE => Key::KEY_E,
+        Code::KeyF => Key::KEY_F,
+        Code::KeyG => Key::KEY_G,
+        Code::KeyH => Key::KEY_H,
+        Code::KeyI => Key::KEY_I,
+        Code::KeyJ => Key::KEY_J,
+        Code::KeyK => Key::KEY_K,
+        Code::KeyL => Key::KEY_L,
+        Code::KeyM => Key::KEY_M,
+        Code::KeyN => Key::KEY_N,
+        Code::KeyO => Key::KEY_O,
+        Code::KeyP => Key::KEY_P,
+        Code::KeyQ => Key::KEY_Q,
+        Code::KeyR => Key::KEY_R,
+        Code::KeyS => Key::KEY_S,
+        Code::KeyT => Key::KEY_T,
+        Code::KeyU => Key::KEY_U,
+        Code::KeyV => Key::KEY_V,
+        Code::KeyW => Key::KEY_W,
+        Code::KeyX => Key::KEY_X,
+        Code::KeyY => Key::KEY_Y,
+        Code::KeyZ => Key::KEY_Z,
+        Code::Digit0 => Key::KEY_0,
+        Code::Digit1 => Key::KEY_1,
+        Code::Digit2 => Key::KEY_2,
+        Code::Digit3 => Key::KEY_3,
+        Code::Digit4 => Key::KEY_4,
+        Code::Digit5 => Key::KEY_5,
+        Code::Digit6 => Key::KEY_6,
+        Code::Digit7 => Key::KEY_7,
+        Code::Digit8 => Key::KEY_8,
+        Code::Digit9 => Key::KEY_9,
+        Code::F1 => Key::KEY_F1,
+        Code::F2 => Key::KEY_F2,
+        Code::F3 => Key::KEY_F3,
+        Code::F4 => Key::KEY_F4,
+        Code::F5 => Key::KEY_F5,
+        Code::F6 => Key::KEY_F6,
+        Code::F7 => Key::KEY_F7,
+        Code::F8 => Key::KEY_F8,
+        Code::F9 => Key::KEY_F9,
+        Code::F10 => Key::KEY_F10,
+        Code::F11 => Key::KEY_F11,
+        Code::F12 => Key::KEY_F12,
+        Code::Comma => Key::KEY_COMMA,
+        Code::Period => Key::KEY_DOT,
+        Code::Slash => Key::KEY_SLASH,
+        Code::Semicolon => Key::KEY_SEMICOLON,
+        Code::Quote => Key::KEY_APOSTROPHE,
+        Code::BracketLeft => Key::KEY_LEFTBRACE,
+        Code::BracketRight => Key::KEY_RIGHTBRACE,
+        Code::Backslash => Key::KEY_BACKSLASH,
+        Code::Minus => Key::KEY_MINUS,
+        Code::Equal => Key::KEY_EQUAL,
+        Code::Backquote => Key::KEY_GRAVE,
+        Code::Space => Key::KEY_SPACE,
+        Code::Enter => Key::KEY_ENTER,
+        Code::Tab => Key::KEY_TAB,
+        Code::Backspace => Key::KEY_BACKSPACE,
+        Code::Escape => Key::KEY_ESC,
+        Code::Insert => Key::KEY_INSERT,
+        Code::Delete => Key::KEY_DELETE,
+        Code::Home => Key::KEY_HOME,
+        Code::End => Key::KEY_END,
+        Code::PageUp => Key::KEY_PAGEUP,
+        Code::PageDown => Key::KEY_PAGEDOWN,
+        Code::ArrowUp => Key::KEY_UP,
+        Code::ArrowDown => Key::KEY_DOWN,
+        Code::ArrowLeft => Key::KEY_LEFT,
+        Code::ArrowRight => Key::KEY_RIGHT,
+        Code::PrintScreen => Key::KEY_SYSRQ,
+        Code::ScrollLock => Key::KEY_SCROLLLOCK,
+        Code::Pause => Key::KEY_PAUSE,
+        _ => return None,
+    };
+    Some(key)
+}
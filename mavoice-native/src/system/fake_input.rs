@@ -0,0 +1,60 @@
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_registry;
+use wayland_client::{delegate_noop, Connection, Dispatch, QueueHandle};
+use wayland_protocols_plasma::fake_input::client::org_kde_kwin_fake_input::OrgKdeKwinFakeInput;
+
+use super::virtual_keyboard::has_global;
+use super::ydotool::{KEY_LEFTCTRL, KEY_V};
+
+/// Presses keys through KWin's `org_kde_kwin_fake_input`, the protocol
+/// KDE Connect and krdp use on Plasma Wayland.
+///
+/// It only sends evdev keycodes on the user's layout, so text goes through
+/// the clipboard and is pasted with Ctrl+V, like the ydotool backend.
+/// KWin only offers the protocol to programs whose desktop file lists it
+/// in `X-KDE-Wayland-Interfaces`.
+pub struct FakeInput;
+
+struct State;
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(State: OrgKdeKwinFakeInput);
+
+impl FakeInput {
+    /// Whether KWin offers fake input to this process.
+    pub fn available() -> bool {
+        has_global("org_kde_kwin_fake_input")
+    }
+
+    /// Press Ctrl+V in the focused window.
+    pub fn paste() -> Result<(), String> {
+        let conn = Connection::connect_to_env().map_err(|e| format!("No Wayland display: {}", e))?;
+        let (globals, mut queue) =
+            registry_queue_init::<State>(&conn).map_err(|e| format!("Wayland registry: {}", e))?;
+        let qh = queue.handle();
+        // keyboard_key arrived in version 4
+        let input: OrgKdeKwinFakeInput = globals
+            .bind(&qh, 4..=4, ())
+            .map_err(|_| "KWin does not offer fake input to maVoice (see the README)".to_string())?;
+        input.authenticate("maVoice".to_string(), "Paste dictated text".to_string());
+
+        for (key, pressed) in [(KEY_LEFTCTRL, 1), (KEY_V, 1), (KEY_V, 0), (KEY_LEFTCTRL, 0)] {
+            input.keyboard_key(u32::from(key), pressed);
+        }
+        queue
+            .roundtrip(&mut State)
+            .map(|_| ())
+            .map_err(|e| format!("Wayland connection lost: {}", e))
+    }
+}
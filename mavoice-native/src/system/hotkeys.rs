@@ -1,52 +1,240 @@
-use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+use global_hotkey::hotkey::HotKey;
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager};
 
+use super::evdev_hotkeys::EvdevHotkeys;
+
 /// Result of polling hotkey events.
 pub struct HotkeyPoll {
     pub toggle_fired: bool,
     pub mode_switch_fired: bool,
+    pub preset_fired: bool,
+    pub cancel_fired: bool,
+    pub reinject_fired: bool,
+    pub screen_fired: bool,
+    pub privacy_fired: bool,
+    /// Push-to-talk key went down / came up
+    pub talk_pressed: bool,
+    pub talk_released: bool,
+}
+
+/// Where key presses come from.
+enum Backend {
+    /// X11 key grabs via global-hotkey
+    Global(GlobalHotKeyManager),
+    /// Raw keyboard events from /dev/input, for Wayland
+    Evdev(EvdevHotkeys),
+}
+
+impl Backend {
+    /// `kind` is "global", "evdev" or "auto" (evdev in a Wayland session).
+    /// An evdev backend that can't read any keyboard falls back to global.
+    fn open(kind: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let wayland = std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t == "wayland")
+            || std::env::var_os("WAYLAND_DISPLAY").is_some();
+        if kind == "evdev" || (kind == "auto" && wayland) {
+            match EvdevHotkeys::new() {
+                Ok(evdev) => {
+                    log::info!("Hotkey backend: evdev");
+                    return Ok(Backend::Evdev(evdev));
+                }
+                Err(e) => log::warn!("evdev hotkeys unavailable, using global-hotkey: {}", e),
+            }
+        }
+        log::info!("Hotkey backend: global-hotkey");
+        Ok(Backend::Global(GlobalHotKeyManager::new()?))
+    }
+
+    fn register(&self, hotkey: HotKey) -> Result<(), String> {
+        match self {
+            Backend::Global(m) => m.register(hotkey).map_err(|e| e.to_string()),
+            Backend::Evdev(e) => e.register(hotkey),
+        }
+    }
+
+    fn register_all(&self, hotkeys: &[HotKey]) -> Result<(), String> {
+        hotkeys.iter().try_for_each(|hk| self.register(*hk))
+    }
+
+    fn unregister(&self, hotkey: HotKey) {
+        match self {
+            Backend::Global(m) => {
+                let _ = m.unregister(hotkey);
+            }
+            Backend::Evdev(e) => e.unregister(hotkey),
+        }
+    }
+
+    fn unregister_all(&self, hotkeys: &[HotKey]) {
+        for hk in hotkeys {
+            self.unregister(*hk);
+        }
+    }
+
+    /// Hotkey events since the last call, as (id, pressed).
+    fn drain(&self) -> Vec<(u32, bool)> {
+        match self {
+            Backend::Global(_) => GlobalHotKeyEvent::receiver()
+                .try_iter()
+                .map(|e| (e.id, e.state == global_hotkey::HotKeyState::Pressed))
+                .collect(),
+            Backend::Evdev(e) => e.drain(),
+        }
+    }
 }
 
 pub struct HotkeyManager {
-    #[allow(dead_code)]
-    manager: GlobalHotKeyManager,
-    toggle_hotkey_id: u32,
-    mode_switch_hotkey_id: u32,
+    manager: Backend,
+    toggle: HotKey,
+    mode_switch: HotKey,
+    /// Optional hotkey cycling named presets
+    preset: Option<HotKey>,
+    /// Optional hotkey discarding the current dictation
+    cancel: Option<HotKey>,
+    /// Optional hotkey pasting the last transcript again
+    reinject: Option<HotKey>,
+    /// Optional hotkey sharing a screenshot with the Gemini session
+    screen: Option<HotKey>,
+    /// Optional hotkey toggling privacy mode
+    privacy: Option<HotKey>,
+    /// Optional push-to-talk key for Gemini sessions (held while speaking)
+    talk: Option<HotKey>,
+}
+
+/// Parse a hotkey string such as "ctrl+shift+Comma" (global-hotkey syntax).
+pub fn parse_hotkey(spec: &str) -> Result<HotKey, String> {
+    spec.parse::<HotKey>()
+        .map_err(|e| format!("Invalid hotkey '{}': {}", spec, e))
 }
 
 impl HotkeyManager {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let manager = GlobalHotKeyManager::new()?;
+    /// Register the toggle (Groq) and mode-switch (Gemini) hotkeys on the
+    /// given backend ("auto", "global" or "evdev").
+    pub fn new(
+        toggle_spec: &str,
+        mode_spec: &str,
+        backend: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let manager = Backend::open(backend)?;
 
-        // Ctrl+Shift+Comma — toggle recording
-        let toggle = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::SHIFT),
-            Code::Comma,
-        );
-        let toggle_id = toggle.id();
+        let toggle = parse_hotkey(toggle_spec)?;
         manager.register(toggle)?;
 
-        // Ctrl+Shift+Period — switch voice mode (Groq ↔ Gemini)
-        let mode_switch = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::SHIFT),
-            Code::Period,
-        );
-        let mode_switch_id = mode_switch.id();
+        let mode_switch = parse_hotkey(mode_spec)?;
         manager.register(mode_switch)?;
 
         log::info!(
-            "Global hotkeys: Ctrl+Shift+Comma (toggle={}), Ctrl+Shift+Period (mode={})",
-            toggle_id,
-            mode_switch_id
+            "Global hotkeys: {} (toggle={}), {} (mode={})",
+            toggle_spec,
+            toggle.id(),
+            mode_spec,
+            mode_switch.id()
         );
 
         Ok(Self {
             manager,
-            toggle_hotkey_id: toggle_id,
-            mode_switch_hotkey_id: mode_switch_id,
+            toggle,
+            mode_switch,
+            preset: None,
+            cancel: None,
+            reinject: None,
+            screen: None,
+            privacy: None,
+            talk: None,
         })
     }
 
+    /// Replace the registered hotkeys. On failure the previous bindings stay active.
+    pub fn rebind(&mut self, toggle: &str, mode_switch: &str) -> Result<(), String> {
+        let new_toggle = parse_hotkey(toggle)?;
+        let new_mode = parse_hotkey(mode_switch)?;
+        if new_toggle == self.toggle && new_mode == self.mode_switch {
+            return Ok(());
+        }
+
+        self.manager.unregister_all(&[self.toggle, self.mode_switch]);
+
+        if let Err(e) = self.manager.register_all(&[new_toggle, new_mode]) {
+            self.manager.unregister_all(&[new_toggle, new_mode]);
+            let _ = self.manager.register_all(&[self.toggle, self.mode_switch]);
+            return Err(format!("Failed to register hotkeys: {}", e));
+        }
+
+        log::info!("Global hotkeys rebound: {} / {}", toggle, mode_switch);
+        self.toggle = new_toggle;
+        self.mode_switch = new_mode;
+        Ok(())
+    }
+
+    /// Bind the preset-cycling hotkey; an empty spec removes it. On failure
+    /// the previous binding stays active.
+    pub fn set_preset_hotkey(&mut self, spec: &str) -> Result<(), String> {
+        self.preset = self.replace_optional(self.preset, spec, "Preset")?;
+        Ok(())
+    }
+
+    /// Bind the cancel hotkey; an empty spec removes it. On failure the
+    /// previous binding stays active.
+    pub fn set_cancel_hotkey(&mut self, spec: &str) -> Result<(), String> {
+        self.cancel = self.replace_optional(self.cancel, spec, "Cancel")?;
+        Ok(())
+    }
+
+    /// Bind the hotkey that pastes the last transcript again; an empty spec
+    /// removes it. On failure the previous binding stays active.
+    pub fn set_reinject_hotkey(&mut self, spec: &str) -> Result<(), String> {
+        self.reinject = self.replace_optional(self.reinject, spec, "Re-paste")?;
+        Ok(())
+    }
+
+    /// Bind the hotkey sending a screenshot to Gemini; an empty spec removes
+    /// it. On failure the previous binding stays active.
+    pub fn set_screen_hotkey(&mut self, spec: &str) -> Result<(), String> {
+        self.screen = self.replace_optional(self.screen, spec, "Screen share")?;
+        Ok(())
+    }
+
+    /// Bind the hotkey toggling privacy mode; an empty spec removes it. On
+    /// failure the previous binding stays active.
+    pub fn set_privacy_hotkey(&mut self, spec: &str) -> Result<(), String> {
+        self.privacy = self.replace_optional(self.privacy, spec, "Privacy")?;
+        Ok(())
+    }
+
+    /// Bind the Gemini push-to-talk key; an empty spec removes it. On
+    /// failure the previous binding stays active.
+    pub fn set_talk_hotkey(&mut self, spec: &str) -> Result<(), String> {
+        self.talk = self.replace_optional(self.talk, spec, "Push-to-talk")?;
+        Ok(())
+    }
+
+    /// Swap `current` for the hotkey in `spec` (none if empty), returning
+    /// the new binding.
+    fn replace_optional(
+        &self,
+        current: Option<HotKey>,
+        spec: &str,
+        what: &str,
+    ) -> Result<Option<HotKey>, String> {
+        let new = if spec.trim().is_empty() {
+            None
+        } else {
+            Some(parse_hotkey(spec)?)
+        };
+        if new == current {
+            return Ok(current);
+        }
+        if let Some(hk) = new {
+            self.manager
+                .register(hk)
+                .map_err(|e| format!("Failed to register {} hotkey: {}", what.to_lowercase(), e))?;
+        }
+        if let Some(old) = current {
+            self.manager.unregister(old);
+        }
+        log::info!("{} hotkey: {}", what, if spec.is_empty() { "none" } else { spec });
+        Ok(new)
+    }
+
     /// Check for pending hotkey events. Returns true if toggle was pressed.
     /// Drains all events but only fires on Pressed (ignores Released).
     pub fn poll_toggle(&self) -> bool {
@@ -57,25 +245,50 @@ impl HotkeyManager {
     pub fn poll(&self) -> HotkeyPoll {
         let mut toggle_fired = false;
         let mut mode_switch_fired = false;
+        let mut preset_fired = false;
+        let mut cancel_fired = false;
+        let mut reinject_fired = false;
+        let mut screen_fired = false;
+        let mut privacy_fired = false;
+        let mut talk_pressed = false;
+        let mut talk_released = false;
 
-        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
-            if event.state != global_hotkey::HotKeyState::Pressed {
+        for (id, pressed) in self.manager.drain() {
+            let is_talk = self.talk.is_some_and(|hk| id == hk.id());
+            if !pressed {
+                // Only push-to-talk cares about releases
+                talk_released |= is_talk;
                 continue;
             }
-            if event.id == self.toggle_hotkey_id {
+            if is_talk {
+                talk_pressed = true;
+            } else if id == self.toggle.id() {
                 toggle_fired = true;
-            } else if event.id == self.mode_switch_hotkey_id {
+            } else if id == self.mode_switch.id() {
                 mode_switch_fired = true;
+            } else if self.preset.is_some_and(|hk| id == hk.id()) {
+                preset_fired = true;
+            } else if self.cancel.is_some_and(|hk| id == hk.id()) {
+                cancel_fired = true;
+            } else if self.reinject.is_some_and(|hk| id == hk.id()) {
+                reinject_fired = true;
+            } else if self.screen.is_some_and(|hk| id == hk.id()) {
+                screen_fired = true;
+            } else if self.privacy.is_some_and(|hk| id == hk.id()) {
+                privacy_fired = true;
             }
         }
 
         HotkeyPoll {
             toggle_fired,
             mode_switch_fired,
+            preset_fired,
+            cancel_fired,
+            reinject_fired,
+            screen_fired,
+            privacy_fired,
+            talk_pressed,
+            talk_released,
         }
     }
-
-    pub fn _manager(&self) -> &GlobalHotKeyManager {
-        &self.manager
-    }
 }
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::sync::mpsc as std_mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+use zbus::zvariant::{OwnedObjectPath, StructureBuilder, Value};
+use zbus::Connection;
+
+const ENGINE_NAME: &str = "mavoice";
+const COMPONENT_NAME: &str = "org.freedesktop.IBus.Mavoice";
+const IBUS_SERVICE: &str = "org.freedesktop.IBus";
+const IBUS_PATH: &str = "/org/freedesktop/IBus";
+const FACTORY_PATH: &str = "/org/freedesktop/IBus/Factory";
+const ENGINE_PATH: &str = "/org/freedesktop/IBus/Engine/mavoice";
+
+/// How long the daemon gets to answer while registering
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long IBus gets to switch to the engine and focus it
+const SWITCH_TIMEOUT: Duration = Duration::from_millis(800);
+/// Time for the application to take the text before switching back
+const COMMIT_SETTLE: Duration = Duration::from_millis(60);
+
+struct Commit {
+    text: String,
+    done: std_mpsc::Sender<Result<(), String>>,
+}
+
+/// Types transcripts as input-method commits through IBus.
+///
+/// maVoice registers a minimal engine with ibus-daemon. To type, it makes
+/// that engine the global one, waits for IBus to focus it on the current
+/// input context, commits the text and switches back to the engine that
+/// was active. Applications receive it exactly like text from a Chinese or
+/// Japanese input method, so it works where synthetic keys or clipboard
+/// paste are blocked. The D-Bus side runs on its own thread, like the
+/// ducking worker, so callers stay synchronous.
+pub struct IbusEngine {
+    tx: Option<mpsc::UnboundedSender<Commit>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl IbusEngine {
+    /// Connect to ibus-daemon and register the engine.
+    pub fn spawn() -> Result<Self, String> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Commit>();
+        let (ready_tx, ready_rx) = std_mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("ibus".into())
+            .spawn(move || {
+                let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(format!("IBus runtime: {}", e)));
+                        return;
+                    }
+                };
+                rt.block_on(async move {
+                    let engine = match Registered::connect().await {
+                        Ok(engine) => {
+                            let _ = ready_tx.send(Ok(()));
+                            engine
+                        }
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return;
+                        }
+                    };
+                    while let Some(commit) = rx.recv().await {
+                        let _ = commit.done.send(engine.commit(&commit.text).await);
+                    }
+                });
+            })
+            .map_err(|e| format!("Cannot start the IBus thread: {}", e))?;
+
+        ready_rx
+            .recv_timeout(CONNECT_TIMEOUT)
+            .map_err(|_| "ibus-daemon did not answer".to_string())??;
+        log::info!("[IBus] Registered the {} engine", ENGINE_NAME);
+        Ok(Self {
+            tx: Some(tx),
+            worker: Some(worker),
+        })
+    }
+
+    /// Type `text` into the focused input context.
+    pub fn commit(&self, text: &str) -> Result<(), String> {
+        let tx = self.tx.as_ref().ok_or("IBus engine stopped")?;
+        let (done, result) = std_mpsc::channel();
+        tx.send(Commit {
+            text: text.to_string(),
+            done,
+        })
+        .map_err(|_| "IBus engine stopped".to_string())?;
+        result
+            .recv_timeout(SWITCH_TIMEOUT + CONNECT_TIMEOUT)
+            .map_err(|_| "IBus commit timed out".to_string())?
+    }
+}
+
+impl Drop for IbusEngine {
+    fn drop(&mut self) {
+        // Closing the channel ends the worker, which drops the connection
+        // and with it the engine
+        self.tx.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// The engine as registered on the IBus bus.
+struct Registered {
+    conn: Connection,
+    focused: watch::Receiver<bool>,
+    focus_reset: watch::Sender<bool>,
+}
+
+impl Registered {
+    async fn connect() -> Result<Self, String> {
+        let address = bus_address()?;
+        let conn = zbus::connection::Builder::address(address.as_str())
+            .map_err(|e| format!("Bad IBus address {}: {}", address, e))?
+            .build()
+            .await
+            .map_err(|e| format!("Cannot connect to ibus-daemon: {}", e))?;
+
+        let (focus_reset, focused) = watch::channel(false);
+        let server = conn.object_server();
+        server
+            .at(FACTORY_PATH, Factory)
+            .await
+            .map_err(|e| format!("Cannot export the IBus factory: {}", e))?;
+        server
+            .at(ENGINE_PATH, Engine { focused: focus_reset.clone() })
+            .await
+            .map_err(|e| format!("Cannot export the IBus engine: {}", e))?;
+
+        conn.call_method(
+            Some(IBUS_SERVICE),
+            IBUS_PATH,
+            Some(IBUS_SERVICE),
+            "RegisterComponent",
+            &(component(),),
+        )
+        .await
+        .map_err(|e| format!("IBus refused the engine: {}", e))?;
+
+        Ok(Self {
+            conn,
+            focused,
+            focus_reset,
+        })
+    }
+
+    async fn commit(&self, text: &str) -> Result<(), String> {
+        let previous = self.global_engine().await.filter(|name| name != ENGINE_NAME);
+
+        self.focus_reset.send_replace(false);
+        self.set_global_engine(ENGINE_NAME).await?;
+        let mut focused = self.focused.clone();
+        let result = match tokio::time::timeout(SWITCH_TIMEOUT, focused.wait_for(|f| *f)).await {
+            Ok(Ok(_)) => {
+                let sent = self
+                    .conn
+                    .emit_signal(
+                        None::<&str>,
+                        ENGINE_PATH,
+                        "org.freedesktop.IBus.Engine",
+                        "CommitText",
+                        &Value::from(ibus_text(text)),
+                    )
+                    .await
+                    .map_err(|e| format!("IBus commit failed: {}", e));
+                tokio::time::sleep(COMMIT_SETTLE).await;
+                sent
+            }
+            _ => Err("IBus did not focus the maVoice engine (is a text field focused?)".to_string()),
+        };
+
+        if let Some(previous) = previous {
+            if let Err(e) = self.set_global_engine(&previous).await {
+                log::warn!("[IBus] Could not switch back to {}: {}", previous, e);
+            }
+        }
+        if result.is_ok() {
+            log::info!("[IBus] Committed {} chars", text.chars().count());
+        }
+        result
+    }
+
+    /// Name of the active engine, if any.
+    async fn global_engine(&self) -> Option<String> {
+        let reply = self
+            .conn
+            .call_method(Some(IBUS_SERVICE), IBUS_PATH, Some(IBUS_SERVICE), "GetGlobalEngine", &())
+            .await
+            .map_err(|e| log::debug!("[IBus] No global engine: {}", e))
+            .ok()?;
+        let body = reply.body();
+        let mut desc: Value = body.deserialize().ok()?;
+        while let Value::Value(inner) = desc {
+            desc = *inner;
+        }
+        // (type name, attachments, name, ...)
+        match desc {
+            Value::Structure(s) => match s.fields().get(2) {
+                Some(Value::Str(name)) => Some(name.to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    async fn set_global_engine(&self, name: &str) -> Result<(), String> {
+        self.conn
+            .call_method(Some(IBUS_SERVICE), IBUS_PATH, Some(IBUS_SERVICE), "SetGlobalEngine", &(name,))
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Cannot switch IBus to {}: {}", name, e))
+    }
+}
+
+/// `IBUS_ADDRESS`, else what `ibus address` reports.
+fn bus_address() -> Result<String, String> {
+    if let Ok(address) = std::env::var("IBUS_ADDRESS") {
+        return Ok(address);
+    }
+    let output = std::process::Command::new("ibus")
+        .arg("address")
+        .output()
+        .map_err(|e| format!("ibus not available: {}", e))?;
+    let address = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || address.is_empty() || address == "(null)" {
+        return Err("ibus-daemon is not running".to_string());
+    }
+    Ok(address)
+}
+
+/// An IBusSerializable: type name, attachments, then the object's fields.
+fn serializable(type_name: &str, fields: Vec<Value<'static>>) -> Value<'static> {
+    let attachments: HashMap<String, Value<'static>> = HashMap::new();
+    let mut builder = StructureBuilder::new()
+        .add_field(type_name.to_string())
+        .add_field(attachments);
+    for field in fields {
+        builder = builder.append_field(field);
+    }
+    Value::from(builder.build().expect("non-empty structure"))
+}
+
+/// IBusText with no attributes.
+fn ibus_text(text: &str) -> Value<'static> {
+    let attrs = serializable("IBusAttrList", vec![Value::from(Vec::<Value<'static>>::new())]);
+    serializable("IBusText", vec![Value::from(text.to_string()), Value::new(attrs)])
+}
+
+/// IBusComponent describing the one engine, owned by this connection.
+/// Engine fields in order: name, long name, description, language,
+/// license, author, icon, layout, rank, hotkeys, symbol, setup, layout
+/// variant, layout option, version, text domain.
+fn component() -> Value<'static> {
+    let s = |v: &str| Value::from(v.to_string());
+    let engine = serializable(
+        "IBusEngineDesc",
+        vec![
+            s(ENGINE_NAME),
+            s("maVoice dictation"),
+            s("Types maVoice transcripts"),
+            s("other"),
+            s("MIT"),
+            s("maVoice"),
+            s(""),
+            s("default"),
+            Value::from(0u32),
+            s(""),
+            s("🎤"),
+            s(""),
+            s(""),
+            s(""),
+            s(env!("CARGO_PKG_VERSION")),
+            s(""),
+        ],
+    );
+    serializable(
+        "IBusComponent",
+        vec![
+            s(COMPONENT_NAME),
+            s("maVoice dictation"),
+            s(env!("CARGO_PKG_VERSION")),
+            s("MIT"),
+            s("maVoice"),
+            s("https://github.com/lliWcWill/maVoice-Linux"),
+            // exec (already running), text domain, observed paths, engines
+            s(""),
+            s(""),
+            Value::from(Vec::<Value<'static>>::new()),
+            Value::from(vec![engine]),
+        ],
+    )
+}
+
+/// Hands IBus the engine whenever it's selected.
+struct Factory;
+
+#[zbus::interface(name = "org.freedesktop.IBus.Factory")]
+impl Factory {
+    fn create_engine(&self, name: &str) -> zbus::fdo::Result<OwnedObjectPath> {
+        if name != ENGINE_NAME {
+            return Err(zbus::fdo::Error::InvalidArgs(format!("Unknown engine {}", name)));
+        }
+        Ok(OwnedObjectPath::try_from(ENGINE_PATH).expect("valid path"))
+    }
+}
+
+/// Passes every key through; it only exists to commit text.
+struct Engine {
+    focused: watch::Sender<bool>,
+}
+
+#[zbus::interface(name = "org.freedesktop.IBus.Engine")]
+impl Engine {
+    fn process_key_event(&self, _keyval: u32, _keycode: u32, _state: u32) -> bool {
+        false
+    }
+
+    fn focus_in(&self) {
+        self.focused.send_replace(true);
+    }
+
+    fn focus_in_id(&self, _object_path: &str, _client: &str) {
+        self.focused.send_replace(true);
+    }
+
+    fn focus_out(&self) {
+        self.focused.send_replace(false);
+    }
+
+    fn focus_out_id(&self, _object_path: &str) {
+        self.focused.send_replace(false);
+    }
+
+    fn enable(&self) {}
+
+    fn disable(&self) {
+        self.focused.send_replace(false);
+    }
+
+    fn reset(&self) {}
+
+    fn set_cursor_location(&self, _x: i32, _y: i32, _w: i32, _h: i32) {}
+
+    fn set_capabilities(&self, _caps: u32) {}
+}
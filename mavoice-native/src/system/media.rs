@@ -0,0 +1,90 @@
+use std::process::Command;
+use std::sync::mpsc::{self, Sender};
+
+enum MediaCommand {
+    Pause,
+    Resume,
+}
+
+/// Pauses MPRIS media players while the mic is live and resumes them after.
+///
+/// Talks to players through `playerctl` on a worker thread so a slow D-Bus
+/// round trip never delays the recording. Only players that were actually
+/// playing get resumed.
+pub struct MediaPause {
+    tx: Sender<MediaCommand>,
+}
+
+impl MediaPause {
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("mpris".into())
+            .spawn(move || {
+                let mut paused: Vec<String> = Vec::new();
+                for cmd in rx {
+                    match cmd {
+                        MediaCommand::Pause => paused.extend(pause_playing()),
+                        MediaCommand::Resume => {
+                            for player in paused.drain(..) {
+                                if let Err(e) = playerctl(&["-p", &player, "play"]) {
+                                    log::warn!("[MPRIS] Failed to resume {}: {}", player, e);
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn MPRIS thread");
+        Self { tx }
+    }
+
+    pub fn pause(&self) {
+        let _ = self.tx.send(MediaCommand::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.tx.send(MediaCommand::Resume);
+    }
+}
+
+/// Pause every player reporting "Playing" and return their names.
+fn pause_playing() -> Vec<String> {
+    let players = match playerctl(&["--list-all"]) {
+        Ok(out) => out,
+        Err(e) => {
+            // Also the "No players found" case
+            log::debug!("[MPRIS] Cannot list players: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut paused = Vec::new();
+    for player in players.lines().map(str::trim).filter(|p| !p.is_empty()) {
+        let playing = playerctl(&["-p", player, "status"])
+            .map(|s| s.trim() == "Playing")
+            .unwrap_or(false);
+        if !playing {
+            continue;
+        }
+        match playerctl(&["-p", player, "pause"]) {
+            Ok(_) => {
+                log::info!("[MPRIS] Paused {}", player);
+                paused.push(player.to_string());
+            }
+            Err(e) => log::warn!("[MPRIS] Failed to pause {}: {}", player, e),
+        }
+    }
+    paused
+}
+
+fn playerctl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("playerctl")
+        .args(args)
+        .output()
+        .map_err(|e| format!("playerctl not available: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
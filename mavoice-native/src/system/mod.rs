@@ -1,4 +1,17 @@
+pub mod atspi;
+pub mod config_watch;
+pub mod ducking;
+pub mod evdev_hotkeys;
+pub mod fake_input;
 pub mod text_inject;
 pub mod hotkeys;
+pub mod ibus;
+pub mod media;
+pub mod remote_desktop;
+pub mod screenshot;
+pub mod secrets;
+pub mod tray;
+pub mod virtual_keyboard;
+pub mod ydotool;
 pub use text_inject::TextInjector;
 pub use hotkeys::HotkeyManager;
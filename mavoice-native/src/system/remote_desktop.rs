@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use ashpd::desktop::remote_desktop::{DeviceType, KeyState, RemoteDesktop};
+use ashpd::desktop::{PersistMode, Session};
+use tokio::sync::mpsc;
+
+/// How long the portal gets to open a session
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long typing one transcript may take
+const TYPE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const XK_RETURN: i32 = 0xff0d;
+const XK_TAB: i32 = 0xff09;
+
+struct Typing {
+    text: String,
+    done: std_mpsc::Sender<Result<(), String>>,
+}
+
+/// Types transcripts through the XDG RemoteDesktop portal, the only way
+/// GNOME's Mutter accepts keyboard input from another client on Wayland.
+///
+/// The first session shows GNOME's "Allow remote interaction" dialog. The
+/// restore token the portal hands back is saved, so later sessions start
+/// without asking until the user revokes the permission. The session is
+/// started as soon as the backend is created and stays open; until the
+/// dialog is answered, typing fails right away instead of blocking. Like
+/// the IBus engine, the D-Bus side lives on its own thread.
+pub struct RemoteDesktopSession {
+    tx: Option<mpsc::UnboundedSender<Typing>>,
+    started: Arc<OnceLock<Result<(), String>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl RemoteDesktopSession {
+    /// Open a portal session and ask for keyboard access.
+    pub fn spawn() -> Result<Self, String> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Typing>();
+        let (ready_tx, ready_rx) = std_mpsc::channel();
+        let started = Arc::new(OnceLock::new());
+        let worker_started = Arc::clone(&started);
+        let worker = std::thread::Builder::new()
+            .name("remote-desktop".into())
+            .spawn(move || {
+                let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(format!("Portal runtime: {}", e)));
+                        return;
+                    }
+                };
+                rt.block_on(async move {
+                    let portal = match Portal::connect().await {
+                        Ok(portal) => {
+                            let _ = ready_tx.send(Ok(()));
+                            portal
+                        }
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return;
+                        }
+                    };
+                    let result = portal.start().await;
+                    match result {
+                        Ok(()) => log::info!("[Portal] Remote desktop session started"),
+                        Err(ref e) => log::warn!("[Portal] {}", e),
+                    }
+                    let _ = worker_started.set(result);
+                    while let Some(typing) = rx.recv().await {
+                        let _ = typing.done.send(portal.type_text(&typing.text).await);
+                    }
+                    let _ = portal.session.close().await;
+                });
+            })
+            .map_err(|e| format!("Cannot start the portal thread: {}", e))?;
+
+        ready_rx
+            .recv_timeout(CONNECT_TIMEOUT)
+            .map_err(|_| "The RemoteDesktop portal did not answer".to_string())??;
+        Ok(Self {
+            tx: Some(tx),
+            started,
+            worker: Some(worker),
+        })
+    }
+
+    /// Type `text` into the focused window.
+    pub fn type_text(&self, text: &str) -> Result<(), String> {
+        match self.started.get() {
+            None => return Err("Allow maVoice in the remote desktop dialog first".to_string()),
+            Some(Err(e)) => return Err(e.clone()),
+            Some(Ok(())) => {}
+        }
+        let tx = self.tx.as_ref().ok_or("Remote desktop session closed")?;
+        let (done, result) = std_mpsc::channel();
+        tx.send(Typing {
+            text: text.to_string(),
+            done,
+        })
+        .map_err(|_| "Remote desktop session closed".to_string())?;
+        result
+            .recv_timeout(TYPE_TIMEOUT)
+            .map_err(|_| "Remote desktop typing timed out".to_string())?
+    }
+}
+
+impl Drop for RemoteDesktopSession {
+    fn drop(&mut self) {
+        // Closing the channel ends the worker, which closes the session
+        self.tx.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+struct Portal {
+    proxy: RemoteDesktop<'static>,
+    session: Session<'static, RemoteDesktop<'static>>,
+}
+
+impl Portal {
+    async fn connect() -> Result<Self, String> {
+        let proxy = RemoteDesktop::new()
+            .await
+            .map_err(|e| format!("RemoteDesktop portal not available: {}", e))?;
+        let session = proxy
+            .create_session()
+            .await
+            .map_err(|e| format!("Cannot create a remote desktop session: {}", e))?;
+        let token = std::fs::read_to_string(token_path()).ok();
+        proxy
+            .select_devices(
+                &session,
+                DeviceType::Keyboard.into(),
+                token.as_deref().map(str::trim),
+                PersistMode::ExplicitlyRevoked,
+            )
+            .await
+            .and_then(|request| request.response())
+            .map_err(|e| format!("Cannot select the keyboard: {}", e))?;
+        Ok(Self { proxy, session })
+    }
+
+    /// Start the session, which shows the permission dialog unless a saved
+    /// token is still valid.
+    async fn start(&self) -> Result<(), String> {
+        let selected = self
+            .proxy
+            .start(&self.session, None)
+            .await
+            .and_then(|request| request.response())
+            .map_err(|e| format!("Remote desktop permission not granted: {}", e))?;
+        if !selected.devices().contains(DeviceType::Keyboard) {
+            return Err("Remote desktop permission does not include the keyboard".to_string());
+        }
+        // Tokens are single use; each start hands out the next one
+        if let Some(token) = selected.restore_token() {
+            save_token(token);
+        }
+        Ok(())
+    }
+
+    async fn type_text(&self, text: &str) -> Result<(), String> {
+        for c in text.chars().filter(|&c| c != '\r') {
+            let sym = keysym(c);
+            for state in [KeyState::Pressed, KeyState::Released] {
+                self.proxy
+                    .notify_keyboard_keysym(&self.session, sym, state)
+                    .await
+                    .map_err(|e| format!("Remote desktop key failed: {}", e))?;
+            }
+        }
+        log::info!("[Portal] Typed {} chars", text.chars().count());
+        Ok(())
+    }
+}
+
+/// Keysym for `c`: Latin-1 keysyms equal the code point, everything else
+/// uses the Unicode keysym range.
+fn keysym(c: char) -> i32 {
+    match c {
+        '\n' => XK_RETURN,
+        '\t' => XK_TAB,
+        ' '..='~' | '\u{a0}'..='\u{ff}' => c as i32,
+        c => 0x0100_0000 + c as i32,
+    }
+}
+
+/// `~/.local/share/mavoice/remote-desktop-token`
+fn token_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("~/.local/share"))
+        .join("mavoice")
+        .join("remote-desktop-token")
+}
+
+fn save_token(token: &str) {
+    let path = token_path();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Err(e) = std::fs::write(&path, token) {
+        log::warn!("[Portal] Cannot save the restore token to {}: {}", path.display(), e);
+    }
+}
@@ -0,0 +1,67 @@
+use tokio::process::Command;
+
+/// Longest side of an X11 window capture; larger windows are scaled down to
+/// keep the message to Gemini small.
+const MAX_SIDE: u32 = 1600;
+
+/// A captured image, ready to send as an inline data part.
+#[derive(Debug)]
+pub struct Screenshot {
+    pub mime_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Capture the X11 window `window_id` (the whole screen if none) with
+/// ImageMagick's `import`, or the whole screen through the XDG screenshot
+/// portal in a Wayland session, where other windows can't be read directly.
+pub async fn capture(window_id: Option<&str>) -> Result<Screenshot, String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return capture_portal().await;
+    }
+    capture_x11(window_id.unwrap_or("root")).await
+}
+
+async fn capture_x11(window: &str) -> Result<Screenshot, String> {
+    let resize = format!("{}x{}>", MAX_SIDE, MAX_SIDE);
+    let output = Command::new("import")
+        .args(["-silent", "-window", window, "-resize", &resize, "-quality", "85", "jpg:-"])
+        .output()
+        .await
+        .map_err(|e| format!("ImageMagick 'import' not available: {}", e))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!(
+            "Screenshot failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(Screenshot {
+        mime_type: "image/jpeg",
+        data: output.stdout,
+    })
+}
+
+async fn capture_portal() -> Result<Screenshot, String> {
+    let response = ashpd::desktop::screenshot::Screenshot::request()
+        .interactive(false)
+        .modal(false)
+        .send()
+        .await
+        .and_then(|request| request.response())
+        .map_err(|e| format!("Screenshot portal failed: {}", e))?;
+    let path = response
+        .uri()
+        .to_file_path()
+        .map_err(|_| format!("Screenshot portal returned {}", response.uri()))?;
+    let data = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    // The portal saves into the user's pictures folder; this one was only
+    // for Gemini
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        log::warn!("Cannot remove {}: {}", path.display(), e);
+    }
+    Ok(Screenshot {
+        mime_type: "image/png",
+        data,
+    })
+}
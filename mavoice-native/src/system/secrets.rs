@@ -0,0 +1,35 @@
+//! API key storage in the freedesktop Secret Service (GNOME Keyring, KWallet).
+
+use keyring::Entry;
+
+/// Service name the keys are stored under (`secret-tool search service mavoice`).
+const SERVICE: &str = "mavoice";
+
+fn entry(name: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, name).map_err(|e| format!("Keyring unavailable: {}", e))
+}
+
+/// The stored secret, or `None` if it's missing or the keyring can't be reached.
+pub fn get(name: &str) -> Option<String> {
+    match entry(name).and_then(|e| e.get_password().map_err(|e| e.to_string())) {
+        Ok(secret) => Some(secret),
+        Err(e) => {
+            log::debug!("Keyring lookup for {} failed: {}", name, e);
+            None
+        }
+    }
+}
+
+pub fn set(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(secret)
+        .map_err(|e| format!("Keyring write failed: {}", e))
+}
+
+/// Remove a stored secret; a missing entry is not an error.
+pub fn delete(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Keyring delete failed: {}", e)),
+    }
+}
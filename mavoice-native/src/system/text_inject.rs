@@ -1,15 +1,57 @@
 #![allow(dead_code)]
+use std::collections::HashMap;
 use std::error::Error;
 use std::process::Command;
+use std::time::Duration;
+
+use crate::config::InjectionRule;
+
+use super::fake_input::FakeInput;
+use super::ibus::IbusEngine;
+use super::remote_desktop::RemoteDesktopSession;
+use super::virtual_keyboard::VirtualKeyboard;
+use super::ydotool::YdotoolClient;
+
+/// Default pause between keys for the Type backend, ydotool's own default
+const DEFAULT_TYPE_DELAY: Duration = Duration::from_millis(12);
+
+/// Inserts text at the caret of the focused field over AT-SPI.
+pub type CaretInserter = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
 
 pub struct TextInjector {
     backend: TextInjectionBackend,
+    /// Connected ydotoold client when the Ydotool backend is active
+    ydotool: Option<YdotoolClient>,
+    /// Registered IBus engine when the Ibus backend is active
+    ibus: Option<IbusEngine>,
+    /// Open portal session when the RemoteDesktop backend is active
+    remote_desktop: Option<RemoteDesktopSession>,
+    /// Pause between keys for the Type backend
+    type_delay: Duration,
+    /// Window class pattern rules, most specific pattern first
+    rules: Vec<(String, InjectionRule)>,
+    /// Used by rules with the "atspi" method
+    caret_inserter: Option<CaretInserter>,
+    /// "off", "also" (injected and put in PRIMARY) or "only" (PRIMARY only)
+    primary_selection: String,
 }
 
 #[derive(Debug, Clone)]
 pub enum TextInjectionBackend {
     X11,
     Wayland,
+    /// Clipboard + Ctrl+V sent through ydotoold's uinput device
+    Ydotool,
+    /// Committed as input-method text by maVoice's IBus engine
+    Ibus,
+    /// Typed through the wlroots/KWin virtual keyboard protocol
+    VirtualKeyboard,
+    /// Clipboard + Ctrl+V pressed through KWin's fake input protocol
+    KwinFakeInput,
+    /// Keysyms sent through the XDG RemoteDesktop portal (GNOME)
+    RemoteDesktop,
+    /// Key events one character at a time, for fields that refuse paste
+    Type,
 }
 
 #[derive(Debug, Clone)]
@@ -20,10 +62,117 @@ pub struct WindowInfo {
 }
 
 impl TextInjector {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
-        let backend = Self::detect_display_server()?;
-        log::info!("Text injector using {:?} backend", backend);
-        Ok(TextInjector { backend })
+    /// Create an injector for `method`: "auto", "xdotool", "wtype", "ydotool",
+    /// "ibus", "virtual-keyboard", "kwin", "portal" or "type". On Wayland "auto"
+    /// pastes through KWin's fake input on Plasma, types through the virtual
+    /// keyboard protocol where the compositor has it (Sway, Hyprland), else
+    /// pastes through ydotoold when it's running, else types through the
+    /// RemoteDesktop portal on GNOME, else wtype.
+    pub fn new(method: &str) -> Result<Self, Box<dyn Error>> {
+        let display = Self::detect_display_server()?;
+
+        if method == "ibus" {
+            match IbusEngine::spawn() {
+                Ok(engine) => {
+                    log::info!("Text injector using Ibus backend");
+                    return Ok(TextInjector {
+                        backend: TextInjectionBackend::Ibus,
+                        ydotool: None,
+                        ibus: Some(engine),
+                        remote_desktop: None,
+                        type_delay: DEFAULT_TYPE_DELAY,
+                        rules: Vec::new(),
+                        caret_inserter: None,
+                        primary_selection: "off".to_string(),
+                    });
+                }
+                Err(e) => log::warn!("ibus requested but unavailable ({}), using {:?}", e, display),
+            }
+        }
+
+        // wtype can't type under Mutter, so GNOME without ydotoold goes
+        // through the portal
+        let gnome_fallback = method == "auto"
+            && matches!(display, TextInjectionBackend::Wayland)
+            && is_gnome()
+            && YdotoolClient::detect().is_none();
+        if method == "portal" || gnome_fallback {
+            match RemoteDesktopSession::spawn() {
+                Ok(session) => {
+                    log::info!("Text injector using RemoteDesktop backend");
+                    return Ok(TextInjector {
+                        backend: TextInjectionBackend::RemoteDesktop,
+                        ydotool: None,
+                        ibus: None,
+                        remote_desktop: Some(session),
+                        type_delay: DEFAULT_TYPE_DELAY,
+                        rules: Vec::new(),
+                        caret_inserter: None,
+                        primary_selection: "off".to_string(),
+                    });
+                }
+                Err(e) => log::warn!("RemoteDesktop portal unavailable ({}), using {:?}", e, display),
+            }
+        }
+
+        let (backend, ydotool) = match method {
+            "xdotool" => (TextInjectionBackend::X11, None),
+            "wtype" => (TextInjectionBackend::Wayland, None),
+            // ydotoold can type on Wayland compositors without a typing protocol
+            "type" => match display {
+                TextInjectionBackend::Wayland => (TextInjectionBackend::Type, YdotoolClient::detect()),
+                _ => (TextInjectionBackend::Type, None),
+            },
+            "virtual-keyboard" if VirtualKeyboard::available() => (TextInjectionBackend::VirtualKeyboard, None),
+            "virtual-keyboard" => {
+                log::warn!("virtual-keyboard requested but the compositor lacks it, using {:?}", display);
+                (display, None)
+            }
+            "kwin" if FakeInput::available() => (TextInjectionBackend::KwinFakeInput, None),
+            "kwin" => {
+                log::warn!("kwin requested but KWin offers no fake input, using {:?}", display);
+                (display, None)
+            }
+            "ydotool" => match YdotoolClient::detect() {
+                Some(client) => (TextInjectionBackend::Ydotool, Some(client)),
+                None => {
+                    log::warn!("ydotool requested but ydotoold is not running, using {:?}", display);
+                    (display, None)
+                }
+            },
+            _ => match display {
+                TextInjectionBackend::Wayland if FakeInput::available() => {
+                    (TextInjectionBackend::KwinFakeInput, None)
+                }
+                TextInjectionBackend::Wayland if VirtualKeyboard::available() => {
+                    (TextInjectionBackend::VirtualKeyboard, None)
+                }
+                TextInjectionBackend::Wayland => match YdotoolClient::detect() {
+                    Some(client) => (TextInjectionBackend::Ydotool, Some(client)),
+                    None => (TextInjectionBackend::Wayland, None),
+                },
+                other => (other, None),
+            },
+        };
+
+        match ydotool {
+            Some(ref client) => log::info!(
+                "Text injector using {:?} backend ({})",
+                backend,
+                client.socket_path().display()
+            ),
+            None => log::info!("Text injector using {:?} backend", backend),
+        }
+        Ok(TextInjector {
+            backend,
+            ydotool,
+            ibus: None,
+            remote_desktop: None,
+            type_delay: DEFAULT_TYPE_DELAY,
+            rules: Vec::new(),
+            caret_inserter: None,
+            primary_selection: "off".to_string(),
+        })
     }
 
     fn detect_display_server() -> Result<TextInjectionBackend, Box<dyn Error>> {
@@ -42,6 +191,12 @@ impl TextInjector {
         match self.backend {
             TextInjectionBackend::X11 => self.inject_text_x11(text),
             TextInjectionBackend::Wayland => self.inject_text_wayland(text),
+            TextInjectionBackend::Ydotool => self.inject_text_ydotool(text, None),
+            TextInjectionBackend::Ibus => self.inject_text_ibus(text, None),
+            TextInjectionBackend::VirtualKeyboard => Ok(VirtualKeyboard::type_text(text)?),
+            TextInjectionBackend::KwinFakeInput => self.inject_text_fake_input(text),
+            TextInjectionBackend::RemoteDesktop => self.inject_text_portal(text),
+            TextInjectionBackend::Type => self.inject_text_typed(text, None),
         }
     }
 
@@ -60,17 +215,213 @@ impl TextInjector {
         None
     }
 
+    /// WM_CLASS instance and class names of an X11 window (e.g.
+    /// `["gnome-terminal-server", "Gnome-terminal"]`); empty if unavailable.
+    pub fn get_window_classes(&self, window_id: &str) -> Vec<String> {
+        window_classes(window_id)
+    }
+
     fn inject_text_x11(&self, text: &str) -> Result<(), Box<dyn Error>> {
         self.inject_text_x11_to(text, None)
     }
 
-    /// Inject text on X11 by copying to clipboard and pasting into the target window.
-    /// If `target_window_id` is provided, refocuses that window first.
+    /// Inject text into the target window, following the injection rule for
+    /// its window class if one matches, and put it in the PRIMARY selection
+    /// when asked to. If `target_window_id` is provided, refocuses that
+    /// window first.
     pub fn inject_text_to(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
+        match self.primary_selection.as_str() {
+            "only" => {
+                copy_to_primary(text)?;
+                log::info!("Text put in the primary selection ({} chars)", text.len());
+                Ok(())
+            }
+            "also" => {
+                self.inject_by_rule(text, target_window_id)?;
+                if let Err(e) = copy_to_primary(text) {
+                    log::warn!("Could not set the primary selection: {}", e);
+                }
+                Ok(())
+            }
+            _ => self.inject_by_rule(text, target_window_id),
+        }
+    }
+
+    /// Where PRIMARY gets the transcript: "off", "also" or "only".
+    pub fn set_primary_selection(&mut self, mode: &str) {
+        self.primary_selection = mode.to_string();
+    }
+
+    fn inject_by_rule(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let Some(rule) = target_window_id.and_then(|id| self.rule_for(id)) else {
+            return self.inject_with_backend(text, target_window_id);
+        };
+        let mut text = text.to_string();
+        if rule.capitalize {
+            text = capitalize_first(&text);
+        }
+        if rule.trailing_space && !text.ends_with(char::is_whitespace) {
+            text.push(' ');
+        }
+        match rule.method.as_deref().unwrap_or("paste") {
+            "type" => self.inject_text_typed(&text, target_window_id),
+            "clipboard" => {
+                self.copy_to_clipboard(&text)?;
+                log::info!("Text copied to clipboard ({} chars), not pasted by rule", text.len());
+                Ok(())
+            }
+            "atspi" => {
+                let insert = self.caret_inserter.as_ref().ok_or("AT-SPI insertion not available")?;
+                if let Some(win_id) = target_window_id {
+                    self.refocus_window(win_id)?;
+                }
+                insert(&text)?;
+                Ok(())
+            }
+            "paste" => self.inject_with_backend(&text, target_window_id),
+            other => {
+                log::warn!("Unknown injection rule method {:?}, pasting", other);
+                self.inject_with_backend(&text, target_window_id)
+            }
+        }
+    }
+
+    /// Window class pattern rules consulted by [`Self::inject_text_to`].
+    pub fn set_rules(&mut self, rules: &HashMap<String, InjectionRule>) {
+        let mut rules: Vec<_> = rules.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        // Longer patterns are more specific; ties by name keep the order stable
+        rules.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        self.rules = rules;
+    }
+
+    /// How rules with the "atspi" method insert text.
+    pub fn set_caret_inserter(&mut self, inserter: CaretInserter) {
+        self.caret_inserter = Some(inserter);
+    }
+
+    /// The rule whose pattern matches one of the window's classes.
+    fn rule_for(&self, window_id: &str) -> Option<&InjectionRule> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let classes = window_classes(window_id);
+        self.rules
+            .iter()
+            .find(|(pattern, _)| classes.iter().any(|c| class_matches(pattern, c)))
+            .map(|(pattern, rule)| {
+                log::info!("Injection rule {:?} matches {:?}", pattern, classes);
+                rule
+            })
+    }
+
+    /// Inject through the configured backend.
+    fn inject_with_backend(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
         match self.backend {
             TextInjectionBackend::X11 => self.inject_text_x11_to(text, target_window_id),
             TextInjectionBackend::Wayland => self.inject_text_wayland(text),
+            TextInjectionBackend::Ydotool => self.inject_text_ydotool(text, target_window_id),
+            TextInjectionBackend::Ibus => self.inject_text_ibus(text, target_window_id),
+            // Wayland has no way to focus another client's window
+            TextInjectionBackend::VirtualKeyboard => Ok(VirtualKeyboard::type_text(text)?),
+            TextInjectionBackend::KwinFakeInput => self.inject_text_fake_input(text),
+            TextInjectionBackend::RemoteDesktop => self.inject_text_portal(text),
+            TextInjectionBackend::Type => self.inject_text_typed(text, target_window_id),
+        }
+    }
+
+    /// Pause between keys when typing character by character.
+    pub fn set_type_delay(&mut self, ms: u64) {
+        self.type_delay = Duration::from_millis(ms);
+    }
+
+    /// Send the text as individual key presses: xdotool on X11, the virtual
+    /// keyboard protocol, ydotoold or wtype on Wayland. Nothing touches the
+    /// clipboard.
+    fn inject_text_typed(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
+        if let Some(win_id) = target_window_id {
+            self.refocus_window(win_id)?;
+        }
+        let ms = self.type_delay.as_millis().to_string();
+        let output = match Self::detect_display_server()? {
+            TextInjectionBackend::Wayland if VirtualKeyboard::available() => {
+                return Ok(VirtualKeyboard::type_text_with_delay(text, self.type_delay)?);
+            }
+            TextInjectionBackend::Wayland => match self.ydotool {
+                Some(ref client) => Command::new("ydotool")
+                    .env("YDOTOOL_SOCKET", client.socket_path())
+                    .args(["type", "--key-delay", &ms, "--", text])
+                    .output()?,
+                None => Command::new("wtype").args(["-d", &ms, "--", text]).output()?,
+            },
+            _ => Command::new("xdotool")
+                .args(["type", "--clearmodifiers", "--delay", &ms, "--", text])
+                .output()?,
+        };
+        if !output.status.success() {
+            return Err(format!("Typing failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+        }
+        log::info!("Typed {} chars", text.chars().count());
+        Ok(())
+    }
+
+    /// Type the text through the portal session. The clipboard is left alone.
+    fn inject_text_portal(&self, text: &str) -> Result<(), Box<dyn Error>> {
+        let session = self.remote_desktop.as_ref().ok_or("No remote desktop session")?;
+        session.type_text(text)?;
+        Ok(())
+    }
+
+    /// Copy text to the clipboard, then press Ctrl+V through KWin.
+    fn inject_text_fake_input(&self, text: &str) -> Result<(), Box<dyn Error>> {
+        self.copy_to_clipboard(text)?;
+        log::info!("Text copied to clipboard ({} chars)", text.len());
+
+        if let Err(e) = FakeInput::paste() {
+            log::warn!("KWin paste failed: {}. Text is in clipboard — paste manually with Ctrl+V", e);
+        }
+        Ok(())
+    }
+
+    /// Commit the text through the IBus engine. The clipboard is left alone.
+    fn inject_text_ibus(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let engine = self.ibus.as_ref().ok_or("IBus engine not registered")?;
+        if let Some(win_id) = target_window_id {
+            self.refocus_window(win_id)?;
+        }
+        engine.commit(text)?;
+        Ok(())
+    }
+
+    /// Copy text to the clipboard, then press Ctrl+V via ydotoold.
+    fn inject_text_ydotool(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let client = self.ydotool.as_ref().ok_or("ydotoold not connected")?;
+
+        self.copy_to_clipboard(text)?;
+        log::info!("Text copied to clipboard ({} chars)", text.len());
+
+        if let Some(win_id) = target_window_id {
+            self.refocus_window(win_id)?;
         }
+
+        if let Err(e) = client.paste() {
+            log::warn!("ydotool paste failed: {}. Text is in clipboard — paste manually with Ctrl+V", e);
+        }
+        Ok(())
+    }
+
+    /// Copy text to the clipboard using wl-copy on Wayland, xclip otherwise.
+    pub fn copy_to_clipboard(&self, text: &str) -> Result<(), Box<dyn Error>> {
+        copy_to_clipboard(text)
+    }
+
+    /// Re-activate a window by X11 id (the one that was active before the overlay).
+    fn refocus_window(&self, win_id: &str) -> Result<(), Box<dyn Error>> {
+        if activate_window(win_id).is_err() {
+            log::warn!("Failed to refocus window {}, trying paste anyway", win_id);
+        }
+        // Brief pause to let the window manager complete the focus switch
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        Ok(())
     }
 
     fn inject_text_x11_to(&self, text: &str, target_window_id: Option<&str>) -> Result<(), Box<dyn Error>> {
@@ -93,14 +444,7 @@ impl TextInjector {
 
         // Step 2: Refocus the target window (the one that was active before overlay)
         if let Some(win_id) = target_window_id {
-            let focus_output = Command::new("xdotool")
-                .args(["windowactivate", "--sync", win_id])
-                .output()?;
-            if !focus_output.status.success() {
-                log::warn!("Failed to refocus window {}, trying paste anyway", win_id);
-            }
-            // Brief pause to let the window manager complete the focus switch
-            std::thread::sleep(std::time::Duration::from_millis(50));
+            self.refocus_window(win_id)?;
         }
 
         // Step 3: Paste via Ctrl+V
@@ -153,7 +497,7 @@ impl TextInjector {
     pub fn get_active_window_info(&self) -> Result<WindowInfo, Box<dyn Error>> {
         match self.backend {
             TextInjectionBackend::X11 => self.get_active_window_info_x11(),
-            TextInjectionBackend::Wayland => Ok(WindowInfo {
+            _ => Ok(WindowInfo {
                 id: "unknown".to_string(),
                 title: "Unknown (Wayland)".to_string(),
                 class: "Unknown (Wayland)".to_string(),
@@ -207,3 +551,188 @@ impl TextInjector {
         &self.backend
     }
 }
+
+/// Copy text to the clipboard using wl-copy on Wayland, xclip otherwise.
+pub fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn Error>> {
+    copy_to_selection(text, "clipboard")
+}
+
+/// Put text into the PRIMARY selection (middle-click paste).
+pub fn copy_to_primary(text: &str) -> Result<(), Box<dyn Error>> {
+    copy_to_selection(text, "primary")
+}
+
+/// Copy text into the X11 selection `selection` ("clipboard" or
+/// "primary") with xclip, or its Wayland counterpart with wl-copy.
+fn copy_to_selection(text: &str, selection: &str) -> Result<(), Box<dyn Error>> {
+    let wayland = std::env::var("WAYLAND_DISPLAY").is_ok();
+    let mut cmd = if wayland {
+        let mut c = Command::new("wl-copy");
+        if selection == "primary" {
+            c.arg("--primary");
+        }
+        c
+    } else {
+        let mut c = Command::new("xclip");
+        c.args(["-selection", selection]);
+        c
+    };
+    let mut child = cmd.stdin(std::process::Stdio::piped()).spawn()?;
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        use std::io::Write;
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    if !child.wait()?.success() {
+        return Err(format!(
+            "{} failed to copy text to the {} selection",
+            if wayland { "wl-copy" } else { "xclip" },
+            selection
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Text on the clipboard, read with wl-paste on Wayland, xclip otherwise.
+/// An empty clipboard, or one holding only non-text data, reads as "".
+pub fn read_clipboard() -> Result<String, Box<dyn Error>> {
+    let wayland = std::env::var("WAYLAND_DISPLAY").is_ok();
+    let output = if wayland {
+        Command::new("wl-paste")
+            .args(["--no-newline", "--type", "text"])
+            .output()?
+    } else {
+        Command::new("xclip")
+            .args(["-selection", "clipboard", "-o"])
+            .output()?
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Both tools fail when there is nothing they can paste as text
+        if stderr.contains("No selection") || stderr.contains("not available") || stderr.contains("No suitable type") {
+            return Ok(String::new());
+        }
+        return Err(format!(
+            "{} failed to read the clipboard: {}",
+            if wayland { "wl-paste" } else { "xclip" },
+            stderr.trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// WM_CLASS instance and class names of an X11 window (e.g.
+/// `["gnome-terminal-server", "Gnome-terminal"]`); empty if unavailable.
+pub fn window_classes(window_id: &str) -> Vec<String> {
+    let Ok(output) = Command::new("xprop").args(["-id", window_id, "WM_CLASS"]).output() else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    // WM_CLASS(STRING) = "instance", "Class"
+    String::from_utf8_lossy(&output.stdout)
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Title of an X11 window, if it has one.
+pub fn window_title(window_id: &str) -> Option<String> {
+    let output = Command::new("xdotool").args(["getwindowname", window_id]).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Top-level windows from the window manager's EWMH client list, topmost
+/// first. Only X11 (and XWayland) windows are visible this way.
+pub fn list_windows() -> Result<Vec<WindowInfo>, Box<dyn Error>> {
+    if std::env::var("DISPLAY").is_err() {
+        return Err("Window control needs X11 or XWayland (DISPLAY is not set)".into());
+    }
+    let output = Command::new("xprop")
+        .args(["-root", "_NET_CLIENT_LIST_STACKING"])
+        .output()?;
+    if !output.status.success() {
+        return Err("The window manager doesn't publish a window list (_NET_CLIENT_LIST_STACKING)".into());
+    }
+    // _NET_CLIENT_LIST_STACKING(WINDOW): window id # 0x1e00007, 0x3a0000a
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let ids = stdout.split('#').nth(1).unwrap_or_default();
+    let windows = ids
+        .split(',')
+        .rev()
+        .filter_map(|id| u64::from_str_radix(id.trim().trim_start_matches("0x"), 16).ok())
+        .map(|id| {
+            // Decimal, like `xdotool getactivewindow`
+            let id = id.to_string();
+            WindowInfo {
+                title: window_title(&id).unwrap_or_default(),
+                class: window_classes(&id).pop().unwrap_or_default(),
+                id,
+            }
+        })
+        .collect();
+    Ok(windows)
+}
+
+/// Raise and focus an X11 window.
+pub fn activate_window(window_id: &str) -> Result<(), Box<dyn Error>> {
+    let output = Command::new("xdotool")
+        .args(["windowactivate", "--sync", window_id])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "xdotool could not activate window {}: {}",
+            window_id,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Whether the session is GNOME (including Ubuntu's "ubuntu:GNOME").
+fn is_gnome() -> bool {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .is_ok_and(|desktop| desktop.split(':').any(|d| d.eq_ignore_ascii_case("gnome")))
+}
+
+/// Case-insensitive match of a window class against a pattern where `*`
+/// stands for any run of characters.
+fn class_matches(pattern: &str, class: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let class = class.to_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = class.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// `text` with its first letter upper-cased.
+fn capitalize_first(text: &str) -> String {
+    match text.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((i, c)) => format!("{}{}{}", &text[..i], c.to_uppercase(), &text[i + c.len_utf8()..]),
+        None => text.to_string(),
+    }
+}
@@ -0,0 +1,249 @@
+use ksni::menu::{CheckmarkItem, RadioGroup, RadioItem, StandardItem, SubMenu};
+use ksni::{MenuItem, Tray, TrayService};
+use winit::event_loop::EventLoopProxy;
+
+use crate::app::{AppEvent, VoiceMode};
+
+/// Actions triggered from the tray menu, delivered to the event loop.
+#[derive(Debug, Clone)]
+pub enum TrayAction {
+    ToggleRecording,
+    SetMode(VoiceMode),
+    ToggleMeeting,
+    /// Paste the last transcript into the focused window again
+    Reinject,
+    /// Switch config preset; empty name = no preset
+    SetPreset(String),
+    OpenSettings,
+    /// Zip logs, redacted config and environment info for a bug report
+    ExportDebugBundle,
+    PauseHotkeys(bool),
+    /// Gemini replies with typed text instead of speech
+    GeminiTextOnly(bool),
+    /// Store no history, recordings or transcripts
+    Privacy(bool),
+    Quit,
+}
+
+/// State mirrored into the tray so the menu reflects what the app is doing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrayState {
+    pub active: bool,
+    pub mode: VoiceMode,
+    pub hotkeys_paused: bool,
+    pub meeting: bool,
+    /// There is a last transcript to paste again
+    pub has_transcript: bool,
+    /// Config preset names, in order
+    pub presets: Vec<String>,
+    /// Active preset name, empty if none
+    pub active_preset: String,
+    pub gemini_text_only: bool,
+    pub privacy: bool,
+}
+
+struct MaVoiceTray {
+    proxy: EventLoopProxy<AppEvent>,
+    state: TrayState,
+}
+
+impl MaVoiceTray {
+    fn send(&self, action: TrayAction) {
+        let _ = self.proxy.send_event(AppEvent::Tray(action));
+    }
+}
+
+impl Tray for MaVoiceTray {
+    fn id(&self) -> String {
+        "mavoice".to_string()
+    }
+
+    fn title(&self) -> String {
+        "maVoice".to_string()
+    }
+
+    fn icon_name(&self) -> String {
+        if self.state.active || self.state.meeting {
+            "media-record".to_string()
+        } else {
+            "audio-input-microphone".to_string()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.send(TrayAction::ToggleRecording);
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let toggle_label = match (self.state.mode, self.state.active) {
+            (VoiceMode::Groq, false) => "Start dictation",
+            (VoiceMode::Groq, true) => "Stop dictation",
+            (VoiceMode::GeminiLive, false) => "Start Gemini session",
+            (VoiceMode::GeminiLive, true) => "End Gemini session",
+        };
+
+        let mut items: Vec<MenuItem<Self>> = vec![
+            StandardItem {
+                label: toggle_label.to_string(),
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::ToggleRecording)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Paste last transcript".to_string(),
+                enabled: self.state.has_transcript,
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::Reinject)),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            RadioGroup {
+                selected: match self.state.mode {
+                    VoiceMode::Groq => 0,
+                    VoiceMode::GeminiLive => 1,
+                },
+                select: Box::new(|t: &mut Self, idx| {
+                    let mode = if idx == 1 {
+                        VoiceMode::GeminiLive
+                    } else {
+                        VoiceMode::Groq
+                    };
+                    t.send(TrayAction::SetMode(mode));
+                }),
+                options: vec![
+                    RadioItem {
+                        label: "Dictation (Groq)".to_string(),
+                        ..Default::default()
+                    },
+                    RadioItem {
+                        label: "Voice assistant (Gemini)".to_string(),
+                        ..Default::default()
+                    },
+                ],
+            }
+            .into(),
+        ];
+
+        if !self.state.presets.is_empty() {
+            let selected = self
+                .state
+                .presets
+                .iter()
+                .position(|p| *p == self.state.active_preset)
+                .map_or(0, |i| i + 1);
+            let options = std::iter::once("Default".to_string())
+                .chain(self.state.presets.iter().cloned())
+                .map(|label| RadioItem {
+                    label,
+                    ..Default::default()
+                })
+                .collect();
+            items.push(MenuItem::Separator);
+            items.push(
+                SubMenu {
+                    label: "Profile".to_string(),
+                    submenu: vec![RadioGroup {
+                        selected,
+                        select: Box::new(|t: &mut Self, idx| {
+                            let name = match idx {
+                                0 => String::new(),
+                                i => t.state.presets.get(i - 1).cloned().unwrap_or_default(),
+                            };
+                            t.send(TrayAction::SetPreset(name));
+                        }),
+                        options,
+                    }
+                    .into()],
+                    ..Default::default()
+                }
+                .into(),
+            );
+        }
+
+        items.extend([
+            MenuItem::Separator,
+            CheckmarkItem {
+                label: "Record meeting notes".to_string(),
+                checked: self.state.meeting,
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::ToggleMeeting)),
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: "Gemini replies as text".to_string(),
+                checked: self.state.gemini_text_only,
+                activate: Box::new(|t: &mut Self| {
+                    t.send(TrayAction::GeminiTextOnly(!t.state.gemini_text_only))
+                }),
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: "Privacy mode".to_string(),
+                checked: self.state.privacy,
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::Privacy(!t.state.privacy))),
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: "Pause hotkeys".to_string(),
+                checked: self.state.hotkeys_paused,
+                activate: Box::new(|t: &mut Self| {
+                    t.send(TrayAction::PauseHotkeys(!t.state.hotkeys_paused))
+                }),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Settings…".to_string(),
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::OpenSettings)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Export debug bundle".to_string(),
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::ExportDebugBundle)),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: "Quit".to_string(),
+                icon_name: "application-exit".to_string(),
+                activate: Box::new(|t: &mut Self| t.send(TrayAction::Quit)),
+                ..Default::default()
+            }
+            .into(),
+        ]);
+        items
+    }
+}
+
+/// StatusNotifierItem tray icon running on its own D-Bus thread.
+pub struct TrayIcon {
+    handle: ksni::Handle<MaVoiceTray>,
+    state: TrayState,
+}
+
+impl TrayIcon {
+    pub fn spawn(proxy: EventLoopProxy<AppEvent>, state: TrayState) -> Self {
+        let service = TrayService::new(MaVoiceTray { proxy, state });
+        let handle = service.handle();
+        service.spawn();
+        log::info!("Tray icon registered");
+        Self { handle, state }
+    }
+
+    /// Push new state to the tray; no-op if nothing changed.
+    pub fn update(&mut self, state: TrayState) {
+        if state == self.state {
+            return;
+        }
+        self.state = state.clone();
+        self.handle.update(move |tray| tray.state = state);
+    }
+
+    pub fn shutdown(&self) {
+        self.handle.shutdown();
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::os::fd::AsFd;
+use std::time::Duration;
+
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_keyboard::KeymapFormat, wl_registry, wl_seat::WlSeat};
+use wayland_client::{delegate_noop, Connection, Dispatch, QueueHandle};
+use wayland_protocols_misc::zwp_virtual_keyboard_v1::client::{
+    zwp_virtual_keyboard_manager_v1::ZwpVirtualKeyboardManagerV1,
+    zwp_virtual_keyboard_v1::ZwpVirtualKeyboardV1,
+};
+
+/// Characters per keymap. Keycodes stay below 255 so X11 clients under
+/// Xwayland can still read them.
+const KEYS_PER_KEYMAP: usize = 200;
+
+/// Pause between characters; some clients drop keys sent in one burst.
+const KEY_DELAY: Duration = Duration::from_millis(2);
+
+/// Types text through the `zwp_virtual_keyboard_v1` protocol that wlroots
+/// compositors (Sway, Hyprland, river, ...) and KWin offer.
+///
+/// Instead of mapping characters onto the user's layout, each chunk of
+/// text gets a generated keymap with one key per distinct character (the
+/// way wtype does it), so accents, CJK and emoji type as themselves on any
+/// layout. Nothing is left in the clipboard.
+pub struct VirtualKeyboard;
+
+struct State;
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(State: ignore WlSeat);
+delegate_noop!(State: ZwpVirtualKeyboardManagerV1);
+delegate_noop!(State: ZwpVirtualKeyboardV1);
+
+/// Whether the Wayland compositor advertises the global `interface`.
+pub fn has_global(interface: &str) -> bool {
+    let Ok(conn) = Connection::connect_to_env() else {
+        return false;
+    };
+    registry_queue_init::<State>(&conn).is_ok_and(|(globals, _)| {
+        globals
+            .contents()
+            .with_list(|list| list.iter().any(|g| g.interface == interface))
+    })
+}
+
+impl VirtualKeyboard {
+    /// Whether the compositor offers the virtual keyboard protocol.
+    pub fn available() -> bool {
+        has_global("zwp_virtual_keyboard_manager_v1")
+    }
+
+    /// Type `text` into the focused surface.
+    pub fn type_text(text: &str) -> Result<(), String> {
+        Self::type_text_with_delay(text, KEY_DELAY)
+    }
+
+    /// Type `text`, pausing `delay` after each character.
+    pub fn type_text_with_delay(text: &str, delay: Duration) -> Result<(), String> {
+        let conn = Connection::connect_to_env().map_err(|e| format!("No Wayland display: {}", e))?;
+        let (globals, mut queue) =
+            registry_queue_init::<State>(&conn).map_err(|e| format!("Wayland registry: {}", e))?;
+        let qh = queue.handle();
+        let seat: WlSeat = globals
+            .bind(&qh, 1..=7, ())
+            .map_err(|e| format!("No Wayland seat: {}", e))?;
+        let manager: ZwpVirtualKeyboardManagerV1 = globals
+            .bind(&qh, 1..=1, ())
+            .map_err(|_| "The compositor has no virtual keyboard protocol (wlroots or KWin only)".to_string())?;
+        let keyboard = manager.create_virtual_keyboard(&seat, &qh, ());
+
+        let chars: Vec<char> = text.chars().filter(|&c| c != '\r').collect();
+        let mut time = 0u32;
+        let mut result = Ok(());
+        for chunk in chunks(&chars) {
+            let (keymap, codes) = keymap_for(&chunk);
+            if let Err(e) = upload_keymap(&keyboard, &keymap) {
+                result = Err(e);
+                break;
+            }
+            keyboard.modifiers(0, 0, 0, 0);
+            for c in &chunk {
+                // Keymap keycodes are evdev codes + 8
+                let code = codes[c] - 8;
+                keyboard.key(time, code, 1);
+                keyboard.key(time + 1, code, 0);
+                time += 2;
+                if let Err(e) = conn.flush() {
+                    result = Err(format!("Wayland connection lost: {}", e));
+                    break;
+                }
+                std::thread::sleep(delay);
+            }
+            if result.is_err() {
+                break;
+            }
+            // Let the compositor take the keys before the keymap changes
+            queue
+                .roundtrip(&mut State)
+                .map_err(|e| format!("Wayland connection lost: {}", e))?;
+        }
+
+        keyboard.destroy();
+        let _ = queue.roundtrip(&mut State);
+        if result.is_ok() {
+            log::info!("Typed {} chars with the virtual keyboard", chars.len());
+        }
+        result
+    }
+}
+
+/// Split `chars` so no piece needs more than [`KEYS_PER_KEYMAP`] keys.
+fn chunks(chars: &[char]) -> Vec<Vec<char>> {
+    let mut pieces = Vec::new();
+    let mut piece = Vec::new();
+    let mut distinct = std::collections::HashSet::new();
+    for &c in chars {
+        if !distinct.contains(&c) && distinct.len() == KEYS_PER_KEYMAP {
+            pieces.push(std::mem::take(&mut piece));
+            distinct.clear();
+        }
+        distinct.insert(c);
+        piece.push(c);
+    }
+    if !piece.is_empty() {
+        pieces.push(piece);
+    }
+    pieces
+}
+
+/// An XKB keymap with one key per distinct character, and each
+/// character's keycode.
+fn keymap_for(chars: &[char]) -> (String, HashMap<char, u32>) {
+    let mut codes = HashMap::new();
+    let mut keycodes = String::new();
+    let mut symbols = String::new();
+    for &c in chars {
+        if codes.contains_key(&c) {
+            continue;
+        }
+        let code = 9 + codes.len() as u32;
+        codes.insert(c, code);
+        keycodes.push_str(&format!("    <K{}> = {};\n", code, code));
+        symbols.push_str(&format!("    key <K{}> {{ [ {} ] }};\n", code, keysym_name(c)));
+    }
+    let maximum = 9 + codes.len().max(1);
+    let keymap = format!(
+        "xkb_keymap {{\n\
+         xkb_keycodes \"mavoice\" {{\n    minimum = 8;\n    maximum = {};\n{}}};\n\
+         xkb_types \"mavoice\" {{ include \"complete\" }};\n\
+         xkb_compatibility \"mavoice\" {{ include \"complete\" }};\n\
+         xkb_symbols \"mavoice\" {{\n{}}};\n\
+         }};\n",
+        maximum, keycodes, symbols
+    );
+    (keymap, codes)
+}
+
+/// Keysym for `c`: named keys for line breaks and tabs, Unicode keysyms
+/// (`U00E9`) for everything else.
+fn keysym_name(c: char) -> String {
+    match c {
+        '\n' => "Return".to_string(),
+        '\t' => "Tab".to_string(),
+        c => format!("U{:04X}", c as u32),
+    }
+}
+
+/// Hand the compositor a keymap through a file it can map.
+fn upload_keymap(keyboard: &ZwpVirtualKeyboardV1, keymap: &str) -> Result<(), String> {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR").map_or_else(std::env::temp_dir, Into::into);
+    let path = dir.join(format!("mavoice-keymap-{}", std::process::id()));
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .map_err(|e| format!("Cannot write keymap: {}", e))?;
+    // The compositor reads it through the descriptor; the name isn't needed
+    let _ = std::fs::remove_file(&path);
+    file.write_all(keymap.as_bytes())
+        .and_then(|_| file.write_all(&[0]))
+        .map_err(|e| format!("Cannot write keymap: {}", e))?;
+    keyboard.keymap(KeymapFormat::XkbV1.into(), file.as_fd(), keymap.len() as u32 + 1);
+    Ok(())
+}
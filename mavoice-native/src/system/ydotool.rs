@@ -0,0 +1,87 @@
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Linux input event types/codes (linux/input-event-codes.h)
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const SYN_REPORT: u16 = 0;
+pub const KEY_LEFTCTRL: u16 = 29;
+pub const KEY_LEFTSHIFT: u16 = 42;
+pub const KEY_V: u16 = 47;
+
+/// Delay between key events; ydotool's own default is 12ms.
+const KEY_DELAY: Duration = Duration::from_millis(12);
+
+/// Minimal client for the ydotoold daemon.
+///
+/// ydotoold listens on a Unix datagram socket and replays each received
+/// `struct input_event` through its uinput device, so synthetic keys work on
+/// any compositor (including GNOME/Mutter, where wtype is rejected).
+pub struct YdotoolClient {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl YdotoolClient {
+    /// Connect to a running ydotoold, checking the usual socket locations.
+    pub fn detect() -> Option<Self> {
+        Self::candidate_paths().into_iter().find_map(|path| {
+            let socket = UnixDatagram::unbound().ok()?;
+            socket.connect(&path).ok()?;
+            Some(Self { socket, path })
+        })
+    }
+
+    fn candidate_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Ok(p) = std::env::var("YDOTOOL_SOCKET") {
+            paths.push(PathBuf::from(p));
+        }
+        if let Ok(dir) = std::env::var("XDG_RUNTIME_DIR") {
+            paths.push(PathBuf::from(dir).join(".ydotool_socket"));
+        }
+        paths.push(PathBuf::from("/tmp/.ydotool_socket"));
+        paths
+    }
+
+    pub fn socket_path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Press `modifiers` + `key`, then release in reverse order.
+    pub fn key_combo(&self, modifiers: &[u16], key: u16) -> std::io::Result<()> {
+        for &m in modifiers {
+            self.key(m, true)?;
+        }
+        self.key(key, true)?;
+        self.key(key, false)?;
+        for &m in modifiers.iter().rev() {
+            self.key(m, false)?;
+        }
+        Ok(())
+    }
+
+    /// Ctrl+V
+    pub fn paste(&self) -> std::io::Result<()> {
+        self.key_combo(&[KEY_LEFTCTRL], KEY_V)
+    }
+
+    /// Send a single key press or release followed by a sync report.
+    pub fn key(&self, code: u16, pressed: bool) -> std::io::Result<()> {
+        self.emit(EV_KEY, code, pressed as i32)?;
+        self.emit(EV_SYN, SYN_REPORT, 0)?;
+        std::thread::sleep(KEY_DELAY);
+        Ok(())
+    }
+
+    /// Write one `struct input_event` (zeroed timeval, type, code, value).
+    fn emit(&self, ev_type: u16, code: u16, value: i32) -> std::io::Result<()> {
+        let mut buf = [0u8; 24];
+        buf[16..18].copy_from_slice(&ev_type.to_ne_bytes());
+        buf[18..20].copy_from_slice(&code.to_ne_bytes());
+        buf[20..24].copy_from_slice(&value.to_ne_bytes());
+        self.socket.send(&buf)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use super::commands::{normalize, AUTO_PUNCT};
+
+/// Built-in code grammar. Values use the same syntax as the user's
+/// `code_map` (see [`CodeAction::parse`]).
+const BUILTIN_GRAMMAR: &[(&str, &str)] = &[
+    ("camel case", "{camel}"),
+    ("pascal case", "{pascal}"),
+    ("snake case", "{snake}"),
+    ("kebab case", "{kebab}"),
+    ("constant case", "{constant}"),
+    ("screaming snake", "{constant}"),
+    ("open paren", "{join}("),
+    ("close paren", "{close})"),
+    ("open bracket", "{join}["),
+    ("close bracket", "{close}]"),
+    ("open brace", "{"),
+    ("close brace", "}"),
+    ("open angle", "{join}<"),
+    ("close angle", "{close}>"),
+    ("open quote", "{open}\""),
+    ("close quote", "{close}\""),
+    ("single quote", "{join}'"),
+    ("backtick", "{join}`"),
+    ("underscore", "{join}_"),
+    ("dot", "{join}."),
+    ("double colon", "{join}::"),
+    ("comma", "{close},"),
+    ("semicolon", "{close};"),
+    ("colon", "{close}:"),
+    ("question mark", "{close}?"),
+    ("bang", "{open}!"),
+    ("dash", "{open}-"),
+    ("dash dash", "{open}--"),
+    ("arrow", "->"),
+    ("fat arrow", "=>"),
+    ("equals", "="),
+    ("double equals", "=="),
+    ("triple equals", "==="),
+    ("not equals", "!="),
+    ("less than", "<"),
+    ("greater than", ">"),
+    ("plus", "+"),
+    ("minus", "-"),
+    ("times", "*"),
+    ("star", "*"),
+    ("slash", "/"),
+    ("backslash", "\\"),
+    ("percent", "%"),
+    ("ampersand", "&"),
+    ("and and", "&&"),
+    ("pipe", "|"),
+    ("or or", "||"),
+    ("hash", "#"),
+    ("dollar", "{open}$"),
+    ("at sign", "{open}@"),
+    ("new line", "\n"),
+    ("tab", "\t"),
+];
+
+/// How the words after a case command are joined into one identifier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    /// fooBar
+    Camel,
+    /// FooBar
+    Pascal,
+    /// foo_bar
+    Snake,
+    /// foo-bar
+    Kebab,
+    /// FOO_BAR
+    Constant,
+}
+
+impl Case {
+    fn format(self, words: &[String]) -> String {
+        match self {
+            Case::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+                .collect(),
+            Case::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+            Case::Snake => words.join("_"),
+            Case::Kebab => words.join("-"),
+            Case::Constant => words.join("_").to_uppercase(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CodeAction {
+    /// A symbol or word with spaces around it ("=", "->", "{").
+    Token(String),
+    /// Glued to both neighbours ("(", ".", "_", "::").
+    Join(String),
+    /// Glued to the previous word, spaced after (")", ",", ";").
+    Close(String),
+    /// Spaced before, glued to the next word ("-", "$", opening quote).
+    Open(String),
+    /// Raw text with no spacing around it (newline, tab).
+    Insert(String),
+    /// Join the words up to the next command into one identifier.
+    Case(Case),
+}
+
+impl CodeAction {
+    /// `{camel}`, `{pascal}`, `{snake}`, `{kebab}`, `{constant}` format the
+    /// following words; `{join}x`, `{close}x` and `{open}x` control the
+    /// spacing around `x`; text containing a newline or tab is inserted
+    /// as-is; anything else is a spaced token.
+    fn parse(value: &str) -> Self {
+        match value {
+            "{camel}" => return CodeAction::Case(Case::Camel),
+            "{pascal}" => return CodeAction::Case(Case::Pascal),
+            "{snake}" => return CodeAction::Case(Case::Snake),
+            "{kebab}" => return CodeAction::Case(Case::Kebab),
+            "{constant}" => return CodeAction::Case(Case::Constant),
+            _ => {}
+        }
+        if let Some(s) = value.strip_prefix("{join}") {
+            CodeAction::Join(s.to_string())
+        } else if let Some(s) = value.strip_prefix("{close}") {
+            CodeAction::Close(s.to_string())
+        } else if let Some(s) = value.strip_prefix("{open}") {
+            CodeAction::Open(s.to_string())
+        } else if value.contains(['\n', '\t']) {
+            CodeAction::Insert(value.to_string())
+        } else {
+            CodeAction::Token(value.to_string())
+        }
+    }
+}
+
+/// Turns spoken symbols and identifier casing into code, for dictating
+/// into terminals and editors: "camel case foo bar equals open paren" ->
+/// `fooBar = (`.
+pub struct CodeDictation {
+    /// (normalized phrase words, action), longest phrases first.
+    grammar: Vec<(Vec<String>, CodeAction)>,
+}
+
+impl CodeDictation {
+    /// Built-in grammar plus `overrides` (phrase -> value). An empty value
+    /// removes a built-in phrase so it is typed literally.
+    pub fn new(overrides: &HashMap<String, String>) -> Self {
+        let mut map: HashMap<String, String> = BUILTIN_GRAMMAR
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        for (phrase, value) in overrides {
+            let phrase = phrase.to_lowercase();
+            if value.is_empty() {
+                map.remove(&phrase);
+            } else {
+                map.insert(phrase, value.clone());
+            }
+        }
+
+        let mut grammar: Vec<(Vec<String>, CodeAction)> = map
+            .iter()
+            .map(|(phrase, value)| {
+                let words: Vec<String> = phrase.split_whitespace().map(normalize).collect();
+                (words, CodeAction::parse(value))
+            })
+            .filter(|(words, _)| !words.is_empty())
+            .collect();
+        grammar.sort_by_key(|g| Reverse(g.0.len()));
+
+        Self { grammar }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let normalized: Vec<String> = tokens.iter().map(|t| normalize(t)).collect();
+
+        let mut out = CodeWriter::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            let Some((len, action)) = self.match_at(&normalized[i..]) else {
+                // Whisper's own sentence punctuation means nothing in code
+                let word = tokens[i].trim_end_matches(AUTO_PUNCT);
+                if !word.is_empty() {
+                    out.action(&CodeAction::Token(word.to_string()));
+                }
+                i += 1;
+                continue;
+            };
+            i += len;
+            if let CodeAction::Case(case) = action {
+                let start = i;
+                while i < tokens.len() && self.match_at(&normalized[i..]).is_none() {
+                    i += 1;
+                }
+                let words: Vec<String> = normalized[start..i]
+                    .iter()
+                    .map(|w| w.chars().filter(|c| c.is_alphanumeric()).collect::<String>())
+                    .filter(|w| !w.is_empty())
+                    .collect();
+                if !words.is_empty() {
+                    out.action(&CodeAction::Token(case.format(&words)));
+                }
+            } else {
+                out.action(action);
+            }
+        }
+        out.finish()
+    }
+
+    fn match_at(&self, words: &[String]) -> Option<(usize, &CodeAction)> {
+        self.grammar
+            .iter()
+            .find(|(phrase, _)| words.len() >= phrase.len() && words[..phrase.len()] == phrase[..])
+            .map(|(phrase, action)| (phrase.len(), action))
+    }
+}
+
+#[derive(Default)]
+struct CodeWriter {
+    out: String,
+    /// The next token is glued to what came before
+    glue: bool,
+}
+
+impl CodeWriter {
+    fn action(&mut self, action: &CodeAction) {
+        match action {
+            CodeAction::Token(s) | CodeAction::Open(s) => {
+                if !self.out.is_empty() && !self.glue && !self.out.ends_with(['\n', '\t', ' ']) {
+                    self.out.push(' ');
+                }
+                self.out.push_str(s);
+                self.glue = matches!(action, CodeAction::Open(_));
+            }
+            CodeAction::Join(s) | CodeAction::Insert(s) => {
+                self.trim_spaces();
+                self.out.push_str(s);
+                self.glue = true;
+            }
+            CodeAction::Close(s) => {
+                self.trim_spaces();
+                self.out.push_str(s);
+                self.glue = false;
+            }
+            CodeAction::Case(_) => {}
+        }
+    }
+
+    fn trim_spaces(&mut self) {
+        self.out.truncate(self.out.trim_end_matches(' ').len());
+    }
+
+    fn finish(self) -> String {
+        self.out.trim_matches(' ').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(text: &str) -> String {
+        CodeDictation::new(&HashMap::new()).apply(text)
+    }
+
+    #[test]
+    fn cases_join_the_following_words() {
+        assert_eq!(code("snake case get user open paren close paren"), "get_user()");
+        assert_eq!(code("pascal case http client"), "HttpClient");
+        assert_eq!(code("constant case max retries equals three semicolon"), "MAX_RETRIES = three;");
+        assert_eq!(code("snake case user name."), "user_name");
+    }
+
+    #[test]
+    fn symbols_are_spaced_by_kind() {
+        assert_eq!(code("self dot name equals one"), "self.name = one");
+        assert_eq!(code("ls dash dash all"), "ls --all");
+        assert_eq!(code("open brace new line close brace"), "{\n}");
+    }
+
+    #[test]
+    fn overrides_add_and_remove_phrases() {
+        let overrides = HashMap::from([
+            ("dot".to_string(), String::new()),
+            ("walrus".to_string(), ":=".to_string()),
+        ]);
+        let code = CodeDictation::new(&overrides);
+        assert_eq!(code.apply("file dot txt"), "file dot txt");
+        assert_eq!(code.apply("x walrus five"), "x := five");
+    }
+}
@@ -0,0 +1,272 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Built-in phrases and what they turn into. Values use the same syntax as
+/// the user's `command_map` (see [`Action::parse`]).
+const BUILTIN_COMMANDS: &[(&str, &str)] = &[
+    ("period", "."),
+    ("full stop", "."),
+    ("comma", ","),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+    ("new line", "\n"),
+    ("newline", "\n"),
+    ("new paragraph", "\n\n"),
+    ("open quote", "{open}\""),
+    ("close quote", "\""),
+    ("end quote", "\""),
+    ("open paren", "{open}("),
+    ("close paren", ")"),
+    ("all caps", "{allcaps}"),
+    ("caps on", "{caps_on}"),
+    ("caps off", "{caps_off}"),
+    ("no space", "{no_space}"),
+    ("scratch that", "{scratch}"),
+];
+
+/// Punctuation Whisper tends to add on its own around spoken commands.
+pub(super) const AUTO_PUNCT: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    /// Attach to the previous word (".", ",", ")", closing quote).
+    Punct(String),
+    /// Space before, none after ("(", opening quote).
+    Open(String),
+    /// Raw text with no spacing around it (newlines).
+    Insert(String),
+    /// An ordinary word.
+    Word(String),
+    /// Delete the previous sentence.
+    Scratch,
+    /// Upper-case the next word.
+    AllCapsNext,
+    CapsOn,
+    CapsOff,
+    /// Join the next word to the previous one.
+    NoSpace,
+}
+
+impl Action {
+    /// `{scratch}`, `{allcaps}`, `{caps_on}`, `{caps_off}`, `{no_space}` are
+    /// actions; `{open}x` opens with `x`; text containing a newline is inserted
+    /// as-is; pure punctuation attaches to the previous word; anything else is
+    /// emitted as a word.
+    fn parse(value: &str) -> Self {
+        match value {
+            "{scratch}" => return Action::Scratch,
+            "{allcaps}" => return Action::AllCapsNext,
+            "{caps_on}" => return Action::CapsOn,
+            "{caps_off}" => return Action::CapsOff,
+            "{no_space}" => return Action::NoSpace,
+            _ => {}
+        }
+        if let Some(open) = value.strip_prefix("{open}") {
+            Action::Open(open.to_string())
+        } else if value.contains('\n') {
+            Action::Insert(value.to_string())
+        } else if !value.is_empty() && value.chars().all(|c| c.is_ascii_punctuation()) {
+            Action::Punct(value.to_string())
+        } else {
+            Action::Word(value.to_string())
+        }
+    }
+}
+
+/// Turns spoken punctuation and editing commands in a transcript into text.
+pub struct SpokenCommands {
+    /// (normalized phrase words, action), longest phrases first.
+    commands: Vec<(Vec<String>, Action)>,
+}
+
+impl SpokenCommands {
+    /// Built-in commands plus `overrides` (phrase -> value). An empty value
+    /// removes a built-in phrase so it is typed literally.
+    pub fn new(overrides: &HashMap<String, String>) -> Self {
+        let mut map: HashMap<String, String> = BUILTIN_COMMANDS
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        for (phrase, value) in overrides {
+            let phrase = phrase.to_lowercase();
+            if value.is_empty() {
+                map.remove(&phrase);
+            } else {
+                map.insert(phrase, value.clone());
+            }
+        }
+
+        let mut commands: Vec<(Vec<String>, Action)> = map
+            .iter()
+            .map(|(phrase, value)| {
+                let words: Vec<String> = phrase.split_whitespace().map(normalize).collect();
+                (words, Action::parse(value))
+            })
+            .filter(|(words, _)| !words.is_empty())
+            .collect();
+        commands.sort_by_key(|c| Reverse(c.0.len()));
+
+        Self { commands }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let normalized: Vec<String> = tokens.iter().map(|t| normalize(t)).collect();
+
+        let mut out = Writer::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            match self.match_at(&normalized[i..]) {
+                Some((len, action)) => {
+                    out.action(action);
+                    i += len;
+                }
+                None => {
+                    out.word(tokens[i]);
+                    i += 1;
+                }
+            }
+        }
+        out.finish()
+    }
+
+    fn match_at(&self, words: &[String]) -> Option<(usize, &Action)> {
+        self.commands
+            .iter()
+            .find(|(phrase, _)| words.len() >= phrase.len() && words[..phrase.len()] == phrase[..])
+            .map(|(phrase, action)| (phrase.len(), action))
+    }
+}
+
+/// Lower-case and strip punctuation, so "Period." matches "period".
+pub(super) fn normalize(token: &str) -> String {
+    token
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    no_space: bool,
+    upper_next: bool,
+    capitalize_next: bool,
+    caps_lock: bool,
+}
+
+impl Writer {
+    fn word(&mut self, word: &str) {
+        if !self.out.is_empty() && !self.no_space && !self.out.ends_with('\n') {
+            self.out.push(' ');
+        }
+        self.no_space = false;
+
+        if self.caps_lock || self.upper_next {
+            self.out.push_str(&word.to_uppercase());
+        } else if self.capitalize_next {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                self.out.extend(first.to_uppercase());
+                self.out.push_str(chars.as_str());
+            }
+        } else {
+            self.out.push_str(word);
+        }
+        self.upper_next = false;
+        self.capitalize_next = false;
+    }
+
+    fn action(&mut self, action: &Action) {
+        match action {
+            Action::Punct(p) => {
+                if p.starts_with(AUTO_PUNCT) {
+                    self.trim_auto_punct();
+                }
+                self.out.push_str(p);
+                self.no_space = false;
+                if p.ends_with(['.', '!', '?']) {
+                    self.capitalize_next = true;
+                }
+            }
+            Action::Open(p) => {
+                if !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+                    self.out.push(' ');
+                }
+                self.out.push_str(p);
+                self.no_space = true;
+            }
+            Action::Insert(s) => {
+                self.out.truncate(self.out.trim_end_matches(' ').len());
+                self.out.push_str(s);
+                self.no_space = true;
+            }
+            Action::Word(w) => self.word(w),
+            Action::Scratch => self.scratch(),
+            Action::AllCapsNext => self.upper_next = true,
+            Action::CapsOn => self.caps_lock = true,
+            Action::CapsOff => self.caps_lock = false,
+            Action::NoSpace => self.no_space = true,
+        }
+    }
+
+    fn trim_auto_punct(&mut self) {
+        let trimmed = self.out.trim_end_matches(AUTO_PUNCT).len();
+        self.out.truncate(trimmed);
+    }
+
+    /// Drop the last sentence, including any punctuation Whisper put after it.
+    fn scratch(&mut self) {
+        let body = self.out.trim_end_matches(|c: char| c.is_whitespace() || AUTO_PUNCT.contains(&c));
+        let keep = body
+            .rfind(['.', '!', '?', '\n'])
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        self.out.truncate(keep);
+        self.no_space = false;
+    }
+
+    /// Keep trailing newlines the user asked for; only spaces are trimmed.
+    fn finish(self) -> String {
+        self.out.trim_matches(' ').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spoken(text: &str) -> String {
+        SpokenCommands::new(&HashMap::new()).apply(text)
+    }
+
+    #[test]
+    fn period_ends_the_sentence() {
+        assert_eq!(spoken("hello period how are you"), "hello. How are you");
+        // Whisper's own punctuation around the command is dropped
+        assert_eq!(spoken("hello, period."), "hello.");
+    }
+
+    #[test]
+    fn new_line_has_no_spaces_around_it() {
+        assert_eq!(spoken("first line new line second line"), "first line\nsecond line");
+    }
+
+    #[test]
+    fn all_caps_upper_cases_the_next_word() {
+        assert_eq!(spoken("all caps nasa rocks"), "NASA rocks");
+    }
+
+    #[test]
+    fn scratch_that_drops_the_last_sentence() {
+        assert_eq!(
+            spoken("I like tea. I hate coffee scratch that I love coffee"),
+            "I like tea. I love coffee"
+        );
+        assert_eq!(spoken("delete this. Scratch that."), "");
+    }
+}
@@ -0,0 +1,80 @@
+/// Characters after which the next word follows without a space.
+const OPENERS: &[char] = &['(', '[', '{', '"', '\'', '“', '‘', '/', '-'];
+
+/// Punctuation that attaches to whatever comes before it.
+const CLOSERS: &[char] = &[',', '.', ';', ':', '!', '?', ')', ']', '}'];
+
+/// Everyday words that are only capitalized at a sentence start, so they
+/// can be lower-cased mid-sentence. Anything else might be a name.
+const COMMON_WORDS: &[&str] = &[
+    "a", "an", "the", "and", "but", "or", "so", "then", "also", "because", "if", "when", "while",
+    "after", "before", "since", "until", "although", "though", "as", "at", "by", "for", "from",
+    "in", "into", "of", "on", "to", "with", "without", "about", "just", "maybe", "now", "well",
+    "yes", "no", "not", "okay", "ok", "please", "thanks", "this", "that", "these", "those", "there",
+    "here", "it", "it's", "its", "we", "we're", "you", "you're", "he", "she", "they", "they're",
+    "my", "your", "our", "their", "his", "her", "me", "us", "them", "what", "which", "who", "why",
+    "how", "where", "is", "are", "was", "were", "be", "been", "do", "does", "did", "have", "has",
+    "had", "can", "could", "would", "should", "let's", "let", "all", "some", "any", "more",
+    "most", "very", "really", "still", "even", "only", "other", "another", "one", "like",
+];
+
+/// Adjust the start of `text` to what is already before the cursor
+/// (`before`, the last few characters): a space after a word or sentence
+/// end but not after whitespace or an opening bracket, and a capital only
+/// where a sentence starts.
+pub fn fit_to_context(text: &str, before: &str) -> String {
+    let text = text.trim_start();
+    let Some(first) = text.chars().next() else {
+        return String::new();
+    };
+
+    let line = before.trim_end_matches([' ', '\t']);
+    let sentence_start = line.is_empty()
+        || line.ends_with('\n')
+        || line.ends_with(['.', '!', '?'])
+        // "said: \"" or "(" after a sentence end: look past the opener
+        || line.trim_end_matches(OPENERS).is_empty();
+    let needs_space = match before.chars().last() {
+        None => false,
+        Some(c) if c.is_whitespace() || OPENERS.contains(&c) => false,
+        Some(_) => !CLOSERS.contains(&first),
+    };
+
+    let body = if sentence_start {
+        capitalize_first(text)
+    } else {
+        lowercase_first(text)
+    };
+    if needs_space {
+        format!(" {}", body)
+    } else {
+        body
+    }
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Lower-case the first word only if Whisper capitalized it as a sentence
+/// start and it's one of [`COMMON_WORDS`]: not "I", acronyms like "NASA"
+/// or anything that could be a name.
+fn lowercase_first(text: &str) -> String {
+    let word: &str = text.split_whitespace().next().unwrap_or_default();
+    let word = word.trim_end_matches(|c: char| !c.is_alphanumeric()).replace('’', "'");
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let sentence_cased = letters.first().is_some_and(|c| c.is_uppercase())
+        && letters[1..].iter().all(|c| c.is_lowercase());
+    if !sentence_cased || !COMMON_WORDS.contains(&word.to_lowercase().as_str()) {
+        return text.to_string();
+    }
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,195 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use super::commands::normalize;
+
+/// Built-in fillers per language. Only sounds and phrases that are almost
+/// never meant as words are listed; "like" or "so" stay.
+const BUILTIN_FILLERS: &[(&str, &[&str])] = &[
+    ("en", &["um", "umm", "uh", "uhh", "uh huh", "er", "erm", "hmm", "mm", "you know"]),
+    ("de", &["äh", "ähm", "öh", "öhm", "hm", "hmm", "mhm"]),
+    ("fr", &["euh", "heu", "bah", "hum"]),
+    ("es", &["eh", "em", "mmm", "o sea"]),
+    ("it", &["ehm", "eh", "cioè"]),
+    ("nl", &["eh", "ehm", "uh", "uhm"]),
+    ("pt", &["hum", "tipo assim"]),
+];
+
+/// Doubled words that are usually meant ("had had", "that that").
+const REAL_REPEATS: &[&str] = &["had", "that", "is", "do"];
+
+/// Removes filler words and stutters ("I I I think", "th- the") from a
+/// transcript. Multi-word fillers like "you know" are only removed where
+/// they are set off by commas or end the text, so "do you know" survives.
+pub struct FillerFilter {
+    /// (normalized phrase words), longest first
+    fillers: Vec<Vec<String>>,
+}
+
+impl FillerFilter {
+    /// Fillers for `language` (a code like "en" or "de-AT"): the entry in
+    /// `overrides` if there is one, else the built-in list. With no language
+    /// (auto-detect) or one without a list only stutters are removed, since
+    /// another language's fillers can be real words.
+    pub fn new(language: Option<&str>, overrides: &HashMap<String, Vec<String>>) -> Self {
+        let lang = language
+            .map(|l| l.split(['-', '_']).next().unwrap_or_default().to_lowercase())
+            .unwrap_or_default();
+        let custom = overrides.iter().find(|(k, _)| k.eq_ignore_ascii_case(&lang));
+        let phrases: Vec<String> = match custom {
+            Some((_, list)) => list.clone(),
+            None => BUILTIN_FILLERS
+                .iter()
+                .find(|(code, _)| *code == lang)
+                .map(|(_, list)| list.iter().map(|s| s.to_string()).collect())
+                .unwrap_or_default(),
+        };
+        let mut fillers: Vec<Vec<String>> = phrases
+            .iter()
+            .map(|p| p.split_whitespace().map(normalize).collect::<Vec<_>>())
+            .filter(|words| !words.is_empty())
+            .collect();
+        fillers.sort_by_key(|w| Reverse(w.len()));
+        Self { fillers }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let normalized: Vec<String> = tokens.iter().map(|t| normalize(t)).collect();
+
+        let mut out: Vec<String> = Vec::new();
+        // The next kept word starts a sentence whose first word was dropped
+        let mut capitalize = false;
+        let mut i = 0;
+        while i < tokens.len() {
+            let len = self
+                .filler_at(&tokens, &normalized, i, &out)
+                .or_else(|| stutter_at(&tokens, &normalized, i));
+            let Some(len) = len else {
+                let mut word = tokens[i].to_string();
+                if std::mem::take(&mut capitalize) {
+                    word = capitalize_first(&word);
+                }
+                out.push(word);
+                i += 1;
+                continue;
+            };
+
+            let sentence_start = out.last().is_none_or(|w| w.ends_with(['.', '!', '?']));
+            if sentence_start && tokens[i].starts_with(char::is_uppercase) {
+                capitalize = true;
+            }
+            // "done, uh." keeps its full stop
+            let last = tokens[i + len - 1];
+            let end = last.trim_end_matches(|c: char| !c.is_alphanumeric());
+            let trail = &last[end.len()..];
+            if trail.contains(['.', '!', '?']) {
+                if let Some(prev) = out.last_mut() {
+                    let trimmed = prev.trim_end_matches([',', ';', ':']).len();
+                    prev.truncate(trimmed);
+                    if !prev.ends_with(['.', '!', '?']) {
+                        prev.push_str(trail.trim_start_matches(','));
+                    }
+                }
+            }
+            i += len;
+        }
+        out.join(" ")
+    }
+
+    /// Length of the filler phrase starting at token `i`, if any.
+    fn filler_at(
+        &self,
+        tokens: &[&str],
+        normalized: &[String],
+        i: usize,
+        out: &[String],
+    ) -> Option<usize> {
+        self.fillers
+            .iter()
+            .find(|phrase| {
+                let end = i + phrase.len();
+                if end > tokens.len() || normalized[i..end] != phrase[..] {
+                    return false;
+                }
+                if phrase.len() == 1 {
+                    return true;
+                }
+                // Only inside punctuation: "so, you know, it ..."
+                let before = out.last().is_none_or(|w| w.ends_with([',', '.', '!', '?', ';', ':']));
+                let after =
+                    end == tokens.len() || tokens[end - 1].ends_with([',', '.', '!', '?', ';', ':']);
+                before && after
+            })
+            .map(Vec::len)
+    }
+}
+
+/// Tokens to drop at `i` when they stutter into the next word: "I I",
+/// "the the", "th- the".
+fn stutter_at(tokens: &[&str], normalized: &[String], i: usize) -> Option<usize> {
+    let next = normalized.get(i + 1).filter(|n| !n.is_empty())?;
+    let word = &normalized[i];
+    // Punctuation between them means the repeat is deliberate ("no, no")
+    if tokens[i].ends_with([',', '.', '!', '?', ';', ':']) {
+        return None;
+    }
+    if word == next
+        && !REAL_REPEATS.contains(&word.as_str())
+        && word.chars().any(char::is_alphabetic)
+    {
+        return Some(1);
+    }
+    let cut = tokens[i].strip_suffix('-').map(normalize)?;
+    (!cut.is_empty() && next.starts_with(&cut)).then_some(1)
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english(text: &str) -> String {
+        FillerFilter::new(Some("en"), &HashMap::new()).apply(text)
+    }
+
+    #[test]
+    fn removes_fillers() {
+        assert_eq!(english("um I think uh we should go"), "I think we should go");
+        assert_eq!(english("Um, the build failed."), "The build failed.");
+        assert_eq!(english("we're done, uh."), "we're done.");
+        assert_eq!(
+            FillerFilter::new(Some("de-AT"), &HashMap::new()).apply("ähm ich weiß"),
+            "ich weiß"
+        );
+    }
+
+    #[test]
+    fn phrases_only_between_punctuation() {
+        assert_eq!(english("do you know the way"), "do you know the way");
+        assert_eq!(english("it was, you know, pretty bad"), "it was, pretty bad");
+    }
+
+    #[test]
+    fn removes_stutters_but_not_deliberate_repeats() {
+        assert_eq!(english("I I I think th- the plan works"), "I think the plan works");
+        assert_eq!(english("no, no, that that is fine"), "no, no, that that is fine");
+    }
+
+    #[test]
+    fn without_a_list_only_stutters_go() {
+        assert_eq!(FillerFilter::new(None, &HashMap::new()).apply("um the the plan"), "um the plan");
+        let overrides = HashMap::from([("en".to_string(), vec!["like".to_string()])]);
+        assert_eq!(
+            FillerFilter::new(Some("en"), &overrides).apply("um it was like big"),
+            "um it was big"
+        );
+    }
+}
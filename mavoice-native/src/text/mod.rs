@@ -0,0 +1,13 @@
+//! Post-processing applied to dictation transcripts before they are injected.
+
+mod code;
+mod commands;
+mod context;
+mod fillers;
+mod numbers;
+
+pub use code::CodeDictation;
+pub use commands::SpokenCommands;
+pub use context::fit_to_context;
+pub use fillers::FillerFilter;
+pub use numbers::NumberFormatter;
@@ -0,0 +1,578 @@
+use super::commands::normalize;
+
+const UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen",
+];
+const TENS: &[&str] = &[
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const SCALES: &[(&str, u64)] = &[
+    ("thousand", 1_000),
+    ("million", 1_000_000),
+    ("billion", 1_000_000_000),
+];
+const ORDINALS: &[&str] = &[
+    "", "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+    "tenth", "eleventh", "twelfth", "thirteenth", "fourteenth", "fifteenth", "sixteenth",
+    "seventeenth", "eighteenth", "nineteenth", "twentieth",
+];
+const MONTHS: &[&str] = &[
+    "january", "february", "march", "april", "may", "june", "july", "august", "september",
+    "october", "november", "december",
+];
+
+/// Word currencies and their symbols.
+const CURRENCIES: &[(&str, &str)] = &[
+    ("dollars", "$"),
+    ("dollar", "$"),
+    ("bucks", "$"),
+    ("euros", "€"),
+    ("euro", "€"),
+];
+
+/// How numbers, amounts, times and dates are written in a locale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Style {
+    decimal: char,
+    thousands: char,
+    /// "25 €" rather than "€25"
+    currency_after: bool,
+    /// "15:30" rather than "3:30 PM"
+    clock_24h: bool,
+    /// "5 March" rather than "March 5"
+    day_first: bool,
+}
+
+impl Style {
+    const US: Style = Style {
+        decimal: '.',
+        thousands: ',',
+        currency_after: false,
+        clock_24h: false,
+        day_first: false,
+    };
+
+    /// Style for a locale like "en-US", "en_GB" or "de".
+    fn for_locale(locale: &str) -> Self {
+        let locale = locale.to_lowercase().replace('_', "-");
+        let lang = locale.split('-').next().unwrap_or_default();
+        match lang {
+            "en" if ["gb", "ie", "au", "nz", "za", "in"]
+                .iter()
+                .any(|r| locale.ends_with(&format!("-{}", r))) =>
+            {
+                Style {
+                    day_first: true,
+                    ..Style::US
+                }
+            }
+            "de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" => Style {
+                decimal: ',',
+                thousands: '.',
+                currency_after: true,
+                clock_24h: true,
+                day_first: true,
+            },
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => Style {
+                decimal: ',',
+                thousands: '\u{a0}',
+                currency_after: true,
+                clock_24h: true,
+                day_first: true,
+            },
+            _ => Style::US,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    /// zero-nine
+    Unit,
+    /// ten-nineteen
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+}
+
+fn number_word(word: &str) -> Option<(Kind, u64)> {
+    if let Some(n) = UNITS.iter().position(|w| *w == word) {
+        let kind = if n < 10 { Kind::Unit } else { Kind::Teen };
+        return Some((kind, n as u64));
+    }
+    if let Some(n) = TENS.iter().position(|w| *w == word) {
+        return Some((Kind::Tens, 20 + 10 * n as u64));
+    }
+    if word == "hundred" {
+        return Some((Kind::Hundred, 100));
+    }
+    SCALES
+        .iter()
+        .find(|(w, _)| *w == word)
+        .map(|(_, scale)| (Kind::Scale, *scale))
+}
+
+/// A number read from the start of a word slice.
+#[derive(Debug, Clone, PartialEq)]
+struct Number {
+    int: u64,
+    /// Digits after "point"
+    frac: Option<String>,
+    /// Words consumed
+    len: usize,
+    /// Spelled out, as opposed to digits Whisper already wrote
+    spelled: bool,
+}
+
+/// "twenty five", "one hundred and five", "a thousand", "three point one
+/// four", or digits ("25", "1,250"). Sequences that don't read as one
+/// number ("five six") stop at the break.
+fn parse_number(words: &[String]) -> Option<Number> {
+    let first = words.first()?;
+    if !first.is_empty() && first.chars().all(|c| c.is_ascii_digit()) {
+        return Some(Number {
+            int: first.parse().ok()?,
+            frac: None,
+            len: 1,
+            spelled: false,
+        });
+    }
+
+    let mut total = 0u64;
+    let mut current = 0u64;
+    let mut last: Option<Kind> = None;
+    let mut last_scale = u64::MAX;
+    let mut i = 0;
+    while i < words.len() {
+        let next = words.get(i + 1).and_then(|w| number_word(w));
+        match words[i].as_str() {
+            "a" if i == 0 && matches!(next, Some((Kind::Hundred | Kind::Scale, _))) => {
+                current = 1;
+                last = Some(Kind::Unit);
+                i += 1;
+                continue;
+            }
+            "and" if matches!(last, Some(Kind::Hundred | Kind::Scale))
+                && matches!(next, Some((Kind::Unit | Kind::Teen | Kind::Tens, _))) =>
+            {
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let Some((kind, n)) = number_word(&words[i]) else {
+            break;
+        };
+        let after_group = matches!(last, None | Some(Kind::Hundred | Kind::Scale));
+        match kind {
+            Kind::Unit | Kind::Teen | Kind::Tens if after_group => current += n,
+            Kind::Unit if last == Some(Kind::Tens) && n > 0 => current += n,
+            Kind::Hundred if matches!(last, Some(Kind::Unit | Kind::Teen)) && current < 100 => {
+                current *= 100
+            }
+            Kind::Scale
+                if matches!(last, Some(Kind::Unit | Kind::Teen | Kind::Tens | Kind::Hundred))
+                    && n < last_scale =>
+            {
+                total += current * n;
+                current = 0;
+                last_scale = n;
+            }
+            _ => break,
+        }
+        last = Some(kind);
+        i += 1;
+    }
+    if i == 0 {
+        return None;
+    }
+
+    // "point" and single digits after it
+    let mut frac = None;
+    if words.get(i).map(String::as_str) == Some("point") {
+        let digits: String = words[i + 1..]
+            .iter()
+            .map_while(|w| match number_word(w) {
+                Some((Kind::Unit, n)) => char::from_digit(n as u32, 10),
+                _ if w == "oh" => Some('0'),
+                _ => None,
+            })
+            .collect();
+        if !digits.is_empty() {
+            i += 1 + digits.len();
+            frac = Some(digits);
+        }
+    }
+
+    Some(Number {
+        int: total + current,
+        frac,
+        len: i,
+        spelled: true,
+    })
+}
+
+/// A day of the month: "fifth", "twenty first", "5th", "5", or "five"
+/// (cardinals only if `cardinal`).
+fn parse_day(words: &[String], cardinal: bool) -> Option<(u32, usize)> {
+    let first = words.first()?;
+    if let Some(n) = ORDINALS.iter().position(|w| *w == first.as_str()).filter(|n| *n > 0) {
+        return Some((n as u32, 1));
+    }
+    if first == "thirtieth" {
+        return Some((30, 1));
+    }
+    if first == "twenty" || first == "thirty" {
+        let base = if first == "twenty" { 20 } else { 30 };
+        let unit = words
+            .get(1)
+            .and_then(|w| ORDINALS[1..10].iter().position(|o| *o == w.as_str()))
+            .map(|n| n as u32 + 1);
+        if let Some(unit) = unit.filter(|u| base + u <= 31) {
+            return Some((base + unit, 2));
+        }
+    }
+    let digits = first.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    if !digits.is_empty() && digits.len() < first.len() && digits.chars().all(|c| c.is_ascii_digit()) {
+        return digits.parse().ok().filter(|d| (1..=31).contains(d)).map(|d| (d, 1));
+    }
+    if cardinal {
+        let n = parse_number(words)?;
+        if n.frac.is_none() && (1..=31).contains(&n.int) {
+            return Some((n.int as u32, n.len));
+        }
+    }
+    None
+}
+
+/// "twenty twenty six", "two thousand and five", "nineteen ninety nine",
+/// "2026".
+fn parse_year(words: &[String]) -> Option<(u64, usize)> {
+    let first = parse_number(words)?;
+    if first.frac.is_some() {
+        return None;
+    }
+    if (1000..=2999).contains(&first.int) {
+        return Some((first.int, first.len));
+    }
+    if !first.spelled || !(10..=99).contains(&first.int) {
+        return None;
+    }
+    let rest = &words[first.len..];
+    if rest.first().map(String::as_str) == Some("oh") {
+        let (kind, n) = rest.get(1).and_then(|w| number_word(w))?;
+        return (kind == Kind::Unit).then_some((first.int * 100 + n, first.len + 2));
+    }
+    let second = parse_number(rest).filter(|n| n.spelled && n.frac.is_none() && n.int < 100)?;
+    Some((first.int * 100 + second.int, first.len + second.len))
+}
+
+fn month_name(index: usize) -> String {
+    let month = MONTHS[index];
+    let mut chars = month.chars();
+    chars
+        .next()
+        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// Rewrites spelled-out numbers in a transcript as digits, amounts, times
+/// and dates in the style of a locale: "twenty five dollars" -> "$25",
+/// "three thirty pm" -> "3:30 PM", "march fifth" -> "March 5".
+///
+/// Lone numbers below ten stay words ("one of them"); everything else with
+/// a number in it is converted.
+pub struct NumberFormatter {
+    style: Style,
+}
+
+impl NumberFormatter {
+    /// Formatting for `locale` ("en-US", "en_GB", "de", ...); unknown
+    /// locales get US style.
+    pub fn new(locale: &str) -> Self {
+        Self {
+            style: Style::for_locale(locale),
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let tokens = split_tokens(text);
+        let words: Vec<String> = tokens
+            .iter()
+            .map(|t| {
+                // Keep "1.5" and "3:30" whole so they aren't read as 15 and 330
+                let inner = t.trim_matches(|c: char| !c.is_alphanumeric());
+                if inner.contains(|c: char| c.is_ascii_digit())
+                    && inner.contains(|c: char| !c.is_alphanumeric())
+                {
+                    inner.to_lowercase()
+                } else {
+                    normalize(t)
+                }
+            })
+            .collect();
+
+        let mut out: Vec<String> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            // Numbers don't run across punctuation ("five, six")
+            let end = (i..tokens.len())
+                .find(|&j| ends_clause(&tokens[j]))
+                .map_or(tokens.len(), |j| j + 1);
+            let span = &words[i..end];
+            match self.date_at(span).or_else(|| self.number_at(span)) {
+                Some((formatted, len)) => {
+                    let lead: String = tokens[i].chars().take_while(|c| !c.is_alphanumeric()).collect();
+                    let last = &tokens[i + len - 1];
+                    let trail = if is_meridiem(last) {
+                        &last[4..]
+                    } else {
+                        &last[last.trim_end_matches(|c: char| !c.is_alphanumeric()).len()..]
+                    };
+                    out.push(format!("{}{}{}", lead, formatted, trail));
+                    i += len;
+                }
+                None => {
+                    out.push(tokens[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        out.join(" ")
+    }
+
+    /// A number, possibly with a currency, percent sign, or clock time.
+    fn number_at(&self, words: &[String]) -> Option<(String, usize)> {
+        let num = parse_number(words)?;
+        let rest = &words[num.len..];
+        let next = rest.first().map(String::as_str);
+
+        if let Some((_, symbol)) = CURRENCIES.iter().find(|(w, _)| Some(*w) == next) {
+            let mut len = num.len + 1;
+            let mut frac = num.frac.clone();
+            // "... and fifty cents"
+            if frac.is_none() && rest.get(1).map(String::as_str) == Some("and") {
+                if let Some(cents) = parse_number(&rest[2..]).filter(|c| c.int < 100 && c.frac.is_none()) {
+                    if matches!(rest.get(2 + cents.len).map(String::as_str), Some("cents" | "cent")) {
+                        frac = Some(format!("{:02}", cents.int));
+                        len += 2 + cents.len;
+                    }
+                }
+            }
+            let amount = self.format_number(num.int, frac.as_deref(), 1000);
+            let formatted = if self.style.currency_after {
+                format!("{}\u{a0}{}", amount, symbol)
+            } else {
+                format!("{}{}", symbol, amount)
+            };
+            return Some((formatted, len));
+        }
+
+        match next {
+            Some("percent") => {
+                let amount = self.format_number(num.int, num.frac.as_deref(), 10_000);
+                return Some((format!("{}%", amount), num.len + 1));
+            }
+            Some("per") if rest.get(1).map(String::as_str) == Some("cent") => {
+                let amount = self.format_number(num.int, num.frac.as_deref(), 10_000);
+                return Some((format!("{}%", amount), num.len + 2));
+            }
+            _ => {}
+        }
+
+        if let Some(time) = self.time_at(&num, rest) {
+            return Some(time);
+        }
+
+        if !num.spelled || (num.len == 1 && num.int < 10 && num.frac.is_none()) {
+            return None;
+        }
+        Some((self.format_number(num.int, num.frac.as_deref(), 10_000), num.len))
+    }
+
+    /// "seven o'clock", "three pm", "three thirty pm", "twelve oh five am".
+    fn time_at(&self, hour: &Number, rest: &[String]) -> Option<(String, usize)> {
+        if hour.frac.is_some() || hour.int > 24 {
+            return None;
+        }
+        let meridiem = |w: Option<&String>| match w.map(String::as_str) {
+            Some("am") => Some(false),
+            Some("pm") => Some(true),
+            _ => None,
+        };
+
+        if matches!(rest.first().map(String::as_str), Some("o'clock" | "oclock")) {
+            return Some((self.format_time(hour.int, 0, None), hour.len + 1));
+        }
+        if hour.int == 0 || hour.int > 12 {
+            return None;
+        }
+        if let Some(pm) = meridiem(rest.first()) {
+            return Some((self.format_time(hour.int, 0, Some(pm)), hour.len + 1));
+        }
+
+        // Minutes are "oh five" or ten to fifty-nine
+        let (minute, len) = if rest.first().map(String::as_str) == Some("oh") {
+            match rest.get(1).and_then(|w| number_word(w)) {
+                Some((Kind::Unit, n)) => (n, 2),
+                _ => return None,
+            }
+        } else {
+            let m = parse_number(rest)
+                .filter(|m| m.spelled && m.frac.is_none() && (10..60).contains(&m.int))?;
+            (m.int, m.len)
+        };
+        let pm = meridiem(rest.get(len))?;
+        Some((self.format_time(hour.int, minute, Some(pm)), hour.len + len + 1))
+    }
+
+    /// "march fifth", "march the fifth, twenty twenty six", "the fifth of
+    /// march".
+    fn date_at(&self, words: &[String]) -> Option<(String, usize)> {
+        let month_at =
+            |w: Option<&String>| w.and_then(|w| MONTHS.iter().position(|m| *m == w.as_str()));
+
+        let (month, day, mut len) = if let Some(month) = month_at(words.first()) {
+            let skip = if words.get(1).map(String::as_str) == Some("the") { 2 } else { 1 };
+            // "may two" is more likely the verb than the date
+            let (day, day_len) = parse_day(&words[skip.min(words.len())..], MONTHS[month] != "may")?;
+            (month, day, skip + day_len)
+        } else {
+            let skip = usize::from(words.first().map(String::as_str) == Some("the"));
+            let (day, day_len) = parse_day(&words[skip..], false)?;
+            let of = skip + day_len;
+            if words.get(of).map(String::as_str) != Some("of") {
+                return None;
+            }
+            let month = month_at(words.get(of + 1))?;
+            (month, day, of + 2)
+        };
+
+        let year = parse_year(&words[len..]).map(|(year, year_len)| {
+            len += year_len;
+            year
+        });
+        let name = month_name(month);
+        let formatted = match (self.style.day_first, year) {
+            (false, Some(year)) => format!("{} {}, {}", name, day, year),
+            (false, None) => format!("{} {}", name, day),
+            (true, Some(year)) => format!("{} {} {}", day, name, year),
+            (true, None) => format!("{} {}", day, name),
+        };
+        Some((formatted, len))
+    }
+
+    /// Digits with a thousands separator from `group_from` up.
+    fn format_number(&self, int: u64, frac: Option<&str>, group_from: u64) -> String {
+        let digits = int.to_string();
+        let mut out = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if int >= group_from && i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(self.style.thousands);
+            }
+            out.push(c);
+        }
+        if let Some(frac) = frac {
+            out.push(self.style.decimal);
+            out.push_str(frac);
+        }
+        out
+    }
+
+    fn format_time(&self, hour: u64, minute: u64, pm: Option<bool>) -> String {
+        let hour24 = match pm {
+            Some(true) if hour < 12 => hour + 12,
+            Some(false) if hour == 12 => 0,
+            _ => hour,
+        };
+        if self.style.clock_24h {
+            return format!("{}:{:02}", hour24, minute);
+        }
+        match pm {
+            Some(pm) => format!("{}:{:02} {}", hour, minute, if pm { "PM" } else { "AM" }),
+            None => format!("{}:{:02}", hour, minute),
+        }
+    }
+}
+
+/// Whitespace tokens, with "twenty-five" split into its words.
+fn split_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for token in text.split_whitespace() {
+        let parts: Vec<&str> = token.split('-').collect();
+        let spelled = parts.len() > 1
+            && parts
+                .iter()
+                .all(|p| number_word(&normalize(p)).is_some() || ORDINALS.contains(&normalize(p).as_str()));
+        if spelled {
+            tokens.extend(parts.iter().map(|p| p.to_string()));
+        } else {
+            tokens.push(token.to_string());
+        }
+    }
+    tokens
+}
+
+/// "a.m." or "p.m.", possibly with punctuation after.
+fn is_meridiem(token: &str) -> bool {
+    let lower = token.to_lowercase();
+    lower.starts_with("a.m.") || lower.starts_with("p.m.")
+}
+
+/// Ends with punctuation other than the dots of "a.m."/"p.m.".
+fn ends_clause(token: &str) -> bool {
+    if is_meridiem(token) {
+        return token.len() > 4;
+    }
+    token.ends_with([',', '.', ';', ':', '!', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us(text: &str) -> String {
+        NumberFormatter::new("en-US").apply(text)
+    }
+
+    #[test]
+    fn ordinals_become_dates() {
+        assert_eq!(us("march fifth"), "March 5");
+        assert_eq!(us("march twenty-first"), "March 21");
+        assert_eq!(us("on the twenty first of may"), "on May 21");
+        assert_eq!(us("march fifth twenty twenty six"), "March 5, 2026");
+        assert_eq!(NumberFormatter::new("en-GB").apply("march fifth"), "5 March");
+    }
+
+    #[test]
+    fn may_with_a_cardinal_stays_a_verb() {
+        assert_eq!(us("march five"), "March 5");
+        assert_eq!(us("may two"), "may two");
+    }
+
+    #[test]
+    fn and_only_joins_after_hundreds_and_scales() {
+        assert_eq!(us("we have one hundred and five apples"), "we have 105 apples");
+        assert_eq!(us("a thousand and one nights"), "1001 nights");
+        assert_eq!(us("five and six"), "five and six");
+        assert_eq!(us("five dollars and fifty cents"), "$5.50");
+    }
+
+    #[test]
+    fn point_reads_single_digits() {
+        assert_eq!(us("three point one four"), "3.14");
+        assert_eq!(us("two point oh five"), "2.05");
+        assert_eq!(NumberFormatter::new("de").apply("three point five"), "3,5");
+    }
+
+    #[test]
+    fn amounts_and_times() {
+        assert_eq!(us("it costs twenty five dollars"), "it costs $25");
+        assert_eq!(us("three thirty pm"), "3:30 PM");
+        assert_eq!(NumberFormatter::new("de").apply("three thirty pm"), "15:30");
+        assert_eq!(us("one of them"), "one of them");
+    }
+}
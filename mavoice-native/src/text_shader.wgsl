@@ -0,0 +1,17 @@
+// maVoice text layer — the CPU-drawn overlay text, premultiplied, faded by
+// one alpha uniform. Only used when presenting straight to a wgpu surface.
+
+@group(0) @binding(0) var text_tex: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> fade: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(i32(vi & 1u)) * 2.0 - 1.0;
+    let y = f32(i32(vi >> 1u)) * 2.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureLoad(text_tex, vec2<i32>(pos.xy), 0) * fade.x;
+}
@@ -0,0 +1,41 @@
+// maVoice toast shader — the rounded bubble behind the transcript toast.
+// Dark glass with a thin border in the Done color, faded as one.
+
+struct Uniforms {
+    resolution: vec2<f32>,
+    alpha: f32,
+    _pad: f32,
+    color: vec3<f32>,
+    _pad2: f32,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+
+const RADIUS: f32 = 12.0;
+const BORDER: f32 = 1.5;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(i32(vi & 1u)) * 2.0 - 1.0;
+    let y = f32(i32(vi >> 1u)) * 2.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+// Signed distance to a rounded rectangle centered on the origin
+fn rounded_box(p: vec2<f32>, half: vec2<f32>, r: f32) -> f32 {
+    let q = abs(p) - half + vec2<f32>(r);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let half = u.resolution * 0.5;
+    let d = rounded_box(pos.xy - half, half - vec2<f32>(1.0), RADIUS);
+    let inside = clamp(0.5 - d, 0.0, 1.0);
+    let edge = clamp(1.0 - abs(d + BORDER) / BORDER, 0.0, 1.0);
+
+    let fill = vec3<f32>(0.06, 0.07, 0.09);
+    let rgb = mix(fill, pow(u.color, vec3<f32>(1.0 / 2.2)), edge * 0.8);
+    let a = inside * mix(0.78, 1.0, edge) * u.alpha;
+    return vec4<f32>(rgb * a, a);
+}
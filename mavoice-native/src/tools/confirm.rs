@@ -0,0 +1,95 @@
+use serde_json::Value;
+use std::time::Duration;
+
+/// How long the approval notification waits before treating silence as a denial.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The user's answer to a confirmation prompt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Approval {
+    Once,
+    Always,
+    Denied,
+}
+
+/// True if `name` matches any pattern: an exact name, or a prefix ending in `*`.
+pub fn matches_any(name: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|p| match p.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => p == name,
+    })
+}
+
+/// Like [`matches_any`], but a tool of a `kind` ("mcp", "custom") also
+/// matches as `<kind>:<name>`, so `mcp:*` covers every MCP tool.
+pub fn matches_tool(name: &str, kind: Option<&str>, patterns: &[String]) -> bool {
+    matches_any(name, patterns)
+        || kind.is_some_and(|kind| matches_any(&format!("{}:{}", kind, name), patterns))
+}
+
+/// Ask the user to approve a tool call through a desktop notification with
+/// Allow / Always allow / Deny actions. Anything other than an explicit
+/// approval (dismissal, timeout, no notification daemon) counts as a denial.
+pub async fn ask(name: &str, args: &Value) -> Approval {
+    let summary = format!("Gemini wants to run {}", name);
+    let body = describe(name, args);
+
+    log::info!("[Tool:{}] Waiting for user confirmation", name);
+
+    let result = tokio::time::timeout(
+        CONFIRM_TIMEOUT,
+        tokio::process::Command::new("notify-send")
+            .args([
+                "--app-name=maVoice",
+                "--urgency=critical",
+                "--icon=dialog-warning",
+                "--wait",
+                "--action=allow=Allow",
+                "--action=always=Always allow",
+                "--action=deny=Deny",
+                &summary,
+                &body,
+            ])
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+
+    let approval = match result {
+        Ok(Ok(output)) => match String::from_utf8_lossy(&output.stdout).trim() {
+            "allow" => Approval::Once,
+            "always" => Approval::Always,
+            _ => Approval::Denied,
+        },
+        Ok(Err(e)) => {
+            log::warn!("[Tool:{}] notify-send failed, denying: {}", name, e);
+            Approval::Denied
+        }
+        Err(_) => {
+            log::info!("[Tool:{}] Confirmation timed out", name);
+            Approval::Denied
+        }
+    };
+
+    log::info!("[Tool:{}] {:?}", name, approval);
+    approval
+}
+
+/// Short human-readable summary of what the call will do.
+fn describe(name: &str, args: &Value) -> String {
+    let text = match (name, args.get("command").and_then(|c| c.as_str())) {
+        ("run_command", Some(cmd)) => cmd.to_string(),
+        _ => args.to_string(),
+    };
+    let mut short: String = text.chars().take(300).collect();
+    if short.len() < text.len() {
+        short.push('…');
+    }
+    // Notification daemons render the body as markup, which could hide
+    // part of the command
+    escape_markup(&short)
+}
+
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
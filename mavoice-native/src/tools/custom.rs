@@ -0,0 +1,107 @@
+use serde_json::{json, Value};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::CustomToolConfig;
+
+/// Build the Gemini function declaration for a config-defined tool.
+pub fn declaration(tool: &CustomToolConfig) -> Value {
+    let parameters = tool
+        .parameters
+        .clone()
+        .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+    json!({
+        "name": tool.name,
+        "description": tool.description,
+        "parameters": parameters,
+    })
+}
+
+/// Run a config-defined tool with the model-supplied arguments.
+pub async fn execute(tool: &CustomToolConfig, args: &Value) -> Value {
+    let mut cmd = if let Some(template) = &tool.command {
+        let script = render(template, args, shell_quote);
+        log::info!("[Tool:{}] Executing: {}", tool.name, script);
+        let mut cmd = tokio::process::Command::new("bash");
+        cmd.args(["-c", &script]);
+        cmd
+    } else if let Some((program, rest)) = tool.exec.split_first() {
+        let argv: Vec<String> = rest
+            .iter()
+            .map(|a| render(a, args, |s| s.to_string()))
+            .collect();
+        log::info!("[Tool:{}] Executing: {} {:?}", tool.name, program, argv);
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(&argv);
+        cmd
+    } else {
+        return json!({ "error": format!("Tool '{}' has no command or exec configured", tool.name) });
+    };
+
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => return json!({ "error": format!("Command failed to execute: {}", e) }),
+    };
+
+    // Write stdin while reading the output, all under the timeout: a tool
+    // that never reads stdin (or fills its stdout first) can't hang us
+    let stdin = child.stdin.take();
+    let payload = args.to_string();
+    let run = async move {
+        let write = async {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(payload.as_bytes()).await;
+            }
+        };
+        let ((), output) = tokio::join!(write, child.wait_with_output());
+        output
+    };
+    let result = tokio::time::timeout(std::time::Duration::from_secs(tool.timeout_secs), run).await;
+
+    match result {
+        Ok(Ok(output)) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            json!({
+                "exit_code": output.status.code().unwrap_or(-1),
+                "stdout": stdout.chars().take(4000).collect::<String>(),
+                "stderr": stderr.chars().take(4000).collect::<String>(),
+            })
+        }
+        Ok(Err(e)) => json!({ "error": format!("Command failed: {}", e) }),
+        Err(_) => json!({ "error": format!("Command timed out after {} seconds", tool.timeout_secs) }),
+    }
+}
+
+/// Replace `{{key}}` placeholders with argument values passed through `escape`.
+/// Unknown keys expand to an empty string.
+fn render(template: &str, args: &Value, escape: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let key = rest[start + 2..start + 2 + len].trim();
+        let value = match args.get(key) {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(v) => v.to_string(),
+        };
+        out.push_str(&escape(&value));
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Single-quote a value for bash.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
@@ -0,0 +1,187 @@
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// Bytes of a file `read_file` will open.
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+/// File text returned to Gemini, in characters.
+const MAX_READ_CHARS: usize = 8000;
+
+fn not_configured() -> Value {
+    json!({
+        "error": "No directories are set up for file access. Ask the user to set file_dirs in \
+                  ~/.config/mavoice/config.toml, e.g. file_dirs = [\"~/Documents/notes\"]."
+    })
+}
+
+/// Resolve `path` (absolute, `~/...`, or relative to the first directory)
+/// and make sure it stays inside one of `roots`, following symlinks for
+/// the part of the path that exists.
+fn resolve(path: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Empty path".to_string());
+    }
+    let joined = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().ok_or("No home directory")?.join(rest),
+        None => roots[0].join(path),
+    };
+
+    let mut normal = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::ParentDir => {
+                normal.pop();
+            }
+            Component::CurDir => {}
+            other => normal.push(other),
+        }
+    }
+
+    // Canonicalize the longest existing ancestor so a symlink can't lead out
+    let mut existing = normal.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        missing.push(existing.file_name().ok_or("Invalid path")?);
+        existing = existing.parent().ok_or("Invalid path")?;
+    }
+    let mut real = existing.canonicalize().map_err(|e| format!("Cannot resolve {}: {}", existing.display(), e))?;
+    real.extend(missing.iter().rev());
+
+    let allowed = roots
+        .iter()
+        .any(|root| root.canonicalize().is_ok_and(|root| real.starts_with(root)));
+    if !allowed {
+        let listed: Vec<String> = roots.iter().map(|d| d.display().to_string()).collect();
+        return Err(format!("{} is outside the allowed directories: {}", path, listed.join(", ")));
+    }
+    Ok(real)
+}
+
+/// Read a text file inside `file_dirs`.
+pub async fn read(args: &Value, dirs: Vec<PathBuf>) -> Value {
+    let path = match args.get("path").and_then(|v| v.as_str()) {
+        Some(p) => p.to_string(),
+        None => return json!({ "error": "Missing 'path' parameter" }),
+    };
+    if dirs.is_empty() {
+        return not_configured();
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let file = match resolve(&path, &dirs) {
+            Ok(file) => file,
+            Err(e) => return json!({ "error": e }),
+        };
+        if file.is_dir() {
+            return list_dir(&file);
+        }
+        match std::fs::metadata(&file) {
+            Ok(meta) if meta.len() > MAX_READ_BYTES => {
+                return json!({ "error": format!("{} is too large to read ({} bytes)", file.display(), meta.len()) });
+            }
+            Ok(_) => {}
+            Err(e) => return json!({ "error": format!("Cannot read {}: {}", file.display(), e) }),
+        }
+
+        log::info!("[Tool:read_file] Reading {}", file.display());
+        let bytes = match std::fs::read(&file) {
+            Ok(bytes) => bytes,
+            Err(e) => return json!({ "error": format!("Cannot read {}: {}", file.display(), e) }),
+        };
+        let Ok(text) = String::from_utf8(bytes) else {
+            return json!({ "error": format!("{} is not a text file", file.display()) });
+        };
+
+        let chars = text.chars().count();
+        let mut response = json!({
+            "path": file.display().to_string(),
+            "text": text.chars().take(MAX_READ_CHARS).collect::<String>(),
+        });
+        if chars > MAX_READ_CHARS {
+            response["truncated"] = json!(format!("First {} of {} characters", MAX_READ_CHARS, chars));
+        }
+        response
+    })
+    .await
+    .unwrap_or_else(|e| json!({ "error": format!("Task failed: {}", e) }))
+}
+
+/// Entries of a directory, so Gemini can find the file it's after.
+fn list_dir(dir: &Path) -> Value {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => {
+            let mut names: Vec<String> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| {
+                    let name = e.file_name().to_string_lossy().into_owned();
+                    if e.path().is_dir() { format!("{}/", name) } else { name }
+                })
+                .collect();
+            names.sort();
+            json!({ "path": dir.display().to_string(), "entries": names })
+        }
+        Err(e) => json!({ "error": format!("Cannot list {}: {}", dir.display(), e) }),
+    }
+}
+
+/// Write a text file inside `file_dirs`. Existing files are only replaced
+/// or appended to when `mode` says so.
+pub async fn write(args: &Value, dirs: Vec<PathBuf>) -> Value {
+    let path = match args.get("path").and_then(|v| v.as_str()) {
+        Some(p) => p.to_string(),
+        None => return json!({ "error": "Missing 'path' parameter" }),
+    };
+    let content = match args.get("content").and_then(|v| v.as_str()) {
+        Some(c) => c.to_string(),
+        None => return json!({ "error": "Missing 'content' parameter" }),
+    };
+    let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("create").to_string();
+    if dirs.is_empty() {
+        return not_configured();
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let file = match resolve(&path, &dirs) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("[Tool:write_file] Refused {}: {}", path, e);
+                return json!({ "error": e, "path": path });
+            }
+        };
+        let shown = file.display().to_string();
+
+        let mut options = std::fs::OpenOptions::new();
+        match mode.as_str() {
+            "create" => options.write(true).create_new(true),
+            "overwrite" => options.write(true).create(true).truncate(true),
+            "append" => options.append(true).create(true),
+            other => {
+                return json!({ "error": format!("Unknown mode '{}' (create, overwrite or append)", other), "path": shown });
+            }
+        };
+
+        let result = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| options.open(&file))
+            .and_then(|mut f| f.write_all(content.as_bytes()));
+        match result {
+            Ok(()) => {
+                log::info!("[Tool:write_file] {} {} bytes to {}", mode, content.len(), shown);
+                json!({ "success": true, "path": shown, "bytes": content.len(), "mode": mode })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => json!({
+                "error": format!("{} already exists; use mode \"overwrite\" or \"append\"", shown),
+                "path": shown,
+            }),
+            Err(e) => {
+                log::warn!("[Tool:write_file] Failed to write {}: {}", shown, e);
+                json!({ "error": format!("Cannot write {}: {}", shown, e), "path": shown })
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|e| json!({ "error": format!("Task failed: {}", e) }))
+}
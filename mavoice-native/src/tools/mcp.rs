@@ -0,0 +1,296 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
+
+use crate::config::McpServerConfig;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Gemini function names: letters, digits, `_`, `.`, `-`; at most 64 chars.
+const MAX_NAME_LEN: usize = 64;
+
+/// JSON Schema keywords that Gemini's function declaration schema rejects.
+const UNSUPPORTED_SCHEMA_KEYS: &[&str] = &["$schema", "$defs", "definitions", "additionalProperties"];
+
+struct Connection {
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+}
+
+/// One running MCP server speaking newline-delimited JSON-RPC over stdio.
+struct McpServer {
+    name: String,
+    conn: Mutex<Connection>,
+    _child: Child,
+}
+
+impl McpServer {
+    async fn spawn(config: &McpServerConfig) -> Result<Self, String> {
+        let mut child = tokio::process::Command::new(&config.command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to launch '{}': {}", config.command, e))?;
+
+        let stdin = child.stdin.take().ok_or("No stdin on MCP server")?;
+        let stdout = child.stdout.take().ok_or("No stdout on MCP server")?;
+
+        let server = Self {
+            name: config.name.clone(),
+            conn: Mutex::new(Connection {
+                stdin,
+                stdout: BufReader::new(stdout).lines(),
+                next_id: 0,
+            }),
+            _child: child,
+        };
+
+        server
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "maVoice", "version": env!("CARGO_PKG_VERSION") }
+                }),
+            )
+            .await?;
+        server
+            .notify("notifications/initialized", json!({}))
+            .await?;
+
+        Ok(server)
+    }
+
+    /// Send a request and wait for the response with the matching id.
+    /// Notifications and server-initiated requests received meanwhile are ignored.
+    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let mut guard = self.conn.lock().await;
+        let conn = &mut *guard;
+        conn.next_id += 1;
+        let id = conn.next_id;
+
+        let msg = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        write_message(&mut conn.stdin, &msg).await?;
+
+        let read = async {
+            loop {
+                let line = conn
+                    .stdout
+                    .next_line()
+                    .await
+                    .map_err(|e| format!("Read failed: {}", e))?
+                    .ok_or_else(|| "Server closed its output".to_string())?;
+                let Ok(resp) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if resp.get("id").and_then(|v| v.as_u64()) != Some(id) || resp.get("method").is_some() {
+                    continue;
+                }
+                if let Some(err) = resp.get("error") {
+                    let message = err.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+                    return Err(format!("{} failed: {}", method, message));
+                }
+                return Ok::<Value, String>(resp.get("result").cloned().unwrap_or(Value::Null));
+            }
+        };
+
+        tokio::time::timeout(REQUEST_TIMEOUT, read)
+            .await
+            .map_err(|_| format!("{} timed out", method))?
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        let mut conn = self.conn.lock().await;
+        let msg = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        write_message(&mut conn.stdin, &msg).await
+    }
+
+    /// Fetch every tool the server exposes, following pagination cursors.
+    async fn list_tools(&self) -> Result<Vec<Value>, String> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+            if let Some(list) = result.get("tools").and_then(|t| t.as_array()) {
+                tools.extend(list.iter().cloned());
+            }
+            cursor = result
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+}
+
+async fn write_message(stdin: &mut ChildStdin, msg: &Value) -> Result<(), String> {
+    let mut line = msg.to_string();
+    line.push('\n');
+    stdin
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("Write failed: {}", e))?;
+    stdin.flush().await.map_err(|e| format!("Write failed: {}", e))
+}
+
+struct McpTool {
+    server: usize,
+    /// Name on the MCP server
+    remote_name: String,
+    /// Name declared to Gemini (`<server>__<tool>`)
+    declared_name: String,
+    declaration: Value,
+}
+
+/// All configured MCP servers and the tools they expose.
+///
+/// Dropping the hub kills the server processes.
+pub struct McpHub {
+    servers: Vec<McpServer>,
+    tools: Vec<McpTool>,
+}
+
+impl McpHub {
+    /// Launch every configured server and collect its tools.
+    /// Servers that fail to start are logged and skipped.
+    pub async fn start(configs: &[McpServerConfig]) -> Self {
+        let mut hub = Self {
+            servers: Vec::new(),
+            tools: Vec::new(),
+        };
+
+        for config in configs {
+            let server = match McpServer::spawn(config).await {
+                Ok(s) => s,
+                Err(e) => {
+                    log::warn!("[MCP] {}: {}", config.name, e);
+                    continue;
+                }
+            };
+            let tools = match server.list_tools().await {
+                Ok(t) => t,
+                Err(e) => {
+                    log::warn!("[MCP] {}: {}", config.name, e);
+                    continue;
+                }
+            };
+
+            let idx = hub.servers.len();
+            for tool in &tools {
+                let Some(remote_name) = tool.get("name").and_then(|n| n.as_str()) else {
+                    continue;
+                };
+                let declared_name = declared_name(&server.name, remote_name);
+                let mut declaration = json!({
+                    "name": declared_name,
+                    "description": tool.get("description").and_then(|d| d.as_str()).unwrap_or(remote_name),
+                });
+                if let Some(schema) = tool.get("inputSchema").map(clean_schema) {
+                    let has_props = schema
+                        .get("properties")
+                        .and_then(|p| p.as_object())
+                        .is_some_and(|p| !p.is_empty());
+                    if has_props {
+                        declaration["parameters"] = schema;
+                    }
+                }
+                hub.tools.push(McpTool {
+                    server: idx,
+                    remote_name: remote_name.to_string(),
+                    declared_name,
+                    declaration,
+                });
+            }
+
+            log::info!("[MCP] {}: {} tools", server.name, tools.len());
+            hub.servers.push(server);
+        }
+
+        hub
+    }
+
+    pub fn declarations(&self) -> impl Iterator<Item = &Value> {
+        self.tools.iter().map(|t| &t.declaration)
+    }
+
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.tools.iter().any(|t| t.declared_name == name)
+    }
+
+    /// Call a tool by its declared name. Returns `None` if no server owns it.
+    pub async fn call(&self, name: &str, args: &Value) -> Option<Value> {
+        let tool = self.tools.iter().find(|t| t.declared_name == name)?;
+        let server = &self.servers[tool.server];
+        log::info!("[MCP] {} -> {}", server.name, tool.remote_name);
+
+        let params = json!({ "name": tool.remote_name, "arguments": args });
+        let result = match server.request("tools/call", params).await {
+            Ok(r) => r,
+            Err(e) => return Some(json!({ "error": e })),
+        };
+
+        // Flatten the content blocks into text; Gemini only needs something readable.
+        let text: Vec<String> = result
+            .get("content")
+            .and_then(|c| c.as_array())
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .map(|b| match b.get("text").and_then(|t| t.as_str()) {
+                        Some(t) => t.to_string(),
+                        None => format!(
+                            "[{} content omitted]",
+                            b.get("type").and_then(|t| t.as_str()).unwrap_or("binary")
+                        ),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let text: String = text.join("\n").chars().take(8000).collect();
+
+        if result.get("isError").and_then(|e| e.as_bool()).unwrap_or(false) {
+            Some(json!({ "error": text }))
+        } else {
+            Some(json!({ "result": text }))
+        }
+    }
+}
+
+fn declared_name(server: &str, tool: &str) -> String {
+    format!("{}__{}", server, tool)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') { c } else { '_' })
+        .take(MAX_NAME_LEN)
+        .collect()
+}
+
+/// Strip JSON Schema keywords Gemini does not accept, recursively.
+fn clean_schema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(k, _)| !UNSUPPORTED_SCHEMA_KEYS.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), clean_schema(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(clean_schema).collect()),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,126 @@
+use rusqlite::{Connection, OpenFlags};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// ShieldCortex-compatible schema, so an existing ShieldCortex database can
+/// be used as is. Only the columns these tools touch are required.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS memories (
+        id       INTEGER PRIMARY KEY,
+        type     TEXT NOT NULL DEFAULT 'long_term',
+        category TEXT NOT NULL DEFAULT 'note',
+        title    TEXT NOT NULL,
+        content  TEXT NOT NULL,
+        project  TEXT,
+        salience REAL NOT NULL DEFAULT 0.5,
+        scope    TEXT,
+        source   TEXT
+    );
+    CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(title, content, tags);
+";
+
+fn not_configured() -> Value {
+    json!({
+        "error": "Memory is not set up. Ask the user to set memory_db in ~/.config/mavoice/config.toml, \
+                  e.g. memory_db = \"~/.local/share/mavoice/memories.db\"."
+    })
+}
+
+/// Open the memory database, creating it and its tables on first use.
+fn open(path: &Path) -> Result<Connection, String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("Cannot open memory DB {}: {}", path.display(), e))?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("Cannot set up memory DB {}: {}", path.display(), e))?;
+    Ok(conn)
+}
+
+/// Search the memory database using FTS5.
+pub async fn search(args: &Value, db: Option<PathBuf>) -> Value {
+    let query = match args.get("query").and_then(|v| v.as_str()) {
+        Some(q) => q.to_string(),
+        None => return json!({ "error": "Missing 'query' parameter" }),
+    };
+    let Some(db) = db else {
+        return not_configured();
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let conn = match open(&db) {
+            Ok(conn) => conn,
+            Err(e) => return json!({ "error": e }),
+        };
+        let sql = "SELECT m.title, m.content, m.category \
+                   FROM memories m \
+                   JOIN memories_fts f ON m.id = f.rowid \
+                   WHERE memories_fts MATCH ?1 \
+                   ORDER BY rank \
+                   LIMIT 5";
+        match conn.prepare(sql) {
+            Ok(mut stmt) => {
+                match stmt.query_map([&query], |row| {
+                    Ok(json!({
+                        "title": row.get::<_, String>(0).unwrap_or_default(),
+                        "content": row.get::<_, String>(1).unwrap_or_default(),
+                        "category": row.get::<_, String>(2).unwrap_or_default(),
+                    }))
+                }) {
+                    Ok(rows) => {
+                        let results: Vec<Value> = rows.filter_map(|r| r.ok()).collect();
+                        if results.is_empty() {
+                            json!({ "results": [], "message": "No memories found matching that query." })
+                        } else {
+                            json!({ "results": results })
+                        }
+                    }
+                    Err(e) => json!({ "error": format!("FTS query failed: {}", e) }),
+                }
+            }
+            Err(e) => json!({ "error": format!("Query failed: {}", e) }),
+        }
+    })
+    .await
+    .unwrap_or_else(|e| json!({ "error": format!("Task failed: {}", e) }))
+}
+
+/// Save a memory to the memory database.
+pub async fn remember(args: &Value, db: Option<PathBuf>) -> Value {
+    let title = match args.get("title").and_then(|v| v.as_str()) {
+        Some(t) => t.to_string(),
+        None => return json!({ "error": "Missing 'title' parameter" }),
+    };
+    let content = match args.get("content").and_then(|v| v.as_str()) {
+        Some(c) => c.to_string(),
+        None => return json!({ "error": "Missing 'content' parameter" }),
+    };
+    let Some(db) = db else {
+        return not_configured();
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let conn = match open(&db) {
+            Ok(conn) => conn,
+            Err(e) => return json!({ "error": e }),
+        };
+        let insert_sql = "INSERT INTO memories (type, category, title, content, project, salience, scope, source) \
+                          VALUES ('long_term', 'note', ?1, ?2, 'maVoice-Linux', 0.6, 'project', 'agent:mavoice')";
+        match conn.execute(insert_sql, rusqlite::params![title, content]) {
+            Ok(_) => {
+                let id = conn.last_insert_rowid();
+                // Also insert into FTS index
+                let fts_sql = "INSERT INTO memories_fts (rowid, title, content, tags) VALUES (?1, ?2, ?3, '[]')";
+                let _ = conn.execute(fts_sql, rusqlite::params![id, title, content]);
+                json!({ "success": true, "message": format!("Saved memory: {}", title), "id": id })
+            }
+            Err(e) => json!({ "error": format!("Failed to save: {}", e) }),
+        }
+    })
+    .await
+    .unwrap_or_else(|e| json!({ "error": format!("Task failed: {}", e) }))
+}
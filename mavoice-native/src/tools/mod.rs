@@ -1,142 +1,528 @@
+pub mod confirm;
+mod custom;
+mod files;
+pub mod mcp;
+mod memory;
+mod sandbox;
+mod web;
+mod windows;
+
+pub use sandbox::CommandPolicy;
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
 use serde_json::{json, Value};
 
-const MEMORY_DB_PATH: &str = "/home/player3vsgpt/.shieldcortex/memories.db";
+use crate::config::{CustomToolConfig, ToolSettings};
+use mcp::McpHub;
 
-/// Execute a tool by name with the given arguments.
-/// Returns a JSON value to send back to Gemini as the function response.
-pub async fn execute(name: &str, args: &Value) -> Value {
-    match name {
-        "search_memory" => search_memory(args).await,
-        "remember" => remember(args).await,
-        "run_command" => run_command(args).await,
-        "ask_claude" => ask_claude(args).await,
-        _ => json!({ "error": format!("Unknown tool: {}", name) }),
+const BUILTIN_TOOLS: &[&str] = &[
+    "search_memory",
+    "remember",
+    "run_command",
+    "ask_claude",
+    "read_clipboard",
+    "write_clipboard",
+    "list_windows",
+    "focus_window",
+    "fetch_url",
+    "web_search",
+    "read_file",
+    "write_file",
+    NOTE_MODE_TOOL,
+    REMINDER_TOOL,
+];
+/// Tools the app handles itself since they change app state, so
+/// [`execute`] never sees them: switching note mode and scheduling reminders.
+pub const NOTE_MODE_TOOL: &str = "note_mode";
+pub const REMINDER_TOOL: &str = "set_reminder";
+
+/// Whether the app runs `name` itself ([`NOTE_MODE_TOOL`], [`REMINDER_TOOL`]).
+pub fn is_app_tool(name: &str) -> bool {
+    name == NOTE_MODE_TOOL || name == REMINDER_TOOL
+}
+
+/// Built-ins left out when `memory_enabled` is off.
+const MEMORY_TOOLS: &[&str] = &["search_memory", "remember"];
+
+/// Tools declared `NON_BLOCKING` when `tool_progress` is on: Gemini keeps
+/// talking while they run and gets updates until the result arrives.
+const STREAMING_TOOLS: &[&str] = &["run_command", "ask_claude"];
+
+/// `ask_claude` timeout unless `[tools]` sets one.
+const ASK_CLAUDE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Built-ins left out when `web_search_url` is unset.
+const SEARCH_TOOLS: &[&str] = &["web_search"];
+
+/// Built-ins left out when `file_dirs` is empty.
+const FILE_TOOLS: &[&str] = &["read_file", "write_file"];
+
+/// Clipboard text returned to Gemini, in characters.
+const CLIPBOARD_MAX_CHARS: usize = 8000;
+
+/// Whether calls to `name` send progress updates (with `tool_progress` on).
+pub fn streams(name: &str) -> bool {
+    STREAMING_TOOLS.iter().any(|t| *t == name)
+}
+
+/// Callback receiving a running tool's output as it is produced.
+#[derive(Clone)]
+pub struct ToolProgress(Arc<dyn Fn(String) + Send + Sync>);
+
+impl ToolProgress {
+    pub fn new(f: impl Fn(String) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn notify(&self, output: String) {
+        (self.0)(output)
     }
 }
 
-/// Search the ShieldCortex memory database using FTS5.
-async fn search_memory(args: &Value) -> Value {
-    let query = match args.get("query").and_then(|v| v.as_str()) {
-        Some(q) => q.to_string(),
-        None => return json!({ "error": "Missing 'query' parameter" }),
-    };
+impl std::fmt::Debug for ToolProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ToolProgress")
+    }
+}
+
+/// Tool sources beyond the built-ins, snapshotted for one session or call.
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    pub custom_tools: Vec<CustomToolConfig>,
+    pub mcp: Option<Arc<McpHub>>,
+    pub memory_enabled: bool,
+    /// `None` when `memory_db` is unset; the memory tools then explain how
+    /// to set it up
+    pub memory_db: Option<PathBuf>,
+    pub command_policy: CommandPolicy,
+    /// `web_search_url`, `None` when unset
+    pub search_url: Option<String>,
+    /// `file_dirs`: where the file tools may read and write
+    pub file_dirs: Vec<PathBuf>,
+    /// The `[tools]` table: per-tool enable flags and timeouts
+    pub settings: BTreeMap<String, ToolSettings>,
+    /// Declare [`STREAMING_TOOLS`] non-blocking (`tool_progress`)
+    pub streaming: bool,
+}
+
+impl ToolContext {
+    pub(crate) fn enabled(&self, name: &str) -> bool {
+        let memory = self.memory_enabled || !MEMORY_TOOLS.iter().any(|m| *m == name);
+        let search = self.search_url.is_some() || !SEARCH_TOOLS.iter().any(|s| *s == name);
+        let files = !self.file_dirs.is_empty() || !FILE_TOOLS.iter().any(|f| *f == name);
+        memory && search && files && self.settings.get(name).is_none_or(|s| s.enabled)
+    }
+
+    fn timeout(&self, name: &str) -> Option<Duration> {
+        self.settings
+            .get(name)
+            .and_then(|s| s.timeout_secs)
+            .map(|secs| Duration::from_secs(secs.max(1)))
+    }
+}
+
+/// Function declarations sent to Gemini: the built-in tools, then config-defined
+/// ones, then MCP server tools. Names that are already taken are skipped, and
+/// tools disabled in `[tools]` are left out.
+pub fn declarations(ctx: &ToolContext) -> Vec<Value> {
+    let mut decls = builtin_declarations();
+    decls.retain(|d| ctx.enabled(d["name"].as_str().unwrap_or_default()));
+    if ctx.streaming {
+        for decl in decls.iter_mut().filter(|d| streams(d["name"].as_str().unwrap_or_default())) {
+            decl["behavior"] = json!("NON_BLOCKING");
+        }
+    }
+    let mut names: HashSet<String> = BUILTIN_TOOLS
+        .iter()
+        .filter(|&&n| ctx.enabled(n))
+        .map(|n| n.to_string())
+        .collect();
+
+    for tool in &ctx.custom_tools {
+        if !ctx.enabled(&tool.name) {
+            continue;
+        }
+        if !names.insert(tool.name.clone()) {
+            log::warn!("Custom tool '{}' duplicates an existing tool, ignoring", tool.name);
+            continue;
+        }
+        decls.push(custom::declaration(tool));
+    }
+
+    if let Some(hub) = &ctx.mcp {
+        for decl in hub.declarations() {
+            let name = decl["name"].as_str().unwrap_or_default();
+            if !ctx.enabled(name) {
+                continue;
+            }
+            if !names.insert(name.to_string()) {
+                log::warn!("MCP tool '{}' duplicates an existing tool, ignoring", name);
+                continue;
+            }
+            decls.push(decl.clone());
+        }
+    }
+
+    decls
+}
 
-    tokio::task::spawn_blocking(move || {
-        match rusqlite::Connection::open_with_flags(
-            MEMORY_DB_PATH,
-            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
-        ) {
-            Ok(conn) => {
-                let sql = "SELECT m.title, m.content, m.category \
-                           FROM memories m \
-                           JOIN memories_fts f ON m.id = f.rowid \
-                           WHERE memories_fts MATCH ?1 \
-                           ORDER BY rank \
-                           LIMIT 5";
-                match conn.prepare(sql) {
-                    Ok(mut stmt) => {
-                        match stmt.query_map([&query], |row| {
-                            Ok(json!({
-                                "title": row.get::<_, String>(0).unwrap_or_default(),
-                                "content": row.get::<_, String>(1).unwrap_or_default(),
-                                "category": row.get::<_, String>(2).unwrap_or_default(),
-                            }))
-                        }) {
-                            Ok(rows) => {
-                                let results: Vec<Value> = rows.filter_map(|r| r.ok()).collect();
-                                if results.is_empty() {
-                                    json!({ "results": [], "message": "No memories found matching that query." })
-                                } else {
-                                    json!({ "results": results })
-                                }
-                            }
-                            Err(e) => json!({ "error": format!("FTS query failed: {}", e) }),
-                        }
+fn builtin_declarations() -> Vec<Value> {
+    let decls = json!([
+        {
+            "name": "search_memory",
+            "description": "Search the user's persistent memory/knowledge base for relevant information. Use this when the user asks about something they've previously stored or when context would help.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Search query to find relevant memories"
                     }
-                    Err(e) => json!({ "error": format!("Query failed: {}", e) }),
-                }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "remember",
+            "description": "Save a piece of information to the user's persistent memory for future recall. Use when the user asks you to remember something.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "title": {
+                        "type": "string",
+                        "description": "Short title for the memory"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "Detailed content to remember"
+                    }
+                },
+                "required": ["title", "content"]
+            }
+        },
+        {
+            "name": "run_command",
+            "description": "Run a shell command on the user's Linux desktop. Use for file operations, system info, package management, etc.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "command": {
+                        "type": "string",
+                        "description": "Bash command to execute"
+                    }
+                },
+                "required": ["command"]
+            }
+        },
+        {
+            "name": "ask_claude",
+            "description": "Delegate a complex task to Claude (Anthropic's AI). Use for code generation, analysis, writing, or anything requiring deep reasoning.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "task": {
+                        "type": "string",
+                        "description": "Detailed description of the task for Claude"
+                    }
+                },
+                "required": ["task"]
+            }
+        },
+        {
+            "name": "read_clipboard",
+            "description": "Read the text the user currently has on the clipboard. Use when the user refers to what they copied, e.g. \"summarize my clipboard\".",
+            "parameters": {
+                "type": "object",
+                "properties": {}
+            }
+        },
+        {
+            "name": "write_clipboard",
+            "description": "Put text on the user's clipboard so they can paste it, e.g. a rewritten version of what they copied.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "text": {
+                        "type": "string",
+                        "description": "Text to copy"
+                    }
+                },
+                "required": ["text"]
+            }
+        },
+        {
+            "name": "list_windows",
+            "description": "List the windows open on the user's desktop (app name and title), most recently used first.",
+            "parameters": {
+                "type": "object",
+                "properties": {}
+            }
+        },
+        {
+            "name": "focus_window",
+            "description": "Switch to a window, e.g. when the user says \"switch to Firefox\". Matches the app name first, then the window title.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "window": {
+                        "type": "string",
+                        "description": "App name (e.g. \"firefox\"), part of the window title, or an id from list_windows"
+                    }
+                },
+                "required": ["window"]
+            }
+        },
+        {
+            "name": "fetch_url",
+            "description": "Fetch a web page and return its text. Use when the user asks about a specific page or link, or to read a search result.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "The page's URL, e.g. \"https://example.com/news\""
+                    }
+                },
+                "required": ["url"]
+            }
+        },
+        {
+            "name": "web_search",
+            "description": "Search the web for current information: news, prices, documentation, anything newer than your training. Returns titles, URLs and snippets; use fetch_url to read a result.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "Search query"
+                    }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "read_file",
+            "description": "Read a text file from the user's allowed folders, e.g. their notes. A folder path returns its file list.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File or folder path; relative paths start in the first allowed folder"
+                    }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "write_file",
+            "description": "Save text to a file in the user's allowed folders, e.g. a dictated document or note.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "File path; relative paths start in the first allowed folder"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "Text to write"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["create", "overwrite", "append"],
+                        "description": "\"create\" (default) fails if the file exists; \"overwrite\" replaces it; \"append\" adds to the end"
+                    }
+                },
+                "required": ["path", "content"]
+            }
+        },
+        {
+            "name": NOTE_MODE_TOOL,
+            "description": "Start or stop note mode. While it's on, the user's dictation is saved to today's notes file instead of being typed into the focused app.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "enabled": {
+                        "type": "boolean",
+                        "description": "true to start note mode, false to stop it"
+                    }
+                },
+                "required": ["enabled"]
+            }
+        },
+        {
+            "name": REMINDER_TOOL,
+            "description": "Remind the user of something after a delay, e.g. \"remind me in 20 minutes to stretch\". Shows a desktop notification and says it out loud when due. Reminders are lost if maVoice quits.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "text": {
+                        "type": "string",
+                        "description": "What to remind the user of, e.g. \"Time to stretch\""
+                    },
+                    "minutes": {
+                        "type": "number",
+                        "description": "Minutes from now (up to 1440)"
+                    }
+                },
+                "required": ["text", "minutes"]
             }
-            Err(e) => json!({ "error": format!("Cannot open memory DB: {}", e) }),
         }
-    })
-    .await
-    .unwrap_or_else(|e| json!({ "error": format!("Task failed: {}", e) }))
+    ]);
+    match decls {
+        Value::Array(v) => v,
+        _ => Vec::new(),
+    }
 }
 
-/// Save a memory to the ShieldCortex database.
-async fn remember(args: &Value) -> Value {
-    let title = match args.get("title").and_then(|v| v.as_str()) {
-        Some(t) => t.to_string(),
-        None => return json!({ "error": "Missing 'title' parameter" }),
-    };
-    let content = match args.get("content").and_then(|v| v.as_str()) {
-        Some(c) => c.to_string(),
-        None => return json!({ "error": "Missing 'content' parameter" }),
-    };
+/// Like [`execute`], but calls `on_update` every `interval` while the tool
+/// runs, with the time so far and the output produced since the last update.
+pub async fn execute_with_updates(
+    name: &str,
+    args: &Value,
+    ctx: &ToolContext,
+    interval: Duration,
+    on_update: impl Fn(Duration, String),
+) -> Value {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let progress = ToolProgress::new(move |output| {
+        let _ = tx.send(output);
+    });
+    let run = execute(name, args, ctx, Some(&progress));
+    tokio::pin!(run);
 
-    tokio::task::spawn_blocking(move || {
-        match rusqlite::Connection::open_with_flags(
-            MEMORY_DB_PATH,
-            rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
-        ) {
-            Ok(conn) => {
-                let insert_sql = "INSERT INTO memories (type, category, title, content, project, salience, scope, source) \
-                                  VALUES ('long_term', 'note', ?1, ?2, 'maVoice-Linux', 0.6, 'project', 'agent:mavoice')";
-                match conn.execute(insert_sql, rusqlite::params![title, content]) {
-                    Ok(_) => {
-                        let id = conn.last_insert_rowid();
-                        // Also insert into FTS index
-                        let fts_sql = "INSERT INTO memories_fts (rowid, title, content, tags) VALUES (?1, ?2, ?3, '[]')";
-                        let _ = conn.execute(fts_sql, rusqlite::params![id, title, content]);
-                        json!({ "success": true, "message": format!("Saved memory: {}", title), "id": id })
-                    }
-                    Err(e) => json!({ "error": format!("Failed to save: {}", e) }),
+    let started = tokio::time::Instant::now();
+    let mut ticker = tokio::time::interval_at(started + interval, interval);
+    let mut output = String::new();
+    loop {
+        tokio::select! {
+            result = &mut run => return result,
+            Some(chunk) = rx.recv() => output.push_str(&chunk),
+            _ = ticker.tick() => on_update(started.elapsed(), std::mem::take(&mut output)),
+        }
+    }
+}
+
+/// Execute a tool by name with the given arguments. Tools that produce
+/// output as they go pass it to `progress`.
+/// Returns a JSON value to send back to Gemini as the function response.
+pub async fn execute(name: &str, args: &Value, ctx: &ToolContext, progress: Option<&ToolProgress>) -> Value {
+    if !ctx.enabled(name) {
+        return json!({ "error": format!("The tool '{}' is disabled", name) });
+    }
+    let limit = ctx.timeout(name);
+    // Tools that start processes apply the limit themselves, so the process
+    // is killed rather than left behind
+    match name {
+        "run_command" => return run_command(args, &ctx.command_policy, progress).await,
+        "ask_claude" => return ask_claude(args, limit.unwrap_or(ASK_CLAUDE_TIMEOUT)).await,
+        // Requests carry their own timeout
+        "fetch_url" => return web::fetch_url(args, limit.unwrap_or(web::FETCH_TIMEOUT)).await,
+        "web_search" => {
+            let engine = ctx.search_url.as_deref().unwrap_or_default();
+            return web::search(args, engine, limit.unwrap_or(web::FETCH_TIMEOUT)).await;
+        }
+        _ => {}
+    }
+    if let Some(tool) = ctx.custom_tools.iter().find(|t| t.name == name) {
+        let mut tool = tool.clone();
+        if let Some(limit) = limit {
+            tool.timeout_secs = limit.as_secs();
+        }
+        return custom::execute(&tool, args).await;
+    }
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, execute_other(name, args, ctx))
+            .await
+            .unwrap_or_else(|_| json!({ "error": format!("{} timed out after {} seconds", name, limit.as_secs()) })),
+        None => execute_other(name, args, ctx).await,
+    }
+}
+
+/// Memory and MCP tools.
+async fn execute_other(name: &str, args: &Value, ctx: &ToolContext) -> Value {
+    match name {
+        "search_memory" => memory::search(args, ctx.memory_db.clone()).await,
+        "remember" => memory::remember(args, ctx.memory_db.clone()).await,
+        "read_clipboard" => read_clipboard().await,
+        "write_clipboard" => write_clipboard(args).await,
+        "list_windows" => windows::list().await,
+        "focus_window" => windows::focus(args).await,
+        "read_file" => files::read(args, ctx.file_dirs.clone()).await,
+        "write_file" => files::write(args, ctx.file_dirs.clone()).await,
+        _ => {
+            if let Some(hub) = ctx.mcp.as_ref().filter(|h| h.has_tool(name)) {
+                if let Some(result) = hub.call(name, args).await {
+                    return result;
                 }
             }
-            Err(e) => json!({ "error": format!("Cannot open memory DB: {}", e) }),
+            json!({ "error": format!("Unknown tool: {}", name) })
         }
-    })
-    .await
-    .unwrap_or_else(|e| json!({ "error": format!("Task failed: {}", e) }))
+    }
 }
 
-/// Run a shell command with a 30-second timeout.
-async fn run_command(args: &Value) -> Value {
+/// Run a shell command within the configured policy.
+async fn run_command(args: &Value, policy: &CommandPolicy, progress: Option<&ToolProgress>) -> Value {
     let command = match args.get("command").and_then(|v| v.as_str()) {
         Some(c) => c.to_string(),
         None => return json!({ "error": "Missing 'command' parameter" }),
     };
 
     log::info!("[Tool:run_command] Executing: {}", command);
+    policy.run(&command, progress).await
+}
 
-    let result = tokio::time::timeout(
-        std::time::Duration::from_secs(30),
-        tokio::process::Command::new("bash")
-            .args(["-c", &command])
-            .output(),
-    )
+/// Read the clipboard through the same tools the text injector pastes with.
+async fn read_clipboard() -> Value {
+    let result = tokio::task::spawn_blocking(|| {
+        crate::system::text_inject::read_clipboard().map_err(|e| e.to_string())
+    })
     .await;
 
     match result {
-        Ok(Ok(output)) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // Truncate to 4000 chars
-            let stdout_trunc: String = stdout.chars().take(4000).collect();
-            let stderr_trunc: String = stderr.chars().take(4000).collect();
-            json!({
-                "exit_code": output.status.code().unwrap_or(-1),
-                "stdout": stdout_trunc,
-                "stderr": stderr_trunc,
-            })
+        Ok(Ok(text)) if text.trim().is_empty() => {
+            json!({ "text": "", "message": "The clipboard is empty or holds no text." })
         }
-        Ok(Err(e)) => json!({ "error": format!("Command failed to execute: {}", e) }),
-        Err(_) => json!({ "error": "Command timed out after 30 seconds" }),
+        Ok(Ok(text)) => {
+            let chars = text.chars().count();
+            let mut response = json!({ "text": text.chars().take(CLIPBOARD_MAX_CHARS).collect::<String>() });
+            if chars > CLIPBOARD_MAX_CHARS {
+                response["truncated"] = json!(format!("First {} of {} characters", CLIPBOARD_MAX_CHARS, chars));
+            }
+            response
+        }
+        Ok(Err(e)) => json!({ "error": e }),
+        Err(e) => json!({ "error": format!("Task failed: {}", e) }),
+    }
+}
+
+async fn write_clipboard(args: &Value) -> Value {
+    let text = match args.get("text").and_then(|v| v.as_str()) {
+        Some(t) => t.to_string(),
+        None => return json!({ "error": "Missing 'text' parameter" }),
+    };
+
+    log::info!("[Tool:write_clipboard] Copying {} chars", text.chars().count());
+
+    let result = tokio::task::spawn_blocking(move || {
+        crate::system::text_inject::copy_to_clipboard(&text).map_err(|e| e.to_string())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => json!({ "success": true, "message": "Copied to the clipboard" }),
+        Ok(Err(e)) => json!({ "error": e }),
+        Err(e) => json!({ "error": format!("Task failed: {}", e) }),
     }
 }
 
 /// Delegate a task to Claude via the CLI.
-async fn ask_claude(args: &Value) -> Value {
+async fn ask_claude(args: &Value, limit: Duration) -> Value {
     let task = match args.get("task").and_then(|v| v.as_str()) {
         Some(t) => t.to_string(),
         None => return json!({ "error": "Missing 'task' parameter" }),
@@ -145,9 +531,10 @@ async fn ask_claude(args: &Value) -> Value {
     log::info!("[Tool:ask_claude] Delegating: {}", task);
 
     let result = tokio::time::timeout(
-        std::time::Duration::from_secs(120),
+        limit,
         tokio::process::Command::new("claude")
             .args(["-p", &task, "--output-format", "text"])
+            .kill_on_drop(true)
             .output(),
     )
     .await;
@@ -165,6 +552,6 @@ async fn ask_claude(args: &Value) -> Value {
             }
         }
         Ok(Err(e)) => json!({ "error": format!("Failed to run Claude CLI: {}", e) }),
-        Err(_) => json!({ "error": "Claude timed out after 120 seconds" }),
+        Err(_) => json!({ "error": format!("Claude timed out after {} seconds", limit.as_secs()) }),
     }
 }
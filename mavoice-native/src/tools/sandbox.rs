@@ -0,0 +1,357 @@
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::ToolProgress;
+use crate::config::Config;
+
+/// Commands that only run the command after them, so the lists check that
+/// one too (`sudo rm`, `env FOO=1 rm`, `xargs rm`).
+const WRAPPERS: &[&str] = &[
+    "sudo", "doas", "env", "nice", "nohup", "time", "timeout", "xargs", "exec", "command", "builtin",
+    "stdbuf", "ionice", "setsid", "eval",
+];
+
+/// Limits for the `run_command` tool, from the `run_command_*` config keys.
+///
+/// The command is still run by bash; the binaries it names are checked
+/// before it starts. The lists and the directory jail catch a model going
+/// off the rails, but a determined script can get around a denylist
+/// (`bash -c`, interpreters), so use the allowlist for anything stricter.
+/// With a jail, variables and `cd` without a path are refused since their
+/// targets can't be checked. The command runs in its own process group,
+/// all of which is killed on timeout.
+#[derive(Debug, Clone)]
+pub struct CommandPolicy {
+    /// Binaries that may run (empty = any not denied)
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    /// Working directory; paths in the command must stay inside it
+    pub jail: Option<PathBuf>,
+    pub timeout: Duration,
+    /// Bytes kept from each of stdout and stderr
+    pub max_output: usize,
+    /// Address-space limit in MiB (0 = none)
+    pub max_memory_mb: u64,
+}
+
+impl Default for CommandPolicy {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+impl CommandPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            allow: config.run_command_allow.clone(),
+            deny: config.run_command_deny.clone(),
+            jail: config.run_command_dir_path(),
+            timeout: config.run_command_timeout(),
+            max_output: config.run_command_max_output,
+            max_memory_mb: config.run_command_max_memory_mb,
+        }
+    }
+
+    fn restricted(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty() || self.jail.is_some()
+    }
+
+    /// Why `command` may not run, if it may not.
+    pub fn check(&self, command: &str) -> Result<(), String> {
+        if self.restricted()
+            && ["$(", "`", "<(", ">("].iter().any(|s| command.contains(s))
+        {
+            return Err(
+                "Command substitution isn't allowed; run each command directly".to_string(),
+            );
+        }
+
+        if self.jail.is_some() && command.contains('$') {
+            return Err("Variables aren't allowed with run_command_dir; write paths out".to_string());
+        }
+
+        for words in split_commands(command) {
+            for binary in command_names(&words) {
+                let name = binary.rsplit('/').next().unwrap_or(binary);
+                if self.deny.iter().any(|d| d == name) {
+                    return Err(format!("'{}' is not allowed (run_command_deny)", name));
+                }
+                if !self.allow.is_empty() && !self.allow.iter().any(|a| a == name) {
+                    return Err(format!(
+                        "'{}' is not allowed; allowed commands: {}",
+                        name,
+                        self.allow.join(", ")
+                    ));
+                }
+            }
+            if let Some(ref jail) = self.jail {
+                if let Some(cd) = bare_cd(&words) {
+                    return Err(format!("'{}' leaves {}; give a path inside it", cd, jail.display()));
+                }
+                for word in &words {
+                    if let Some(path) = escapes(jail, word) {
+                        return Err(format!("'{}' is outside {}", path, jail.display()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `command` under this policy and return the tool response. Output
+    /// is also passed to `progress` as it arrives.
+    pub async fn run(&self, command: &str, progress: Option<&ToolProgress>) -> Value {
+        if let Err(e) = self.check(command) {
+            log::warn!("[Tool:run_command] Refused: {}", e);
+            return json!({ "error": e });
+        }
+
+        let script = if self.max_memory_mb > 0 {
+            format!("ulimit -v {}\n{}", self.max_memory_mb * 1024, command)
+        } else {
+            command.to_string()
+        };
+        let mut cmd = tokio::process::Command::new("bash");
+        cmd.args(["-c", &script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .kill_on_drop(true);
+        if let Some(ref jail) = self.jail {
+            cmd.current_dir(jail);
+        }
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => return json!({ "error": format!("Command failed to execute: {}", e) }),
+        };
+        let pid = child.id();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let result = tokio::time::timeout(self.timeout, async {
+            let (stdout, stderr, status) = tokio::join!(
+                read_capped(stdout, self.max_output, progress),
+                read_capped(stderr, self.max_output, progress),
+                child.wait(),
+            );
+            (stdout, stderr, status)
+        })
+        .await;
+
+        match result {
+            Ok(((stdout, stdout_cut), (stderr, stderr_cut), Ok(status))) => {
+                let mut response = json!({
+                    "exit_code": status.code().unwrap_or(-1),
+                    "stdout": stdout,
+                    "stderr": stderr,
+                });
+                if stdout_cut || stderr_cut {
+                    response["truncated"] = json!(format!("Output cut at {} bytes", self.max_output));
+                }
+                response
+            }
+            Ok((_, _, Err(e))) => json!({ "error": format!("Command failed: {}", e) }),
+            Err(_) => {
+                if let Some(pid) = pid {
+                    kill_group(pid);
+                }
+                json!({ "error": format!("Command timed out after {} seconds", self.timeout.as_secs()) })
+            }
+        }
+    }
+}
+
+/// SIGKILL the process group led by `pid`, so background jobs and pipelines
+/// the command started die with it.
+fn kill_group(pid: u32) {
+    let _ = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pid)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Up to `max` bytes of a pipe as text, and whether more was discarded.
+/// The rest is drained so the command doesn't block on a full pipe.
+async fn read_capped<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    max: usize,
+    progress: Option<&ToolProgress>,
+) -> (String, bool) {
+    let Some(mut reader) = reader else {
+        return (String::new(), false);
+    };
+    let mut kept = Vec::new();
+    let mut cut = false;
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        let take = n.min(max - kept.len());
+        cut |= take < n;
+        if take > 0 {
+            kept.extend_from_slice(&chunk[..take]);
+            if let Some(progress) = progress {
+                progress.notify(String::from_utf8_lossy(&chunk[..take]).into_owned());
+            }
+        }
+    }
+    (String::from_utf8_lossy(&kept).into_owned(), cut)
+}
+
+/// Split a shell command into simple commands (at `;`, `&&`, `||`, `|`,
+/// `&`, newlines and parentheses) of unquoted words. Redirections become
+/// their own `>` / `<` words.
+fn split_commands(command: &str) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    let end_word = |words: &mut Vec<String>, word: &mut String, in_word: &mut bool| {
+        if *in_word {
+            words.push(std::mem::take(word));
+            *in_word = false;
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    word.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => word.extend(chars.next()),
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                word.extend(chars.next());
+            }
+            '>' | '<' => {
+                // A file descriptor number belongs to the redirection
+                if in_word && word.chars().all(|c| c.is_ascii_digit()) {
+                    word.clear();
+                    in_word = false;
+                }
+                end_word(&mut words, &mut word, &mut in_word);
+                while chars.next_if(|c| matches!(c, '>' | '<' | '&' | '|')).is_some() {}
+                words.push(c.to_string());
+            }
+            '&' if chars.peek() == Some(&'>') => {
+                end_word(&mut words, &mut word, &mut in_word);
+            }
+            ';' | '&' | '|' | '\n' | '(' | ')' => {
+                end_word(&mut words, &mut word, &mut in_word);
+                if !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                }
+            }
+            c if c.is_whitespace() => end_word(&mut words, &mut word, &mut in_word),
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    end_word(&mut words, &mut word, &mut in_word);
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    commands
+}
+
+/// The binaries a simple command runs: its first word after variable
+/// assignments and redirections, plus whatever a wrapper like `sudo` runs.
+fn command_names(words: &[String]) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut skip_next = false;
+    let mut after_wrapper = false;
+    for word in words {
+        if std::mem::take(&mut skip_next) {
+            continue;
+        }
+        if word == ">" || word == "<" {
+            skip_next = true;
+            continue;
+        }
+        let assignment = word
+            .split_once('=')
+            .is_some_and(|(name, _)| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_'));
+        if assignment || (after_wrapper && word.starts_with('-')) {
+            continue;
+        }
+        // `timeout 5 cmd`: skip the duration
+        if after_wrapper && word.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        names.push(word.as_str());
+        let name = word.rsplit('/').next().unwrap_or(word);
+        if !WRAPPERS.iter().any(|w| *w == name) {
+            break;
+        }
+        after_wrapper = true;
+    }
+    names
+}
+
+/// `cd`/`pushd` with no path or `-`, which go somewhere the jail can't
+/// check (`$HOME`, the previous directory).
+fn bare_cd(words: &[String]) -> Option<String> {
+    let first = words.iter().position(|w| !w.contains('='))?;
+    let name = words[first].as_str();
+    if !matches!(name, "cd" | "pushd" | "popd") {
+        return None;
+    }
+    let args: Vec<&str> = words[first + 1..]
+        .iter()
+        .map(String::as_str)
+        .filter(|w| !w.starts_with('-') || *w == "-")
+        .collect();
+    (name == "popd" || args.is_empty() || args[0] == "-").then(|| words[first..].join(" "))
+}
+
+/// The path in `word` (or after `=` in `--opt=path`), if it leads out of `jail`.
+fn escapes<'a>(jail: &Path, word: &'a str) -> Option<&'a str> {
+    let path = match word.split_once('=') {
+        Some((_, value)) if word.starts_with('-') => value,
+        _ => word,
+    };
+    if !(path.starts_with('/') || path.starts_with('~') || path.contains("..")) {
+        return None;
+    }
+    let expanded = match path.strip_prefix('~') {
+        Some(rest) => dirs::home_dir()?.join(rest.trim_start_matches('/')),
+        None => jail.join(path),
+    };
+    let mut resolved = PathBuf::new();
+    for component in expanded.components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    (!resolved.starts_with(jail)).then_some(path)
+}
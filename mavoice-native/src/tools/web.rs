@@ -0,0 +1,475 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Url};
+use serde_json::{json, Value};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout of a fetch unless `[tools]` sets one.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Bytes of a response body read before giving up on the rest.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Page text returned to Gemini, in characters.
+const MAX_TEXT_CHARS: usize = 8000;
+
+/// Search results returned to Gemini.
+const MAX_RESULTS: usize = 8;
+
+/// Redirects followed before giving up, as reqwest does by default.
+const MAX_REDIRECTS: usize = 10;
+
+/// Elements whose content is never page text.
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "noscript", "template", "svg", "head"];
+
+/// Elements that start a new line of text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "br", "li", "tr", "h1", "h2", "h3", "h4", "h5", "h6", "section", "article", "header",
+    "footer", "blockquote", "pre", "table", "ul", "ol", "dt", "dd", "hr",
+];
+
+/// Fetch a web page and return its text.
+pub async fn fetch_url(args: &Value, timeout: Duration) -> Value {
+    let url = match args.get("url").and_then(|v| v.as_str()) {
+        Some(u) => u.trim().to_string(),
+        None => return json!({ "error": "Missing 'url' parameter" }),
+    };
+    let url = match parse_url(&url).and_then(|url| check_host(&url).map(|()| url)) {
+        Ok(url) => url,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    log::info!("[Tool:fetch_url] GET {}", url);
+
+    match get(url, timeout, true).await {
+        Ok(page) => {
+            let title = page.html.then(|| html_title(&page.body)).flatten();
+            let text = if page.html { html_to_text(&page.body) } else { page.body };
+            let mut response = json!({ "url": page.url, "text": truncate(&text) });
+            if let Some(title) = title {
+                response["title"] = json!(title);
+            }
+            if page.cut || text.chars().count() > MAX_TEXT_CHARS {
+                response["truncated"] = json!(format!("First {} characters of the page", MAX_TEXT_CHARS));
+            }
+            response
+        }
+        Err(e) => json!({ "error": e }),
+    }
+}
+
+/// Search the web with the `web_search_url` engine.
+pub async fn search(args: &Value, engine: &str, timeout: Duration) -> Value {
+    let query = match args.get("query").and_then(|v| v.as_str()) {
+        Some(q) if !q.trim().is_empty() => q.trim().to_string(),
+        _ => return json!({ "error": "Missing 'query' parameter" }),
+    };
+    let url = match parse_url(&engine.replace("{query}", &encode_query(&query))) {
+        Ok(url) => url,
+        Err(e) => return json!({ "error": format!("web_search_url is invalid: {}", e) }),
+    };
+
+    log::info!("[Tool:web_search] {}", query);
+
+    // The engine is the user's own choice and may well be a local SearXNG
+    let page = match get(url, timeout, false).await {
+        Ok(page) => page,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    // SearXNG and similar engines answer `format=json` with a results list
+    if let Ok(body) = serde_json::from_str::<Value>(&page.body) {
+        if let Some(results) = body.get("results").and_then(|r| r.as_array()) {
+            let results: Vec<Value> = results
+                .iter()
+                .take(MAX_RESULTS)
+                .map(|r| {
+                    json!({
+                        "title": r.get("title").and_then(|v| v.as_str()).unwrap_or_default(),
+                        "url": r.get("url").and_then(|v| v.as_str()).unwrap_or_default(),
+                        "snippet": r.get("content").and_then(|v| v.as_str()).unwrap_or_default(),
+                    })
+                })
+                .collect();
+            if results.is_empty() {
+                return json!({ "results": [], "message": "No results found." });
+            }
+            return json!({ "results": results });
+        }
+    }
+
+    let text = if page.html { html_to_text(&page.body) } else { page.body };
+    json!({ "text": truncate(&text) })
+}
+
+/// Only http(s) URLs; a bare host gets `https://`.
+fn parse_url(url: &str) -> Result<Url, String> {
+    let url = if url.contains("://") { url.to_string() } else { format!("https://{}", url) };
+    let parsed = Url::parse(&url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(format!("Only http and https URLs can be fetched, not {}", scheme)),
+    }
+}
+
+struct Page {
+    /// Final URL after redirects
+    url: String,
+    body: String,
+    html: bool,
+    /// The body was longer than [`MAX_BODY_BYTES`]
+    cut: bool,
+}
+
+/// GET `url`, reading at most [`MAX_BODY_BYTES`] of the body. With
+/// `public_only`, hosts (including those redirected to) must resolve to
+/// global addresses, so Gemini can't be steered at the machine itself or
+/// the local network.
+async fn get(url: Url, timeout: Duration, public_only: bool) -> Result<Page, String> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("maVoice/", env!("CARGO_PKG_VERSION")));
+    if public_only {
+        builder = builder
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(redirect::Policy::custom(|attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("Too many redirects");
+                }
+                let checked = match attempt.url().scheme() {
+                    "http" | "https" => check_host(attempt.url()),
+                    scheme => Err(format!("Redirected to a {} URL", scheme)),
+                };
+                match checked {
+                    Ok(()) => attempt.follow(),
+                    Err(e) => attempt.error(e),
+                }
+            }));
+    }
+    let client = builder.build().map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let mut response = client.get(url).send().await.map_err(|e| describe(e, timeout))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("The server answered {}", status));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase();
+    let textual = content_type.is_empty()
+        || content_type.starts_with("text/")
+        || content_type.contains("json")
+        || content_type.contains("xml");
+    if !textual {
+        return Err(format!("Not a text page ({})", content_type));
+    }
+
+    let final_url = response.url().to_string();
+    let mut body = Vec::new();
+    let mut cut = false;
+    while let Some(chunk) = response.chunk().await.map_err(|e| describe(e, timeout))? {
+        let take = chunk.len().min(MAX_BODY_BYTES - body.len());
+        body.extend_from_slice(&chunk[..take]);
+        if take < chunk.len() {
+            cut = true;
+            break;
+        }
+    }
+
+    Ok(Page {
+        url: final_url,
+        body: String::from_utf8_lossy(&body).into_owned(),
+        html: content_type.contains("html") || (content_type.is_empty() && looks_like_html(&body)),
+        cut,
+    })
+}
+
+fn describe(err: reqwest::Error, timeout: Duration) -> String {
+    if err.is_timeout() {
+        format!("The page took longer than {} seconds", timeout.as_secs())
+    } else {
+        // The resolver's and redirect policy's refusals are in the source
+        let mut message = format!("Request failed: {}", err);
+        let mut source = std::error::Error::source(&err);
+        while let Some(e) = source {
+            message = format!("{}: {}", message, e);
+            source = e.source();
+        }
+        message
+    }
+}
+
+/// Refuse a URL whose host is an IP address that isn't global. Names are
+/// checked by [`PublicResolver`] when they're looked up.
+fn check_host(url: &Url) -> Result<(), String> {
+    let host = url.host_str().ok_or_else(|| format!("{} has no host", url))?;
+    let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() else {
+        return Ok(());
+    };
+    if is_global(ip) {
+        Ok(())
+    } else {
+        Err(format!("{} is a local or private address", ip))
+    }
+}
+
+/// Resolves names like the system does, but only returns global addresses,
+/// so a name pointing at localhost or the LAN fails to connect.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_global(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} resolves to a local or private address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is reachable on the public internet: not loopback,
+/// private, link-local, shared (CGNAT), documentation or otherwise reserved.
+fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_global_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_global_v4(v4),
+            None => is_global_v6(ip),
+        },
+    }
+}
+
+fn is_global_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_global_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8)
+        || ip.to_ipv4().is_some_and(|v4| !is_global_v4(v4)))
+}
+
+fn looks_like_html(body: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&body[..body.len().min(512)]).to_lowercase();
+    start.contains("<html") || start.contains("<!doctype html")
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_TEXT_CHARS).collect()
+}
+
+/// The page's `<title>`, if it has a non-empty one.
+fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let content = start + lower[start..].find('>')? + 1;
+    let end = content + lower[content..].find("</title>")?;
+    let mut title = String::new();
+    push_text(&mut title, &html[content..end]);
+    let title = title.trim().to_string();
+    (!title.is_empty()).then_some(title)
+}
+
+/// Readable text of an HTML page: tags dropped, scripts and styles
+/// skipped, entities decoded, one line per block element.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    let mut skipping: Option<String> = None;
+
+    while let Some(open) = rest.find('<') {
+        if skipping.is_none() {
+            push_text(&mut text, &rest[..open]);
+        }
+        rest = &rest[open..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+
+        if skipping.is_some() {
+            // Browsers end <head> at <body> even when </head> is missing
+            let head_ends = skipping.as_deref() == Some("head") && name == "body";
+            if (closing && skipping.as_deref() == Some(name.as_str())) || head_ends {
+                skipping = None;
+            }
+        } else if !closing && !tag.ends_with('/') && SKIPPED_ELEMENTS.iter().any(|e| *e == name) {
+            skipping = Some(name);
+        } else if BLOCK_ELEMENTS.iter().any(|e| *e == name) && !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+    if skipping.is_none() {
+        push_text(&mut text, rest);
+    }
+
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Append a run of text, decoding entities and collapsing whitespace.
+fn push_text(text: &mut String, raw: &str) {
+    let decoded = decode_entities(raw);
+    for word in decoded.split_whitespace() {
+        if !text.is_empty() && !text.ends_with('\n') && !text.ends_with(' ') {
+            text.push(' ');
+        }
+        text.push_str(word);
+    }
+    if decoded.ends_with(char::is_whitespace) && !text.ends_with('\n') && !text.is_empty() {
+        text.push(' ');
+    }
+}
+
+fn decode_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end + 1];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 2))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Percent-encode a search query for a URL's query string.
+fn encode_query(query: &str) -> String {
+    let mut out = String::new();
+    for byte in query.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global(ip: &str) -> bool {
+        is_global(ip.parse().unwrap())
+    }
+
+    fn host_ok(url: &str) -> bool {
+        check_host(&Url::parse(url).unwrap()).is_ok()
+    }
+
+    #[test]
+    fn local_and_private_addresses_are_refused() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "100.64.0.1", "0.0.0.0"] {
+            assert!(!global(ip), "{}", ip);
+        }
+        for ip in ["::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1", "::ffff:192.168.1.1"] {
+            assert!(!global(ip), "{}", ip);
+        }
+        assert!(!host_ok("http://127.0.0.1:8080/admin"));
+        assert!(!host_ok("http://169.254.169.254/latest/meta-data"));
+        assert!(!host_ok("http://[::ffff:127.0.0.1]/"));
+    }
+
+    #[test]
+    fn public_addresses_pass() {
+        assert!(global("93.184.216.34"));
+        assert!(global("2606:4700:4700::1111"));
+        assert!(host_ok("https://93.184.216.34/"));
+        // Names are checked when they resolve
+        assert!(host_ok("https://example.com/"));
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(decode_entities("a &amp; b &lt;c&gt; &quot;d&quot;"), "a & b <c> \"d\"");
+        assert_eq!(decode_entities("&#65;&#x42;&#X43; caf&#233;"), "ABC café");
+        assert_eq!(decode_entities("&bogus; & done"), "&bogus; & done");
+    }
+
+    #[test]
+    fn html_keeps_readable_text() {
+        let html = "<html><head><title>T</title></head><body><p>Fish &amp; chips</p>\
+                    <script>var a = 1;</script><p>caf&#233; &mdash; ok</p></body></html>";
+        assert_eq!(html_to_text(html), "Fish & chips\ncafé — ok");
+        assert_eq!(html_title(html).as_deref(), Some("T"));
+    }
+}
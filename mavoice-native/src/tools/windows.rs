@@ -0,0 +1,68 @@
+use serde_json::{json, Value};
+
+use crate::system::text_inject::{self, WindowInfo};
+
+/// Open windows except maVoice's own overlay and settings windows.
+fn windows() -> Result<Vec<WindowInfo>, String> {
+    let windows = text_inject::list_windows().map_err(|e| e.to_string())?;
+    Ok(windows
+        .into_iter()
+        .filter(|w| !matches!(w.title.as_str(), "maVoice" | "maVoice-AI" | "maVoice-Toast" | "maVoice Settings"))
+        .collect())
+}
+
+/// The best match for `query`: an exact class name, then a class or
+/// title containing it. Windows are topmost first, so ties go to the one
+/// used last.
+fn find<'a>(windows: &'a [WindowInfo], query: &str) -> Option<&'a WindowInfo> {
+    let query = query.trim().to_lowercase();
+    windows
+        .iter()
+        .find(|w| w.class.to_lowercase() == query)
+        .or_else(|| windows.iter().find(|w| w.class.to_lowercase().contains(&query)))
+        .or_else(|| windows.iter().find(|w| w.title.to_lowercase().contains(&query)))
+}
+
+fn describe(w: &WindowInfo) -> Value {
+    json!({ "id": w.id, "title": w.title, "app": w.class })
+}
+
+/// List the open windows, topmost first.
+pub async fn list() -> Value {
+    match tokio::task::spawn_blocking(windows).await {
+        Ok(Ok(windows)) => json!({ "windows": windows.iter().map(describe).collect::<Vec<_>>() }),
+        Ok(Err(e)) => json!({ "error": e }),
+        Err(e) => json!({ "error": format!("Task failed: {}", e) }),
+    }
+}
+
+/// Bring the window matching the `window` argument (app name, title or id)
+/// to the front.
+pub async fn focus(args: &Value) -> Value {
+    let query = match args.get("window").and_then(|v| v.as_str()) {
+        Some(q) if !q.trim().is_empty() => q.to_string(),
+        _ => return json!({ "error": "Missing 'window' parameter" }),
+    };
+
+    let result = tokio::task::spawn_blocking(move || {
+        let windows = windows()?;
+        let target = windows
+            .iter()
+            .find(|w| w.id == query)
+            .or_else(|| find(&windows, &query))
+            .ok_or_else(|| {
+                let open: Vec<&str> = windows.iter().map(|w| w.class.as_str()).collect();
+                format!("No window matches '{}'. Open apps: {}", query, open.join(", "))
+            })?;
+        log::info!("[Tool:focus_window] Activating {} ({})", target.title, target.id);
+        text_inject::activate_window(&target.id).map_err(|e| e.to_string())?;
+        Ok::<_, String>(describe(target))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(window)) => json!({ "success": true, "focused": window }),
+        Ok(Err(e)) => json!({ "error": e }),
+        Err(e) => json!({ "error": format!("Task failed: {}", e) }),
+    }
+}
@@ -0,0 +1,95 @@
+use serde_json::{json, Value};
+
+/// Groq bills every transcription request as at least this long.
+const GROQ_MIN_BILLED_SECS: f64 = 10.0;
+
+/// Gemini Live list prices, USD per million (input, output) tokens: audio
+/// in and out for the native audio model, text for the others.
+const GEMINI_AUDIO_PER_MTOK: (f64, f64) = (3.0, 12.0);
+const GEMINI_TEXT_PER_MTOK: (f64, f64) = (0.5, 2.0);
+
+/// One billable API request.
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub provider: String,
+    pub model: String,
+    pub audio_secs: f64,
+    pub tokens: u64,
+    /// Estimate from list prices; 0 for models without a known price
+    pub cost_usd: f64,
+}
+
+impl UsageRecord {
+    /// A transcription request for `audio_secs` of audio.
+    pub fn transcription(provider: &str, model: &str, audio_secs: f64) -> Self {
+        let per_hour = match (provider, model) {
+            ("groq", "whisper-large-v3-turbo") => 0.04,
+            ("groq", "whisper-large-v3") => 0.111,
+            ("groq", "distil-whisper-large-v3-en") => 0.02,
+            ("openai", "whisper-1") | ("openai", "gpt-4o-transcribe") => 0.36,
+            ("openai", "gpt-4o-mini-transcribe") => 0.18,
+            _ => 0.0,
+        };
+        let billed = if provider == "groq" {
+            audio_secs.max(GROQ_MIN_BILLED_SECS)
+        } else {
+            audio_secs
+        };
+        Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            audio_secs,
+            tokens: 0,
+            cost_usd: billed / 3600.0 * per_hour,
+        }
+    }
+
+    /// Tokens reported by a Gemini Live session (`model` as in the setup
+    /// message).
+    pub fn gemini(model: &str, input_tokens: u64, output_tokens: u64) -> Self {
+        let (input_price, output_price) = if model.contains("native-audio") {
+            GEMINI_AUDIO_PER_MTOK
+        } else {
+            GEMINI_TEXT_PER_MTOK
+        };
+        Self {
+            provider: "gemini".to_string(),
+            model: model.to_string(),
+            audio_secs: 0.0,
+            tokens: input_tokens + output_tokens,
+            cost_usd: (input_tokens as f64 * input_price + output_tokens as f64 * output_price)
+                / 1_000_000.0,
+        }
+    }
+}
+
+/// Sum of the requests in some period.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub audio_secs: f64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "requests": self.requests,
+            "audioSecs": self.audio_secs,
+            "tokens": self.tokens,
+            "costUsd": self.cost_usd,
+        })
+    }
+
+    /// e.g. "14 requests, 9.5 min audio, 3200 tokens, ~$0.02"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} requests, {:.1} min audio, {} tokens, ~${:.2}",
+            self.requests,
+            self.audio_secs / 60.0,
+            self.tokens,
+            self.cost_usd
+        )
+    }
+}
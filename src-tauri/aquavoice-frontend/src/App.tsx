@@ -1,5 +1,6 @@
 import React, { useState, useCallback } from 'react';
 import { FloatingOverlay } from './components/FloatingOverlay';
+import { GeminiLivePanel } from './components/GeminiLivePanel';
 import './App.css';
 
 // Check if running in Tauri
@@ -51,6 +52,13 @@ const getSettings = () => {
 
 // Removed unused MediaRecorderState interface
 
+// Payload of the `transcription-result` event from the global shortcut flow
+interface TranscriptionResult {
+  text: string;
+  injected: boolean;
+  error: string | null;
+}
+
 function App() {
   const [isRecording, setIsRecording] = useState(false);
   const [transcript, setTranscript] = useState('');
@@ -60,6 +68,8 @@ function App() {
   const [apiKeyInput, setApiKeyInput] = useState(getApiKey());
   const [settings, setSettings] = useState(getSettings());
   const [tempSettings, setTempSettings] = useState(getSettings());
+  const [geminiOpen, setGeminiOpen] = useState(false);
+  const [geminiKeyInput, setGeminiKeyInput] = useState(localStorage.getItem('gemini_api_key') || '');
 
   const updateStatus = useCallback((message: string, type: 'info' | 'success' | 'error' | 'processing' = 'info') => {
     setStatus(message);
@@ -74,9 +84,10 @@ function App() {
 
   const startRecording = useCallback(async () => {
     try {
-      // Check API key before starting
+      // Check API key before starting (the backend may have one saved)
       const currentApiKey = getApiKey();
-      if (!currentApiKey) {
+      const hasSavedKey = isTauri && tauriInvoke && await tauriInvoke('has_api_key');
+      if (!currentApiKey && !hasSavedKey) {
         setShowSettings(true);
         updateStatus('Please set your Groq API key', 'error');
         return;
@@ -87,11 +98,13 @@ function App() {
       
       if (isTauri && tauriInvoke) {
         // Set API key first
-        try {
-          await tauriInvoke('set_groq_api_key', { apiKey: currentApiKey });
-          console.log('✅ API key set');
-        } catch (e) {
-          console.log('⚠️ API key already set:', e);
+        if (currentApiKey) {
+          try {
+            await tauriInvoke('set_groq_api_key', { apiKey: currentApiKey });
+            console.log('✅ API key set');
+          } catch (e) {
+            console.log('⚠️ API key already set:', e);
+          }
         }
         
         // Start recording
@@ -209,22 +222,58 @@ function App() {
 
   // REMOVED UNUSED openSettings
 
-  // Global recording function for Rust backend to call
+  // Skip the first-run settings prompt when the backend has a saved key
+  React.useEffect(() => {
+    if (!isTauri || getApiKey()) return;
+    import('@tauri-apps/api/core').then(async ({ invoke }) => {
+      if (await invoke('has_api_key')) {
+        setShowSettings(false);
+      }
+    });
+  }, []);
+
+  // Recording driven by the global shortcut in Rust; follow along via events
   React.useEffect(() => {
-    // Expose global recording function to window object
-    (window as any).startGlobalRecording = () => {
-      console.log('🌍 Global Alt+Alt triggered - starting recording!');
-      if (!isRecording) {
-        startRecording();
+    if (!isTauri) return;
+
+    let cancelled = false;
+    const unlisteners: Array<() => void> = [];
+
+    import('@tauri-apps/api/event').then(async ({ listen }) => {
+      const subscriptions = await Promise.all([
+        listen('recording-started', () => {
+          console.log('🌍 Global shortcut - recording started');
+          setIsRecording(true);
+          updateStatus('Recording...', 'processing');
+        }),
+        listen('recording-stopped', () => {
+          setIsRecording(false);
+          updateStatus('Transcribing...', 'processing');
+        }),
+        listen<TranscriptionResult>('transcription-result', ({ payload }) => {
+          setIsRecording(false);
+          if (payload.text) {
+            setTranscript(payload.text);
+          }
+          if (payload.error) {
+            updateStatus(`Transcription failed: ${payload.error}`, 'error');
+          } else {
+            updateStatus('Ready', 'success');
+          }
+        }),
+      ]);
+      if (cancelled) {
+        subscriptions.forEach(unlisten => unlisten());
       } else {
-        stopRecording();
+        unlisteners.push(...subscriptions);
       }
-    };
-    
+    });
+
     return () => {
-      delete (window as any).startGlobalRecording;
+      cancelled = true;
+      unlisteners.forEach(unlisten => unlisten());
     };
-  }, [isRecording, startRecording, stopRecording]);
+  }, [updateStatus]);
 
   // Global hotkey handling
   React.useEffect(() => {
@@ -266,6 +315,16 @@ function App() {
         stopRecording();
       }
       
+      // Alt + G to start or end a Gemini Live session
+      if (event.code === 'KeyG' && event.altKey) {
+        event.preventDefault();
+        if (!geminiOpen && isRecording) {
+          updateStatus('Stop dictation before starting Gemini Live', 'error');
+        } else {
+          setGeminiOpen(open => !open);
+        }
+      }
+
       // Escape to close settings
       if (event.code === 'Escape' && showSettings) {
         event.preventDefault();
@@ -281,7 +340,7 @@ function App() {
 
     document.addEventListener('keydown', handleKeyDown);
     return () => document.removeEventListener('keydown', handleKeyDown);
-  }, [isRecording, startRecording, stopRecording, showSettings]);
+  }, [isRecording, startRecording, stopRecording, showSettings, geminiOpen, updateStatus]);
 
   return (
     <>
@@ -296,6 +355,8 @@ function App() {
         onOpenSettings={() => setShowSettings(true)}
       />
 
+      <GeminiLivePanel open={geminiOpen} onClose={() => setGeminiOpen(false)} />
+
       {/* Settings modal */}
       {showSettings && (
         <div className="fixed inset-0 bg-black/50 backdrop-blur-sm z-50 flex items-center justify-center">
@@ -319,6 +380,23 @@ function App() {
               </p>
             </div>
             
+            {/* Gemini API Key */}
+            <div className="mb-4">
+              <label className="block text-white/90 text-sm font-medium mb-2">
+                Gemini API Key (optional)
+              </label>
+              <input
+                type="password"
+                value={geminiKeyInput}
+                onChange={(e) => setGeminiKeyInput(e.target.value)}
+                placeholder="Uses GEMINI_API_KEY or the keyring if empty"
+                className="w-full bg-white/10 border border-white/20 rounded-lg px-3 py-2 text-white placeholder-white/50 focus:outline-none focus:border-blue-400 focus:ring-1 focus:ring-blue-400"
+              />
+              <p className="text-white/60 text-xs mt-1">
+                For Gemini Live voice conversations (Alt+G)
+              </p>
+            </div>
+
             {/* Model Selection */}
             <div className="mb-4">
               <label className="block text-white/90 text-sm font-medium mb-2">
@@ -418,6 +496,12 @@ function App() {
                     setApiKey(apiKeyInput.trim());
                   }
                   
+                  if (geminiKeyInput.trim()) {
+                    localStorage.setItem('gemini_api_key', geminiKeyInput.trim());
+                  } else {
+                    localStorage.removeItem('gemini_api_key');
+                  }
+
                   // Save settings
                   localStorage.setItem('groq_settings', JSON.stringify(tempSettings));
                   setSettings(tempSettings);
@@ -433,6 +517,7 @@ function App() {
                 onClick={() => {
                   setTempSettings(settings);
                   setApiKeyInput(apiKey);
+                  setGeminiKeyInput(localStorage.getItem('gemini_api_key') || '');
                   setShowSettings(false);
                 }}
                 className="flex-1 bg-gray-600 hover:bg-gray-700 text-white font-medium py-2 px-4 rounded-lg transition-colors"
@@ -444,7 +529,7 @@ function App() {
             {/* Keyboard Shortcuts */}
             <div className="mt-4 pt-4 border-t border-white/20">
               <p className="text-white/60 text-xs text-center">
-                🎯 Shortcuts: Ctrl+, (settings) • Alt+Space (record) • Double Alt (quick record)
+                🎯 Shortcuts: Ctrl+, (settings) • Alt+Space (record) • Double Alt (quick record) • Alt+G (Gemini Live)
               </p>
             </div>
           </div>
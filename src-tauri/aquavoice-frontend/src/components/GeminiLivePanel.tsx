@@ -0,0 +1,172 @@
+import React, { useState, useEffect, useCallback, useRef } from 'react';
+import { Check, X } from 'lucide-react';
+
+// Payloads of the `gemini-*` events from src-tauri/src/live.rs
+interface LiveStatus {
+  state: 'connecting' | 'ready' | 'closed' | 'error';
+  message: string | null;
+}
+
+interface ToolCallEvent {
+  id: string;
+  name: string;
+  args: Record<string, unknown>;
+  status: 'confirm' | 'running' | 'done' | 'denied';
+  result: unknown;
+}
+
+interface GeminiLivePanelProps {
+  // The session runs while this is true
+  open: boolean;
+  onClose: () => void;
+}
+
+const isTauri = window.__TAURI__ !== undefined;
+
+export const GeminiLivePanel: React.FC<GeminiLivePanelProps> = ({ open, onClose }) => {
+  const [status, setStatus] = useState<LiveStatus | null>(null);
+  const [heard, setHeard] = useState('');
+  const [reply, setReply] = useState('');
+  const [speaking, setSpeaking] = useState(false);
+  const [pending, setPending] = useState<ToolCallEvent[]>([]);
+  // The last turn stays on screen until the next one starts
+  const turnDone = useRef(false);
+
+  const startTurn = () => {
+    if (turnDone.current) {
+      turnDone.current = false;
+      setHeard('');
+      setReply('');
+    }
+  };
+
+  // Start or stop the session with the panel
+  useEffect(() => {
+    if (!isTauri) return;
+    import('@tauri-apps/api/core').then(({ invoke }) => {
+      if (open) {
+        setHeard('');
+        setReply('');
+        const apiKey = localStorage.getItem('gemini_api_key') || undefined;
+        invoke('start_gemini_live', { apiKey }).catch((error) => {
+          setStatus({ state: 'error', message: String(error) });
+        });
+      } else {
+        invoke('stop_gemini_live');
+        setPending([]);
+        setStatus(null);
+      }
+    });
+  }, [open]);
+
+  useEffect(() => {
+    if (!isTauri) return;
+
+    let cancelled = false;
+    const unlisteners: Array<() => void> = [];
+
+    import('@tauri-apps/api/event').then(async ({ listen }) => {
+      const subscriptions = await Promise.all([
+        listen<LiveStatus>('gemini-state', ({ payload }) => {
+          setStatus(payload);
+          if (payload.state === 'closed' || payload.state === 'error') {
+            setPending([]);
+          }
+        }),
+        listen<string>('gemini-heard', ({ payload }) => {
+          startTurn();
+          setHeard(text => text + payload);
+        }),
+        listen<string>('gemini-text', ({ payload }) => {
+          startTurn();
+          setReply(text => text + payload);
+        }),
+        listen<boolean>('gemini-speaking', ({ payload }) => {
+          setSpeaking(payload);
+        }),
+        listen('gemini-turn-complete', () => {
+          turnDone.current = true;
+        }),
+        listen<ToolCallEvent>('gemini-tool-call', ({ payload }) => {
+          setPending(calls => payload.status === 'confirm'
+            ? [...calls.filter(c => c.id !== payload.id), payload]
+            : calls.filter(c => c.id !== payload.id));
+        }),
+      ]);
+      if (cancelled) {
+        subscriptions.forEach(unlisten => unlisten());
+      } else {
+        unlisteners.push(...subscriptions);
+      }
+    });
+
+    return () => {
+      cancelled = true;
+      unlisteners.forEach(unlisten => unlisten());
+    };
+  }, []);
+
+  const answer = useCallback(async (id: string, allow: boolean) => {
+    setPending(calls => calls.filter(c => c.id !== id));
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      await invoke('confirm_tool_call', { id, allow });
+    } catch (error) {
+      // Already answered, timed out or cancelled by Gemini
+      console.error('❌ Tool call confirmation failed:', error);
+    }
+  }, []);
+
+  if (!open && pending.length === 0) {
+    return null;
+  }
+
+  const stateLabel = !status ? 'Starting...'
+    : status.state === 'connecting' ? 'Connecting...'
+    : status.state === 'ready' ? (speaking ? 'Gemini is speaking' : 'Listening')
+    : status.state === 'closed' ? `Session ended${status.message ? `: ${status.message}` : ''}`
+    : `Error: ${status.message ?? 'unknown'}`;
+
+  return (
+    <div className="fixed bottom-4 left-1/2 -translate-x-1/2 z-40 w-[28rem] max-w-[95vw] glass rounded-xl p-4 floating-shadow text-white">
+      <div className="flex items-center justify-between mb-2">
+        <span className="text-sm font-medium">✨ Gemini Live · {stateLabel}</span>
+        <button
+          onClick={onClose}
+          className="text-white/60 hover:text-white"
+          title="End the session (Alt+G)"
+        >
+          <X size={16} />
+        </button>
+      </div>
+
+      {heard && <p className="text-white/60 text-xs mb-1">You: {heard}</p>}
+      {reply && <p className="text-sm whitespace-pre-wrap max-h-40 overflow-y-auto">{reply}</p>}
+
+      {pending.map(call => (
+        <div key={call.id} className="mt-3 border border-yellow-400/40 rounded-lg p-2">
+          <p className="text-sm mb-1">
+            Gemini wants to run <span className="font-mono">{call.name}</span>
+          </p>
+          <pre className="text-xs text-white/80 bg-black/30 rounded p-2 overflow-x-auto max-h-32">
+            {typeof call.args.command === 'string' ? call.args.command : JSON.stringify(call.args, null, 2)}
+          </pre>
+          <div className="flex gap-2 mt-2">
+            <button
+              onClick={() => answer(call.id, true)}
+              className="flex-1 flex items-center justify-center gap-1 bg-green-600 hover:bg-green-700 text-white text-sm py-1 rounded-lg transition-colors"
+            >
+              <Check size={14} /> Allow
+            </button>
+            <button
+              onClick={() => answer(call.id, false)}
+              className="flex-1 flex items-center justify-center gap-1 bg-gray-600 hover:bg-gray-700 text-white text-sm py-1 rounded-lg transition-colors"
+            >
+              <X size={14} /> Deny
+            </button>
+          </div>
+        </div>
+      ))}
+    </div>
+  );
+};
@@ -0,0 +1,621 @@
+use base64::prelude::*;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+/// A single function call from Gemini.
+#[derive(Debug, Clone)]
+pub struct FunctionCall {
+    pub id: String,
+    pub name: String,
+    pub args: serde_json::Value,
+}
+
+/// A response to a function call, sent back to Gemini.
+#[derive(Debug, Clone)]
+pub struct FunctionResponse {
+    pub id: String,
+    pub name: String,
+    pub response: serde_json::Value,
+}
+
+/// How a session authenticates with the Live API.
+#[derive(Debug, Clone)]
+pub enum GeminiAuth {
+    /// Long-lived API key, sent in the WebSocket URL
+    ApiKey(String),
+    /// Endpoint handing out short-lived tokens, asked before every connect
+    TokenEndpoint(String),
+}
+
+/// Per-session options sent in the setup message.
+#[derive(Debug, Clone)]
+pub struct GeminiSetup {
+    pub voice_name: String,
+    pub system_instruction: String,
+    /// Reply with text instead of speech
+    pub text_only: bool,
+    /// Server-side voice activity detection. `None` turns it off and the
+    /// client marks speech with `send_activity_start`/`send_activity_end`
+    /// (push-to-talk).
+    pub activity_detection: Option<ActivityDetection>,
+}
+
+/// Tuning for Gemini's automatic voice activity detection.
+#[derive(Debug, Clone)]
+pub struct ActivityDetection {
+    /// "high" starts a user turn on quieter or shorter sounds, "low" needs
+    /// clearer speech (noisy rooms)
+    pub start_sensitivity: String,
+    /// "high" ends a turn at shorter pauses, "low" waits longer
+    pub end_sensitivity: String,
+    /// Audio kept from before detected speech
+    pub prefix_padding_ms: u64,
+    /// Silence that ends the user's turn
+    pub silence_ms: u64,
+}
+
+/// Native-audio model, used for spoken replies
+pub const AUDIO_MODEL: &str = "models/gemini-2.5-flash-native-audio-preview-12-2025";
+/// Half-cascade Live model; the native-audio one can't reply with text
+pub const TEXT_MODEL: &str = "models/gemini-live-2.5-flash-preview";
+
+/// Commands sent from the main thread to the WebSocket write task.
+enum ClientCommand {
+    SendAudio(Vec<u8>),
+    SendText(String),
+    SendImage {
+        mime_type: String,
+        data: Vec<u8>,
+    },
+    SendToolResponse(Vec<FunctionResponse>),
+    ActivityStart,
+    ActivityEnd,
+    Close,
+}
+
+/// Events sent from the WebSocket read task back to the session bridge.
+#[derive(Debug)]
+pub enum GeminiEvent {
+    Ready,
+    Audio(Vec<u8>),
+    Text(String),
+    /// Transcription of the user's speech, in pieces as it's recognized
+    InputTranscription(String),
+    /// Transcription of Gemini's spoken reply, in pieces
+    OutputTranscription(String),
+    Interrupted,
+    TurnComplete,
+    ToolCall(Vec<FunctionCall>),
+    ToolCallCancellation(Vec<String>),
+    /// Tokens used by the response just sent
+    Usage {
+        input_tokens: u64,
+        output_tokens: u64,
+    },
+    Error(String),
+    Closed(String),
+}
+
+/// Async Gemini Live WebSocket client.
+///
+/// Spawns two tokio tasks (read + write) and communicates via channels.
+/// The `send_*` methods are non-blocking and safe to call from any thread.
+pub struct GeminiLiveClient {
+    cmd_tx: mpsc::UnboundedSender<ClientCommand>,
+    open: Arc<AtomicBool>,
+    send_count: Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl GeminiLiveClient {
+    /// Build the JSON setup message with model config, VAD, compression, and tools.
+    fn build_setup_message(setup: &GeminiSetup, function_declarations: &[Value]) -> Value {
+        let (model, generation_config) = if setup.text_only {
+            (TEXT_MODEL, json!({ "responseModalities": ["TEXT"] }))
+        } else {
+            (
+                AUDIO_MODEL,
+                json!({
+                    "responseModalities": ["AUDIO"],
+                    "speechConfig": {
+                        "voiceConfig": {
+                            "prebuiltVoiceConfig": {
+                                "voiceName": setup.voice_name
+                            }
+                        }
+                    }
+                }),
+            )
+        };
+
+        let activity_detection = match setup.activity_detection {
+            Some(ref vad) => json!({
+                "startOfSpeechSensitivity": if vad.start_sensitivity == "low" {
+                    "START_SENSITIVITY_LOW"
+                } else {
+                    "START_SENSITIVITY_HIGH"
+                },
+                "endOfSpeechSensitivity": if vad.end_sensitivity == "high" {
+                    "END_SENSITIVITY_HIGH"
+                } else {
+                    "END_SENSITIVITY_LOW"
+                },
+                "prefixPaddingMs": vad.prefix_padding_ms,
+                "silenceDurationMs": vad.silence_ms
+            }),
+            None => json!({ "disabled": true }),
+        };
+
+        let mut message = json!({
+            "setup": {
+                "model": model,
+                "generationConfig": generation_config,
+                "systemInstruction": {
+                    "parts": [{ "text": setup.system_instruction }]
+                },
+                "inputAudioTranscription": {},
+                "realtimeInputConfig": {
+                    "automaticActivityDetection": activity_detection
+                },
+                "contextWindowCompression": {
+                    "triggerTokens": 80000,
+                    "slidingWindow": {}
+                },
+                "tools": [
+                    { "googleSearch": {} },
+                    { "functionDeclarations": function_declarations }
+                ]
+            }
+        });
+        // Only spoken replies have a transcription
+        if !setup.text_only {
+            message["setup"]["outputAudioTranscription"] = json!({});
+        }
+        message
+    }
+
+    /// Connect to Gemini Live and start the read/write tasks.
+    ///
+    /// `function_declarations` are the tool schemas the model may call.
+    /// `event_tx` delivers parsed server events back to the caller, which
+    /// forwards them to the webview.
+    pub async fn connect(
+        auth: &GeminiAuth,
+        setup: &GeminiSetup,
+        function_declarations: Vec<Value>,
+        event_tx: mpsc::UnboundedSender<GeminiEvent>,
+    ) -> Result<Self, String> {
+        let url = match auth {
+            GeminiAuth::ApiKey(api_key) => format!(
+                "wss://generativelanguage.googleapis.com/ws/\
+                 google.ai.generativelanguage.v1beta.GenerativeService.\
+                 BidiGenerateContent?key={}",
+                api_key
+            ),
+            GeminiAuth::TokenEndpoint(endpoint) => format!(
+                "wss://generativelanguage.googleapis.com/ws/\
+                 google.ai.generativelanguage.v1alpha.GenerativeService.\
+                 BidiGenerateContentConstrained?access_token={}",
+                fetch_ephemeral_token(endpoint).await?
+            ),
+        };
+
+        let mut ws_config = tokio_tungstenite::tungstenite::protocol::WebSocketConfig::default();
+        ws_config.max_message_size = Some(64 * 1024 * 1024);
+        ws_config.max_frame_size = Some(16 * 1024 * 1024);
+
+        let (ws_stream, _response) =
+            tokio_tungstenite::connect_async_with_config(&url, Some(ws_config), true)
+                .await
+                .map_err(|e| format!("WebSocket connect failed: {}", e))?;
+
+        log::info!("[Gemini] WebSocket connected");
+
+        let (mut ws_write, mut ws_read) = ws_stream.split();
+
+        // Send setup message
+        let setup = Self::build_setup_message(setup, &function_declarations);
+        log::info!("[Gemini] Setup JSON: {}", serde_json::to_string_pretty(&setup).unwrap_or_default());
+
+        ws_write
+            .send(Message::Text(setup.to_string().into()))
+            .await
+            .map_err(|e| format!("Failed to send setup: {}", e))?;
+
+        log::info!("[Gemini] Setup message sent");
+
+        let open = Arc::new(AtomicBool::new(true));
+        let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel::<ClientCommand>();
+
+        // Write task — receives commands and sends them as JSON over WebSocket
+        let write_open = open.clone();
+        let write_handle = tokio::spawn(async move {
+            log::info!("[Gemini] Write task started, waiting for commands...");
+            while let Some(cmd) = cmd_rx.recv().await {
+                if !write_open.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let msg = match cmd {
+                    ClientCommand::SendAudio(pcm_bytes) => {
+                        let encoded = BASE64_STANDARD.encode(&pcm_bytes);
+                        json!({
+                            "realtimeInput": {
+                                "audio": {
+                                    "mimeType": "audio/pcm;rate=16000",
+                                    "data": encoded
+                                }
+                            }
+                        })
+                    }
+                    ClientCommand::SendText(text) => {
+                        json!({
+                            "clientContent": {
+                                "turns": [{ "role": "user", "parts": [{ "text": text }] }],
+                                "turnComplete": true
+                            }
+                        })
+                    }
+                    ClientCommand::SendImage { mime_type, data } => {
+                        // Context for the user's next turn, not a turn of its own
+                        json!({
+                            "clientContent": {
+                                "turns": [{
+                                    "role": "user",
+                                    "parts": [{
+                                        "inlineData": {
+                                            "mimeType": mime_type,
+                                            "data": BASE64_STANDARD.encode(&data)
+                                        }
+                                    }]
+                                }],
+                                "turnComplete": false
+                            }
+                        })
+                    }
+                    ClientCommand::SendToolResponse(responses) => {
+                        let parts: Vec<Value> = responses
+                            .into_iter()
+                            .map(|r| {
+                                json!({
+                                    "id": r.id,
+                                    "name": r.name,
+                                    "response": r.response
+                                })
+                            })
+                            .collect();
+                        json!({
+                            "toolResponse": {
+                                "functionResponses": parts
+                            }
+                        })
+                    }
+                    ClientCommand::ActivityStart => {
+                        json!({ "realtimeInput": { "activityStart": {} } })
+                    }
+                    ClientCommand::ActivityEnd => {
+                        json!({ "realtimeInput": { "activityEnd": {} } })
+                    }
+                    ClientCommand::Close => {
+                        write_open.store(false, Ordering::Relaxed);
+                        let _ = ws_write.close().await;
+                        break;
+                    }
+                };
+
+                if let Err(e) = ws_write.send(Message::Text(msg.to_string().into())).await {
+                    log::error!("[Gemini] Write error: {}", e);
+                    write_open.store(false, Ordering::Relaxed);
+                    break;
+                }
+            }
+
+            log::info!("[Gemini] Write task exiting");
+        });
+
+        // Read task — parses server messages and sends GeminiEvents
+        let read_open = open.clone();
+        let read_event_tx = event_tx.clone();
+        log::info!("[Gemini] About to spawn read task...");
+        let read_handle = tokio::spawn(async move {
+            log::info!("[Gemini] Read task started, waiting for server messages...");
+            while let Some(msg_result) = ws_read.next().await {
+                log::info!("[Gemini] Read task received a message");
+                match msg_result {
+                    Ok(Message::Text(text)) => {
+                        let preview: String = text.chars().take(200).collect();
+                        log::debug!("[Gemini] Text msg: {}", preview);
+                        Self::parse_server_message(&text, &read_event_tx);
+                    }
+                    Ok(Message::Binary(data)) => {
+                        // Gemini sends JSON as binary frames
+                        match std::str::from_utf8(&data) {
+                            Ok(text) => {
+                                let preview: String = text.chars().take(200).collect();
+                                log::debug!("[Gemini] Binary msg (as text): {}", preview);
+                                Self::parse_server_message(text, &read_event_tx);
+                            }
+                            Err(_) => {
+                                log::warn!("[Gemini] Received non-UTF8 binary frame ({} bytes)", data.len());
+                            }
+                        }
+                    }
+                    Ok(Message::Close(frame)) => {
+                        let reason = frame
+                            .map(|f| format!("code={}, reason={}", f.code, f.reason))
+                            .unwrap_or_else(|| "no frame".to_string());
+                        log::info!("[Gemini] WebSocket closed: {}", reason);
+                        read_open.store(false, Ordering::Relaxed);
+                        let _ = read_event_tx.send(GeminiEvent::Closed(reason));
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("[Gemini] Read error: {}", e);
+                        read_open.store(false, Ordering::Relaxed);
+                        let _ = read_event_tx.send(GeminiEvent::Error(e.to_string()));
+                        break;
+                    }
+                    _ => {} // Ping/Pong handled by tungstenite
+                }
+            }
+
+            read_open.store(false, Ordering::Relaxed);
+            log::info!("[Gemini] Read task exiting");
+        });
+
+        Ok(Self {
+            cmd_tx,
+            open,
+            send_count: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+        })
+    }
+
+    /// Parse a server JSON message and emit the appropriate GeminiEvent.
+    fn parse_server_message(text: &str, tx: &mpsc::UnboundedSender<GeminiEvent>) {
+        let msg: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("[Gemini] Malformed server message: {}", e);
+                return;
+            }
+        };
+
+        // Log all server message keys for debugging
+        if let Some(obj) = msg.as_object() {
+            let keys: Vec<&String> = obj.keys().collect();
+            log::debug!("[Gemini] Server message keys: {:?}", keys);
+        }
+
+        // usageMetadata — rides along with other messages, so don't return
+        if let Some(usage) = msg.get("usageMetadata") {
+            let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            let _ = tx.send(GeminiEvent::Usage {
+                input_tokens: count("promptTokenCount"),
+                output_tokens: count("responseTokenCount"),
+            });
+        }
+
+        // setupComplete
+        if msg.get("setupComplete").is_some() {
+            log::info!("[Gemini] Setup complete");
+            let _ = tx.send(GeminiEvent::Ready);
+            return;
+        }
+
+        // goAway — server will disconnect soon
+        if let Some(go_away) = msg.get("goAway") {
+            let time_left = go_away
+                .get("timeLeft")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            log::warn!("[Gemini] goAway received, timeLeft={}", time_left);
+            return;
+        }
+
+        // toolCall — parse function calls and forward to app
+        if let Some(tool_call) = msg.get("toolCall") {
+            let mut calls = Vec::new();
+            if let Some(fn_calls) = tool_call.get("functionCalls").and_then(|v| v.as_array()) {
+                for fc in fn_calls {
+                    let id = fc.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    let name = fc.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    let args = fc.get("args").cloned().unwrap_or(json!({}));
+                    log::info!("[Gemini] Tool call: {}({}) id={}", name, args, id);
+                    calls.push(FunctionCall { id, name, args });
+                }
+            }
+            if !calls.is_empty() {
+                let _ = tx.send(GeminiEvent::ToolCall(calls));
+            }
+            return;
+        }
+
+        // toolCallCancellation — cancel pending calls
+        if let Some(cancel) = msg.get("toolCallCancellation") {
+            let mut ids = Vec::new();
+            if let Some(arr) = cancel.get("ids").and_then(|v| v.as_array()) {
+                for id in arr {
+                    if let Some(s) = id.as_str() {
+                        ids.push(s.to_string());
+                    }
+                }
+            }
+            log::info!("[Gemini] Tool call cancellation: {:?}", ids);
+            if !ids.is_empty() {
+                let _ = tx.send(GeminiEvent::ToolCallCancellation(ids));
+            }
+            return;
+        }
+
+        // serverContent
+        if let Some(content) = msg.get("serverContent") {
+            // Input/output transcription (separate from modelTurn, may share a
+            // message with interrupted/turnComplete)
+            if let Some(text) = content
+                .get("outputTranscription")
+                .and_then(|t| t.get("text"))
+                .and_then(|t| t.as_str())
+            {
+                log::debug!("[Gemini] Output transcription: {}", text);
+                let _ = tx.send(GeminiEvent::OutputTranscription(text.to_string()));
+            }
+            if let Some(text) = content
+                .get("inputTranscription")
+                .and_then(|t| t.get("text"))
+                .and_then(|t| t.as_str())
+            {
+                log::debug!("[Gemini] Input transcription: {}", text);
+                let _ = tx.send(GeminiEvent::InputTranscription(text.to_string()));
+            }
+
+            // Interruption (barge-in)
+            if content.get("interrupted").and_then(|v| v.as_bool()) == Some(true) {
+                log::info!("[Gemini] Interrupted (barge-in)");
+                let _ = tx.send(GeminiEvent::Interrupted);
+                return;
+            }
+
+            // Turn complete
+            if content.get("turnComplete").and_then(|v| v.as_bool()) == Some(true) {
+                let _ = tx.send(GeminiEvent::TurnComplete);
+                return;
+            }
+
+            // Model turn parts — audio and text
+            if let Some(parts) = content
+                .get("modelTurn")
+                .and_then(|mt| mt.get("parts"))
+                .and_then(|p| p.as_array())
+            {
+                for part in parts {
+                    // Audio data (base64 PCM 24kHz s16le)
+                    if let Some(b64_data) = part
+                        .get("inlineData")
+                        .and_then(|d| d.get("data"))
+                        .and_then(|d| d.as_str())
+                    {
+                        match BASE64_STANDARD.decode(b64_data) {
+                            Ok(pcm_bytes) => {
+                                let _ = tx.send(GeminiEvent::Audio(pcm_bytes));
+                            }
+                            Err(e) => {
+                                log::warn!("[Gemini] Base64 decode error: {}", e);
+                            }
+                        }
+                    }
+
+                    // Text transcription
+                    if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
+                        let _ = tx.send(GeminiEvent::Text(text.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send raw PCM audio (16kHz mono s16le) to Gemini.
+    pub fn send_audio(&self, pcm_s16le_16khz: &[u8]) {
+        if self.open.load(Ordering::Relaxed) {
+            let count = self.send_count.fetch_add(1, Ordering::Relaxed);
+            if count == 0 || count % 50 == 0 {
+                log::info!("[Gemini] Audio chunks sent: {} ({}KB total)", count + 1,
+                    (count + 1) * pcm_s16le_16khz.len() as u64 / 1024);
+            }
+            let _ = self.cmd_tx.send(ClientCommand::SendAudio(pcm_s16le_16khz.to_vec()));
+        }
+    }
+
+    /// Signal that the user started speaking (manual VAD).
+    pub fn send_activity_start(&self) {
+        if self.open.load(Ordering::Relaxed) {
+            let _ = self.cmd_tx.send(ClientCommand::ActivityStart);
+        }
+    }
+
+    /// Signal that the user stopped speaking (manual VAD).
+    pub fn send_activity_end(&self) {
+        if self.open.load(Ordering::Relaxed) {
+            let _ = self.cmd_tx.send(ClientCommand::ActivityEnd);
+        }
+    }
+
+    /// Send tool/function responses back to Gemini.
+    pub fn send_tool_response(&self, responses: Vec<FunctionResponse>) {
+        if self.open.load(Ordering::Relaxed) {
+            let _ = self.cmd_tx.send(ClientCommand::SendToolResponse(responses));
+        }
+    }
+
+    /// Send a text message to Gemini (it replies in the session's modality).
+    pub fn send_text(&self, text: &str) {
+        if self.open.load(Ordering::Relaxed) {
+            let _ = self.cmd_tx.send(ClientCommand::SendText(text.to_string()));
+        }
+    }
+
+    /// Add an image to the conversation without ending the user's turn, so
+    /// Gemini answers the next thing said about it.
+    pub fn send_image(&self, mime_type: &str, data: Vec<u8>) {
+        if self.open.load(Ordering::Relaxed) {
+            let _ = self.cmd_tx.send(ClientCommand::SendImage {
+                mime_type: mime_type.to_string(),
+                data,
+            });
+        }
+    }
+
+    /// Close the WebSocket connection.
+    pub fn close(&self) {
+        self.open.store(false, Ordering::Relaxed);
+        let _ = self.cmd_tx.send(ClientCommand::Close);
+    }
+
+    /// Check if the connection is still open.
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for GeminiLiveClient {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// POST to the user's token endpoint and read the ephemeral token from the
+/// reply: a JSON object with `token` or `name` (the shape of Google's
+/// `authTokens.create`), or the bare token as plain text.
+async fn fetch_ephemeral_token(endpoint: &str) -> Result<String, String> {
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Token endpoint returned {}: {}", status, body.trim()));
+    }
+
+    let token = match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Object(obj)) => ["token", "name"]
+            .iter()
+            .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .ok_or("Token endpoint reply has no 'token' or 'name'")?,
+        Ok(Value::String(token)) => token,
+        _ => body.trim().to_string(),
+    };
+    if token.is_empty() {
+        return Err("Token endpoint returned an empty token".to_string());
+    }
+    log::info!("[Gemini] Got an ephemeral token");
+    Ok(token)
+}
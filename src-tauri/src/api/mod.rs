@@ -1,3 +1,4 @@
+pub mod gemini;
 pub mod groq;
 
-pub use groq::*;
\ No newline at end of file
+pub use groq::*;
@@ -3,6 +3,51 @@ use hound::{WavSpec, WavWriter};
 use std::sync::{Arc, Mutex};
 use std::io::Cursor;
 use crossbeam_channel::{unbounded, Receiver, Sender};
+use serde::Serialize;
+
+use super::{Recorder, StreamingCallback};
+
+/// Rates offered in the mic picker, checked against what each device supports
+const COMMON_SAMPLE_RATES: &[u32] = &[8_000, 16_000, 22_050, 24_000, 32_000, 44_100, 48_000, 96_000];
+
+/// A capture device for the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub sample_rates: Vec<u32>,
+}
+
+/// Collects captured samples into ~100 ms chunks for the streaming callback
+struct Streamer {
+    callback: Arc<Mutex<Option<StreamingCallback>>>,
+    pending: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl Streamer {
+    fn push(&mut self, data: &[f32]) {
+        let Some(callback) = self.callback.lock().unwrap().clone() else {
+            self.pending.clear();
+            return;
+        };
+        self.pending.extend_from_slice(data);
+        if self.pending.len() < (self.sample_rate / 10) as usize {
+            return;
+        }
+
+        let chunk = std::mem::take(&mut self.pending);
+        let ratio = self.sample_rate as f64 / 16_000.0;
+        let out_len = (chunk.len() as f64 / ratio) as usize;
+        let mut pcm = Vec::with_capacity(out_len * 2);
+        for i in 0..out_len {
+            let s = chunk[((i as f64 * ratio) as usize).min(chunk.len() - 1)];
+            let s16 = (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            pcm.extend_from_slice(&s16.to_le_bytes());
+        }
+        callback(&pcm);
+    }
+}
 
 pub struct GroqRecorder {
     device: Device,
@@ -11,18 +56,27 @@ pub struct GroqRecorder {
     audio_buffer: Arc<Mutex<Vec<f32>>>,
     sample_sender: Sender<f32>,
     _sample_receiver: Receiver<f32>,
+    streaming_callback: Arc<Mutex<Option<StreamingCallback>>>,
 }
 
 impl GroqRecorder {
-    pub fn new() -> Result<Self, String> {
+    /// Record from the input device called `name`, or the system default
+    pub fn with_device(name: Option<&str>) -> Result<Self, String> {
         println!("🎤 Initializing Groq-compatible audio recorder");
 
         let host = cpal::default_host();
         println!("🔧 Audio host: {}", host.id().name());
 
-        let input_device = host
-            .default_input_device()
-            .ok_or("No input device available")?;
+        let input_device = match name {
+            Some(name) => host
+                .input_devices()
+                .map_err(|e| e.to_string())?
+                .find(|d| d.name().map_or(false, |n| n == name))
+                .ok_or_else(|| format!("Input device not found: {}", name))?,
+            None => host
+                .default_input_device()
+                .ok_or("No input device available")?,
+        };
         println!("🎧 Using device: {}", input_device.name().unwrap_or_default());
 
         // prefer 16 kHz mono; fallback to device default
@@ -67,10 +121,44 @@ impl GroqRecorder {
             audio_buffer,
             sample_sender: tx,
             _sample_receiver: rx,
+            streaming_callback: Arc::new(Mutex::new(None)),
         })
     }
     
-    pub fn start_recording(&mut self) -> Result<(), String> {
+    /// Capture devices with the common sample rates each one supports
+    pub fn list_devices() -> Result<Vec<InputDeviceInfo>, String> {
+        let host = cpal::default_host();
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+        let devices = host.input_devices().map_err(|e| e.to_string())?;
+
+        Ok(devices
+            .filter_map(|device| {
+                let name = device.name().ok()?;
+                let ranges: Vec<_> = device
+                    .supported_input_configs()
+                    .map(|configs| configs.collect())
+                    .unwrap_or_default();
+                let sample_rates = COMMON_SAMPLE_RATES
+                    .iter()
+                    .copied()
+                    .filter(|&rate| {
+                        ranges.iter().any(|r| {
+                            r.min_sample_rate() <= SampleRate(rate) && r.max_sample_rate() >= SampleRate(rate)
+                        })
+                    })
+                    .collect();
+                Some(InputDeviceInfo {
+                    is_default: default_name.as_deref() == Some(name.as_str()),
+                    name,
+                    sample_rates,
+                })
+            })
+            .collect())
+    }
+}
+
+impl Recorder for GroqRecorder {
+    fn start_recording(&mut self) -> Result<(), String> {
         if self.stream.is_some() {
             return Err("Already recording".into());
         }
@@ -89,6 +177,12 @@ impl GroqRecorder {
 
         let err_fn = |err| eprintln!("❌ Stream error: {err}");
 
+        let mut streamer = Streamer {
+            callback: self.streaming_callback.clone(),
+            pending: Vec::new(),
+            sample_rate: self.config.sample_rate.0,
+        };
+
         self.stream = Some(match sample_format {
             SampleFormat::F32 => {
                 self.device
@@ -99,6 +193,7 @@ impl GroqRecorder {
                                 let _ = tx.send(s);
                             }
                             audio_buf.lock().unwrap().extend_from_slice(data);
+                            streamer.push(data);
                         },
                         err_fn,
                         None,
@@ -110,11 +205,12 @@ impl GroqRecorder {
                     .build_input_stream(
                         &self.config,
                         move |data: &[i16], _| {
-                            for &s in data {
-                                let f = s as f32 / i16::MAX as f32;
+                            let samples: Vec<f32> = data.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
+                            for &f in &samples {
                                 let _ = tx.send(f);
-                                audio_buf.lock().unwrap().push(f);
                             }
+                            audio_buf.lock().unwrap().extend_from_slice(&samples);
+                            streamer.push(&samples);
                         },
                         err_fn,
                         None,
@@ -126,11 +222,13 @@ impl GroqRecorder {
                     .build_input_stream(
                         &self.config,
                         move |data: &[u16], _| {
-                            for &s in data {
-                                let f = (s as f32 / u16::MAX as f32) * 2.0 - 1.0;
+                            let samples: Vec<f32> =
+                                data.iter().map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0).collect();
+                            for &f in &samples {
                                 let _ = tx.send(f);
-                                audio_buf.lock().unwrap().push(f);
                             }
+                            audio_buf.lock().unwrap().extend_from_slice(&samples);
+                            streamer.push(&samples);
                         },
                         err_fn,
                         None,
@@ -150,7 +248,7 @@ impl GroqRecorder {
         Ok(())
     }
     
-    pub fn stop_recording(&mut self) -> Result<Vec<u8>, String> {
+    fn stop_recording(&mut self) -> Result<Vec<u8>, String> {
         if self.stream.is_none() {
             return Err("Not recording".into());
         }
@@ -191,12 +289,15 @@ impl GroqRecorder {
         Ok(wav_bytes)
     }
     
-    pub fn is_recording(&self) -> bool {
+    fn is_recording(&self) -> bool {
         self.stream.is_some()
     }
 
-    // NEW: Get real-time audio levels for visualization
-    pub fn get_audio_levels(&self) -> [f32; 4] {
+    fn set_streaming_callback(&self, callback: Option<StreamingCallback>) {
+        *self.streaming_callback.lock().unwrap() = callback;
+    }
+
+    fn get_audio_levels(&self) -> [f32; 4] {
         if !self.is_recording() {
             return [0.0, 0.0, 0.0, 0.0]; // Silent when not recording
         }
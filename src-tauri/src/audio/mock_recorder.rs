@@ -1,34 +1,33 @@
-// Mock audio recorder for WSL2 testing
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tokio::time::sleep;
+// Mock audio recorder for WSL2, containers and CI (no capture device)
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Recorder, StreamingCallback};
 
 pub struct MockGroqRecorder {
-    recording: Arc<Mutex<bool>>,
+    recording: bool,
 }
 
 impl MockGroqRecorder {
-    pub fn new() -> Result<Self, String> {
-        println!("🎤 Using MOCK audio recorder for WSL2 testing");
-        Ok(Self {
-            recording: Arc::new(Mutex::new(false)),
-        })
+    pub fn new() -> Self {
+        println!("🎤 Using MOCK audio recorder (no microphone)");
+        Self { recording: false }
     }
+}
 
-    pub fn start_recording(&self) -> Result<(), String> {
-        let mut recording = self.recording.lock().unwrap();
-        *recording = true;
+impl Recorder for MockGroqRecorder {
+    fn start_recording(&mut self) -> Result<(), String> {
+        self.recording = true;
         println!("🔴 Mock recording started");
         Ok(())
     }
 
-    pub async fn stop_recording(&self) -> Result<Vec<u8>, String> {
-        let mut recording = self.recording.lock().unwrap();
-        *recording = false;
+    fn stop_recording(&mut self) -> Result<Vec<u8>, String> {
+        if !self.recording {
+            return Err("Not recording".into());
+        }
+        self.recording = false;
         println!("⏹️ Mock recording stopped");
         
-        // Simulate processing delay
-        sleep(Duration::from_millis(500)).await;
         
         // Return a minimal valid WAV file (silent audio)
         // WAV header for 1 second of silence at 16kHz mono
@@ -57,15 +56,26 @@ impl MockGroqRecorder {
         Ok(wav_data)
     }
 
-    pub async fn get_audio_level(&self) -> f32 {
-        // Return random levels for visual feedback
-        if *self.recording.lock().unwrap() {
-            rand::random::<f32>() * 0.5
-        } else {
-            0.0
+    fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    fn get_audio_levels(&self) -> [f32; 4] {
+        if !self.recording {
+            return [0.0; 4];
+        }
+        // Wobbling levels so the visualizer has something to show
+        let t = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as f32 / 1000.0)
+            .unwrap_or(0.0);
+        let mut levels = [0.0f32; 4];
+        for (i, level) in levels.iter_mut().enumerate() {
+            *level = 0.25 + 0.2 * (t * (3.0 + i as f32)).sin();
         }
+        levels
     }
-}
 
-// Re-export as GroqRecorder for compatibility
-pub type GroqRecorder = MockGroqRecorder;
\ No newline at end of file
+    /// Nothing to stream; Gemini Live just hears silence
+    fn set_streaming_callback(&self, _callback: Option<StreamingCallback>) {}
+}
\ No newline at end of file
@@ -1,3 +1,44 @@
 pub mod groq_recorder;
+pub mod mock_recorder;
+pub mod player;
 
-pub use groq_recorder::GroqRecorder;
\ No newline at end of file
+use std::sync::Arc;
+
+pub use groq_recorder::GroqRecorder;
+pub use mock_recorder::MockGroqRecorder;
+pub use player::AudioPlayer;
+
+/// Receives the live mic as 16 kHz mono s16le PCM in ~100 ms chunks
+pub type StreamingCallback = Arc<dyn Fn(&[u8]) + Send + Sync>;
+
+/// A microphone the app can record from: the real one, or the mock used
+/// where there is no capture device
+pub trait Recorder: Send {
+    fn start_recording(&mut self) -> Result<(), String>;
+
+    /// Stop and return the recording as a WAV
+    fn stop_recording(&mut self) -> Result<Vec<u8>, String>;
+
+    fn is_recording(&self) -> bool;
+
+    /// Real-time levels for visualization (4 pseudo-frequency bands)
+    fn get_audio_levels(&self) -> [f32; 4];
+
+    /// Stream the mic while recording (Gemini Live); `None` stops streaming
+    fn set_streaming_callback(&self, callback: Option<StreamingCallback>);
+}
+
+/// The mock when `mock` is set, else the microphone called `device` (or
+/// the default one), falling back to the mock if there is none
+pub fn create_recorder(mock: bool, device: Option<&str>) -> Box<dyn Recorder> {
+    if mock {
+        return Box::new(MockGroqRecorder::new());
+    }
+    match GroqRecorder::with_device(device) {
+        Ok(recorder) => Box::new(recorder),
+        Err(e) => {
+            println!("⚠️ No usable microphone ({}), using the mock recorder", e);
+            Box::new(MockGroqRecorder::new())
+        }
+    }
+}
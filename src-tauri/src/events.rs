@@ -0,0 +1,30 @@
+use serde::Serialize;
+
+// Events emitted to the webview
+
+/// The global shortcut started a recording
+pub const RECORDING_STARTED: &str = "recording-started";
+/// The global shortcut stopped the recording; transcription is under way
+pub const RECORDING_STOPPED: &str = "recording-stopped";
+/// Outcome of a shortcut-driven recording ([`TranscriptionResult`])
+pub const TRANSCRIPTION_RESULT: &str = "transcription-result";
+/// A transcript was saved to history (a `HistoryEntry`)
+pub const TRANSCRIPTION_COMPLETED: &str = "transcription-completed";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    /// Whether the text was typed into the focused window
+    pub injected: bool,
+    pub error: Option<String>,
+}
+
+impl TranscriptionResult {
+    pub fn injected(text: String) -> Self {
+        Self { text, injected: true, error: None }
+    }
+
+    pub fn failed(error: String) -> Self {
+        Self { text: String::new(), injected: false, error: Some(error) }
+    }
+}
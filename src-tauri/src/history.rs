@@ -0,0 +1,110 @@
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Same table the native app writes its dictations to, so both apps share
+/// one history in `~/.local/share/mavoice/history.db`
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS dictations (
+        id       INTEGER PRIMARY KEY,
+        at       INTEGER NOT NULL,
+        provider TEXT NOT NULL,
+        text     TEXT NOT NULL
+    );
+";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    /// Unix time in milliseconds
+    pub at: i64,
+    pub provider: String,
+    pub text: String,
+}
+
+impl HistoryEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            at: row.get(1)?,
+            provider: row.get(2)?,
+            text: row.get(3)?,
+        })
+    }
+}
+
+pub struct TranscriptHistory {
+    conn: Connection,
+}
+
+impl TranscriptHistory {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let conn = Connection::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Cannot set up history DB: {}", e))?;
+        println!("🗂️ History DB: {}", path.display());
+        Ok(Self { conn })
+    }
+
+    pub fn add(&self, provider: &str, text: &str) -> Result<HistoryEntry, String> {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        self.conn
+            .execute(
+                "INSERT INTO dictations (at, provider, text) VALUES (?1, ?2, ?3)",
+                params![at, provider, text],
+            )
+            .map_err(|e| format!("Cannot save transcript: {}", e))?;
+        Ok(HistoryEntry {
+            id: self.conn.last_insert_rowid(),
+            at,
+            provider: provider.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    /// Newest first
+    pub fn list(&self, limit: usize, offset: usize) -> Result<Vec<HistoryEntry>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, at, provider, text FROM dictations ORDER BY at DESC LIMIT ?1 OFFSET ?2")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![limit as i64, offset as i64], HistoryEntry::from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Case-insensitive substring search, newest first
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        let pattern = format!(
+            "%{}%",
+            query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+        );
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, at, provider, text FROM dictations \
+                 WHERE text LIKE ?1 ESCAPE '\\' ORDER BY at DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![pattern, limit as i64], HistoryEntry::from_row)
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Returns whether an entry was deleted
+    pub fn delete(&self, id: i64) -> Result<bool, String> {
+        self.conn
+            .execute("DELETE FROM dictations WHERE id = ?1", params![id])
+            .map(|n| n > 0)
+            .map_err(|e| format!("Cannot delete transcript: {}", e))
+    }
+}
@@ -0,0 +1,339 @@
+//! Gemini Live voice assistant: the mic streams to Gemini, spoken replies
+//! play through `AudioPlayer`, tool calls run locally, and everything the
+//! webview shows arrives as `gemini-*` events.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::api::gemini::{
+    ActivityDetection, FunctionCall, FunctionResponse, GeminiAuth, GeminiEvent, GeminiLiveClient, GeminiSetup,
+};
+use crate::audio::StreamingCallback;
+use crate::audio::AudioPlayer;
+use crate::store::SettingsStore;
+use crate::tools;
+use crate::AppState;
+
+/// How long a tool call waits for the user before counting as denied
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Same defaults as the native app
+pub const DEFAULT_VOICE: &str = "Aoede";
+pub const DEFAULT_INSTRUCTION: &str = "You are maVoice, a warm and helpful desktop voice assistant running on Linux. \
+    Keep responses concise and conversational. You have access to tools: you can search the web \
+    via Google Search, search and save memories, run shell commands on the user's machine, and \
+    delegate complex tasks to Claude. Use tools proactively when they'd help answer a question.";
+
+// Events emitted to the webview
+
+/// Session state ([`LiveStatus`])
+pub const GEMINI_STATE: &str = "gemini-state";
+/// Gemini's reply text (text-only sessions) or transcribed speech, in pieces
+pub const GEMINI_TEXT: &str = "gemini-text";
+/// What Gemini heard the user say, in pieces
+pub const GEMINI_HEARD: &str = "gemini-heard";
+/// Gemini started (`true`) or stopped (`false`) speaking
+pub const GEMINI_SPEAKING: &str = "gemini-speaking";
+pub const GEMINI_TURN_COMPLETE: &str = "gemini-turn-complete";
+/// A tool call started, waits for approval, or finished ([`ToolCallEvent`])
+pub const GEMINI_TOOL_CALL: &str = "gemini-tool-call";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveStatus {
+    /// "connecting", "ready", "closed" or "error"
+    pub state: &'static str,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCallEvent {
+    pub id: String,
+    pub name: String,
+    pub args: Value,
+    /// "confirm", "running", "done" or "denied"
+    pub status: &'static str,
+    pub result: Option<Value>,
+}
+
+/// Options from the frontend; anything left out uses the defaults
+#[derive(Debug, Clone, Default)]
+pub struct LiveOptions {
+    pub api_key: Option<String>,
+    pub voice_name: Option<String>,
+    pub system_instruction: Option<String>,
+    pub text_only: bool,
+}
+
+/// Managed state of the Gemini Live session
+#[derive(Default)]
+pub struct LiveState {
+    /// Shared with the mic streaming callback
+    client: Arc<Mutex<Option<GeminiLiveClient>>>,
+    player: Arc<Mutex<Option<AudioPlayer>>>,
+    /// Tool calls waiting for `confirm_tool_call`
+    confirmations: Arc<Mutex<HashMap<String, oneshot::Sender<bool>>>>,
+}
+
+impl LiveState {
+    pub fn is_active(&self) -> bool {
+        self.client.lock().unwrap().is_some()
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<(), String> {
+        let client = self.client.lock().unwrap();
+        let client = client.as_ref().ok_or("Gemini Live is not running")?;
+        client.send_text(text);
+        Ok(())
+    }
+
+    /// Answer a pending tool call confirmation
+    pub fn confirm(&self, id: &str, allow: bool) -> Result<(), String> {
+        let sender = self
+            .confirmations
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| format!("No tool call {} is waiting", id))?;
+        let _ = sender.send(allow);
+        Ok(())
+    }
+
+    /// Gemini's voice as 4 bands for the visualizer
+    pub fn output_levels(&self) -> [f32; 4] {
+        self.player
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or([0.0; 4], |p| p.get_output_levels())
+    }
+}
+
+fn emit_status(app: &AppHandle, state: &'static str, message: Option<String>) {
+    let _ = app.emit(GEMINI_STATE, LiveStatus { state, message });
+}
+
+/// Connect to Gemini Live and start forwarding its events. The mic starts
+/// once the server has accepted the setup.
+pub async fn start(app: &AppHandle, options: LiveOptions) -> Result<(), String> {
+    let live = app.state::<LiveState>();
+    if live.is_active() {
+        return Ok(());
+    }
+    if app.state::<AppState>().groq_recorder.lock().map_err(|e| e.to_string())?.is_recording() {
+        return Err("Stop dictation before starting Gemini Live".to_string());
+    }
+
+    let api_key = options
+        .api_key
+        .filter(|k| !k.trim().is_empty())
+        .or_else(|| std::env::var("GEMINI_API_KEY").ok())
+        .or_else(crate::system::secrets::gemini_key)
+        .ok_or("No Gemini API key: set GEMINI_API_KEY or pass one")?;
+
+    {
+        let mut player = live.player.lock().unwrap();
+        if player.is_none() {
+            *player = Some(AudioPlayer::new()?);
+        }
+    }
+
+    emit_status(app, "connecting", None);
+    let setup = GeminiSetup {
+        voice_name: options.voice_name.unwrap_or_else(|| DEFAULT_VOICE.to_string()),
+        system_instruction: options
+            .system_instruction
+            .unwrap_or_else(|| DEFAULT_INSTRUCTION.to_string()),
+        text_only: options.text_only,
+        activity_detection: Some(ActivityDetection {
+            start_sensitivity: "high".to_string(),
+            end_sensitivity: "low".to_string(),
+            prefix_padding_ms: 100,
+            silence_ms: 500,
+        }),
+    };
+    let (event_tx, event_rx) = mpsc::unbounded_channel::<GeminiEvent>();
+    let client = match GeminiLiveClient::connect(&GeminiAuth::ApiKey(api_key), &setup, tools::declarations(), event_tx)
+        .await
+    {
+        Ok(client) => client,
+        Err(e) => {
+            emit_status(app, "error", Some(e.clone()));
+            return Err(e);
+        }
+    };
+    *live.client.lock().unwrap() = Some(client);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(bridge_events(app, event_rx));
+    Ok(())
+}
+
+/// Close the session, stop the mic and silence any reply still playing
+pub fn stop(app: &AppHandle) {
+    let live = app.state::<LiveState>();
+    if let Some(client) = live.client.lock().unwrap().take() {
+        client.close();
+    }
+    stop_mic(app);
+    if let Some(player) = live.player.lock().unwrap().as_ref() {
+        player.clear();
+    }
+    // Pending confirmations count as denied
+    live.confirmations.lock().unwrap().clear();
+}
+
+fn start_mic(app: &AppHandle) -> Result<(), String> {
+    let client = app.state::<LiveState>().client.clone();
+    let callback: StreamingCallback = Arc::new(move |pcm_s16le: &[u8]| {
+        if let Some(client) = client.lock().unwrap().as_ref() {
+            client.send_audio(pcm_s16le);
+        }
+    });
+
+    let state = app.state::<AppState>();
+    let mut recorder = state.groq_recorder.lock().map_err(|e| e.to_string())?;
+    recorder.set_streaming_callback(Some(callback));
+    recorder.start_recording()
+}
+
+fn stop_mic(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(mut recorder) = state.groq_recorder.lock() else {
+        return;
+    };
+    recorder.set_streaming_callback(None);
+    if recorder.is_recording() {
+        let _ = recorder.stop_recording();
+    }
+}
+
+/// Forward Gemini's events to the player, the tools and the webview until
+/// the session ends.
+async fn bridge_events(app: AppHandle, mut event_rx: mpsc::UnboundedReceiver<GeminiEvent>) {
+    let mut speaking = false;
+    while let Some(event) = event_rx.recv().await {
+        match event {
+            GeminiEvent::Ready => {
+                println!("✨ Gemini Live ready");
+                match start_mic(&app) {
+                    Ok(()) => emit_status(&app, "ready", None),
+                    Err(e) => {
+                        emit_status(&app, "error", Some(format!("Microphone: {}", e)));
+                        stop(&app);
+                    }
+                }
+            }
+            GeminiEvent::Audio(data) => {
+                if let Some(player) = app.state::<LiveState>().player.lock().unwrap().as_ref() {
+                    player.enqueue(&data);
+                }
+                if !speaking {
+                    speaking = true;
+                    let _ = app.emit(GEMINI_SPEAKING, true);
+                }
+            }
+            GeminiEvent::Text(text) | GeminiEvent::OutputTranscription(text) => {
+                let _ = app.emit(GEMINI_TEXT, text);
+            }
+            GeminiEvent::InputTranscription(text) => {
+                let _ = app.emit(GEMINI_HEARD, text);
+            }
+            GeminiEvent::Interrupted => {
+                // Barge-in: drop the rest of the reply
+                if let Some(player) = app.state::<LiveState>().player.lock().unwrap().as_ref() {
+                    player.clear();
+                }
+                if std::mem::take(&mut speaking) {
+                    let _ = app.emit(GEMINI_SPEAKING, false);
+                }
+            }
+            GeminiEvent::TurnComplete => {
+                if std::mem::take(&mut speaking) {
+                    let _ = app.emit(GEMINI_SPEAKING, false);
+                }
+                let _ = app.emit(GEMINI_TURN_COMPLETE, ());
+            }
+            GeminiEvent::ToolCall(calls) => {
+                for call in calls {
+                    tauri::async_runtime::spawn(run_tool_call(app.clone(), call));
+                }
+            }
+            GeminiEvent::ToolCallCancellation(ids) => {
+                let live = app.state::<LiveState>();
+                let mut confirmations = live.confirmations.lock().unwrap();
+                for id in ids {
+                    confirmations.remove(&id);
+                }
+            }
+            GeminiEvent::Usage { .. } => {}
+            GeminiEvent::Error(e) => {
+                println!("❌ Gemini Live error: {}", e);
+                emit_status(&app, "error", Some(e));
+                stop(&app);
+                break;
+            }
+            GeminiEvent::Closed(reason) => {
+                println!("🔌 Gemini Live closed: {}", reason);
+                emit_status(&app, "closed", Some(reason));
+                stop(&app);
+                break;
+            }
+        }
+    }
+}
+
+/// Run one tool call, after the user approves it if it needs approval, and
+/// send the result back to Gemini.
+async fn run_tool_call(app: AppHandle, call: FunctionCall) {
+    let event = |status, result| ToolCallEvent {
+        id: call.id.clone(),
+        name: call.name.clone(),
+        args: call.args.clone(),
+        status,
+        result,
+    };
+
+    let allowed = if tools::needs_confirmation(&call.name) {
+        let (tx, rx) = oneshot::channel();
+        app.state::<LiveState>()
+            .confirmations
+            .lock()
+            .unwrap()
+            .insert(call.id.clone(), tx);
+        let _ = app.emit(GEMINI_TOOL_CALL, event("confirm", None));
+        // A dropped sender (cancelled call, closed session) is a denial too
+        let answer = matches!(tokio::time::timeout(CONFIRM_TIMEOUT, rx).await, Ok(Ok(true)));
+        app.state::<LiveState>().confirmations.lock().unwrap().remove(&call.id);
+        answer
+    } else {
+        true
+    };
+
+    let response = if allowed {
+        let _ = app.emit(GEMINI_TOOL_CALL, event("running", None));
+        let memory_db = app
+            .state::<Mutex<SettingsStore>>()
+            .lock()
+            .ok()
+            .and_then(|store| store.memory_db_path());
+        let result = tools::execute(&call.name, &call.args, memory_db).await;
+        let _ = app.emit(GEMINI_TOOL_CALL, event("done", Some(result.clone())));
+        result
+    } else {
+        let _ = app.emit(GEMINI_TOOL_CALL, event("denied", None));
+        json!({ "error": "The user declined to run this tool" })
+    };
+
+    if let Some(client) = app.state::<LiveState>().client.lock().unwrap().as_ref() {
+        client.send_tool_response(vec![FunctionResponse {
+            id: call.id.clone(),
+            name: call.name.clone(),
+            response,
+        }]);
+    }
+}
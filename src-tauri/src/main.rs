@@ -3,21 +3,35 @@
 
 mod api;
 mod audio;
+mod events;
+mod history;
+mod live;
+mod store;
 mod system;
+mod tools;
 mod webm;
 
-use tauri::{State, Manager};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri::{AppHandle, Emitter, State, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 use std::sync::{Arc, Mutex};
 use api::GroqClient;
-use audio::GroqRecorder;
+use audio::groq_recorder::InputDeviceInfo;
+use audio::{GroqRecorder, Recorder};
+use history::{HistoryEntry, TranscriptHistory};
+use live::{LiveOptions, LiveState};
+use serde::Serialize;
+use store::{SettingsStore, DEFAULT_SHORTCUT};
+use system::secrets::KeyStore;
 use system::{TextInjector, WindowInfo};
 use webm::WebMProcessor;
 
+/// Shared history store; `None` if the database could not be opened
+pub type HistoryState = Mutex<Option<TranscriptHistory>>;
+
 // Simplified application state
 pub struct AppState {
     pub groq_client: Arc<Mutex<Option<GroqClient>>>,
-    pub groq_recorder: Arc<Mutex<GroqRecorder>>,
+    pub groq_recorder: Arc<Mutex<Box<dyn Recorder>>>,
     pub text_injector: Arc<Mutex<TextInjector>>,
     pub webm_processor: Arc<Mutex<WebMProcessor>>,
 }
@@ -75,29 +89,90 @@ async fn get_audio_levels(state: State<'_, AppState>) -> Result<[f32; 4], String
 }
 
 #[tauri::command]
-async fn set_groq_api_key(state: State<'_, AppState>, api_key: String) -> Result<String, String> {
+async fn list_input_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    GroqRecorder::list_devices()
+}
+
+/// Switch microphones (`None` or empty = system default) and remember it
+#[tauri::command]
+async fn set_input_device(
+    state: State<'_, AppState>,
+    store: State<'_, Mutex<SettingsStore>>,
+    name: Option<String>,
+) -> Result<String, String> {
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    let mut recorder = state.groq_recorder.lock()
+        .map_err(|e| format!("Recorder lock error: {}", e))?;
+    if recorder.is_recording() {
+        return Err("Stop recording before switching microphones".to_string());
+    }
+    *recorder = Box::new(GroqRecorder::with_device(name.as_deref())?);
+    drop(recorder);
+
+    store
+        .lock()
+        .map_err(|e| e.to_string())?
+        .update(|s| s.input_device = name.clone().unwrap_or_default())?;
+    Ok(format!("Using {}", name.as_deref().unwrap_or("the default microphone")))
+}
+
+#[tauri::command]
+async fn set_groq_api_key(
+    state: State<'_, AppState>,
+    keys: State<'_, KeyStore>,
+    api_key: String,
+) -> Result<String, String> {
+    let api_key = api_key.trim().to_string();
+    if api_key.is_empty() {
+        return Err("Groq API key is empty".to_string());
+    }
+
     let groq_client = state.groq_client.clone();
     let mut client_guard = groq_client.lock().map_err(|e| e.to_string())?;
-    
-    *client_guard = Some(GroqClient::new(api_key));
-    Ok("Groq API key set successfully".to_string())
+    *client_guard = Some(GroqClient::new(api_key.clone()));
+    drop(client_guard);
+
+    // The key still works for this session if it can't be saved
+    match keys.save(&api_key) {
+        Ok(()) => Ok("Groq API key set successfully".to_string()),
+        Err(e) => {
+            println!("⚠️ {}", e);
+            Ok(format!("Groq API key set for this session only: {}", e))
+        }
+    }
 }
 
 #[tauri::command]
-async fn transcribe_audio(
-    state: State<'_, AppState>,
-    audio_data: Vec<u8>,
+async fn has_api_key(state: State<'_, AppState>) -> Result<bool, String> {
+    let client_guard = state.groq_client.lock().map_err(|e| e.to_string())?;
+    Ok(client_guard.is_some())
+}
+
+/// Whisper request parameters the frontend may override
+#[derive(Debug, Default)]
+struct TranscribeOptions {
     model: Option<String>,
     language: Option<String>,
     prompt: Option<String>,
     response_format: Option<String>,
     temperature: Option<f32>,
-) -> Result<String, String> {
+}
+
+/// Send a WAV to Groq, save the result to history and announce it with a
+/// `transcription-completed` event
+async fn transcribe_wav(app: &AppHandle, audio_data: Vec<u8>, options: TranscribeOptions) -> Result<String, String> {
     println!("📤 Sending {:.2}KB WAV to Groq", audio_data.len() as f32 / 1024.0);
+
+    // Fall back to the saved dictionary when the frontend sends no prompt
+    let prompt = match options.prompt.filter(|p| !p.trim().is_empty()) {
+        Some(p) => Some(p),
+        None => app.state::<Mutex<SettingsStore>>().lock().map_err(|e| e.to_string())?.dictionary_prompt(),
+    };
     
     // Get Groq client
     let client = {
-        let groq_client = state.groq_client.clone();
+        let groq_client = app.state::<AppState>().groq_client.clone();
         let client_guard = groq_client.lock().map_err(|e| e.to_string())?;
         client_guard.as_ref().ok_or("Groq API key not set")?.clone()
     };
@@ -105,17 +180,33 @@ async fn transcribe_audio(
     match client.transcribe_audio_bytes(
         &audio_data, 
         "recording.wav", 
-        model.as_deref(), 
-        language.as_deref(), 
+        options.model.as_deref(), 
+        options.language.as_deref(), 
         prompt.as_deref(), 
-        response_format.as_deref(), 
-        temperature
+        options.response_format.as_deref(), 
+        options.temperature
     ).await {
         Ok(transcription) => {
             println!("🎯 === TRANSCRIPTION RESULT ===");
             println!("📝 Text: {}", transcription);
             println!("📊 Length: {} characters", transcription.len());
             println!("🎯 ===========================");
+
+            if !transcription.trim().is_empty() {
+                let saved = app
+                    .state::<HistoryState>()
+                    .lock()
+                    .map_err(|e| e.to_string())?
+                    .as_ref()
+                    .map(|h| h.add("groq", &transcription));
+                match saved {
+                    Some(Ok(entry)) => {
+                        let _ = app.emit(events::TRANSCRIPTION_COMPLETED, &entry);
+                    }
+                    Some(Err(e)) => println!("⚠️ {}", e),
+                    None => {}
+                }
+            }
             Ok(transcription)
         }
         Err(e) => {
@@ -125,6 +216,277 @@ async fn transcribe_audio(
     }
 }
 
+#[tauri::command]
+async fn transcribe_audio(
+    app: AppHandle,
+    audio_data: Vec<u8>,
+    model: Option<String>,
+    language: Option<String>,
+    prompt: Option<String>,
+    response_format: Option<String>,
+    temperature: Option<f32>,
+) -> Result<String, String> {
+    let options = TranscribeOptions { model, language, prompt, response_format, temperature };
+    transcribe_wav(&app, audio_data, options).await
+}
+
+/// Browser-side recording fallback: transcribe a MediaRecorder WebM blob
+#[tauri::command]
+async fn transcribe_webm(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    webm_data: Vec<u8>,
+    model: Option<String>,
+    language: Option<String>,
+    prompt: Option<String>,
+    response_format: Option<String>,
+    temperature: Option<f32>,
+) -> Result<String, String> {
+    println!("🌐 Decoding {:.2}KB WebM from the browser", webm_data.len() as f32 / 1024.0);
+
+    let wav_data = {
+        let processor = state.webm_processor.lock().map_err(|e| e.to_string())?;
+        processor.process_webm_to_wav(webm_data)?
+    };
+
+    let options = TranscribeOptions { model, language, prompt, response_format, temperature };
+    transcribe_wav(&app, wav_data, options).await
+}
+
+/// Global shortcut: start recording, or stop, transcribe and type the
+/// result into the focused window. The webview only follows along through
+/// events.
+fn toggle_recording(app: &AppHandle) {
+    // The mic belongs to the voice assistant while it runs
+    if app.state::<LiveState>().is_active() {
+        println!("🎤 Gemini Live is running, ignoring the dictation shortcut");
+        return;
+    }
+
+    let state = app.state::<AppState>();
+    let mut recorder = match state.groq_recorder.lock() {
+        Ok(recorder) => recorder,
+        Err(e) => {
+            println!("❌ Recorder lock error: {}", e);
+            return;
+        }
+    };
+
+    if !recorder.is_recording() {
+        match recorder.start_recording() {
+            Ok(()) => {
+                let _ = app.emit(events::RECORDING_STARTED, ());
+            }
+            Err(e) => {
+                let _ = app.emit(events::TRANSCRIPTION_RESULT, events::TranscriptionResult::failed(e));
+            }
+        }
+        return;
+    }
+
+    let wav_data = recorder.stop_recording();
+    drop(recorder);
+    let _ = app.emit(events::RECORDING_STOPPED, ());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let transcription = match wav_data {
+            Ok(wav_data) => transcribe_wav(&app, wav_data, TranscribeOptions::default()).await,
+            Err(e) => Err(e),
+        };
+        let result = match transcription {
+            Ok(text) if text.trim().is_empty() => events::TranscriptionResult::failed("No speech detected".to_string()),
+            Ok(text) => {
+                let injected = app
+                    .state::<AppState>()
+                    .text_injector
+                    .lock()
+                    .map_err(|e| e.to_string())
+                    .and_then(|injector| injector.inject_text(&text).map_err(|e| e.to_string()));
+                match injected {
+                    Ok(()) => events::TranscriptionResult::injected(text),
+                    Err(e) => {
+                        println!("❌ Text injection failed: {}", e);
+                        events::TranscriptionResult { text, injected: false, error: Some(e) }
+                    }
+                }
+            }
+            Err(e) => events::TranscriptionResult::failed(e),
+        };
+        let _ = app.emit(events::TRANSCRIPTION_RESULT, result);
+    });
+}
+
+// Transcript history commands
+
+#[tauri::command]
+async fn get_transcription_history(
+    history: State<'_, HistoryState>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let history = history.lock().map_err(|e| e.to_string())?;
+    let history = history.as_ref().ok_or("History is unavailable")?;
+    history.list(limit.unwrap_or(50), offset.unwrap_or(0))
+}
+
+#[tauri::command]
+async fn search_history(
+    history: State<'_, HistoryState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let history = history.lock().map_err(|e| e.to_string())?;
+    let history = history.as_ref().ok_or("History is unavailable")?;
+    history.search(query.trim(), limit.unwrap_or(50))
+}
+
+#[tauri::command]
+async fn delete_history_entry(history: State<'_, HistoryState>, id: i64) -> Result<bool, String> {
+    let history = history.lock().map_err(|e| e.to_string())?;
+    let history = history.as_ref().ok_or("History is unavailable")?;
+    history.delete(id)
+}
+
+// Custom vocabulary commands
+
+#[tauri::command]
+async fn list_dictionary(store: State<'_, Mutex<SettingsStore>>) -> Result<Vec<String>, String> {
+    let store = store.lock().map_err(|e| e.to_string())?;
+    Ok(store.get().dictionary.clone())
+}
+
+#[tauri::command]
+async fn add_dictionary_term(
+    store: State<'_, Mutex<SettingsStore>>,
+    term: String,
+) -> Result<Vec<String>, String> {
+    let term = term.trim().to_string();
+    if term.is_empty() {
+        return Err("Dictionary term is empty".to_string());
+    }
+
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    if !store.get().dictionary.iter().any(|t| t.eq_ignore_ascii_case(&term)) {
+        store.update(|s| s.dictionary.push(term.clone()))?;
+        println!("📖 Added dictionary term: {}", term);
+    }
+    Ok(store.get().dictionary.clone())
+}
+
+#[tauri::command]
+async fn remove_dictionary_term(
+    store: State<'_, Mutex<SettingsStore>>,
+    term: String,
+) -> Result<Vec<String>, String> {
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    if store.get().dictionary.iter().any(|t| t.eq_ignore_ascii_case(term.trim())) {
+        store.update(|s| s.dictionary.retain(|t| !t.eq_ignore_ascii_case(term.trim())))?;
+        println!("📖 Removed dictionary term: {}", term.trim());
+    }
+    Ok(store.get().dictionary.clone())
+}
+
+// Global shortcut commands
+
+#[derive(Debug, Clone, Serialize)]
+struct ShortcutStatus {
+    shortcut: String,
+    /// False when another application already holds the shortcut
+    registered: bool,
+}
+
+#[tauri::command]
+async fn get_shortcut(app: AppHandle, store: State<'_, Mutex<SettingsStore>>) -> Result<ShortcutStatus, String> {
+    let shortcut = store.lock().map_err(|e| e.to_string())?.get().shortcut.clone();
+    let registered = shortcut
+        .parse::<Shortcut>()
+        .map(|s| app.global_shortcut().is_registered(s))
+        .unwrap_or(false);
+    Ok(ShortcutStatus { shortcut, registered })
+}
+
+/// Switch the recording shortcut, keeping the old one if the new one can't
+/// be registered (invalid, or taken by another application)
+#[tauri::command]
+async fn set_shortcut(
+    app: AppHandle,
+    store: State<'_, Mutex<SettingsStore>>,
+    shortcut: String,
+) -> Result<ShortcutStatus, String> {
+    let new = shortcut
+        .trim()
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid shortcut \"{}\": {}", shortcut.trim(), e))?;
+    let mut store = store.lock().map_err(|e| e.to_string())?;
+    let old = store.get().shortcut.parse::<Shortcut>().ok();
+
+    // The same shortcut may still be unregistered if it was taken at startup
+    let global = app.global_shortcut();
+    if old != Some(new) || !global.is_registered(new) {
+        let old = old.filter(|old| *old != new);
+        if let Some(old) = old {
+            let _ = global.unregister(old);
+        }
+        if let Err(e) = global.register(new) {
+            println!("❌ Cannot register {}: {}", shortcut.trim(), e);
+            if let Some(old) = old {
+                let _ = global.register(old);
+            }
+            return Err(format!("Cannot use {}: {}", shortcut.trim(), e));
+        }
+    }
+
+    store.update(|s| s.shortcut = shortcut.trim().to_string())?;
+    println!("✅ Global shortcut set to {}", shortcut.trim());
+    Ok(ShortcutStatus {
+        shortcut: shortcut.trim().to_string(),
+        registered: true,
+    })
+}
+
+// Gemini Live voice assistant commands
+
+#[tauri::command]
+async fn start_gemini_live(
+    app: AppHandle,
+    api_key: Option<String>,
+    voice_name: Option<String>,
+    system_instruction: Option<String>,
+    text_only: Option<bool>,
+) -> Result<String, String> {
+    let options = LiveOptions {
+        api_key,
+        voice_name,
+        system_instruction,
+        text_only: text_only.unwrap_or(false),
+    };
+    live::start(&app, options).await?;
+    Ok("Gemini Live connecting".to_string())
+}
+
+#[tauri::command]
+async fn stop_gemini_live(app: AppHandle) -> Result<(), String> {
+    live::stop(&app);
+    Ok(())
+}
+
+#[tauri::command]
+async fn send_gemini_text(live: State<'_, LiveState>, text: String) -> Result<(), String> {
+    live.send_text(&text)
+}
+
+/// Approve or deny a tool call announced with status "confirm"
+#[tauri::command]
+async fn confirm_tool_call(live: State<'_, LiveState>, id: String, allow: bool) -> Result<(), String> {
+    live.confirm(&id, allow)
+}
+
+#[tauri::command]
+async fn get_gemini_levels(live: State<'_, LiveState>) -> Result<[f32; 4], String> {
+    Ok(live.output_levels())
+}
+
 #[tauri::command]
 async fn inject_text(state: State<'_, AppState>, text: String) -> Result<String, String> {
     println!("🔥 inject_text called with text: '{}'", text);
@@ -229,9 +591,9 @@ pub fn run() {
     apply_graphics_fix();
     let groq_client = Arc::new(Mutex::new(None));
     
-    let groq_recorder = Arc::new(Mutex::new(
-        GroqRecorder::new().expect("Failed to initialize Groq recorder")
-    ));
+    // No microphone needed for UI work in containers and CI
+    let mock_audio = std::env::args().any(|arg| arg == "--mock-audio");
+    let groq_recorder = Arc::new(Mutex::new(audio::create_recorder(mock_audio, None)));
     
     let text_injector = Arc::new(Mutex::new(
         TextInjector::new().expect("Failed to initialize text injector")
@@ -259,47 +621,109 @@ pub fn run() {
             .level(log::LevelFilter::Debug)
             .build())
         .manage(app_state)
+        .manage(LiveState::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             start_groq_recording,
             stop_groq_recording,
             is_recording,
             get_audio_levels,
+            list_input_devices,
+            set_input_device,
             set_groq_api_key,
+            has_api_key,
             transcribe_audio,
+            transcribe_webm,
             inject_text,
             get_active_window_info,
-            copy_to_clipboard
+            copy_to_clipboard,
+            get_transcription_history,
+            search_history,
+            delete_history_entry,
+            list_dictionary,
+            add_dictionary_term,
+            remove_dictionary_term,
+            get_shortcut,
+            set_shortcut,
+            start_gemini_live,
+            stop_gemini_live,
+            send_gemini_text,
+            confirm_tool_call,
+            get_gemini_levels
         ])
-        .setup(|app| {
+        .setup(move |app| {
             // NO devtools auto-open - clean desktop app experience
             println!("🚀 AquaVoice starting up...");
+
+            // A key saved in an earlier session (or by the native app)
+            let keys = KeyStore::new(app.path().app_data_dir()?);
+            if let Some(api_key) = keys.load() {
+                if let Ok(mut client) = app.state::<AppState>().groq_client.lock() {
+                    *client = Some(GroqClient::new(api_key));
+                    println!("🔑 Loaded saved Groq API key");
+                }
+            }
+            app.manage(keys);
+
+            let settings_path = app.path().app_config_dir()?.join("settings.json");
+            let settings = SettingsStore::load(settings_path);
+            let shortcut_name = settings.get().shortcut.clone();
+            let input_device = settings.get().input_device.clone();
+            let saved_mock_audio = settings.get().mock_audio;
+            app.manage(Mutex::new(settings));
+
+            // The recorder starts on the default mic; switch to the saved
+            // one, or to the mock if the settings ask for it
+            let recorder: Option<Box<dyn Recorder>> = if mock_audio {
+                None
+            } else if saved_mock_audio {
+                Some(audio::create_recorder(true, None))
+            } else if !input_device.is_empty() {
+                match GroqRecorder::with_device(Some(&input_device)) {
+                    Ok(recorder) => Some(Box::new(recorder)),
+                    Err(e) => {
+                        println!("⚠️ {}, using the default microphone", e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            if let Some(recorder) = recorder {
+                if let Ok(mut current) = app.state::<AppState>().groq_recorder.lock() {
+                    *current = recorder;
+                }
+            }
+
+            // The native app keeps its history in the same file
+            let history_path = app.path().data_dir()?.join("mavoice").join("history.db");
+            let history = TranscriptHistory::open(&history_path)
+                .map_err(|e| println!("⚠️ Transcript history disabled: {}", e))
+                .ok();
+            app.manage::<HistoryState>(Mutex::new(history));
             
-            // Register global Ctrl+Shift+, hotkey for voice recording
-            use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
-            
-            let shortcut = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::Comma);
-            
+            // Register the global recording shortcut
             app.handle().plugin(
                 tauri_plugin_global_shortcut::Builder::new()
-                    .with_handler(move |app, _shortcut, event| {
-                        println!("🎤 Global Ctrl+Shift+, pressed! Event: {:?}", event);
-                        
-                        // Trigger on any event (pressed/released doesn't matter)
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            
-                            // Trigger recording start via frontend
-                            let _ = window.eval("window.startGlobalRecording && window.startGlobalRecording()");
+                    .with_handler(move |app, shortcut, event| {
+                        // Releasing the keys would toggle a second time
+                        if event.state() == ShortcutState::Pressed {
+                            println!("🎤 Global {} pressed!", shortcut.into_string());
+                            toggle_recording(app);
                         }
                     })
                     .build(),
             )?;
             
-            // Register the shortcut
-            app.global_shortcut().register(shortcut)?;
-            println!("✅ Global Ctrl+Shift+, hotkey registered!");
+            let shortcut = shortcut_name.parse::<Shortcut>().unwrap_or_else(|e| {
+                println!("⚠️ Invalid shortcut \"{}\" ({}), using {}", shortcut_name, e, DEFAULT_SHORTCUT);
+                DEFAULT_SHORTCUT.parse().expect("default shortcut parses")
+            });
+            // A taken shortcut shouldn't stop the app; get_shortcut reports it
+            match app.global_shortcut().register(shortcut) {
+                Ok(()) => println!("✅ Global {} hotkey registered!", shortcut_name),
+                Err(e) => println!("❌ Cannot register {}: {}", shortcut_name, e),
+            }
             
             Ok(())
         })
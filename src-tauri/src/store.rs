@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Recording shortcut until the user picks another one
+pub const DEFAULT_SHORTCUT: &str = "Ctrl+Shift+Comma";
+
+/// Settings that survive a restart, kept as JSON in the app config dir
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Custom vocabulary sent to Whisper as the prompt
+    pub dictionary: Vec<String>,
+    /// Global shortcut that toggles recording, e.g. "Ctrl+Shift+Comma"
+    pub shortcut: String,
+    /// Microphone name (empty = system default)
+    pub input_device: String,
+    /// Record with the mock recorder instead of a microphone (UI work in
+    /// containers and CI); same as `--mock-audio`
+    pub mock_audio: bool,
+    /// SQLite file for the Gemini memory tools, ShieldCortex's
+    /// `memories.db` schema; `~` is expanded (empty = not set up)
+    pub memory_db: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            dictionary: Vec::new(),
+            shortcut: DEFAULT_SHORTCUT.to_string(),
+            input_device: String::new(),
+            mock_audio: false,
+            memory_db: String::new(),
+        }
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Settings,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, starting from defaults if the file is
+    /// missing or unreadable
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                println!("⚠️ Ignoring unreadable settings {}: {}", path.display(), e);
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+        Self { path, settings }
+    }
+
+    pub fn get(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Change the settings and write them to disk
+    pub fn update(&mut self, f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+        f(&mut self.settings);
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(&self.settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| format!("Cannot save settings: {}", e))
+    }
+
+    /// `memory_db` with `~` expanded, or `None` if it is unset
+    pub fn memory_db_path(&self) -> Option<PathBuf> {
+        let path = self.settings.memory_db.trim();
+        if path.is_empty() {
+            return None;
+        }
+        match path.strip_prefix("~/") {
+            Some(rest) => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(rest)),
+            None => Some(PathBuf::from(path)),
+        }
+    }
+
+    /// The dictionary as a Whisper prompt, or `None` if it is empty
+    pub fn dictionary_prompt(&self) -> Option<String> {
+        if self.settings.dictionary.is_empty() {
+            None
+        } else {
+            Some(self.settings.dictionary.join(", "))
+        }
+    }
+}
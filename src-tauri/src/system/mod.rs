@@ -1,3 +1,4 @@
+pub mod secrets;
 pub mod text_inject;
 
-pub use text_inject::*;
\ No newline at end of file
+pub use text_inject::*;
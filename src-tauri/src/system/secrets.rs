@@ -0,0 +1,96 @@
+//! Groq API key storage: the freedesktop Secret Service (GNOME Keyring,
+//! KWallet), or a private file in the app data dir when there is none.
+
+use keyring::Entry;
+use std::path::PathBuf;
+
+/// Service and entry the native app also uses, so both share one key
+/// (`secret-tool search service mavoice`).
+const SERVICE: &str = "mavoice";
+const GROQ_KEY: &str = "groq_api_key";
+const GEMINI_KEY: &str = "gemini_api_key";
+
+/// The Gemini API key the native app saved, if any
+pub fn gemini_key() -> Option<String> {
+    Entry::new(SERVICE, GEMINI_KEY)
+        .and_then(|e| e.get_password())
+        .ok()
+        .filter(|key| !key.is_empty())
+}
+
+pub struct KeyStore {
+    /// Used when the keyring can't be reached (headless, no secret service)
+    fallback: PathBuf,
+}
+
+impl KeyStore {
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self {
+            fallback: data_dir.join("groq_api_key"),
+        }
+    }
+
+    fn entry() -> Result<Entry, String> {
+        Entry::new(SERVICE, GROQ_KEY).map_err(|e| format!("Keyring unavailable: {}", e))
+    }
+
+    /// The saved key, from the keyring first and then the fallback file
+    pub fn load(&self) -> Option<String> {
+        match Self::entry().and_then(|e| e.get_password().map_err(|e| e.to_string())) {
+            Ok(key) if !key.is_empty() => return Some(key),
+            Ok(_) => {}
+            Err(e) => println!("🔑 Keyring lookup failed: {}", e),
+        }
+        std::fs::read_to_string(&self.fallback)
+            .ok()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+    }
+
+    /// Save the key to the keyring, or to the fallback file (readable only
+    /// by the user) if the keyring refuses it
+    pub fn save(&self, key: &str) -> Result<(), String> {
+        if self.load().as_deref() == Some(key) {
+            return Ok(());
+        }
+        match Self::entry().and_then(|e| e.set_password(key).map_err(|e| e.to_string())) {
+            Ok(()) => {
+                // Don't leave an older key behind in plaintext
+                let _ = std::fs::remove_file(&self.fallback);
+                println!("🔑 Groq API key saved to the keyring");
+                Ok(())
+            }
+            Err(e) => {
+                println!("⚠️ Keyring write failed ({}), saving to {}", e, self.fallback.display());
+                self.write_fallback(key)
+            }
+        }
+    }
+
+    fn write_fallback(&self, key: &str) -> Result<(), String> {
+        use std::io::Write;
+        #[cfg(unix)]
+        use std::os::unix::fs::OpenOptionsExt;
+
+        if let Some(dir) = self.fallback.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(&self.fallback)
+            .map_err(|e| format!("Cannot save API key: {}", e))?;
+        // `mode` only applies when the file is created; tighten one left
+        // from an older version or a different umask too
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Cannot restrict {}: {}", self.fallback.display(), e))?;
+        }
+        file.write_all(key.as_bytes())
+            .map_err(|e| format!("Cannot save API key: {}", e))
+    }
+}
@@ -5,6 +5,10 @@ use symphonia::core::meta::MetadataOptions;
 use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
 use symphonia::core::audio::{AudioBufferRef, SampleBuffer};
 use std::io::Cursor;
+use hound::{WavSpec, WavWriter};
+
+/// Sample rate Whisper works at; anything higher only makes uploads bigger
+const GROQ_SAMPLE_RATE: u32 = 16_000;
 
 pub struct WebMProcessor;
 
@@ -88,6 +92,39 @@ impl WebMProcessor {
         Ok((all_samples, sample_rate, channels))
     }
 
+    /// Decode a MediaRecorder blob into a 16 kHz mono 16-bit WAV for Groq
+    pub fn process_webm_to_wav(&self, webm_data: Vec<u8>) -> Result<Vec<u8>, String> {
+        let (samples, sample_rate, channels) = self.process_webm_to_pcm(webm_data)?;
+        if samples.is_empty() {
+            return Err("No audio in recording".to_string());
+        }
+
+        let mono = downmix(&samples, channels.max(1) as usize);
+        let resampled = resample_linear(&mono, sample_rate, GROQ_SAMPLE_RATE);
+        println!(
+            "🔄 WebM: {} Hz x{} → {} Hz mono ({} samples)",
+            sample_rate, channels, GROQ_SAMPLE_RATE, resampled.len()
+        );
+
+        let mut wav_bytes = Vec::<u8>::new();
+        {
+            let spec = WavSpec {
+                channels: 1,
+                sample_rate: GROQ_SAMPLE_RATE,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            let mut writer = WavWriter::new(Cursor::new(&mut wav_bytes), spec)
+                .map_err(|e| e.to_string())?;
+            for &s in &resampled {
+                let s16 = (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                writer.write_sample(s16).map_err(|e| e.to_string())?;
+            }
+            writer.finalize().map_err(|e| e.to_string())?;
+        }
+        Ok(wav_bytes)
+    }
+
     fn convert_to_f32_samples(&self, decoded: AudioBufferRef) -> Result<Vec<f32>, String> {
         // Convert to interleaved f32 sample buffer
         let mut sample_buf = SampleBuffer::<f32>::new(
@@ -98,4 +135,34 @@ impl WebMProcessor {
         sample_buf.copy_interleaved_ref(decoded);
         Ok(sample_buf.samples().to_vec())
     }
-}
\ No newline at end of file
+}
+
+/// Average interleaved frames down to one channel
+fn downmix(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels == 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Linear-interpolation resampling; plenty for speech going to Whisper
+fn resample_linear(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from as f64 / to as f64;
+    let out_len = (samples.len() as f64 / ratio) as usize;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = samples[idx.min(samples.len() - 1)];
+            let b = samples[(idx + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}